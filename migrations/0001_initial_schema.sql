@@ -1,7 +1,5 @@
--- ========================
--- FastWatcher Database Init
--- ========================
-PRAGMA foreign_keys = ON;
+-- Tables and indexes from before schema versioning. IF NOT EXISTS lets
+-- databases created by the old init.sql upgrade cleanly.
 
 -- ---------- taxa ----------
 CREATE TABLE IF NOT EXISTS taxa (
@@ -103,4 +101,4 @@ CREATE INDEX IF NOT EXISTS idx_sightings_species_epithet ON sightings(species_ep
 
 CREATE INDEX IF NOT EXISTS idx_sightings_date ON sightings(date);
 
-CREATE INDEX IF NOT EXISTS idx_sightings_location ON sightings(location);
\ No newline at end of file
+CREATE INDEX IF NOT EXISTS idx_sightings_location ON sightings(location);
@@ -0,0 +1,17 @@
+-- ---------- triggers ----------
+-- keep the taxonomy copied into sightings in step with corrections to taxa
+CREATE TRIGGER IF NOT EXISTS trg_taxa_sync_sightings
+AFTER UPDATE OF kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name ON taxa
+BEGIN
+    UPDATE sightings
+    SET kingdom = NEW.kingdom,
+        phylum = NEW.phylum,
+        class = NEW.class,
+        "order" = NEW."order",
+        family = NEW.family,
+        subfamily = NEW.subfamily,
+        genus = NEW.genus,
+        species_epithet = NEW.species_epithet,
+        common_name = NEW.common_name
+    WHERE taxon_id = NEW.id;
+END;
@@ -0,0 +1,117 @@
+-- ---------- full-text search ----------
+-- external-content FTS5 indexes; the triggers below keep them current
+CREATE VIRTUAL TABLE IF NOT EXISTS sightings_fts USING fts5(
+    common_name,
+    kingdom,
+    phylum,
+    class,
+    "order",
+    family,
+    subfamily,
+    genus,
+    species_epithet,
+    notes,
+    location,
+    date,
+    content = 'sightings',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS trips_fts USING fts5(
+    name,
+    location,
+    notes,
+    date,
+    content = 'trips',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS taxa_fts USING fts5(
+    common_name,
+    kingdom,
+    phylum,
+    class,
+    "order",
+    family,
+    subfamily,
+    genus,
+    species_epithet,
+    content = 'taxa',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+-- sightings_fts
+CREATE TRIGGER IF NOT EXISTS trg_sightings_fts_insert AFTER INSERT ON sightings
+BEGIN
+    INSERT INTO sightings_fts (rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, notes, location, date)
+    VALUES (NEW.id, NEW.common_name, NEW.kingdom, NEW.phylum, NEW.class, NEW."order", NEW.family, NEW.subfamily, NEW.genus, NEW.species_epithet, NEW.notes, NEW.location, NEW.date);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sightings_fts_delete AFTER DELETE ON sightings
+BEGIN
+    INSERT INTO sightings_fts (sightings_fts, rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, notes, location, date)
+    VALUES ('delete', OLD.id, OLD.common_name, OLD.kingdom, OLD.phylum, OLD.class, OLD."order", OLD.family, OLD.subfamily, OLD.genus, OLD.species_epithet, OLD.notes, OLD.location, OLD.date);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sightings_fts_update AFTER UPDATE ON sightings
+BEGIN
+    INSERT INTO sightings_fts (sightings_fts, rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, notes, location, date)
+    VALUES ('delete', OLD.id, OLD.common_name, OLD.kingdom, OLD.phylum, OLD.class, OLD."order", OLD.family, OLD.subfamily, OLD.genus, OLD.species_epithet, OLD.notes, OLD.location, OLD.date);
+    INSERT INTO sightings_fts (rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, notes, location, date)
+    VALUES (NEW.id, NEW.common_name, NEW.kingdom, NEW.phylum, NEW.class, NEW."order", NEW.family, NEW.subfamily, NEW.genus, NEW.species_epithet, NEW.notes, NEW.location, NEW.date);
+END;
+
+-- trips_fts
+CREATE TRIGGER IF NOT EXISTS trg_trips_fts_insert AFTER INSERT ON trips
+BEGIN
+    INSERT INTO trips_fts (rowid, name, location, notes, date)
+    VALUES (NEW.id, NEW.name, NEW.location, NEW.notes, NEW.date);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_trips_fts_delete AFTER DELETE ON trips
+BEGIN
+    INSERT INTO trips_fts (trips_fts, rowid, name, location, notes, date)
+    VALUES ('delete', OLD.id, OLD.name, OLD.location, OLD.notes, OLD.date);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_trips_fts_update AFTER UPDATE ON trips
+BEGIN
+    INSERT INTO trips_fts (trips_fts, rowid, name, location, notes, date)
+    VALUES ('delete', OLD.id, OLD.name, OLD.location, OLD.notes, OLD.date);
+    INSERT INTO trips_fts (rowid, name, location, notes, date)
+    VALUES (NEW.id, NEW.name, NEW.location, NEW.notes, NEW.date);
+END;
+
+-- taxa_fts
+CREATE TRIGGER IF NOT EXISTS trg_taxa_fts_insert AFTER INSERT ON taxa
+BEGIN
+    INSERT INTO taxa_fts (rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet)
+    VALUES (NEW.id, NEW.common_name, NEW.kingdom, NEW.phylum, NEW.class, NEW."order", NEW.family, NEW.subfamily, NEW.genus, NEW.species_epithet);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_taxa_fts_delete AFTER DELETE ON taxa
+BEGIN
+    INSERT INTO taxa_fts (taxa_fts, rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet)
+    VALUES ('delete', OLD.id, OLD.common_name, OLD.kingdom, OLD.phylum, OLD.class, OLD."order", OLD.family, OLD.subfamily, OLD.genus, OLD.species_epithet);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_taxa_fts_update AFTER UPDATE ON taxa
+BEGIN
+    INSERT INTO taxa_fts (taxa_fts, rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet)
+    VALUES ('delete', OLD.id, OLD.common_name, OLD.kingdom, OLD.phylum, OLD.class, OLD."order", OLD.family, OLD.subfamily, OLD.genus, OLD.species_epithet);
+    INSERT INTO taxa_fts (rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet)
+    VALUES (NEW.id, NEW.common_name, NEW.kingdom, NEW.phylum, NEW.class, NEW."order", NEW.family, NEW.subfamily, NEW.genus, NEW.species_epithet);
+END;
+
+-- index rows that existed before the FTS tables did
+INSERT INTO sightings_fts (sightings_fts) VALUES ('rebuild');
+
+INSERT INTO trips_fts (trips_fts) VALUES ('rebuild');
+
+INSERT INTO taxa_fts (taxa_fts) VALUES ('rebuild');
@@ -0,0 +1,7 @@
+-- eBird checklists already imported, so importing the same export twice doesn't duplicate trips
+CREATE TABLE IF NOT EXISTS ebird_checklists (
+    submission_id TEXT PRIMARY KEY,
+    trip_id INTEGER NOT NULL REFERENCES trips(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_ebird_checklists_trip_id ON ebird_checklists(trip_id);
@@ -0,0 +1,7 @@
+-- Darwin Core occurrences already imported, so importing the same archive twice doesn't duplicate sightings
+CREATE TABLE IF NOT EXISTS dwc_occurrences (
+    occurrence_id TEXT PRIMARY KEY,
+    sighting_id INTEGER NOT NULL REFERENCES sightings(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_dwc_occurrences_sighting_id ON dwc_occurrences(sighting_id);
@@ -0,0 +1,35 @@
+-- Structured observation details: how many birds (a number, or 'X' for present
+-- but not counted), a sex/age breakdown such as "2 male adult, 1 female" and an
+-- eBird breeding/atlas code
+ALTER TABLE sightings ADD COLUMN count INTEGER
+    CHECK (count IS NULL OR count = 'X' OR (typeof(count) = 'integer' AND count >= 0));
+
+ALTER TABLE sightings ADD COLUMN age_sex TEXT;
+
+ALTER TABLE sightings ADD COLUMN breeding_code TEXT
+    CHECK (breeding_code IN (
+        'NY', 'NE', 'FS', 'FY', 'CF', 'FL', 'ON', 'UN', 'DD', 'NB',
+        'CN', 'PE', 'B', 'A', 'N', 'C', 'T', 'P', 'M', 'S7',
+        'S', 'H', 'F'
+    ));
+
+-- eBird imports used to keep the count as a "Count: N" first line of the notes
+UPDATE sightings
+SET count = parsed.count, notes = parsed.rest
+FROM (
+    SELECT id,
+           CASE WHEN line = 'X' THEN 'X' ELSE CAST(line AS INTEGER) END AS count,
+           NULLIF(rest, '') AS rest
+    FROM (
+        SELECT id,
+               trim(CASE WHEN instr(notes, char(10)) > 0
+                         THEN substr(notes, 8, instr(notes, char(10)) - 8)
+                         ELSE substr(notes, 8) END) AS line,
+               CASE WHEN instr(notes, char(10)) > 0
+                    THEN substr(notes, instr(notes, char(10)) + 1) END AS rest
+        FROM sightings
+        WHERE substr(notes, 1, 7) = 'Count: '
+    )
+    WHERE line = 'X' OR (line <> '' AND line NOT GLOB '*[^0-9]*' AND length(line) <= 9)
+) AS parsed
+WHERE sightings.id = parsed.id;
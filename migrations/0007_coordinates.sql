@@ -0,0 +1,54 @@
+-- Optional WGS84 coordinates on trips and sightings, with an accuracy radius in meters
+ALTER TABLE trips ADD COLUMN latitude REAL CHECK (latitude BETWEEN -90 AND 90);
+ALTER TABLE trips ADD COLUMN longitude REAL CHECK (longitude BETWEEN -180 AND 180);
+ALTER TABLE trips ADD COLUMN accuracy_m REAL CHECK (accuracy_m >= 0);
+
+ALTER TABLE sightings ADD COLUMN latitude REAL CHECK (latitude BETWEEN -90 AND 90);
+ALTER TABLE sightings ADD COLUMN longitude REAL CHECK (longitude BETWEEN -180 AND 180);
+ALTER TABLE sightings ADD COLUMN accuracy_m REAL CHECK (accuracy_m >= 0);
+
+-- R*Tree indexes over the points, kept in sync by triggers. Only rows with both
+-- a latitude and a longitude are indexed.
+CREATE VIRTUAL TABLE IF NOT EXISTS trips_geo USING rtree(id, min_lat, max_lat, min_lon, max_lon);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS sightings_geo USING rtree(id, min_lat, max_lat, min_lon, max_lon);
+
+CREATE TRIGGER IF NOT EXISTS trg_trips_geo_insert AFTER INSERT ON trips
+WHEN NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL
+BEGIN
+    INSERT INTO trips_geo (id, min_lat, max_lat, min_lon, max_lon)
+    VALUES (NEW.id, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_trips_geo_update AFTER UPDATE OF latitude, longitude ON trips
+BEGIN
+    DELETE FROM trips_geo WHERE id = OLD.id;
+    INSERT INTO trips_geo (id, min_lat, max_lat, min_lon, max_lon)
+    SELECT NEW.id, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude
+    WHERE NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_trips_geo_delete AFTER DELETE ON trips
+BEGIN
+    DELETE FROM trips_geo WHERE id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sightings_geo_insert AFTER INSERT ON sightings
+WHEN NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL
+BEGIN
+    INSERT INTO sightings_geo (id, min_lat, max_lat, min_lon, max_lon)
+    VALUES (NEW.id, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sightings_geo_update AFTER UPDATE OF latitude, longitude ON sightings
+BEGIN
+    DELETE FROM sightings_geo WHERE id = OLD.id;
+    INSERT INTO sightings_geo (id, min_lat, max_lat, min_lon, max_lon)
+    SELECT NEW.id, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude
+    WHERE NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sightings_geo_delete AFTER DELETE ON sightings
+BEGIN
+    DELETE FROM sightings_geo WHERE id = OLD.id;
+END;
@@ -0,0 +1,145 @@
+-- Places as records of their own: a locality inside an optional county, state
+-- and country, with optional coordinates. Trips and sightings point at one by
+-- location_id and keep its full name in their location column for search, the
+-- same way sightings copy their taxon's names.
+--
+-- name_key and locality_key fold case, punctuation and spacing so that
+-- "Central Park" and "central  park." match. Code that looks a place up by name
+-- applies the same expression to its input (core::location::location_key).
+CREATE TABLE IF NOT EXISTS locations (
+    id INTEGER PRIMARY KEY,
+    locality TEXT NOT NULL CHECK (trim(locality) <> ''),
+    county TEXT,
+    state TEXT,
+    country TEXT,
+    latitude REAL CHECK (latitude BETWEEN -90 AND 90),
+    longitude REAL CHECK (longitude BETWEEN -180 AND 180),
+    accuracy_m REAL CHECK (accuracy_m >= 0),
+    hotspot INTEGER NOT NULL DEFAULT 0 CHECK (hotspot IN (0, 1)),
+    name TEXT GENERATED ALWAYS AS (
+        locality || COALESCE(', ' || county, '') || COALESCE(', ' || state, '') || COALESCE(', ' || country, '')
+    ) VIRTUAL,
+    name_key TEXT GENERATED ALWAYS AS (
+        trim(replace(replace(replace(replace(replace(replace(replace(lower(
+            locality || COALESCE(', ' || county, '') || COALESCE(', ' || state, '') || COALESCE(', ' || country, '')
+        ), ',', ' '), '.', ' '), '-', ' '), '''', ''), '  ', ' '), '  ', ' '), '  ', ' '))
+    ) VIRTUAL,
+    locality_key TEXT GENERATED ALWAYS AS (
+        trim(replace(replace(replace(replace(replace(replace(replace(lower(
+            locality
+        ), ',', ' '), '.', ' '), '-', ' '), '''', ''), '  ', ' '), '  ', ' '), '  ', ' '))
+    ) VIRTUAL
+);
+
+CREATE INDEX IF NOT EXISTS idx_locations_name_key ON locations(name_key);
+CREATE INDEX IF NOT EXISTS idx_locations_locality_key ON locations(locality_key);
+
+ALTER TABLE trips ADD COLUMN location_id INTEGER REFERENCES locations(id) ON DELETE SET NULL;
+ALTER TABLE sightings ADD COLUMN location_id INTEGER REFERENCES locations(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_trips_location_id ON trips(location_id);
+CREATE INDEX IF NOT EXISTS idx_sightings_location_id ON sightings(location_id);
+
+-- keep the copied names in step when a place is renamed or moved in the hierarchy
+CREATE TRIGGER IF NOT EXISTS trg_locations_sync_names
+AFTER UPDATE OF locality, county, state, country ON locations
+BEGIN
+    UPDATE trips SET location = NEW.name WHERE location_id = NEW.id;
+    UPDATE sightings SET location = NEW.name WHERE location_id = NEW.id;
+END;
+
+-- ---------- full-text search ----------
+CREATE VIRTUAL TABLE IF NOT EXISTS locations_fts USING fts5(
+    locality,
+    county,
+    state,
+    country,
+    content = 'locations',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_locations_fts_insert AFTER INSERT ON locations
+BEGIN
+    INSERT INTO locations_fts (rowid, locality, county, state, country)
+    VALUES (NEW.id, NEW.locality, NEW.county, NEW.state, NEW.country);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_locations_fts_delete AFTER DELETE ON locations
+BEGIN
+    INSERT INTO locations_fts (locations_fts, rowid, locality, county, state, country)
+    VALUES ('delete', OLD.id, OLD.locality, OLD.county, OLD.state, OLD.country);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_locations_fts_update AFTER UPDATE ON locations
+BEGIN
+    INSERT INTO locations_fts (locations_fts, rowid, locality, county, state, country)
+    VALUES ('delete', OLD.id, OLD.locality, OLD.county, OLD.state, OLD.country);
+    INSERT INTO locations_fts (rowid, locality, county, state, country)
+    VALUES (NEW.id, NEW.locality, NEW.county, NEW.state, NEW.country);
+END;
+
+-- ---------- spatial index ----------
+CREATE VIRTUAL TABLE IF NOT EXISTS locations_geo USING rtree(id, min_lat, max_lat, min_lon, max_lon);
+
+CREATE TRIGGER IF NOT EXISTS trg_locations_geo_insert AFTER INSERT ON locations
+WHEN NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL
+BEGIN
+    INSERT INTO locations_geo (id, min_lat, max_lat, min_lon, max_lon)
+    VALUES (NEW.id, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_locations_geo_update AFTER UPDATE OF latitude, longitude ON locations
+BEGIN
+    DELETE FROM locations_geo WHERE id = OLD.id;
+    INSERT INTO locations_geo (id, min_lat, max_lat, min_lon, max_lon)
+    SELECT NEW.id, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude
+    WHERE NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_locations_geo_delete AFTER DELETE ON locations
+BEGIN
+    DELETE FROM locations_geo WHERE id = OLD.id;
+END;
+
+-- ---------- cluster existing location strings ----------
+-- Spellings that fold to the same key become one place, named after the most
+-- used spelling (the earliest on a tie). Trip locations count before sightings'.
+CREATE TEMP TABLE location_spellings AS
+SELECT spelling,
+       trim(replace(replace(replace(replace(replace(replace(replace(lower(
+           spelling
+       ), ',', ' '), '.', ' '), '-', ' '), '''', ''), '  ', ' '), '  ', ' '), '  ', ' ')) AS key,
+       COUNT(*) AS uses,
+       MIN(seen) AS first_seen
+FROM (
+    SELECT trim(location) AS spelling, id AS seen FROM trips WHERE trim(location) <> ''
+    UNION ALL
+    SELECT trim(location), 4294967296 + id FROM sightings WHERE trim(location) <> ''
+)
+GROUP BY spelling;
+
+INSERT INTO locations (locality)
+SELECT spelling
+FROM (
+    SELECT spelling, first_seen,
+           ROW_NUMBER() OVER (PARTITION BY key ORDER BY uses DESC, first_seen) AS position
+    FROM location_spellings
+)
+WHERE position = 1
+ORDER BY first_seen;
+
+UPDATE trips
+SET location_id = locations.id, location = locations.name
+FROM location_spellings
+JOIN locations ON locations.name_key = location_spellings.key
+WHERE location_spellings.spelling = trim(trips.location);
+
+UPDATE sightings
+SET location_id = locations.id, location = locations.name
+FROM location_spellings
+JOIN locations ON locations.name_key = location_spellings.key
+WHERE location_spellings.spelling = trim(sightings.location);
+
+DROP TABLE location_spellings;
@@ -0,0 +1,128 @@
+-- Taxa below and beside species: subspecies (with their own epithet in the new
+-- subspecies column), and the identification categories field birders log when
+-- they can't or don't name a single species: hybrids ("Anas platyrhynchos x
+-- rubripes"), slashes ("Tringa melanoleuca/flavipes") and spuhs ("duck sp.",
+-- which fill in only the ranks the candidates share).
+--
+-- SQLite can't change a CHECK in place, so taxa is rebuilt. Sightings reference
+-- taxa(id): dropping the table counts their rows as foreign key violations, and
+-- putting the same ids back clears them again before the transaction commits.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMP TABLE taxa_backup AS SELECT * FROM taxa;
+
+DROP TABLE taxa;
+
+CREATE TABLE taxa (
+    id INTEGER PRIMARY KEY,
+    rank TEXT NOT NULL CHECK(rank IN (
+        'kingdom', 'phylum', 'class', 'order', 'family', 'subfamily', 'genus', 'species', 'subspecies',
+        'hybrid', 'slash', 'spuh'
+    )),
+    kingdom TEXT NOT NULL,
+    phylum TEXT,
+    class TEXT,
+    "order" TEXT,
+    family TEXT,
+    subfamily TEXT,
+    genus TEXT,
+    species_epithet TEXT,
+    common_name TEXT,
+    subspecies TEXT,
+    UNIQUE(
+        rank,
+        kingdom,
+        phylum,
+        class,
+        "order",
+        family,
+        subfamily,
+        genus,
+        species_epithet,
+        subspecies
+    )
+);
+
+INSERT INTO taxa (id, rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name)
+SELECT id, rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
+FROM taxa_backup;
+
+DROP TABLE taxa_backup;
+
+CREATE INDEX IF NOT EXISTS idx_taxa_rank ON taxa(rank);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_kingdom ON taxa(kingdom);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_phylum ON taxa(phylum);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_class ON taxa(class);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_order ON taxa("order");
+
+CREATE INDEX IF NOT EXISTS idx_taxa_family ON taxa(family);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_subfamily ON taxa(subfamily);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_genus ON taxa(genus);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_species_epithet ON taxa(species_epithet);
+
+-- Sightings copy a subspecies' names down to its species; the subspecies itself
+-- is found through taxon_id
+CREATE TRIGGER IF NOT EXISTS trg_taxa_sync_sightings
+AFTER UPDATE OF kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name ON taxa
+BEGIN
+    UPDATE sightings
+    SET kingdom = NEW.kingdom,
+        phylum = NEW.phylum,
+        class = NEW.class,
+        "order" = NEW."order",
+        family = NEW.family,
+        subfamily = NEW.subfamily,
+        genus = NEW.genus,
+        species_epithet = NEW.species_epithet,
+        common_name = NEW.common_name
+    WHERE taxon_id = NEW.id;
+END;
+
+-- ---------- full-text search ----------
+DROP TABLE taxa_fts;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS taxa_fts USING fts5(
+    common_name,
+    kingdom,
+    phylum,
+    class,
+    "order",
+    family,
+    subfamily,
+    genus,
+    species_epithet,
+    subspecies,
+    content = 'taxa',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_taxa_fts_insert AFTER INSERT ON taxa
+BEGIN
+    INSERT INTO taxa_fts (rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies)
+    VALUES (NEW.id, NEW.common_name, NEW.kingdom, NEW.phylum, NEW.class, NEW."order", NEW.family, NEW.subfamily, NEW.genus, NEW.species_epithet, NEW.subspecies);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_taxa_fts_delete AFTER DELETE ON taxa
+BEGIN
+    INSERT INTO taxa_fts (taxa_fts, rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies)
+    VALUES ('delete', OLD.id, OLD.common_name, OLD.kingdom, OLD.phylum, OLD.class, OLD."order", OLD.family, OLD.subfamily, OLD.genus, OLD.species_epithet, OLD.subspecies);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_taxa_fts_update AFTER UPDATE ON taxa
+BEGIN
+    INSERT INTO taxa_fts (taxa_fts, rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies)
+    VALUES ('delete', OLD.id, OLD.common_name, OLD.kingdom, OLD.phylum, OLD.class, OLD."order", OLD.family, OLD.subfamily, OLD.genus, OLD.species_epithet, OLD.subspecies);
+    INSERT INTO taxa_fts (rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies)
+    VALUES (NEW.id, NEW.common_name, NEW.kingdom, NEW.phylum, NEW.class, NEW."order", NEW.family, NEW.subfamily, NEW.genus, NEW.species_epithet, NEW.subspecies);
+END;
+
+INSERT INTO taxa_fts (taxa_fts) VALUES ('rebuild');
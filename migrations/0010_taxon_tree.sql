@@ -0,0 +1,78 @@
+-- Taxa form a tree through parent_id: a species points at its genus, a genus at
+-- its subfamily or family, and so on up to a kingdom, skipping ranks a taxon
+-- doesn't fill in. A subspecies' parent is its species; a hybrid, slash or spuh
+-- hangs off the narrowest rank above species it names. Hierarchy queries walk
+-- this tree instead of matching name columns.
+ALTER TABLE taxa ADD COLUMN parent_id INTEGER REFERENCES taxa(id);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_parent_id ON taxa(parent_id);
+
+-- ---------- lineage keys ----------
+-- A taxon's lineage is its names from kingdom down, one slot per rank
+-- ("Animalia/Chordata/Aves/Passeriformes/////" for an order). Blanking its own
+-- name gives the key of the next broader taxon it names, its parent_lineage: a
+-- parent is the first taxon whose lineage matches, so homonyms in other
+-- kingdoms or families don't collide. The identification categories hang off
+-- the narrowest rank above species they name and have no lineage themselves.
+ALTER TABLE taxa ADD COLUMN lineage TEXT GENERATED ALWAYS AS (
+    CASE WHEN rank NOT IN ('hybrid', 'slash', 'spuh') THEN
+        kingdom || '/' || COALESCE(phylum, '') || '/' || COALESCE(class, '') || '/' || COALESCE("order", '')
+        || '/' || COALESCE(family, '') || '/' || COALESCE(subfamily, '') || '/' || COALESCE(genus, '')
+        || '/' || COALESCE(species_epithet, '') || '/' || COALESCE(subspecies, '')
+    END
+) VIRTUAL;
+
+ALTER TABLE taxa ADD COLUMN parent_lineage TEXT GENERATED ALWAYS AS (
+    CASE WHEN rank <> 'kingdom' THEN
+        kingdom
+        || '/' || CASE WHEN rank = 'phylum' THEN '' ELSE COALESCE(phylum, '') END
+        || '/' || CASE WHEN rank = 'class' THEN '' ELSE COALESCE(class, '') END
+        || '/' || CASE WHEN rank = 'order' THEN '' ELSE COALESCE("order", '') END
+        || '/' || CASE WHEN rank = 'family' THEN '' ELSE COALESCE(family, '') END
+        || '/' || CASE WHEN rank = 'subfamily' THEN '' ELSE COALESCE(subfamily, '') END
+        || '/' || CASE WHEN rank = 'genus' THEN '' ELSE COALESCE(genus, '') END
+        || '/' || CASE WHEN rank = 'subspecies' THEN COALESCE(species_epithet, '') ELSE '' END
+        || '/'
+    END
+) VIRTUAL;
+
+CREATE INDEX IF NOT EXISTS idx_taxa_lineage ON taxa(lineage);
+
+CREATE INDEX IF NOT EXISTS idx_taxa_parent_lineage ON taxa(parent_lineage);
+
+-- Setting parent_id mustn't touch the search index: the link trigger below runs
+-- before a new row is indexed, and deleting an unindexed row corrupts taxa_fts
+DROP TRIGGER trg_taxa_fts_update;
+
+CREATE TRIGGER IF NOT EXISTS trg_taxa_fts_update
+AFTER UPDATE OF common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies ON taxa
+BEGIN
+    INSERT INTO taxa_fts (taxa_fts, rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies)
+    VALUES ('delete', OLD.id, OLD.common_name, OLD.kingdom, OLD.phylum, OLD.class, OLD."order", OLD.family, OLD.subfamily, OLD.genus, OLD.species_epithet, OLD.subspecies);
+    INSERT INTO taxa_fts (rowid, common_name, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies)
+    VALUES (NEW.id, NEW.common_name, NEW.kingdom, NEW.phylum, NEW.class, NEW."order", NEW.family, NEW.subfamily, NEW.genus, NEW.species_epithet, NEW.subspecies);
+END;
+
+UPDATE taxa SET parent_id = (SELECT MIN(p.id) FROM taxa p WHERE p.lineage = taxa.parent_lineage);
+
+-- Taxa inserted with plain SQL (the seed files) link themselves, and adopt
+-- children inserted before them. core::taxon::create_taxon also creates
+-- missing ancestors.
+CREATE TRIGGER IF NOT EXISTS trg_taxa_link_parent AFTER INSERT ON taxa
+BEGIN
+    UPDATE taxa SET parent_id = (SELECT MIN(p.id) FROM taxa p WHERE p.lineage = NEW.parent_lineage)
+    WHERE id = NEW.id AND NEW.parent_id IS NULL;
+
+    UPDATE taxa SET parent_id = NEW.id
+    WHERE parent_id IS NULL AND parent_lineage = NEW.lineage AND id <> NEW.id;
+END;
+
+-- Correcting a taxon's names or rank moves it under the parent they now point at.
+-- Its children keep it as their parent; core::taxon::update_taxon passes the new
+-- names down to them.
+CREATE TRIGGER IF NOT EXISTS trg_taxa_relink_parent
+AFTER UPDATE OF rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet ON taxa
+BEGIN
+    UPDATE taxa SET parent_id = (SELECT MIN(p.id) FROM taxa p WHERE p.lineage = NEW.parent_lineage)
+    WHERE id = NEW.id;
+END;
@@ -0,0 +1,47 @@
+-- Photos, recordings and videos, any number per sighting or trip. Each row
+-- belongs to exactly one of the two and goes with it when it's deleted.
+--
+-- content_hash is the SHA-256 of the file (hex), taken when it's attached; it's
+-- NULL for files that couldn't be read then, such as ones carried over below.
+-- core::media::check_media uses it to tell a moved file from a different one.
+CREATE TABLE IF NOT EXISTS media (
+    id INTEGER PRIMARY KEY,
+    sighting_id INTEGER REFERENCES sightings(id) ON DELETE CASCADE,
+    trip_id INTEGER REFERENCES trips(id) ON DELETE CASCADE,
+    path TEXT NOT NULL CHECK (trim(path) <> ''),
+    content_hash TEXT,
+    mime_type TEXT NOT NULL,
+    captured_at TEXT,
+    caption TEXT,
+    CHECK ((sighting_id IS NULL) <> (trip_id IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_sighting_id ON media(sighting_id);
+CREATE INDEX IF NOT EXISTS idx_media_trip_id ON media(trip_id);
+CREATE INDEX IF NOT EXISTS idx_media_content_hash ON media(content_hash);
+
+-- each sighting's single media_path becomes its first attachment
+INSERT INTO media (sighting_id, path, mime_type)
+SELECT id, trim(media_path),
+       CASE lower(replace(trim(media_path), rtrim(trim(media_path), replace(trim(media_path), '.', '')), ''))
+           WHEN 'jpg' THEN 'image/jpeg'
+           WHEN 'jpeg' THEN 'image/jpeg'
+           WHEN 'png' THEN 'image/png'
+           WHEN 'gif' THEN 'image/gif'
+           WHEN 'webp' THEN 'image/webp'
+           WHEN 'heic' THEN 'image/heic'
+           WHEN 'tif' THEN 'image/tiff'
+           WHEN 'tiff' THEN 'image/tiff'
+           WHEN 'mp3' THEN 'audio/mpeg'
+           WHEN 'wav' THEN 'audio/wav'
+           WHEN 'm4a' THEN 'audio/mp4'
+           WHEN 'flac' THEN 'audio/flac'
+           WHEN 'ogg' THEN 'audio/ogg'
+           WHEN 'mp4' THEN 'video/mp4'
+           WHEN 'mov' THEN 'video/quicktime'
+           ELSE 'application/octet-stream'
+       END
+FROM sightings
+WHERE trim(COALESCE(media_path, '')) <> '';
+
+ALTER TABLE sightings DROP COLUMN media_path;
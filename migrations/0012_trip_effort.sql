@@ -0,0 +1,20 @@
+-- Checklist effort, so a trip's list can be used as survey data: when it started
+-- (HH:MM), for how many minutes, over how many kilometers, by how many people and
+-- following which eBird protocol, and whether every species detected was reported
+ALTER TABLE trips ADD COLUMN start_time TEXT
+    CHECK (start_time IS NULL OR (length(start_time) = 5 AND start_time GLOB '[0-2][0-9]:[0-5][0-9]' AND start_time < '24:00'));
+
+ALTER TABLE trips ADD COLUMN duration_min INTEGER
+    CHECK (duration_min IS NULL OR (typeof(duration_min) = 'integer' AND duration_min > 0));
+
+ALTER TABLE trips ADD COLUMN distance_km REAL
+    CHECK (distance_km IS NULL OR distance_km >= 0);
+
+ALTER TABLE trips ADD COLUMN party_size INTEGER
+    CHECK (party_size IS NULL OR (typeof(party_size) = 'integer' AND party_size > 0));
+
+ALTER TABLE trips ADD COLUMN protocol TEXT
+    CHECK (protocol IN ('stationary', 'traveling', 'incidental'));
+
+ALTER TABLE trips ADD COLUMN complete INTEGER NOT NULL DEFAULT 0
+    CHECK (complete IN (0, 1));
@@ -0,0 +1,135 @@
+-- Dates are stored as ISO 8601 text: YYYY, YYYY-MM, YYYY-MM-DD, or YYYY-MM-DDTHH:MM
+-- with an optional ±HH:MM offset, and a season as its months (spring 1998 is
+-- 1998-03/1998-05, winter 1998 is 1998-12/1999-02). Until now dates were stored as
+-- typed, so bring the formats seen in practice into that form first.
+UPDATE trips SET date = NULLIF(trim(date), '') WHERE date IS NOT NULL;
+UPDATE sightings SET date = NULLIF(trim(date), '') WHERE date IS NOT NULL;
+
+-- YYYY/MM/DD
+UPDATE trips SET date = replace(date, '/', '-')
+WHERE date GLOB '[0-9][0-9][0-9][0-9]/[0-9][0-9]/[0-9][0-9]';
+UPDATE sightings SET date = replace(date, '/', '-')
+WHERE date GLOB '[0-9][0-9][0-9][0-9]/[0-9][0-9]/[0-9][0-9]';
+
+-- MM-DD-YYYY and MM/DD/YYYY, as older eBird exports wrote them
+UPDATE trips SET date = substr(date, 7, 4) || '-' || substr(date, 1, 2) || '-' || substr(date, 4, 2)
+WHERE date GLOB '[0-9][0-9][-/][0-9][0-9][-/][0-9][0-9][0-9][0-9]';
+UPDATE sightings SET date = substr(date, 7, 4) || '-' || substr(date, 1, 2) || '-' || substr(date, 4, 2)
+WHERE date GLOB '[0-9][0-9][-/][0-9][0-9][-/][0-9][0-9][0-9][0-9]';
+
+-- A time after a space or with seconds, without an offset (or in UTC); kept to the minute
+UPDATE trips
+SET date = strftime('%Y-%m-%dT%H:%M', date) || CASE WHEN date GLOB '*Z' THEN '+00:00' ELSE '' END
+WHERE date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9][ T][0-9][0-9]:[0-9][0-9]*'
+  AND NOT substr(date, 12) GLOB '*[-+]*'
+  AND strftime('%Y-%m-%dT%H:%M', date) IS NOT NULL;
+UPDATE sightings
+SET date = strftime('%Y-%m-%dT%H:%M', date) || CASE WHEN date GLOB '*Z' THEN '+00:00' ELSE '' END
+WHERE date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9][ T][0-9][0-9]:[0-9][0-9]*'
+  AND NOT substr(date, 12) GLOB '*[-+]*'
+  AND strftime('%Y-%m-%dT%H:%M', date) IS NOT NULL;
+
+-- A time with an offset, after a space or with seconds (as ISO exporters write
+-- 2025-05-14T07:30:15-05:00); kept to the minute with its offset
+UPDATE trips
+SET date = substr(date, 1, 10) || 'T' || substr(date, 12, 5) || substr(date, -6)
+WHERE date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9][ T][0-9][0-9]:[0-9][0-9]*[-+][0-9][0-9]:[0-9][0-9]'
+  AND (length(date) = 22 OR (length(date) = 25 AND substr(date, 17, 1) = ':'));
+UPDATE sightings
+SET date = substr(date, 1, 10) || 'T' || substr(date, 12, 5) || substr(date, -6)
+WHERE date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9][ T][0-9][0-9]:[0-9][0-9]*[-+][0-9][0-9]:[0-9][0-9]'
+  AND (length(date) = 22 OR (length(date) = 25 AND substr(date, 17, 1) = ':'));
+
+-- Seasons written out, e.g. "Spring 1998" or "fall 2001"
+UPDATE trips
+SET date = CASE lower(substr(date, 1, length(date) - 5))
+    WHEN 'spring' THEN substr(date, -4) || '-03/' || substr(date, -4) || '-05'
+    WHEN 'summer' THEN substr(date, -4) || '-06/' || substr(date, -4) || '-08'
+    WHEN 'autumn' THEN substr(date, -4) || '-09/' || substr(date, -4) || '-11'
+    WHEN 'fall' THEN substr(date, -4) || '-09/' || substr(date, -4) || '-11'
+    WHEN 'winter' THEN substr(date, -4) || '-12/' || printf('%04d', substr(date, -4) + 1) || '-02'
+    ELSE date END
+WHERE date GLOB '[A-Za-z]* [0-9][0-9][0-9][0-9]';
+UPDATE sightings
+SET date = CASE lower(substr(date, 1, length(date) - 5))
+    WHEN 'spring' THEN substr(date, -4) || '-03/' || substr(date, -4) || '-05'
+    WHEN 'summer' THEN substr(date, -4) || '-06/' || substr(date, -4) || '-08'
+    WHEN 'autumn' THEN substr(date, -4) || '-09/' || substr(date, -4) || '-11'
+    WHEN 'fall' THEN substr(date, -4) || '-09/' || substr(date, -4) || '-11'
+    WHEN 'winter' THEN substr(date, -4) || '-12/' || printf('%04d', substr(date, -4) + 1) || '-02'
+    ELSE date END
+WHERE date GLOB '[A-Za-z]* [0-9][0-9][0-9][0-9]';
+
+-- How much of the date is known: year, season, month, day or time. NULL for a date
+-- that isn't in the stored form; UTC offsets run from -14:00 to +14:00.
+ALTER TABLE trips ADD COLUMN date_precision TEXT GENERATED ALWAYS AS (
+    CASE
+        WHEN date IS NULL THEN NULL
+        WHEN date GLOB '[0-9][0-9][0-9][0-9]' THEN 'year'
+        WHEN date GLOB '[0-9][0-9][0-9][0-9]-[01][0-9]' AND substr(date, 6, 2) BETWEEN '01' AND '12' THEN 'month'
+        WHEN date(date) IS date THEN 'day'
+        WHEN strftime('%Y-%m-%dT%H:%M', substr(date, 1, 16)) IS substr(date, 1, 16)
+             AND (length(date) = 16 OR (length(date) = 22 AND substr(date, 17) GLOB '[-+][01][0-9]:[0-5][0-9]'
+                  AND substr(date, 18, 5) <= '14:00')) THEN 'time'
+        WHEN date GLOB '[0-9][0-9][0-9][0-9]-[01][0-9]/[0-9][0-9][0-9][0-9]-[01][0-9]' AND date IN (
+            substr(date, 1, 4) || '-03/' || substr(date, 1, 4) || '-05',
+            substr(date, 1, 4) || '-06/' || substr(date, 1, 4) || '-08',
+            substr(date, 1, 4) || '-09/' || substr(date, 1, 4) || '-11',
+            substr(date, 1, 4) || '-12/' || printf('%04d', substr(date, 1, 4) + 1) || '-02'
+        ) THEN 'season'
+    END
+) VIRTUAL;
+
+ALTER TABLE sightings ADD COLUMN date_precision TEXT GENERATED ALWAYS AS (
+    CASE
+        WHEN date IS NULL THEN NULL
+        WHEN date GLOB '[0-9][0-9][0-9][0-9]' THEN 'year'
+        WHEN date GLOB '[0-9][0-9][0-9][0-9]-[01][0-9]' AND substr(date, 6, 2) BETWEEN '01' AND '12' THEN 'month'
+        WHEN date(date) IS date THEN 'day'
+        WHEN strftime('%Y-%m-%dT%H:%M', substr(date, 1, 16)) IS substr(date, 1, 16)
+             AND (length(date) = 16 OR (length(date) = 22 AND substr(date, 17) GLOB '[-+][01][0-9]:[0-5][0-9]'
+                  AND substr(date, 18, 5) <= '14:00')) THEN 'time'
+        WHEN date GLOB '[0-9][0-9][0-9][0-9]-[01][0-9]/[0-9][0-9][0-9][0-9]-[01][0-9]' AND date IN (
+            substr(date, 1, 4) || '-03/' || substr(date, 1, 4) || '-05',
+            substr(date, 1, 4) || '-06/' || substr(date, 1, 4) || '-08',
+            substr(date, 1, 4) || '-09/' || substr(date, 1, 4) || '-11',
+            substr(date, 1, 4) || '-12/' || printf('%04d', substr(date, 1, 4) + 1) || '-02'
+        ) THEN 'season'
+    END
+) VIRTUAL;
+
+-- Anything else ("yesterday", "2025-02-30") can't be read as a date. Keep what was
+-- typed in the notes rather than lose it, and clear the date.
+UPDATE trips
+SET notes = CASE WHEN COALESCE(notes, '') = '' THEN '' ELSE notes || char(10) END || 'Date: ' || date,
+    date = NULL
+WHERE date IS NOT NULL AND date_precision IS NULL;
+UPDATE sightings
+SET notes = CASE WHEN COALESCE(notes, '') = '' THEN '' ELSE notes || char(10) END || 'Date: ' || date,
+    date = NULL
+WHERE date IS NOT NULL AND date_precision IS NULL;
+
+-- From now on such dates are refused
+CREATE TRIGGER IF NOT EXISTS trg_trips_date_insert AFTER INSERT ON trips
+WHEN NEW.date IS NOT NULL AND NEW.date_precision IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'invalid trip date (expected YYYY, YYYY-MM, YYYY-MM-DD, YYYY-MM-DDTHH:MM[±HH:MM] or a season such as 1998-03/1998-05)');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_trips_date_update AFTER UPDATE OF date ON trips
+WHEN NEW.date IS NOT NULL AND NEW.date_precision IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'invalid trip date (expected YYYY, YYYY-MM, YYYY-MM-DD, YYYY-MM-DDTHH:MM[±HH:MM] or a season such as 1998-03/1998-05)');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sightings_date_insert AFTER INSERT ON sightings
+WHEN NEW.date IS NOT NULL AND NEW.date_precision IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'invalid sighting date (expected YYYY, YYYY-MM, YYYY-MM-DD, YYYY-MM-DDTHH:MM[±HH:MM] or a season such as 1998-03/1998-05)');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sightings_date_update AFTER UPDATE OF date ON sightings
+WHEN NEW.date IS NOT NULL AND NEW.date_precision IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'invalid sighting date (expected YYYY, YYYY-MM, YYYY-MM-DD, YYYY-MM-DDTHH:MM[±HH:MM] or a season such as 1998-03/1998-05)');
+END;
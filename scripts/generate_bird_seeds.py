@@ -33,8 +33,21 @@ def sql_value(val):
 def generate_parent_taxa(df_subset):
     """
     Generate unique parent taxa from species subset.
-    Returns: (orders_df, families_df, subfamilies_df, genera_df)
+    Returns: (kingdoms_df, phyla_df, classes_df, orders_df, families_df, subfamilies_df, genera_df)
     """
+    # Kingdoms, phyla and classes (common name = scientific name), so the tree has a root
+    kingdoms = df_subset[['kingdom']].drop_duplicates()
+    kingdoms['rank'] = 'kingdom'
+    kingdoms['common_name'] = kingdoms['kingdom']
+
+    phyla = df_subset[['kingdom', 'phylum']].drop_duplicates()
+    phyla['rank'] = 'phylum'
+    phyla['common_name'] = phyla['phylum']
+
+    classes = df_subset[['kingdom', 'phylum', 'class']].drop_duplicates()
+    classes['rank'] = 'class'
+    classes['common_name'] = classes['class']
+
     # Orders (with common name = order name)
     orders = df_subset[['kingdom', 'phylum', 'class', 'order']].drop_duplicates()
     orders['rank'] = 'order'
@@ -67,7 +80,7 @@ def generate_parent_taxa(df_subset):
     genera['species_epithet'] = None
     genera['common_name'] = genera['genus']
 
-    return orders, families, subfamilies, genera
+    return kingdoms, phyla, classes, orders, families, subfamilies, genera
 
 def generate_taxon_insert(row):
     """Generate SQL INSERT statement for a single taxon"""
@@ -88,13 +101,20 @@ def generate_taxon_insert(row):
 
 def write_taxa_sql(df_subset, output_path, description):
     """Write taxa SQL file with parent taxa + species"""
-    orders, families, subfamilies, genera = generate_parent_taxa(df_subset)
+    kingdoms, phyla, classes, orders, families, subfamilies, genera = generate_parent_taxa(df_subset)
 
     with open(output_path, 'w', encoding='utf-8') as f:
         f.write(f"-- {description}\n")
         f.write("-- Generated from NACC bird species list\n")
         f.write("-- DO NOT EDIT MANUALLY - regenerate using scripts/generate_bird_seeds.py\n\n")
 
+        # Insert kingdoms, phyla and classes
+        for heading, higher in [("Kingdoms", kingdoms), ("Phyla", phyla), ("Classes", classes)]:
+            f.write(f"-- {heading}\n")
+            for _, row in higher.iterrows():
+                f.write(generate_taxon_insert(row) + "\n")
+            f.write("\n")
+
         # Insert orders
         f.write("-- Orders\n")
         for _, row in orders.iterrows():
@@ -122,12 +142,13 @@ def write_taxa_sql(df_subset, output_path, description):
             f.write(generate_taxon_insert(row) + "\n")
 
     print(f"  Wrote {output_path}")
+    print(f"    - {len(kingdoms) + len(phyla) + len(classes)} kingdoms, phyla and classes")
     print(f"    - {len(orders)} orders")
     print(f"    - {len(families)} families")
     print(f"    - {len(subfamilies)} subfamilies")
     print(f"    - {len(genera)} genera")
     print(f"    - {len(df_subset)} species")
-    print(f"    Total: {len(orders) + len(families) + len(subfamilies) + len(genera) + len(df_subset)} taxa")
+    print(f"    Total: {len(kingdoms) + len(phyla) + len(classes) + len(orders) + len(families) + len(subfamilies) + len(genera) + len(df_subset)} taxa")
 
 def generate_sightings_sql(df_first_100, output_path):
     """Generate sample sightings using birds from first 100 species"""
@@ -148,13 +169,13 @@ def generate_sightings_sql(df_first_100, output_path):
             epithet = bird['species_epithet']
             f.write(f"""INSERT OR IGNORE INTO sightings (
     trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name,
-    notes, media_path, date, location
+    notes, date, location
 ) VALUES (
     {trip_id},
     (SELECT id FROM taxa WHERE rank='species' AND genus='{bird['genus']}' AND species_epithet='{epithet}'),
     '{bird['kingdom']}', '{bird['phylum']}', '{bird['class']}', '{bird['order']}', '{bird['family']}',
     {sql_value(bird['subfamily'])}, '{bird['genus']}', '{epithet}', {sql_value(bird['common_name'])},
-    'Observed during field trip', NULL, '2025-10-0{((i - 1) % 9) + 1}', 'Field location {i}'
+    'Observed during field trip', '2025-10-0{((i - 1) % 9) + 1}', 'Field location {i}'
 );
 """)
 
@@ -165,13 +186,13 @@ def generate_sightings_sql(df_first_100, output_path):
             epithet = bird['species_epithet']
             f.write(f"""INSERT OR IGNORE INTO sightings (
     trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name,
-    notes, media_path, date, location
+    notes, date, location
 ) VALUES (
     NULL,
     (SELECT id FROM taxa WHERE rank='species' AND genus='{bird['genus']}' AND species_epithet='{epithet}'),
     '{bird['kingdom']}', '{bird['phylum']}', '{bird['class']}', '{bird['order']}', '{bird['family']}',
     {sql_value(bird['subfamily'])}, '{bird['genus']}', '{epithet}', {sql_value(bird['common_name'])},
-    'Backyard observation', NULL, '2025-09-{10 + i}', 'Home backyard'
+    'Backyard observation', '2025-09-{10 + i}', 'Home backyard'
 );
 """)
 
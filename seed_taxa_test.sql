@@ -2,6 +2,54 @@
 -- Generated from NACC bird species list
 -- DO NOT EDIT MANUALLY - regenerate using scripts/generate_bird_seeds.py
 
+-- Kingdoms
+INSERT OR IGNORE INTO taxa (
+    rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
+) VALUES (
+    'kingdom',
+    'Animalia',
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    'Animalia'
+);
+
+-- Phyla
+INSERT OR IGNORE INTO taxa (
+    rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
+) VALUES (
+    'phylum',
+    'Animalia',
+    'Chordata',
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    'Chordata'
+);
+
+-- Classes
+INSERT OR IGNORE INTO taxa (
+    rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
+) VALUES (
+    'class',
+    'Animalia',
+    'Chordata',
+    'Aves',
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    'Aves'
+);
+
 -- Orders
 INSERT OR IGNORE INTO taxa (
     rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
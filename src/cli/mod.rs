@@ -1,52 +1,233 @@
+use crate::models::{BreedingCode, Coordinates, Count, ObservedDate, Protocol, Rank, parse_start_time};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "fast_watcher", version, about = "Offline watching CLI")]
 pub struct Cli {
+    /// Database file (default: $FASTWATCHER_DB, then fastwatcher/fastwatcher.db in the XDG data dir)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub db: Option<PathBuf>,
+
+    /// Run without a command to open the GUI
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Search for sightings
+    /// Search sightings, trips, taxa and places at once (e.g. "trip:owl date:2025 location:park")
+    Search { query: String },
+
+    /// Search for sightings (supports field filters, date ranges and negation)
     SearchSightings { query: String },
 
-    /// Search for trips
+    /// Search for trips (supports field filters, date ranges and negation)
     SearchTrips { query: String },
 
-    /// Search for taxa
+    /// Search for taxa (supports field filters and negation)
     SearchTaxa { query: String },
 
+    /// Search for places (supports county:, state:, country: and near: filters)
+    SearchLocations { query: String },
+
+    /// List trips and sightings within a distance of a point, nearest first
+    Near {
+        /// LAT,LON,RADIUS with the radius in km, or meters with an "m" suffix (e.g. 40.78,-73.97,500m)
+        #[arg(allow_hyphen_values = true)]
+        radius: String,
+    },
+
+    /// List trips and sightings inside a latitude/longitude box
+    InBox {
+        /// SOUTH,WEST,NORTH,EAST in degrees
+        #[arg(allow_hyphen_values = true)]
+        bounds: String,
+    },
+
     /// Initialize the database and seed initial data
     InitDb,
 
     /// Drop all tables in the database (use with caution!)
     DropDb,
 
+    /// Import an eBird "My Data" CSV export (one trip per checklist)
+    ImportEbird {
+        /// Path to the CSV file (MyEBirdData.csv)
+        file: PathBuf,
+
+        /// Report what would be imported without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Export sightings as eBird Record Format CSV (one checklist per trip, date and location)
+    ExportEbird {
+        /// Only sightings from this trip ID
+        #[arg(long)]
+        trip: Option<i64>,
+
+        /// Only sightings of this taxon ID or its descendants
+        #[arg(long)]
+        taxon: Option<i64>,
+
+        /// Only sightings in this date range: FROM..TO, FROM.., ..TO or a single date
+        #[arg(long, value_name = "RANGE")]
+        date: Option<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// List every species seen, in taxonomic order
+    LifeList,
+
+    /// List the species seen in a year (default: this year), marking new ones
+    YearList {
+        /// Year as YYYY
+        year: Option<String>,
+    },
+
+    /// Export all trips and sightings as a zipped Darwin Core Archive
+    ExportDwca {
+        /// Path of the archive to write (e.g. fastwatcher-dwca.zip)
+        output: PathBuf,
+    },
+
+    /// Import occurrences from a zipped Darwin Core Archive
+    ImportDwca {
+        /// Path to the archive
+        file: PathBuf,
+
+        /// Report what would be imported without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Make trips and sightings from a folder of photos, using their EXIF date and GPS
+    ImportPhotos {
+        /// Directory of photos (searched recursively)
+        dir: PathBuf,
+
+        /// Hours between photos that start a new trip
+        #[arg(long, value_name = "HOURS", default_value_t = 3.0)]
+        gap: f64,
+
+        /// Location to give each trip
+        #[arg(short, long)]
+        location: Option<String>,
+
+        /// Ask for the taxon of each photo that has none
+        #[arg(short, long)]
+        interactive: bool,
+
+        /// Report what would be imported without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Apply pending schema migrations (every command also does this on connect)
+    Migrate {
+        /// Only report the schema version and pending migrations
+        #[arg(long)]
+        status: bool,
+    },
+
+    /// Find sightings whose copied taxonomy no longer matches their taxon and repair them
+    ResyncDenormalized {
+        /// Only report drifted sightings, don't repair them
+        #[arg(long)]
+        check: bool,
+    },
+
     // Trip commands
     /// Add a new trip
     AddTrip {
         name: String,
+        /// YYYY-MM-DD, YYYY-MM, YYYY or a season ("spring 1998"), optionally with a time ("2025-05-14 07:30")
         #[arg(short, long)]
-        date: Option<String>,
+        date: Option<ObservedDate>,
         #[arg(short, long)]
         location: Option<String>,
         #[arg(short, long)]
         notes: Option<String>,
+        /// Where the trip was: LAT,LON or LAT,LON,ACCURACY_M
+        #[arg(long, allow_hyphen_values = true)]
+        coords: Option<Coordinates>,
+        /// Start time, e.g. 07:30 or "7:30 PM"
+        #[arg(long, value_parser = parse_start_time)]
+        start: Option<String>,
+        /// How long the birding lasted, in minutes
+        #[arg(long, value_name = "MINUTES")]
+        duration: Option<u32>,
+        /// Distance covered, in kilometers
+        #[arg(long, value_name = "KM")]
+        distance: Option<f64>,
+        /// Number of people birding together
+        #[arg(long)]
+        party_size: Option<u32>,
+        /// stationary, traveling or incidental
+        #[arg(long)]
+        protocol: Option<Protocol>,
+        /// Every species detected was reported (a complete checklist)
+        #[arg(long)]
+        complete: bool,
     },
 
     /// Show trip details by ID
     ShowTrip { id: i64 },
 
+    /// Edit a trip by ID (only the given fields change; pass "" to clear one)
+    EditTrip {
+        id: i64,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(short, long)]
+        date: Option<String>,
+        /// Location name; a new location is created if none matches
+        #[arg(short, long, conflicts_with = "location_id")]
+        location: Option<String>,
+        /// Move the trip to this location ID
+        #[arg(long)]
+        location_id: Option<i64>,
+        #[arg(short, long)]
+        notes: Option<String>,
+        /// Where the trip was: LAT,LON or LAT,LON,ACCURACY_M
+        #[arg(long, allow_hyphen_values = true)]
+        coords: Option<String>,
+        /// Start time, e.g. 07:30 or "7:30 PM"
+        #[arg(long)]
+        start: Option<String>,
+        /// How long the birding lasted, in minutes
+        #[arg(long, value_name = "MINUTES")]
+        duration: Option<String>,
+        /// Distance covered, in kilometers
+        #[arg(long, value_name = "KM")]
+        distance: Option<String>,
+        /// Number of people birding together
+        #[arg(long)]
+        party_size: Option<String>,
+        /// stationary, traveling or incidental
+        #[arg(long)]
+        protocol: Option<String>,
+        /// Mark the trip as a complete checklist
+        #[arg(long, conflicts_with = "incomplete")]
+        complete: bool,
+        /// Mark the trip as not a complete checklist
+        #[arg(long)]
+        incomplete: bool,
+    },
+
     /// Delete a trip by ID
     DeleteTrip { id: i64 },
 
     // Taxon commands
     /// Add a new taxon
     AddTaxon {
-        /// Taxonomic rank (kingdom, phylum, class, order, family, subfamily, genus, species)
-        rank: String,
+        /// Taxonomic rank (kingdom, phylum, class, order, family, subfamily, genus, species,
+        /// subspecies) or identification category (hybrid, slash, spuh)
+        rank: Rank,
         /// Kingdom (required)
         kingdom: String,
         /// Common name
@@ -63,13 +244,44 @@ pub enum Commands {
         subfamily: Option<String>,
         #[arg(long)]
         genus: Option<String>,
+        /// Species epithet; "a/b" for a slash, "a x b" for a hybrid
         #[arg(long)]
         species_epithet: Option<String>,
+        #[arg(long)]
+        subspecies: Option<String>,
     },
 
     /// Show taxon details by ID
     ShowTaxon { id: i64 },
 
+    /// Edit a taxon by ID (only the given fields change; pass "" to clear an optional rank)
+    EditTaxon {
+        id: i64,
+        #[arg(long)]
+        rank: Option<Rank>,
+        #[arg(long)]
+        kingdom: Option<String>,
+        #[arg(long)]
+        common_name: Option<String>,
+        #[arg(long)]
+        phylum: Option<String>,
+        #[arg(long)]
+        class: Option<String>,
+        #[arg(long)]
+        order: Option<String>,
+        #[arg(long)]
+        family: Option<String>,
+        #[arg(long)]
+        subfamily: Option<String>,
+        #[arg(long)]
+        genus: Option<String>,
+        /// Species epithet; "a/b" for a slash, "a x b" for a hybrid
+        #[arg(long)]
+        species_epithet: Option<String>,
+        #[arg(long)]
+        subspecies: Option<String>,
+    },
+
     /// Delete a taxon by ID
     DeleteTaxon { id: i64 },
 
@@ -81,17 +293,157 @@ pub enum Commands {
         taxon_id: i64,
         #[arg(short, long)]
         notes: Option<String>,
+        /// Photo or recording to attach (the file must exist)
         #[arg(short, long)]
         media_path: Option<String>,
+        /// YYYY-MM-DD, YYYY-MM, YYYY or a season ("spring 1998"), optionally with a time ("2025-05-14 07:30")
         #[arg(short, long)]
-        date: Option<String>,
+        date: Option<ObservedDate>,
         #[arg(short, long)]
         location: Option<String>,
+        /// Number of birds, or X for present but not counted
+        #[arg(long)]
+        count: Option<Count>,
+        /// Sex/age breakdown, e.g. "2 male adult, 1 female, 1 juvenile"
+        #[arg(long)]
+        age_sex: Option<String>,
+        /// eBird breeding code, e.g. FY
+        #[arg(long)]
+        breeding: Option<BreedingCode>,
+        /// Where the bird was, if not at the trip's coordinates: LAT,LON or LAT,LON,ACCURACY_M
+        #[arg(long, allow_hyphen_values = true)]
+        coords: Option<Coordinates>,
     },
 
     /// Show sighting details by ID
     ShowSighting { id: i64 },
 
+    /// Edit a sighting by ID (only the given fields change; pass "" to clear one)
+    EditSighting {
+        id: i64,
+        #[arg(short, long, conflicts_with = "no_trip")]
+        trip_id: Option<i64>,
+        /// Detach the sighting from its trip
+        #[arg(long)]
+        no_trip: bool,
+        /// Re-identify the sighting as another taxon
+        #[arg(long)]
+        taxon_id: Option<i64>,
+        #[arg(short, long)]
+        notes: Option<String>,
+        #[arg(short, long)]
+        date: Option<String>,
+        /// Location name; a new location is created if none matches
+        #[arg(short, long, conflicts_with = "location_id")]
+        location: Option<String>,
+        /// Move the sighting to this location ID
+        #[arg(long)]
+        location_id: Option<i64>,
+        /// Number of birds, or X for present but not counted
+        #[arg(long)]
+        count: Option<String>,
+        /// Sex/age breakdown, e.g. "2 male adult, 1 female, 1 juvenile"
+        #[arg(long)]
+        age_sex: Option<String>,
+        /// eBird breeding code, e.g. FY
+        #[arg(long)]
+        breeding: Option<String>,
+        /// Where the bird was, if not at the trip's coordinates: LAT,LON or LAT,LON,ACCURACY_M
+        #[arg(long, allow_hyphen_values = true)]
+        coords: Option<String>,
+    },
+
     /// Delete a sighting by ID
     DeleteSighting { id: i64 },
+
+    // Media commands
+    /// Attach a photo, recording or video to a sighting or trip
+    AddMedia {
+        /// Path to the file (it must exist)
+        path: String,
+        #[arg(long, required_unless_present = "trip", conflicts_with = "trip")]
+        sighting: Option<i64>,
+        #[arg(long)]
+        trip: Option<i64>,
+        #[arg(long)]
+        caption: Option<String>,
+        /// When it was taken, e.g. "2025-05-01 07:30" (default: the file's modification time)
+        #[arg(long)]
+        captured: Option<String>,
+    },
+
+    /// Detach a media file by ID (the file itself is kept)
+    RemoveMedia { id: i64 },
+
+    /// Point a media record at the file's new location
+    MoveMedia { id: i64, path: String },
+
+    /// Rewrite the paths of all media under one directory to be under another
+    MoveMediaDir { from: String, to: String },
+
+    /// Report attached files that are missing, moved or changed
+    CheckMedia {
+        /// Directory to look for missing files in (repeatable)
+        #[arg(long = "search")]
+        search: Vec<PathBuf>,
+        /// Point moved files' records at where they were found
+        #[arg(long)]
+        fix: bool,
+    },
+
+    // Location commands
+    /// Add a new location
+    AddLocation {
+        /// The place itself, e.g. "Central Park"
+        locality: String,
+        #[arg(long)]
+        county: Option<String>,
+        #[arg(long)]
+        state: Option<String>,
+        #[arg(long)]
+        country: Option<String>,
+        /// LAT,LON or LAT,LON,ACCURACY_M
+        #[arg(long, allow_hyphen_values = true)]
+        coords: Option<Coordinates>,
+        /// Mark the location as a shared hotspot
+        #[arg(long)]
+        hotspot: bool,
+    },
+
+    /// Show a location with its trips and sightings
+    ShowLocation { id: i64 },
+
+    /// List all locations
+    ListLocations,
+
+    /// Edit a location by ID (only the given fields change; pass "" to clear one)
+    EditLocation {
+        id: i64,
+        #[arg(long)]
+        locality: Option<String>,
+        #[arg(long)]
+        county: Option<String>,
+        #[arg(long)]
+        state: Option<String>,
+        #[arg(long)]
+        country: Option<String>,
+        /// LAT,LON or LAT,LON,ACCURACY_M
+        #[arg(long, allow_hyphen_values = true)]
+        coords: Option<String>,
+        /// Whether the location is a shared hotspot
+        #[arg(long)]
+        hotspot: Option<bool>,
+    },
+
+    /// Delete a location by ID (its trips and sightings keep the name as text)
+    DeleteLocation { id: i64 },
+
+    /// Move the trips and sightings of duplicate locations to one location and delete the duplicates
+    MergeLocations {
+        /// Location ID to keep
+        keep: i64,
+        /// Location IDs to fold into it
+        #[arg(required = true)]
+        duplicates: Vec<i64>,
+    },
 }
@@ -1,11 +1,68 @@
-use anyhow::Result;
-use rusqlite::Connection;
+use crate::core::migrations::migrate;
+use crate::models::{Coordinates, ObservedDate};
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, Row, ToSql};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef};
 use std::fs;
+use std::path::{Path, PathBuf};
 
-/// Connects (or creates) the database file and ensures all tables exist.
-/// It loads and executes the bundled init.sql schema.
-pub fn connect() -> Result<Connection> {
-    let conn = Connection::open("fastwatcher.db")?;
+/// Environment variable that overrides the default database location
+pub const DB_ENV_VAR: &str = "FASTWATCHER_DB";
+
+const DB_FILE_NAME: &str = "fastwatcher.db";
+const DATA_DIR_NAME: &str = "fastwatcher";
+
+/// Picks the database file: the `--db` flag, then `$FASTWATCHER_DB`, then
+/// `fastwatcher.db` in the XDG data directory (`$XDG_DATA_HOME` or `~/.local/share`).
+pub fn resolve_db_path(flag: Option<&Path>) -> Result<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).map(PathBuf::from);
+    db_path_from(flag, var(DB_ENV_VAR), var("XDG_DATA_HOME"), var("HOME"))
+}
+
+fn db_path_from(
+    flag: Option<&Path>,
+    env_db: Option<PathBuf>,
+    xdg_data_home: Option<PathBuf>,
+    home: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let non_empty = |path: Option<PathBuf>| path.filter(|p| !p.as_os_str().is_empty());
+
+    if let Some(path) = flag {
+        return Ok(path.to_path_buf());
+    }
+    if let Some(path) = non_empty(env_db) {
+        return Ok(path);
+    }
+
+    // the XDG spec says relative values are invalid and should be ignored
+    let data_home = match non_empty(xdg_data_home).filter(|p| p.is_absolute()) {
+        Some(dir) => dir,
+        None => match non_empty(home) {
+            Some(home) => home.join(".local").join("share"),
+            None => bail!("could not find a data directory; pass --db or set {}", DB_ENV_VAR),
+        },
+    };
+
+    Ok(data_home.join(DATA_DIR_NAME).join(DB_FILE_NAME))
+}
+
+/// Connects to (or creates) the database file at `path` and upgrades its schema
+/// to the latest version (see [`crate::core::migrations`]).
+pub fn connect(path: &Path) -> Result<Connection> {
+    let conn = open(path)?;
+    migrate(&conn)?;
+    Ok(conn)
+}
+
+/// Opens the database file at `path` without touching its schema, creating its directory if needed.
+pub fn open(path: &Path) -> Result<Connection> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create database directory {}", dir.display()))?;
+    }
+
+    let conn = Connection::open(path)
+        .with_context(|| format!("Failed to open database {}", path.display()))?;
 
     // Enable write-ahead logging and foreign keys for performance and integrity
     conn.pragma_update(None, "journal_mode", "WAL")?;
@@ -14,6 +71,99 @@ pub fn connect() -> Result<Connection> {
     Ok(conn)
 }
 
+/// Converts an edited text field into a column value; an empty string clears it.
+pub fn optional_text(value: &str) -> Value {
+    if value.is_empty() {
+        Value::Null
+    } else {
+        Value::from(value.to_string())
+    }
+}
+
+/// Converts an edited date into its stored form; an empty string clears it.
+pub fn optional_date(value: &str) -> Result<Value> {
+    if value.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    Ok(Value::from(value.parse::<ObservedDate>()?.to_iso()))
+}
+
+/// Parses an optional date for insertion
+pub fn parse_date(value: Option<&str>) -> Result<Option<ObservedDate>> {
+    value.map(str::parse).transpose()
+}
+
+impl ToSql for ObservedDate {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_iso()))
+    }
+}
+
+impl FromSql for ObservedDate {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e: anyhow::Error| FromSqlError::Other(e.into()))
+    }
+}
+
+/// Latitude, longitude and accuracy columns for optional coordinates
+pub fn coordinate_values(coordinates: Option<Coordinates>) -> (Option<f64>, Option<f64>, Option<f64>) {
+    match coordinates {
+        Some(point) => (Some(point.latitude), Some(point.longitude), point.accuracy_m),
+        None => (None, None, None),
+    }
+}
+
+/// One page of a longer list, with the length of the whole list
+#[derive(Debug)]
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+}
+
+impl<T> Paged<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Paged<U> {
+        Paged { items: self.items.into_iter().map(f).collect(), total: self.total }
+    }
+}
+
+/// Run a list query (`sql`, bound to `params` as ?1, ?2, …) for `limit` rows starting
+/// `offset` rows in, and count the rows in the whole list
+pub fn query_page<T>(
+    conn: &Connection,
+    sql: &str,
+    params: &[&dyn ToSql],
+    limit: usize,
+    offset: usize,
+    map: impl FnMut(&Row) -> rusqlite::Result<T>,
+) -> Result<Paged<T>> {
+    let total: i64 = conn
+        .query_row(&format!("SELECT COUNT(*) FROM ({})", sql), params, |row| row.get(0))
+        .context("Failed to count list rows")?;
+
+    let window = [limit.min(i64::MAX as usize) as i64, offset as i64];
+    let page_params: Vec<&dyn ToSql> = params.iter().copied().chain(window.iter().map(|n| n as &dyn ToSql)).collect();
+    let page_sql = format!("{} LIMIT ?{} OFFSET ?{}", sql, params.len() + 1, params.len() + 2);
+
+    let mut stmt = conn.prepare(&page_sql)
+        .context("Failed to prepare list page query")?;
+    let rows = stmt.query_map(page_params.as_slice(), map)
+        .context("Failed to execute list page query")?;
+    let items = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse list rows")?;
+
+    Ok(Paged { items, total: total as usize })
+}
+
+/// Coordinates from the latitude, longitude and accuracy columns starting at `index`
+pub fn coordinates_at(row: &Row, index: usize) -> rusqlite::Result<Option<Coordinates>> {
+    let latitude: Option<f64> = row.get(index)?;
+    let longitude: Option<f64> = row.get(index + 1)?;
+    Ok(match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => Some(Coordinates { latitude, longitude, accuracy_m: row.get(index + 2)? }),
+        _ => None,
+    })
+}
+
 pub fn execute_sql_file(conn: &Connection, path: &str) -> Result<()> {
     let sql = fs::read_to_string(path)?;
     conn.execute_batch(&sql)?;
@@ -21,12 +171,94 @@ pub fn execute_sql_file(conn: &Connection, path: &str) -> Result<()> {
 }
 
 /// Drop all tables in the database. Use with caution!
+/// The schema version is reset, so the next `connect` recreates everything.
 pub fn drop_all_tables(conn: &Connection) -> Result<()> {
     let sql = r#"
+        DROP TABLE IF EXISTS sightings_fts;
+        DROP TABLE IF EXISTS trips_fts;
+        DROP TABLE IF EXISTS taxa_fts;
+        DROP TABLE IF EXISTS locations_fts;
+        DROP TABLE IF EXISTS sightings_geo;
+        DROP TABLE IF EXISTS trips_geo;
+        DROP TABLE IF EXISTS locations_geo;
+        DROP TABLE IF EXISTS ebird_checklists;
+        DROP TABLE IF EXISTS dwc_occurrences;
+        DROP TABLE IF EXISTS media;
         DROP TABLE IF EXISTS sightings;
         DROP TABLE IF EXISTS trips;
+        DROP TABLE IF EXISTS locations;
         DROP TABLE IF EXISTS taxa;
+        PRAGMA user_version = 0;
     "#;
     conn.execute_batch(sql)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_db_path_precedence() {
+        let flag = PathBuf::from("flag.db");
+        let env = Some(PathBuf::from("/tmp/env.db"));
+        let xdg = Some(PathBuf::from("/data"));
+        let home = Some(PathBuf::from("/home/birder"));
+
+        assert_eq!(db_path_from(Some(&flag), env.clone(), xdg.clone(), home.clone()).unwrap(), flag);
+        assert_eq!(db_path_from(None, env.clone(), xdg.clone(), home.clone()).unwrap(), PathBuf::from("/tmp/env.db"));
+        assert_eq!(
+            db_path_from(None, None, xdg.clone(), home.clone()).unwrap(),
+            PathBuf::from("/data/fastwatcher/fastwatcher.db")
+        );
+        assert_eq!(
+            db_path_from(None, None, None, home.clone()).unwrap(),
+            PathBuf::from("/home/birder/.local/share/fastwatcher/fastwatcher.db")
+        );
+    }
+
+    #[test]
+    fn test_db_path_ignores_empty_and_relative_values() {
+        let home = Some(PathBuf::from("/home/birder"));
+        let expected = PathBuf::from("/home/birder/.local/share/fastwatcher/fastwatcher.db");
+
+        assert_eq!(db_path_from(None, Some(PathBuf::new()), Some(PathBuf::new()), home.clone()).unwrap(), expected);
+        assert_eq!(db_path_from(None, None, Some(PathBuf::from("relative/data")), home).unwrap(), expected);
+        assert!(db_path_from(None, None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_connect_creates_missing_directories() {
+        let dir = std::env::temp_dir().join(format!("fastwatcher-test-{}", std::process::id()));
+        let path = dir.join("nested").join("test.db");
+
+        let conn = connect(&path).unwrap();
+        conn.execute_batch("CREATE TABLE t (id INTEGER)").unwrap();
+        assert!(path.exists());
+
+        drop(conn);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_connect_migrates_and_drop_resets_version() {
+        use crate::core::migrations::{latest_version, schema_version};
+
+        let dir = std::env::temp_dir().join(format!("fastwatcher-drop-test-{}", std::process::id()));
+        let path = dir.join("test.db");
+
+        let conn = connect(&path).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+
+        drop_all_tables(&conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        drop(conn);
+
+        let conn = connect(&path).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        conn.execute("INSERT INTO trips (name) VALUES ('After reset')", []).unwrap();
+
+        drop(conn);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1240 @@
+use crate::core::ebird::UnmatchedName;
+use crate::core::media::{MediaFile, attach_media, get_all_media};
+use crate::core::sighting::{create_sighting, get_all_sightings};
+use crate::core::taxon::{find_taxon_by_scientific_name, get_taxon_by_id};
+use crate::core::trip::{create_trip, get_all_trips};
+use crate::models::{
+    Age, AgeSexCount, BreedingCode, Coordinates, Count, Effort, MediaOwner, ObservedDate, Protocol, Rank, Sex, Sighting, Taxon, Trip,
+    parse_age_sex,
+    parse_start_time,
+};
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+const DWC_TERMS: &str = "http://rs.tdwg.org/dwc/terms/";
+
+/// Columns of event.txt; the first is the core id
+const EVENT_TERMS: &[&str] = &[
+    "eventID",
+    "eventType",
+    "eventDate",
+    "eventTime",
+    "locality",
+    "decimalLatitude",
+    "decimalLongitude",
+    "coordinateUncertaintyInMeters",
+    "eventRemarks",
+    "fieldNotes",
+    "samplingProtocol",
+    "samplingEffort",
+];
+
+/// Columns of occurrence.txt; the first links each occurrence to its event
+const OCCURRENCE_TERMS: &[&str] = &[
+    "eventID",
+    "occurrenceID",
+    "basisOfRecord",
+    "eventDate",
+    "scientificName",
+    "taxonRank",
+    "higherClassification",
+    "kingdom",
+    "phylum",
+    "class",
+    "order",
+    "family",
+    "subfamily",
+    "genus",
+    "specificEpithet",
+    "infraspecificEpithet",
+    "vernacularName",
+    "locality",
+    "decimalLatitude",
+    "decimalLongitude",
+    "coordinateUncertaintyInMeters",
+    "individualCount",
+    "sex",
+    "lifeStage",
+    "behavior",
+    "occurrenceRemarks",
+    "associatedMedia",
+];
+
+/// How `samplingEffort` marks a complete checklist
+const ALL_SPECIES_REPORTED: &str = "all species reported";
+
+/// Event type for sightings made outside a trip; the importer doesn't turn these into trips
+const OBSERVATION_EVENT: &str = "Observation";
+
+/// Outcome of a Darwin Core Archive export
+#[derive(Debug, Default)]
+pub struct DwcExportReport {
+    pub events: usize,
+    pub occurrences: usize,
+}
+
+/// Outcome of a Darwin Core Archive import (or what a dry run would do)
+#[derive(Debug, Default)]
+pub struct DwcImportReport {
+    pub trips_created: usize,
+    pub sightings_created: usize,
+    /// Occurrences skipped because an earlier import already created their sighting
+    pub already_imported: usize,
+    /// Names with no matching taxon; their occurrences are skipped
+    pub unmatched: Vec<UnmatchedName>,
+}
+
+/// Export the whole database as a Darwin Core Archive file; see [`export_dwca`]
+pub fn export_dwca_file(conn: &Connection, path: &Path) -> Result<DwcExportReport> {
+    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    export_dwca(conn, file)
+}
+
+/// Write a zipped Darwin Core Archive: event.txt (Event core) holds one event per trip,
+/// plus one per sighting made outside a trip, and occurrence.txt holds the sightings
+/// with their taxonomy taken from the denormalized columns. meta.xml describes both.
+pub fn export_dwca<W: Write + Seek>(conn: &Connection, writer: W) -> Result<DwcExportReport> {
+    let trips = get_all_trips(conn)?;
+    let sightings = get_all_sightings(conn)?;
+    let trips_by_id: HashMap<i64, &Trip> = trips.iter().map(|trip| (trip.id, trip)).collect();
+    // Sightings copy their taxon's names but not its rank or subspecies
+    let mut taxa: HashMap<i64, Taxon> = HashMap::new();
+    for sighting in &sightings {
+        if let Entry::Vacant(entry) = taxa.entry(sighting.taxon_id) {
+            entry.insert(get_taxon_by_id(conn, sighting.taxon_id)?);
+        }
+    }
+    let mut media_by_sighting: HashMap<i64, Vec<String>> = HashMap::new();
+    for media in get_all_media(conn)? {
+        if let MediaOwner::Sighting(sighting_id) = media.owner {
+            media_by_sighting.entry(sighting_id).or_default().push(media.path);
+        }
+    }
+
+    let mut events: Vec<Vec<String>> = trips
+        .iter()
+        .map(|trip| {
+            let [latitude, longitude, uncertainty] = coordinate_columns(trip.coordinates);
+            vec![
+                trip_event_id(trip.id),
+                "Survey".to_string(),
+                iso_date(trip.date),
+                text(&trip.effort.start_time),
+                text(&trip.location),
+                latitude,
+                longitude,
+                uncertainty,
+                trip.name.clone(),
+                text(&trip.notes),
+                trip.effort.protocol.map(|protocol| protocol.to_string()).unwrap_or_default(),
+                sampling_effort(&trip.effort),
+            ]
+        })
+        .collect();
+
+    let mut occurrences = Vec::new();
+    for sighting in &sightings {
+        let trip = sighting.trip_id.and_then(|trip_id| trips_by_id.get(&trip_id).copied());
+        let event_id = match trip {
+            Some(trip) => trip_event_id(trip.id),
+            None => {
+                let event_id = format!("urn:fastwatcher:observation:{}", sighting.id);
+                let [latitude, longitude, uncertainty] = coordinate_columns(sighting.coordinates);
+                events.push(vec![
+                    event_id.clone(),
+                    OBSERVATION_EVENT.to_string(),
+                    iso_date(sighting.date),
+                    String::new(),
+                    text(&sighting.location),
+                    latitude,
+                    longitude,
+                    uncertainty,
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ]);
+                event_id
+            }
+        };
+        let media = media_by_sighting.get(&sighting.id).map(Vec::as_slice).unwrap_or_default();
+        occurrences.push(occurrence_row(sighting, &taxa[&sighting.taxon_id], trip, event_id, media));
+    }
+
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("meta.xml", options).context("Failed to write meta.xml")?;
+    zip.write_all(meta_xml().as_bytes()).context("Failed to write meta.xml")?;
+
+    zip.start_file("event.txt", options).context("Failed to write event.txt")?;
+    write_table(&mut zip, EVENT_TERMS, &events).context("Failed to write event.txt")?;
+
+    zip.start_file("occurrence.txt", options).context("Failed to write occurrence.txt")?;
+    write_table(&mut zip, OCCURRENCE_TERMS, &occurrences).context("Failed to write occurrence.txt")?;
+
+    zip.finish().context("Failed to finish archive")?;
+
+    Ok(DwcExportReport { events: events.len(), occurrences: occurrences.len() })
+}
+
+fn trip_event_id(trip_id: i64) -> String {
+    format!("urn:fastwatcher:trip:{}", trip_id)
+}
+
+fn occurrence_row(sighting: &Sighting, taxon: &Taxon, trip: Option<&Trip>, event_id: String, media: &[String]) -> Vec<String> {
+    let (rank, scientific_name, higher) = classification(sighting, taxon);
+    // a slash's or hybrid's "epithet" names several species, so it isn't one
+    let epithet = matches!(taxon.rank, Rank::Species | Rank::Subspecies).then(|| sighting.species_epithet.clone()).flatten();
+    let infraspecific = (taxon.rank == Rank::Subspecies).then(|| taxon.subspecies.clone()).flatten();
+    let date = sighting.date.or(trip.and_then(|trip| trip.date));
+    let locality = sighting.location.as_ref().or(trip.and_then(|trip| trip.location.as_ref()));
+    let [latitude, longitude, uncertainty] = coordinate_columns(sighting.coordinates.or(trip.and_then(|trip| trip.coordinates)));
+
+    vec![
+        event_id,
+        format!("urn:fastwatcher:sighting:{}", sighting.id),
+        "HumanObservation".to_string(),
+        iso_date(date),
+        scientific_name,
+        rank.to_string(),
+        higher.join(" | "),
+        sighting.kingdom.clone(),
+        text(&sighting.phylum),
+        text(&sighting.class),
+        text(&sighting.order),
+        text(&sighting.family),
+        text(&sighting.subfamily),
+        text(&sighting.genus),
+        text(&epithet),
+        text(&infraspecific),
+        sighting.common_name.clone(),
+        text(&locality.cloned()),
+        latitude,
+        longitude,
+        uncertainty,
+        sighting.count.and_then(Count::exact).map(|n| n.to_string()).unwrap_or_default(),
+        breakdown(&sighting.age_sex, |cell| (cell.sex != Sex::Unknown).then(|| cell.sex.as_str())),
+        breakdown(&sighting.age_sex, |cell| (cell.age != Age::Unknown).then(|| cell.age.as_str())),
+        sighting.breeding_code.map(|code| code.to_string()).unwrap_or_default(),
+        text(&sighting.notes),
+        media.join(" | "),
+    ]
+}
+
+/// One side of the sex/age breakdown as a Darwin Core list, e.g. "2 male | 1 female"
+fn breakdown(cells: &[AgeSexCount], value: impl Fn(&AgeSexCount) -> Option<&'static str>) -> String {
+    let mut totals: Vec<(&str, u32)> = Vec::new();
+    for cell in cells {
+        let Some(value) = value(cell) else { continue };
+        match totals.iter_mut().find(|(name, _)| *name == value) {
+            Some((_, total)) => *total += cell.count,
+            None => totals.push((value, cell.count)),
+        }
+    }
+    totals.iter().map(|(name, total)| format!("{} {}", total, name)).collect::<Vec<_>>().join(" | ")
+}
+
+/// taxonRank, scientificName and the ranks above it, from the sighting's copied taxonomy.
+/// A subspecies is named by its trinomial. A slash or hybrid has no rank in Darwin Core,
+/// so its taxonRank is left empty; a spuh goes out as the taxon its candidates share.
+fn classification(sighting: &Sighting, taxon: &Taxon) -> (&'static str, String, Vec<String>) {
+    let mut ranks = vec![("kingdom", sighting.kingdom.clone())];
+    for (rank, value) in [
+        ("phylum", &sighting.phylum),
+        ("class", &sighting.class),
+        ("order", &sighting.order),
+        ("family", &sighting.family),
+        ("subfamily", &sighting.subfamily),
+        ("genus", &sighting.genus),
+    ] {
+        if let Some(value) = value {
+            ranks.push((rank, value.clone()));
+        }
+    }
+
+    let higher = |ranks: Vec<(&str, String)>| ranks.into_iter().map(|(_, name)| name).collect();
+    match taxon.rank {
+        Rank::Species | Rank::Subspecies => (taxon.rank.as_str(), taxon.scientific_name(), higher(ranks)),
+        Rank::Hybrid | Rank::Slash => ("", taxon.scientific_name(), higher(ranks)),
+        _ => {
+            let (rank, name) = ranks.pop().unwrap_or(("kingdom", sighting.kingdom.clone()));
+            (rank, name, higher(ranks))
+        }
+    }
+}
+
+/// samplingEffort as "90 min | 2.1 km | 2 observers | all species reported"
+fn sampling_effort(effort: &Effort) -> String {
+    let mut parts = Vec::new();
+    if let Some(minutes) = effort.duration_min {
+        parts.push(format!("{} min", minutes));
+    }
+    if let Some(distance) = effort.distance_km {
+        parts.push(format!("{} km", distance));
+    }
+    if let Some(size) = effort.party_size {
+        parts.push(format!("{} observer{}", size, if size == 1 { "" } else { "s" }));
+    }
+    if effort.complete {
+        parts.push(ALL_SPECIES_REPORTED.to_string());
+    }
+    parts.join(" | ")
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+/// decimalLatitude, decimalLongitude and coordinateUncertaintyInMeters
+fn coordinate_columns(point: Option<Coordinates>) -> [String; 3] {
+    match point {
+        Some(point) => [
+            point.latitude.to_string(),
+            point.longitude.to_string(),
+            point.accuracy_m.map(|accuracy| accuracy.to_string()).unwrap_or_default(),
+        ],
+        None => Default::default(),
+    }
+}
+
+/// eventDate: ISO 8601, with a season as its months
+fn iso_date(date: Option<ObservedDate>) -> String {
+    date.map(|date| date.to_iso()).unwrap_or_default()
+}
+
+/// Tab-separated with a header row and no quoting, so tabs and line breaks become spaces
+fn write_table<W: Write>(writer: &mut W, terms: &[&str], rows: &[Vec<String>]) -> std::io::Result<()> {
+    writeln!(writer, "{}", terms.join("\t"))?;
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace(['\t', '\r', '\n'], " ")).collect();
+        writeln!(writer, "{}", cells.join("\t"))?;
+    }
+    Ok(())
+}
+
+fn meta_xml() -> String {
+    let fields = |terms: &[&str]| -> String {
+        terms
+            .iter()
+            .enumerate()
+            .map(|(index, term)| format!("    <field index=\"{}\" term=\"{}{}\"/>\n", index, DWC_TERMS, term))
+            .collect()
+    };
+    let table = "encoding=\"UTF-8\" fieldsTerminatedBy=\"\\t\" linesTerminatedBy=\"\\n\" fieldsEnclosedBy=\"\" ignoreHeaderLines=\"1\"";
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <archive xmlns=\"http://rs.tdwg.org/dwc/text/\">\n  \
+         <core {table} rowType=\"{dwc}Event\">\n    \
+         <files><location>event.txt</location></files>\n    \
+         <id index=\"0\"/>\n{events}  </core>\n  \
+         <extension {table} rowType=\"{dwc}Occurrence\">\n    \
+         <files><location>occurrence.txt</location></files>\n    \
+         <coreid index=\"0\"/>\n{occurrences}  </extension>\n\
+         </archive>\n",
+        table = table,
+        dwc = DWC_TERMS,
+        events = fields(EVENT_TERMS),
+        occurrences = fields(OCCURRENCE_TERMS),
+    )
+}
+
+/// A data file described by meta.xml
+#[derive(Debug)]
+struct DataFile {
+    /// Last part of the rowType URI, e.g. "Occurrence"
+    row_type: String,
+    location: String,
+    delimiter: u8,
+    quote: Option<u8>,
+    header_lines: usize,
+    /// Column of the record id (`<id>` in the core, `<coreid>` in an extension)
+    id_index: Option<usize>,
+    /// Column index (or None for a constant) and default for each term
+    fields: Vec<(Option<usize>, String, String)>,
+}
+
+/// A row keyed by term name; the record id is under "id"
+type DwcRecord = HashMap<String, String>;
+
+/// Import a Darwin Core Archive file; see [`import_dwca`]
+pub fn import_dwca_file(conn: &Connection, path: &Path, dry_run: bool) -> Result<DwcImportReport> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    import_dwca(conn, file, dry_run)
+}
+
+/// Import a zipped Darwin Core Archive with an Occurrence core, or an Event core with an
+/// Occurrence extension. Each occurrence becomes a sighting matched to a taxon by name;
+/// events (other than single observations) become trips. Occurrences imported before are
+/// skipped. Everything runs in one transaction; a dry run rolls it back and only reports.
+pub fn import_dwca<R: Read + Seek>(conn: &Connection, reader: R, dry_run: bool) -> Result<DwcImportReport> {
+    let mut archive = ZipArchive::new(reader).context("not a Darwin Core Archive: not a zip file")?;
+
+    let files = match read_entry(&mut archive, "meta.xml")? {
+        Some(xml) => parse_meta(&xml)?,
+        None => vec![bare_occurrence_file(&mut archive)?],
+    };
+    let core = &files[0];
+
+    let (events, occurrences) = match core.row_type.as_str() {
+        "Occurrence" => (HashMap::new(), read_records(&mut archive, core)?),
+        "Event" => {
+            let events: HashMap<String, DwcRecord> = read_records(&mut archive, core)?
+                .into_iter()
+                .filter_map(|event| Some((event.get("id")?.clone(), event)))
+                .collect();
+            let extension = files
+                .iter()
+                .find(|file| file.row_type == "Occurrence")
+                .context("archive has an Event core but no Occurrence extension")?;
+            (events, read_records(&mut archive, extension)?)
+        }
+        other => bail!("unsupported Darwin Core core type '{}'", other),
+    };
+
+    let tx = conn.unchecked_transaction().context("Failed to start import")?;
+    let mut report = DwcImportReport::default();
+    let mut taxa: HashMap<String, Option<i64>> = HashMap::new();
+    let mut trips: HashMap<String, i64> = HashMap::new();
+
+    for occurrence in &occurrences {
+        let occurrence_id = field(occurrence, "occurrenceID");
+        if let Some(occurrence_id) = occurrence_id {
+            let existing: Option<i64> = tx
+                .query_row(
+                    "SELECT sighting_id FROM dwc_occurrences WHERE occurrence_id = ?1",
+                    params![occurrence_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .context("Failed to check for an earlier import")?;
+            if existing.is_some() {
+                report.already_imported += 1;
+                continue;
+            }
+        }
+
+        // The exact subspecies, slash, hybrid or spuh when the taxonomy has it, else the
+        // species or higher taxon named
+        let exact = canonical_name(occurrence);
+        let taxon_id = match exact.as_ref().and_then(|name| taxa.get(name)) {
+            Some(taxon_id) => *taxon_id,
+            None => {
+                let mut taxon_id = match exact {
+                    Some(ref name) => find_taxon_by_scientific_name(&tx, name)?.map(|taxon| taxon.id),
+                    None => None,
+                };
+                if taxon_id.is_none() {
+                    if let Some(name) = lookup_name(occurrence).filter(|name| Some(name) != exact.as_ref()) {
+                        taxon_id = find_taxon_by_scientific_name(&tx, &name)?.map(|taxon| taxon.id);
+                    }
+                }
+                if let Some(name) = exact {
+                    taxa.insert(name, taxon_id);
+                }
+                taxon_id
+            }
+        };
+        let Some(taxon_id) = taxon_id else {
+            record_unmatched(&mut report.unmatched, occurrence);
+            continue;
+        };
+
+        // Event-core archives link occurrences through the core id; occurrence cores may carry an eventID
+        let event_id = if core.row_type == "Event" { field(occurrence, "id") } else { field(occurrence, "eventID") };
+        let event = event_id.and_then(|event_id| events.get(event_id).map(|event| (event_id, event)));
+
+        let trip_id = match event {
+            Some((event_id, event)) if field(event, "eventType") != Some(OBSERVATION_EVENT) => match trips.get(event_id) {
+                Some(trip_id) => Some(*trip_id),
+                None => {
+                    let name = field(event, "eventRemarks")
+                        .or(field(event, "locality"))
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("Darwin Core event {}", event_id));
+                    let trip_id = create_trip(
+                        &tx,
+                        &name,
+                        field(event, "eventDate").and_then(dwc_date).as_deref(),
+                        field(event, "locality"),
+                        field(event, "fieldNotes"),
+                        record_coordinates(event),
+                        &event_effort(event),
+                    )
+                    .with_context(|| format!("Failed to create trip for event {}", event_id))?;
+                    trips.insert(event_id.to_string(), trip_id);
+                    report.trips_created += 1;
+                    Some(trip_id)
+                }
+            },
+            _ => None,
+        };
+
+        let event = event.map(|(_, event)| event);
+        let from_event = |term: &str| field(occurrence, term).or(event.and_then(|event| field(event, term)));
+        let date = from_event("eventDate").and_then(dwc_date);
+        let count = occurrence_count(occurrence);
+        let age_sex = occurrence_age_sex(occurrence, count);
+        let breeding_code = field(occurrence, "behavior").and_then(|behavior| behavior.parse::<BreedingCode>().ok());
+        let notes = occurrence_notes(occurrence, breeding_code.is_none());
+        // An occurrence placed where its trip was is left to take the trip's point
+        let coordinates = record_coordinates(occurrence).or(event.and_then(record_coordinates));
+        let coordinates = coordinates.filter(|point| trip_id.is_none() || Some(*point) != event.and_then(record_coordinates));
+
+        let sighting_id = create_sighting(
+            &tx,
+            trip_id,
+            taxon_id,
+            (!notes.is_empty()).then_some(notes.as_str()),
+            None,
+            date.as_deref(),
+            from_event("locality"),
+            count,
+            &age_sex,
+            breeding_code,
+            coordinates,
+        )
+        .context("Failed to create sighting")?;
+        // the archive's media paths are from wherever it was made, so they're recorded as given
+        for path in field(occurrence, "associatedMedia").into_iter().flat_map(|media| media.split('|')) {
+            if !path.trim().is_empty() {
+                attach_media(&tx, MediaOwner::Sighting(sighting_id), &MediaFile::unread(path), None, None)?;
+            }
+        }
+        if let Some(occurrence_id) = occurrence_id {
+            tx.execute(
+                "INSERT INTO dwc_occurrences (occurrence_id, sighting_id) VALUES (?1, ?2)",
+                params![occurrence_id, sighting_id],
+            )
+            .context("Failed to record imported occurrence")?;
+        }
+        report.sightings_created += 1;
+    }
+
+    if dry_run {
+        tx.rollback().context("Failed to roll back dry run")?;
+    } else {
+        tx.commit().context("Failed to commit import")?;
+    }
+
+    Ok(report)
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Option<String>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {} from archive", name)),
+    };
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents).with_context(|| format!("Failed to read {} from archive", name))?;
+    Ok(Some(contents))
+}
+
+/// Archives without meta.xml: a tab-separated occurrence.txt whose header names the terms
+fn bare_occurrence_file<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<DataFile> {
+    let contents = read_entry(archive, "occurrence.txt")?
+        .context("not a Darwin Core Archive: no meta.xml or occurrence.txt")?;
+    let header = contents.lines().next().unwrap_or("");
+
+    Ok(DataFile {
+        row_type: "Occurrence".to_string(),
+        location: "occurrence.txt".to_string(),
+        delimiter: b'\t',
+        quote: None,
+        header_lines: 1,
+        id_index: None,
+        fields: header.split('\t').enumerate().map(|(index, term)| (Some(index), term_name(term), String::new())).collect(),
+    })
+}
+
+/// Reads the core and extension descriptions from meta.xml; the core comes first
+fn parse_meta(xml: &str) -> Result<Vec<DataFile>> {
+    let mut core = None;
+    let mut extensions = Vec::new();
+    let mut current: Option<DataFile> = None;
+    let mut in_location = false;
+
+    // meta.xml is a flat list of simple elements, so a tag scanner is enough
+    for piece in xml.split('<').skip(1) {
+        let (tag, text) = piece.split_once('>').context("malformed meta.xml")?;
+        let tag = tag.trim_end_matches('/');
+        let name = tag.split_whitespace().next().unwrap_or("");
+        let name = name.rsplit(':').next().unwrap_or(name);
+        let attrs = attributes(tag);
+        let index = || attrs.get("index").and_then(|index| index.parse::<usize>().ok());
+
+        match name {
+            "core" | "extension" => {
+                let delimiter = unescape(attrs.get("fieldsTerminatedBy").map_or(",", String::as_str));
+                let quote = unescape(attrs.get("fieldsEnclosedBy").map_or("\"", String::as_str));
+                current = Some(DataFile {
+                    row_type: term_name(attrs.get("rowType").map_or("", String::as_str)),
+                    location: String::new(),
+                    delimiter: delimiter.bytes().next().unwrap_or(b','),
+                    quote: quote.bytes().next(),
+                    header_lines: attrs.get("ignoreHeaderLines").and_then(|lines| lines.parse().ok()).unwrap_or(0),
+                    id_index: None,
+                    fields: Vec::new(),
+                });
+            }
+            "/core" => core = current.take(),
+            "/extension" => extensions.extend(current.take()),
+            "location" => in_location = true,
+            "/location" => in_location = false,
+            "id" | "coreid" => {
+                if let Some(file) = current.as_mut() {
+                    file.id_index = index();
+                }
+            }
+            "field" => {
+                if let Some(file) = current.as_mut() {
+                    let term = term_name(attrs.get("term").map_or("", String::as_str));
+                    file.fields.push((index(), term, attrs.get("default").cloned().unwrap_or_default()));
+                }
+            }
+            _ => {}
+        }
+
+        if in_location {
+            if let Some(file) = current.as_mut() {
+                if file.location.is_empty() {
+                    file.location = text.trim().to_string();
+                }
+            }
+        }
+    }
+
+    let core = core.context("meta.xml has no <core> element")?;
+    let mut files = vec![core];
+    files.extend(extensions);
+    Ok(files)
+}
+
+fn attributes(tag: &str) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    let mut rest = tag.split_once(char::is_whitespace).map_or("", |(_, rest)| rest);
+
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_string();
+        let value = rest[eq + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(end) = value[1..].find(quote) else {
+            break;
+        };
+        attrs.insert(key, xml_unescape(&value[1..end + 1]));
+        rest = &value[end + 2..];
+    }
+
+    attrs
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// meta.xml writes tab and newline as `\t` and `\n`
+fn unescape(value: &str) -> String {
+    value.replace("\\t", "\t").replace("\\n", "\n").replace("\\r", "\r")
+}
+
+/// "http://rs.tdwg.org/dwc/terms/scientificName" → "scientificName"
+fn term_name(term: &str) -> String {
+    term.trim().rsplit(['/', '#']).next().unwrap_or("").to_string()
+}
+
+fn read_records<R: Read + Seek>(archive: &mut ZipArchive<R>, file: &DataFile) -> Result<Vec<DwcRecord>> {
+    let contents = read_entry(archive, &file.location)?
+        .with_context(|| format!("archive is missing {}", file.location))?;
+
+    let mut builder = csv::ReaderBuilder::new();
+    builder.delimiter(file.delimiter).has_headers(false).flexible(true);
+    match file.quote {
+        Some(quote) => builder.quote(quote),
+        None => builder.quoting(false),
+    };
+
+    let mut records = Vec::new();
+    for row in builder.from_reader(contents.as_bytes()).records().skip(file.header_lines) {
+        let row = row.with_context(|| format!("Failed to read {}", file.location))?;
+        let value = |index: Option<usize>| index.and_then(|index| row.get(index)).map(str::trim).unwrap_or("");
+
+        let mut record = DwcRecord::new();
+        for (index, term, default) in &file.fields {
+            let value = match value(*index) {
+                "" => default.as_str(),
+                value => value,
+            };
+            if !value.is_empty() {
+                record.insert(term.clone(), value.to_string());
+            }
+        }
+        if let Some(id) = Some(value(file.id_index)).filter(|id| !id.is_empty()) {
+            record.insert("id".to_string(), id.to_string());
+        }
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn field<'a>(record: &'a DwcRecord, term: &str) -> Option<&'a str> {
+    record.get(term).map(String::as_str).filter(|value| !value.is_empty())
+}
+
+/// The full name an occurrence gives: genus, specificEpithet and infraspecificEpithet when
+/// given, otherwise scientificName without its authorship or rank markers ("subsp.",
+/// "var."). Keeps subspecies, slashes ("viduata/autumnalis"), hybrids and "sp.".
+fn canonical_name(record: &DwcRecord) -> Option<String> {
+    if let (Some(genus), Some(epithet)) = (field(record, "genus"), field(record, "specificEpithet")) {
+        let words = [Some(genus), Some(epithet), field(record, "infraspecificEpithet")];
+        return Some(words.into_iter().flatten().collect::<Vec<_>>().join(" "));
+    }
+
+    let name = field(record, "scientificName")?;
+    let mut words = name.split_whitespace();
+    let mut canonical = vec![words.next()?.to_string()];
+    for word in words {
+        match word {
+            "subsp." | "ssp." | "var." | "f." => continue,
+            "x" | "×" => canonical.push("x".to_string()),
+            "sp." => canonical.push(word.to_string()),
+            word if word.chars().all(|c| c.is_lowercase() || c == '-' || c == '/') => canonical.push(word.to_string()),
+            _ => break,
+        }
+    }
+    Some(canonical.join(" "))
+}
+
+/// The name to look up: genus + specificEpithet when given, otherwise the canonical part of
+/// scientificName (authorship dropped). Hybrids return None.
+fn lookup_name(record: &DwcRecord) -> Option<String> {
+    if let (Some(genus), Some(epithet)) = (field(record, "genus"), field(record, "specificEpithet")) {
+        return Some(format!("{} {}", genus, epithet));
+    }
+
+    let name = field(record, "scientificName")?;
+    let words: Vec<&str> = name.split_whitespace().collect();
+    if words.iter().any(|word| *word == "x" || word.starts_with('×')) {
+        return None;
+    }
+
+    let rank = field(record, "taxonRank").unwrap_or("").to_lowercase();
+    let binomial = match rank.as_str() {
+        "" => words.get(1).is_some_and(|word| word.chars().all(|c| c.is_lowercase() || c == '-')),
+        rank => matches!(rank, "species" | "subspecies" | "variety" | "form"),
+    };
+    let count = if binomial { 2 } else { 1 };
+    Some(words.iter().take(count).copied().collect::<Vec<_>>().join(" "))
+}
+
+/// decimalLatitude/decimalLongitude and their uncertainty; a point out of range is dropped
+fn record_coordinates(record: &DwcRecord) -> Option<Coordinates> {
+    let number = |term: &str| field(record, term).and_then(|value| value.trim().parse::<f64>().ok());
+    Coordinates::new(number("decimalLatitude")?, number("decimalLongitude")?, number("coordinateUncertaintyInMeters")).ok()
+}
+
+/// ISO 8601 eventDate → a stored date. A season's months stay a season; other ranges keep
+/// their start.
+fn dwc_date(date: &str) -> Option<String> {
+    let date = date.trim();
+    let start = date.split('/').next().unwrap_or(date);
+    let parsed = date.parse::<ObservedDate>().or_else(|_| start.parse::<ObservedDate>());
+    parsed.ok().map(|date| date.to_iso())
+}
+
+/// A trip's effort from an event's eventTime (its start, if a range), samplingProtocol and
+/// samplingEffort as written by [`sampling_effort`]; anything else is left unset
+fn event_effort(event: &DwcRecord) -> Effort {
+    let mut effort = Effort {
+        start_time: field(event, "eventTime")
+            .and_then(|time| time.split('/').next())
+            .and_then(|time| parse_start_time(time.get(..5).unwrap_or(time)).ok()),
+        ..Default::default()
+    };
+    let protocol = field(event, "samplingProtocol").unwrap_or_default().to_lowercase();
+    effort.protocol = Protocol::ALL.into_iter().find(|known| protocol.contains(known.as_str()));
+
+    for part in field(event, "samplingEffort").unwrap_or_default().split('|').map(str::trim) {
+        let number = |unit: &str| part.strip_suffix(unit).and_then(|value| value.trim().parse::<f64>().ok());
+        if let Some(minutes) = number("min").filter(|minutes| *minutes >= 1.0) {
+            effort.duration_min = Some(minutes.round() as u32);
+        } else if let Some(distance) = number("km").filter(|distance| *distance >= 0.0) {
+            effort.distance_km = Some(distance);
+        } else if let Some(size) = number("observers").or(number("observer")).filter(|size| *size >= 1.0) {
+            effort.party_size = Some(size.round() as u32);
+        } else if part.eq_ignore_ascii_case(ALL_SPECIES_REPORTED) {
+            effort.complete = true;
+        }
+    }
+    // as on insert, a stationary count can't cover a distance
+    if effort.protocol == Some(Protocol::Stationary) {
+        effort.distance_km = None;
+    }
+    effort
+}
+
+fn occurrence_count(record: &DwcRecord) -> Option<Count> {
+    field(record, "individualCount").and_then(|count| count.parse::<u32>().ok()).map(Count::Exact)
+}
+
+/// Rebuild a breakdown from `sex`, or from `lifeStage` if there's no sex. Darwin Core keeps
+/// the two apart, so a breakdown by both comes back by sex only. Terms we don't know
+/// ("undetermined") are ignored, as is a breakdown adding up to more than the count.
+fn occurrence_age_sex(record: &DwcRecord, count: Option<Count>) -> Vec<AgeSexCount> {
+    let items = |term: &str| -> Vec<String> {
+        let Some(list) = field(record, term) else { return Vec::new() };
+        let items: Vec<&str> = list.split('|').map(str::trim).filter(|item| !item.is_empty()).collect();
+        let single = items.len() == 1;
+        items
+            .into_iter()
+            .map(|item| match item.split_whitespace().next().and_then(|word| word.parse::<u32>().ok()) {
+                Some(_) => item.to_string(),
+                // A bare "female" covers the whole count
+                None => format!("{} {}", count.and_then(Count::exact).filter(|_| single).unwrap_or(1), item),
+            })
+            .filter(|item| parse_age_sex(item).is_ok())
+            .collect()
+    };
+
+    let mut list = items("sex");
+    if list.is_empty() {
+        list = items("lifeStage");
+    }
+    let cells = parse_age_sex(&list.join(", ")).unwrap_or_default();
+
+    let total: u32 = cells.iter().map(|cell| cell.count).sum();
+    match count.and_then(Count::exact) {
+        Some(n) if total > n => Vec::new(),
+        _ => cells,
+    }
+}
+
+/// Remarks, plus any behavior that isn't a breeding code
+fn occurrence_notes(record: &DwcRecord, keep_behavior: bool) -> String {
+    let mut parts = Vec::new();
+    if let Some(remarks) = field(record, "occurrenceRemarks") {
+        parts.push(remarks.to_string());
+    }
+    if let Some(behavior) = field(record, "behavior").filter(|_| keep_behavior) {
+        parts.push(format!("Behavior: {}", behavior));
+    }
+    parts.join("\n")
+}
+
+fn record_unmatched(unmatched: &mut Vec<UnmatchedName>, record: &DwcRecord) {
+    let scientific_name = field(record, "scientificName").unwrap_or("(no scientificName)");
+    match unmatched.iter_mut().find(|name| name.scientific_name == scientific_name) {
+        Some(name) => name.rows += 1,
+        None => unmatched.push(UnmatchedName {
+            scientific_name: scientific_name.to_string(),
+            common_name: field(record, "vernacularName").unwrap_or("").to_string(),
+            rows: 1,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::sighting::get_sightings_by_trip_id;
+    use crate::models::format_age_sex;
+    use std::io::Cursor;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+        crate::core::migrations::migrate(&conn).unwrap();
+
+        // Load test taxa (first 100 species + parent taxa)
+        let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
+        conn.execute_batch(&test_taxa).unwrap();
+
+        conn
+    }
+
+    fn taxon_id(conn: &Connection, name: &str) -> i64 {
+        find_taxon_by_scientific_name(conn, name).unwrap().unwrap().id
+    }
+
+    fn point(latitude: f64, longitude: f64, accuracy_m: Option<f64>) -> Option<Coordinates> {
+        Some(Coordinates::new(latitude, longitude, accuracy_m).unwrap())
+    }
+
+    /// A trip with a species placed by the trip and a genus-level sighting with its own
+    /// point, plus one sighting on no trip
+    fn populate(conn: &Connection) -> i64 {
+        let effort = Effort {
+            start_time: Some("07:30".to_string()),
+            duration_min: Some(90),
+            distance_km: Some(2.1),
+            party_size: Some(1),
+            protocol: Some(Protocol::Traveling),
+            complete: true,
+        };
+        let trip_id = create_trip(conn, "Rio Grande Valley", Some("2024-03-02"), Some("Estero Llano Grande SP"), Some("Windy\tmorning"), point(26.12, -97.95, Some(30.0)), &effort).unwrap();
+        let age_sex = parse_age_sex("1 male adult, 1 female adult, 1 juvenile").unwrap();
+        let duck = create_sighting(
+            conn,
+            Some(trip_id),
+            taxon_id(conn, "Dendrocygna viduata"),
+            Some("Pair on the pond"),
+            None,
+            None,
+            None,
+            Some(Count::Exact(3)),
+            &age_sex,
+            Some(BreedingCode::RecentlyFledged),
+            None,
+        )
+        .unwrap();
+        attach_media(conn, MediaOwner::Sighting(duck), &MediaFile::unread("photos/duck.jpg"), None, None).unwrap();
+        create_sighting(conn, Some(trip_id), taxon_id(conn, "Dendrocygna"), None, None, None, None, None, &[], None, point(26.13, -97.96, None)).unwrap();
+        create_sighting(conn, None, taxon_id(conn, "Ortalis vetula"), None, None, Some("2024-03-05"), Some("Backyard"), None, &[], None, point(26.2, -98.2, None)).unwrap();
+        trip_id
+    }
+
+    fn zip_of(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        let mut cursor = zip.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    fn export_archive(conn: &Connection) -> (DwcExportReport, Cursor<Vec<u8>>) {
+        let mut out = Cursor::new(Vec::new());
+        let report = export_dwca(conn, &mut out).unwrap();
+        out.set_position(0);
+        (report, out)
+    }
+
+    #[test]
+    fn test_export_archive_contents() {
+        let conn = setup_test_db();
+        populate(&conn);
+
+        let (report, archive) = export_archive(&conn);
+        assert_eq!(report.events, 2);
+        assert_eq!(report.occurrences, 3);
+
+        let mut archive = ZipArchive::new(archive).unwrap();
+        let files = parse_meta(&read_entry(&mut archive, "meta.xml").unwrap().unwrap()).unwrap();
+        assert_eq!(files[0].row_type, "Event");
+        assert_eq!(files[0].location, "event.txt");
+        assert_eq!(files[0].delimiter, b'\t');
+        assert_eq!(files[1].row_type, "Occurrence");
+        assert_eq!(files[1].fields.len(), OCCURRENCE_TERMS.len());
+
+        let occurrences = read_records(&mut archive, &files[1]).unwrap();
+        let duck = &occurrences[0];
+        assert_eq!(field(duck, "scientificName"), Some("Dendrocygna viduata"));
+        assert_eq!(field(duck, "taxonRank"), Some("species"));
+        assert_eq!(
+            field(duck, "higherClassification"),
+            Some("Animalia | Chordata | Aves | Anseriformes | Anatidae | Dendrocygninae | Dendrocygna")
+        );
+        assert_eq!(field(duck, "eventDate"), Some("2024-03-02"));
+        assert_eq!(field(duck, "locality"), Some("Estero Llano Grande SP"));
+        // Placed by its trip
+        assert_eq!(field(duck, "decimalLatitude"), Some("26.12"));
+        assert_eq!(field(duck, "decimalLongitude"), Some("-97.95"));
+        assert_eq!(field(duck, "coordinateUncertaintyInMeters"), Some("30"));
+        assert_eq!(field(duck, "individualCount"), Some("3"));
+        assert_eq!(field(duck, "sex"), Some("1 male | 1 female"));
+        assert_eq!(field(duck, "lifeStage"), Some("2 adult | 1 juvenile"));
+        assert_eq!(field(duck, "behavior"), Some("FL Recently fledged young"));
+        assert_eq!(field(duck, "occurrenceRemarks"), Some("Pair on the pond"));
+        assert_eq!(field(duck, "associatedMedia"), Some("photos/duck.jpg"));
+
+        let genus = &occurrences[1];
+        assert_eq!(field(genus, "scientificName"), Some("Dendrocygna"));
+        assert_eq!(field(genus, "taxonRank"), Some("genus"));
+        assert_eq!(field(genus, "individualCount"), None);
+        assert_eq!(field(genus, "decimalLatitude"), Some("26.13"));
+        assert_eq!(field(genus, "coordinateUncertaintyInMeters"), None);
+
+        let events = read_records(&mut archive, &files[0]).unwrap();
+        assert_eq!(field(&events[0], "eventRemarks"), Some("Rio Grande Valley"));
+        // Tabs can't survive in a tab-separated file
+        assert_eq!(field(&events[0], "fieldNotes"), Some("Windy morning"));
+        assert_eq!(field(&events[0], "eventTime"), Some("07:30"));
+        assert_eq!(field(&events[0], "samplingProtocol"), Some("traveling"));
+        assert_eq!(field(&events[0], "samplingEffort"), Some("90 min | 2.1 km | 1 observer | all species reported"));
+        assert_eq!(field(&events[0], "decimalLongitude"), Some("-97.95"));
+        assert_eq!(field(&events[1], "eventType"), Some(OBSERVATION_EVENT));
+        assert_eq!(field(&events[1], "decimalLatitude"), Some("26.2"));
+    }
+
+    /// A sighting of each taxon category under Dendrocygna, identified as they'd be in the field
+    fn categories(conn: &Connection) {
+        let create = |rank: Rank, epithet: Option<&str>, subspecies: Option<&str>, name: &str| {
+            let taxon = crate::core::taxon::create_taxon(conn, rank, "Animalia", Some("Chordata"), Some("Aves"), Some("Anseriformes"), Some("Anatidae"), Some("Dendrocygninae"), Some("Dendrocygna"), epithet, subspecies, name).unwrap();
+            create_sighting(conn, None, taxon, None, None, Some("2024-03-02"), Some("Pond"), None, &[], None, None).unwrap();
+        };
+        create(Rank::Species, Some("viduata"), None, "White-faced Whistling-Duck");
+        create(Rank::Subspecies, Some("autumnalis"), Some("fulgens"), "Black-bellied Whistling-Duck (fulgens)");
+        create(Rank::Slash, Some("viduata/autumnalis"), None, "White-faced/Black-bellied Whistling-Duck");
+        create(Rank::Hybrid, Some("viduata x autumnalis"), None, "White-faced x Black-bellied Whistling-Duck (hybrid)");
+        create(Rank::Spuh, None, None, "whistling-duck sp.");
+    }
+
+    #[test]
+    fn test_export_identification_categories() {
+        let conn = setup_test_db();
+        categories(&conn);
+
+        let (_, archive) = export_archive(&conn);
+        let mut archive = ZipArchive::new(archive).unwrap();
+        let files = parse_meta(&read_entry(&mut archive, "meta.xml").unwrap().unwrap()).unwrap();
+        let occurrences = read_records(&mut archive, &files[1]).unwrap();
+        let names: Vec<Vec<Option<&str>>> = occurrences
+            .iter()
+            .map(|occurrence| {
+                ["scientificName", "taxonRank", "specificEpithet", "infraspecificEpithet"].map(|term| field(occurrence, term)).to_vec()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                vec![Some("Dendrocygna viduata"), Some("species"), Some("viduata"), None],
+                vec![Some("Dendrocygna autumnalis fulgens"), Some("subspecies"), Some("autumnalis"), Some("fulgens")],
+                vec![Some("Dendrocygna viduata/autumnalis"), None, None, None],
+                vec![Some("Dendrocygna viduata x autumnalis"), None, None, None],
+                vec![Some("Dendrocygna"), Some("genus"), None, None],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exported_archive_imports_back() {
+        let source = setup_test_db();
+        populate(&source);
+        let (_, archive) = export_archive(&source);
+
+        let conn = setup_test_db();
+        let report = import_dwca(&conn, archive.clone(), false).unwrap();
+        assert_eq!(report.trips_created, 1);
+        assert_eq!(report.sightings_created, 3);
+        assert!(report.unmatched.is_empty());
+
+        let trip_id: i64 = conn.query_row("SELECT id FROM trips WHERE name = 'Rio Grande Valley'", [], |row| row.get(0)).unwrap();
+        let original = get_all_trips(&source).unwrap().remove(0);
+        assert_eq!(get_all_trips(&conn).unwrap()[0].effort, original.effort);
+        assert_eq!(get_all_trips(&conn).unwrap()[0].coordinates, original.coordinates);
+        let sightings = get_sightings_by_trip_id(&conn, trip_id).unwrap();
+        assert_eq!(sightings.len(), 2);
+        assert_eq!(sightings[0].notes, Some("Pair on the pond".to_string()));
+        assert_eq!(sightings[0].count, Some(Count::Exact(3)));
+        assert_eq!(sightings[0].breeding_code, Some(BreedingCode::RecentlyFledged));
+        // Sex survives; the age split can't be paired back up with it
+        assert_eq!(format_age_sex(&sightings[0].age_sex), "1 male, 1 female");
+        assert_eq!(sightings[0].date, Some("2024-03-02".parse().unwrap()));
+        assert_eq!(sightings[1].species_epithet, None);
+        // The duck still takes the trip's point; the genus keeps its own
+        assert_eq!(sightings[0].coordinates, None);
+        assert_eq!(sightings[1].coordinates, point(26.13, -97.96, None));
+
+        // The lone observation stays off any trip
+        let loose: i64 = conn.query_row("SELECT COUNT(*) FROM sightings WHERE trip_id IS NULL AND location = 'Backyard' AND latitude = 26.2", [], |row| row.get(0)).unwrap();
+        assert_eq!(loose, 1);
+
+        let again = import_dwca(&conn, archive, false).unwrap();
+        assert_eq!(again.sightings_created, 0);
+        assert_eq!(again.trips_created, 0);
+        assert_eq!(again.already_imported, 3);
+    }
+
+    #[test]
+    fn test_import_occurrence_core_archive() {
+        let meta = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/" metadata="eml.xml">
+  <core encoding="UTF-8" fieldsTerminatedBy="," linesTerminatedBy="\n" fieldsEnclosedBy='"' ignoreHeaderLines="1" rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files>
+      <location>occurrences.csv</location>
+    </files>
+    <id index="0" />
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/taxonRank"/>
+    <field index="3" term="http://rs.tdwg.org/dwc/terms/eventDate"/>
+    <field index="4" term="http://rs.tdwg.org/dwc/terms/locality"/>
+    <field index="5" term="http://rs.tdwg.org/dwc/terms/occurrenceRemarks"/>
+    <field term="http://rs.tdwg.org/dwc/terms/basisOfRecord" default="HumanObservation"/>
+  </core>
+</archive>"#;
+        let data = "\
+occurrenceID,scientificName,taxonRank,eventDate,locality,occurrenceRemarks
+obs-1,\"Dendrocygna viduata (Linnaeus, 1766)\",species,2024-05-01T07:00/2024-05-01T09:00,\"Lake, east shore\",\"Calling, flying over\"
+obs-2,Dendrocygna Lesson 1831,genus,2024-05,,
+obs-3,Foo bar,species,2024-05-02,,
+obs-4,Foo bar,species,2024-05-02,,
+";
+        let conn = setup_test_db();
+        let archive = zip_of(&[("meta.xml", meta), ("occurrences.csv", data)]);
+
+        let dry = import_dwca(&conn, archive.clone(), true).unwrap();
+        assert_eq!(dry.sightings_created, 2);
+        let count = |conn: &Connection| -> i64 { conn.query_row("SELECT COUNT(*) FROM sightings", [], |row| row.get(0)).unwrap() };
+        assert_eq!(count(&conn), 0);
+
+        let report = import_dwca(&conn, archive, false).unwrap();
+        assert_eq!(report.trips_created, 0);
+        assert_eq!(report.sightings_created, 2);
+        assert_eq!(
+            report.unmatched,
+            vec![UnmatchedName { scientific_name: "Foo bar".to_string(), common_name: String::new(), rows: 2 }]
+        );
+
+        let (date, location, notes): (String, String, String) = conn
+            .query_row("SELECT date, location, notes FROM sightings WHERE species_epithet = 'viduata'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!(date, "2024-05-01T07:00");
+        assert_eq!(location, "Lake, east shore");
+        assert_eq!(notes, "Calling, flying over");
+    }
+
+    #[test]
+    fn test_import_bare_occurrence_file() {
+        let data = "scientificName\teventDate\tassociatedMedia\nOrtalis vetula\t2023\tphotos/chachalaca.jpg | https://example.org/call.mp3\n";
+        let conn = setup_test_db();
+
+        let report = import_dwca(&conn, zip_of(&[("occurrence.txt", data)]), false).unwrap();
+        assert_eq!(report.sightings_created, 1);
+
+        let sighting_id: i64 = conn.query_row("SELECT id FROM sightings", [], |row| row.get(0)).unwrap();
+        let media = crate::core::media::get_media(&conn, MediaOwner::Sighting(sighting_id)).unwrap();
+        let media: Vec<(&str, &str)> = media.iter().map(|media| (media.path.as_str(), media.mime_type.as_str())).collect();
+        assert_eq!(media, vec![("photos/chachalaca.jpg", "image/jpeg"), ("https://example.org/call.mp3", "audio/mpeg")]);
+    }
+
+    #[test]
+    fn test_import_rejects_bad_archives() {
+        let conn = setup_test_db();
+
+        let err = import_dwca(&conn, Cursor::new(b"not a zip".to_vec()), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("not a zip file"));
+
+        let err = import_dwca(&conn, zip_of(&[("readme.txt", "hello")]), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("no meta.xml"));
+
+        let event_only = r#"<archive><core fieldsTerminatedBy="\t" rowType="http://rs.tdwg.org/dwc/terms/Event"><files><location>event.txt</location></files><id index="0"/></core></archive>"#;
+        let err = import_dwca(&conn, zip_of(&[("meta.xml", event_only), ("event.txt", "e1\n")]), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("no Occurrence extension"));
+    }
+
+    #[test]
+    fn test_lookup_name() {
+        let record = |pairs: &[(&str, &str)]| -> DwcRecord {
+            pairs.iter().map(|(term, value)| (term.to_string(), value.to_string())).collect()
+        };
+
+        assert_eq!(lookup_name(&record(&[("genus", "Turdus"), ("specificEpithet", "migratorius")])), Some("Turdus migratorius".to_string()));
+        assert_eq!(lookup_name(&record(&[("scientificName", "Turdus migratorius Linnaeus, 1766")])), Some("Turdus migratorius".to_string()));
+        assert_eq!(lookup_name(&record(&[("scientificName", "Turdus Linnaeus, 1758")])), Some("Turdus".to_string()));
+        assert_eq!(lookup_name(&record(&[("scientificName", "Anatidae"), ("taxonRank", "family")])), Some("Anatidae".to_string()));
+        assert_eq!(lookup_name(&record(&[("scientificName", "Larus argentatus smithsonianus"), ("taxonRank", "subspecies")])), Some("Larus argentatus".to_string()));
+        assert_eq!(lookup_name(&record(&[("scientificName", "Anas platyrhynchos x rubripes")])), None);
+        assert_eq!(lookup_name(&record(&[])), None);
+    }
+
+    #[test]
+    fn test_canonical_name() {
+        let record = |pairs: &[(&str, &str)]| -> DwcRecord {
+            pairs.iter().map(|(term, value)| (term.to_string(), value.to_string())).collect()
+        };
+        let name = |pairs: &[(&str, &str)]| canonical_name(&record(pairs));
+
+        assert_eq!(name(&[("scientificName", "Larus argentatus smithsonianus Coues, 1862")]), Some("Larus argentatus smithsonianus".to_string()));
+        assert_eq!(name(&[("scientificName", "Quercus alba var. latiloba")]), Some("Quercus alba latiloba".to_string()));
+        assert_eq!(name(&[("scientificName", "Anas platyrhynchos × rubripes")]), Some("Anas platyrhynchos x rubripes".to_string()));
+        assert_eq!(name(&[("scientificName", "Dendrocygna viduata/autumnalis")]), Some("Dendrocygna viduata/autumnalis".to_string()));
+        assert_eq!(name(&[("scientificName", "Turdus (Linnaeus, 1758)")]), Some("Turdus".to_string()));
+        assert_eq!(
+            name(&[("genus", "Junco"), ("specificEpithet", "hyemalis"), ("infraspecificEpithet", "oreganus")]),
+            Some("Junco hyemalis oreganus".to_string())
+        );
+        assert_eq!(name(&[]), None);
+    }
+
+    #[test]
+    fn test_import_matches_categories_exactly() {
+        let source = setup_test_db();
+        categories(&source);
+        let (_, archive) = export_archive(&source);
+
+        let conn = setup_test_db();
+        categories(&conn);
+        conn.execute("DELETE FROM sightings", []).unwrap();
+        let report = import_dwca(&conn, archive, false).unwrap();
+        assert_eq!(report.sightings_created, 5);
+        assert!(report.unmatched.is_empty());
+
+        // Each occurrence lands on the taxon it was exported from
+        let ranks: Vec<String> = conn
+            .prepare("SELECT t.rank FROM sightings s JOIN taxa t ON t.id = s.taxon_id ORDER BY s.id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ranks, ["species", "subspecies", "slash", "hybrid", "genus"]);
+
+        // A subspecies the taxonomy lacks falls back to its species
+        let occurrence = "scientificName\ttaxonRank\nDendrocygna viduata major\tsubspecies\n";
+        import_dwca(&conn, zip_of(&[("occurrence.txt", occurrence)]), false).unwrap();
+        let epithet: Option<String> = conn
+            .query_row("SELECT t.rank FROM sightings s JOIN taxa t ON t.id = s.taxon_id ORDER BY s.id DESC LIMIT 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(epithet, Some("species".to_string()));
+    }
+
+    #[test]
+    fn test_occurrence_age_sex() {
+        let record = |pairs: &[(&str, &str)]| -> DwcRecord {
+            pairs.iter().map(|(term, value)| (term.to_string(), value.to_string())).collect()
+        };
+        let age_sex = |pairs: &[(&str, &str)]| {
+            let record = record(pairs);
+            format_age_sex(&occurrence_age_sex(&record, occurrence_count(&record)))
+        };
+
+        assert_eq!(age_sex(&[("sex", "2 male | 1 female"), ("lifeStage", "3 adult")]), "2 male, 1 female");
+        assert_eq!(age_sex(&[("lifeStage", "juvenile"), ("individualCount", "4")]), "4 juvenile");
+        assert_eq!(age_sex(&[("sex", "female | undetermined")]), "1 female");
+        assert_eq!(age_sex(&[("sex", "5 male"), ("individualCount", "2")]), "");
+        assert_eq!(age_sex(&[]), "");
+    }
+
+    #[test]
+    fn test_dwc_date() {
+        assert_eq!(dwc_date("2024-05-01"), Some("2024-05-01".to_string()));
+        assert_eq!(dwc_date("2024-05-01T07:00:00Z"), Some("2024-05-01T07:00+00:00".to_string()));
+        assert_eq!(dwc_date("2024-05/2024-06"), Some("2024-05".to_string()));
+        assert_eq!(dwc_date("2024-03/2024-05"), Some("2024-03/2024-05".to_string()));
+        assert_eq!(dwc_date("2024-02-30"), None);
+        assert_eq!(dwc_date("2024"), Some("2024".to_string()));
+        assert_eq!(dwc_date("May 2024"), None);
+        assert_eq!(dwc_date(""), None);
+    }
+}
@@ -0,0 +1,947 @@
+use crate::core::search::DateRange;
+use crate::core::sighting::{
+    create_sighting, get_sightings_by_date_range, get_sightings_by_taxon, get_sightings_by_trip_id,
+};
+use crate::core::taxon::{find_taxon_by_scientific_name, get_taxon_by_id};
+use crate::core::trip::{create_trip, get_trip_by_id};
+use crate::models::{
+    BreedingCode, Coordinates, Count, DatePrecision, Effort, ObservedDate, Protocol, Rank, Sighting, Taxon, Trip, format_age_sex,
+    parse_start_time,
+};
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// The record format takes distances in miles
+const KM_PER_MILE: f64 = 1.609344;
+
+/// Outcome of an eBird import (or what a dry run would do)
+#[derive(Debug, Default)]
+pub struct EbirdImportReport {
+    pub trips_created: usize,
+    pub sightings_created: usize,
+    /// Submission IDs skipped because an earlier import already created their trip
+    pub already_imported: Vec<String>,
+    /// Names with no matching taxon; their rows are skipped
+    pub unmatched: Vec<UnmatchedName>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnmatchedName {
+    pub scientific_name: String,
+    pub common_name: String,
+    pub rows: usize,
+}
+
+/// One observation row from an eBird "My Data" export
+struct EbirdRow {
+    line: u64,
+    submission_id: String,
+    common_name: String,
+    scientific_name: String,
+    count: Option<Count>,
+    breeding_code: Option<BreedingCode>,
+    location: Option<String>,
+    coordinates: Option<Coordinates>,
+    date: String,
+    observation_details: String,
+    checklist_comments: String,
+    effort: Effort,
+}
+
+/// Column positions in the export, looked up by header name
+struct Columns {
+    submission_id: usize,
+    scientific_name: usize,
+    date: usize,
+    common_name: Option<usize>,
+    count: Option<usize>,
+    breeding_code: Option<usize>,
+    location: Option<usize>,
+    county: Option<usize>,
+    state: Option<usize>,
+    latitude: Option<usize>,
+    longitude: Option<usize>,
+    observation_details: Option<usize>,
+    checklist_comments: Option<usize>,
+    time: Option<usize>,
+    protocol: Option<usize>,
+    duration: Option<usize>,
+    all_obs_reported: Option<usize>,
+    distance: Option<usize>,
+    observers: Option<usize>,
+}
+
+impl Columns {
+    fn from_headers(headers: &csv::StringRecord) -> Result<Self> {
+        let find = |name: &str| headers.iter().position(|header| header.trim().eq_ignore_ascii_case(name));
+        let require = |name: &str| {
+            find(name).with_context(|| format!("not an eBird \"My Data\" export: missing column '{}'", name))
+        };
+
+        Ok(Columns {
+            submission_id: require("Submission ID")?,
+            scientific_name: require("Scientific Name")?,
+            date: require("Date")?,
+            common_name: find("Common Name"),
+            count: find("Count"),
+            breeding_code: find("Breeding Code"),
+            location: find("Location"),
+            county: find("County"),
+            state: find("State/Province"),
+            latitude: find("Latitude"),
+            longitude: find("Longitude"),
+            observation_details: find("Observation Details"),
+            checklist_comments: find("Checklist Comments"),
+            time: find("Time"),
+            protocol: find("Protocol"),
+            duration: find("Duration (Min)"),
+            all_obs_reported: find("All Obs Reported"),
+            distance: find("Distance Traveled (km)").or(find("Effort Distance Km")),
+            observers: find("Number of Observers"),
+        })
+    }
+}
+
+/// Import an eBird "My Data" CSV file; see [`import_ebird_csv`]
+pub fn import_ebird_file(conn: &Connection, path: &Path, dry_run: bool) -> Result<EbirdImportReport> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    import_ebird_csv(conn, file, dry_run)
+}
+
+/// Import an eBird "My Data" CSV export. Each checklist (Submission ID) becomes a trip and
+/// each row a sighting, matched to a taxon by scientific name. Checklists imported before are
+/// skipped. Everything runs in one transaction; a dry run rolls it back and only reports.
+pub fn import_ebird_csv<R: Read>(conn: &Connection, reader: R, dry_run: bool) -> Result<EbirdImportReport> {
+    let rows = read_rows(reader)?;
+
+    // Group rows into checklists, keeping the file's order
+    let mut checklists: Vec<(String, Vec<EbirdRow>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let position = *positions.entry(row.submission_id.clone()).or_insert_with(|| {
+            checklists.push((row.submission_id.clone(), Vec::new()));
+            checklists.len() - 1
+        });
+        checklists[position].1.push(row);
+    }
+
+    let tx = conn.unchecked_transaction().context("Failed to start import")?;
+    let mut report = EbirdImportReport::default();
+    let mut taxa: HashMap<String, Option<i64>> = HashMap::new();
+
+    for (submission_id, rows) in &checklists {
+        let existing: Option<i64> = tx
+            .query_row(
+                "SELECT trip_id FROM ebird_checklists WHERE submission_id = ?1",
+                params![submission_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to check for an earlier import")?;
+        if existing.is_some() {
+            report.already_imported.push(submission_id.clone());
+            continue;
+        }
+
+        let first = &rows[0];
+        let name = first.location.clone().unwrap_or_else(|| format!("eBird checklist {}", submission_id));
+        let trip_id = create_trip(
+            &tx,
+            &name,
+            Some(&first.date),
+            first.location.as_deref(),
+            non_empty(&first.checklist_comments),
+            first.coordinates,
+            &first.effort,
+        )
+        .with_context(|| format!("Failed to create trip for checklist {}", submission_id))?;
+        tx.execute(
+            "INSERT INTO ebird_checklists (submission_id, trip_id) VALUES (?1, ?2)",
+            params![submission_id, trip_id],
+        )
+        .context("Failed to record imported checklist")?;
+        report.trips_created += 1;
+
+        for row in rows {
+            let taxon_id = match taxa.get(&row.scientific_name) {
+                Some(taxon_id) => *taxon_id,
+                None => {
+                    // The exact subspecies, slash, hybrid or spuh when the taxonomy has it
+                    let exact = find_taxon_by_scientific_name(&tx, &row.scientific_name)?;
+                    let taxon_id = match (exact, taxon_lookup_name(&row.scientific_name)) {
+                        (Some(taxon), _) => Some(taxon.id),
+                        (None, Some(name)) => find_taxon_by_scientific_name(&tx, &name)?.map(|taxon| taxon.id),
+                        (None, None) => None,
+                    };
+                    taxa.insert(row.scientific_name.clone(), taxon_id);
+                    taxon_id
+                }
+            };
+
+            let Some(taxon_id) = taxon_id else {
+                record_unmatched(&mut report.unmatched, row);
+                continue;
+            };
+
+            create_sighting(
+                &tx,
+                Some(trip_id),
+                taxon_id,
+                non_empty(&row.observation_details),
+                None,
+                Some(&row.date),
+                row.location.as_deref(),
+                row.count,
+                &[],
+                row.breeding_code,
+                None,
+            )
+            .with_context(|| format!("line {}: failed to create sighting", row.line))?;
+            report.sightings_created += 1;
+        }
+    }
+
+    if dry_run {
+        tx.rollback().context("Failed to roll back dry run")?;
+    } else {
+        tx.commit().context("Failed to commit import")?;
+    }
+
+    Ok(report)
+}
+
+fn read_rows<R: Read>(reader: R) -> Result<Vec<EbirdRow>> {
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let columns = Columns::from_headers(csv.headers().context("Failed to read CSV header")?)?;
+
+    let mut rows = Vec::new();
+    for record in csv.records() {
+        let record = record.context("Failed to read CSV row")?;
+        let line = record.position().map_or(0, |position| position.line());
+        let field = |index: Option<usize>| {
+            index.and_then(|index| record.get(index)).map(str::trim).unwrap_or("").to_string()
+        };
+
+        let submission_id = field(Some(columns.submission_id));
+        let scientific_name = field(Some(columns.scientific_name));
+        if submission_id.is_empty() || scientific_name.is_empty() {
+            bail!("line {}: missing Submission ID or Scientific Name", line);
+        }
+
+        let date = field(Some(columns.date));
+        let date = normalize_date(&date).with_context(|| format!("line {}: unrecognized date '{}'", line, date))?;
+
+        let count = field(columns.count);
+        let count = match count.as_str() {
+            "" => None,
+            count => Some(count.parse::<Count>().with_context(|| format!("line {}", line))?),
+        };
+        let breeding_code = field(columns.breeding_code);
+        let breeding_code = match breeding_code.as_str() {
+            "" => None,
+            code => Some(code.parse::<BreedingCode>().with_context(|| format!("line {}", line))?),
+        };
+
+        let effort = read_effort(&columns, &field).with_context(|| format!("line {}", line))?;
+
+        let coordinates = match (field(columns.latitude), field(columns.longitude)) {
+            (latitude, longitude) if latitude.is_empty() || longitude.is_empty() => None,
+            (latitude, longitude) => Some(
+                format!("{},{}", latitude, longitude).parse::<Coordinates>().with_context(|| format!("line {}", line))?,
+            ),
+        };
+
+        let location_parts: Vec<String> = [columns.location, columns.county, columns.state]
+            .into_iter()
+            .map(&field)
+            .filter(|part| !part.is_empty())
+            .collect();
+
+        rows.push(EbirdRow {
+            line,
+            submission_id,
+            common_name: field(columns.common_name),
+            scientific_name,
+            count,
+            breeding_code,
+            location: (!location_parts.is_empty()).then(|| location_parts.join(", ")),
+            coordinates,
+            date,
+            observation_details: field(columns.observation_details),
+            checklist_comments: field(columns.checklist_comments),
+            effort,
+        });
+    }
+
+    Ok(rows)
+}
+
+/// A checklist's effort columns. eBird's other protocols (area, historical, pelagic, ...)
+/// have no equivalent and are left unset; a stationary count's distance is dropped.
+fn read_effort(columns: &Columns, field: &dyn Fn(Option<usize>) -> String) -> Result<Effort> {
+    let number = |index: Option<usize>, name: &str| -> Result<Option<f64>> {
+        match field(index).as_str() {
+            "" => Ok(None),
+            value => value.parse::<f64>().map(Some).with_context(|| format!("invalid {} '{}'", name, value)),
+        }
+    };
+    let whole = |value: Option<f64>| value.filter(|value| *value >= 1.0).map(|value| value.round() as u32);
+
+    let protocol = field(columns.protocol).to_lowercase();
+    let protocol = Protocol::ALL.into_iter().find(|known| protocol.contains(known.as_str()))
+        .or(protocol.contains("casual").then_some(Protocol::Incidental));
+    let start_time = match field(columns.time).as_str() {
+        "" => None,
+        time => Some(parse_start_time(time)?),
+    };
+    let distance_km = number(columns.distance, "distance")?.filter(|_| protocol != Some(Protocol::Stationary));
+
+    Ok(Effort {
+        start_time,
+        duration_min: whole(number(columns.duration, "duration")?),
+        distance_km,
+        party_size: whole(number(columns.observers, "number of observers")?),
+        protocol,
+        complete: matches!(field(columns.all_obs_reported).to_uppercase().as_str(), "1" | "Y" | "YES" | "TRUE"),
+    })
+}
+
+/// eBird exports use YYYY-MM-DD; older ones use MM-DD-YYYY
+fn normalize_date(date: &str) -> Option<String> {
+    let parts: Vec<&str> = date.split(['-', '/']).collect();
+    let digits = |part: &str, width: usize| part.len() == width && part.chars().all(|c| c.is_ascii_digit());
+
+    match parts.as_slice() {
+        [year, month, day] if digits(year, 4) && digits(month, 2) && digits(day, 2) => {
+            Some(format!("{}-{}-{}", year, month, day))
+        }
+        [month, day, year] if digits(year, 4) && digits(month, 2) && digits(day, 2) => {
+            Some(format!("{}-{}-{}", year, month, day))
+        }
+        _ => None,
+    }
+}
+
+/// The name to look up when an eBird scientific name has no exact match, or None for
+/// slashes, hybrids and domestics. Spuhs ("Buteo sp.") fall back to the genus or family
+/// named, and subspecies/groups to their species.
+fn taxon_lookup_name(scientific_name: &str) -> Option<String> {
+    let name = scientific_name.trim();
+    if name.contains(['/', '(']) || name.split_whitespace().any(|word| word == "x") {
+        return None;
+    }
+
+    if let Some(higher) = name.strip_suffix(" sp.") {
+        let higher = higher.trim();
+        return (!higher.contains(' ')).then(|| higher.to_string());
+    }
+
+    // "Junco hyemalis [oreganus Group]" and "Larus argentatus smithsonianus" → species
+    let words: Vec<&str> = name.split('[').next().unwrap_or(name).split_whitespace().take(2).collect();
+    Some(words.join(" "))
+}
+
+fn record_unmatched(unmatched: &mut Vec<UnmatchedName>, row: &EbirdRow) {
+    match unmatched.iter_mut().find(|name| name.scientific_name == row.scientific_name) {
+        Some(name) => name.rows += 1,
+        None => unmatched.push(UnmatchedName {
+            scientific_name: row.scientific_name.clone(),
+            common_name: row.common_name.clone(),
+            rows: 1,
+        }),
+    }
+}
+
+/// Which sightings to export; the filters combine
+#[derive(Debug, Default)]
+pub struct EbirdExportFilter {
+    pub trip_id: Option<i64>,
+    pub taxon_id: Option<i64>,
+    /// Matched against each sighting's own date
+    pub dates: Option<DateRange>,
+}
+
+/// Outcome of an eBird export
+#[derive(Debug, Default)]
+pub struct EbirdExportReport {
+    pub checklists: usize,
+    pub sightings: usize,
+    /// Sightings eBird can't accept, left out of the file
+    pub skipped: Vec<SkippedSighting>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SkippedSighting {
+    pub id: i64,
+    pub common_name: String,
+    pub reason: &'static str,
+}
+
+/// A sighting ready to write, with the checklist it belongs to
+struct RecordRow<'a> {
+    sighting: &'a Sighting,
+    trip: Option<&'a Trip>,
+    date: String,
+    location: String,
+    names: (String, String, String),
+}
+
+/// Export sightings to a file; see [`export_ebird_csv`]
+pub fn export_ebird_file(conn: &Connection, filter: &EbirdExportFilter, path: &Path) -> Result<EbirdExportReport> {
+    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    export_ebird_csv(conn, filter, file)
+}
+
+/// Write sightings in eBird Record Format (Extended), one checklist per trip, date and
+/// location. Sightings on no trip with the same date and location share a checklist.
+/// Family and genus sightings become "sp." entries; sightings without a full date or a
+/// location, or identified above family, are skipped and reported.
+pub fn export_ebird_csv<W: Write>(conn: &Connection, filter: &EbirdExportFilter, writer: W) -> Result<EbirdExportReport> {
+    let sightings = select_sightings(conn, filter)?;
+
+    let mut trips: HashMap<i64, Trip> = HashMap::new();
+    for trip_id in sightings.iter().filter_map(|sighting| sighting.trip_id) {
+        if let Entry::Vacant(entry) = trips.entry(trip_id) {
+            entry.insert(get_trip_by_id(conn, trip_id)?);
+        }
+    }
+
+    let mut taxa: HashMap<i64, Taxon> = HashMap::new();
+    for sighting in &sightings {
+        if let Entry::Vacant(entry) = taxa.entry(sighting.taxon_id) {
+            entry.insert(get_taxon_by_id(conn, sighting.taxon_id)?);
+        }
+    }
+
+    let mut report = EbirdExportReport::default();
+    let mut rows = Vec::new();
+    for sighting in &sightings {
+        let trip = sighting.trip_id.and_then(|trip_id| trips.get(&trip_id));
+        match record_row(sighting, &taxa[&sighting.taxon_id], trip) {
+            Ok(row) => rows.push(row),
+            Err(reason) => report.skipped.push(SkippedSighting {
+                id: sighting.id,
+                common_name: sighting.common_name.clone(),
+                reason,
+            }),
+        }
+    }
+
+    // Stable sort keeps each checklist's sightings in their original order
+    rows.sort_by(|a, b| checklist_key(a).cmp(&checklist_key(b)));
+
+    let mut csv = csv::WriterBuilder::new().has_headers(false).from_writer(writer);
+    let mut previous = None;
+    for row in &rows {
+        let key = checklist_key(row);
+        if previous.as_ref() != Some(&key) {
+            report.checklists += 1;
+            previous = Some(key);
+        }
+
+        let count = row.sighting.count.unwrap_or(Count::Present).to_string();
+        let comments = species_comments(row.sighting);
+        let (common_name, genus, species) = &row.names;
+        let checklist_comments = row.trip.and_then(|trip| trip.notes.as_deref()).map(single_line).unwrap_or_default();
+        let effort = effort_columns(row.trip.map(|trip| &trip.effort));
+        let coordinates = row.sighting.coordinates.or(row.trip.and_then(|trip| trip.coordinates));
+        let (latitude, longitude) = coordinates
+            .map(|point| (point.latitude.to_string(), point.longitude.to_string()))
+            .unwrap_or_default();
+
+        csv.write_record([
+            common_name.as_str(),
+            genus,
+            species,
+            &count,
+            &comments,
+            &row.location,
+            &latitude,
+            &longitude,
+            &row.date,
+            &effort.start_time,
+            "", // state/province
+            "", // country
+            &effort.protocol,
+            &effort.observers,
+            &effort.duration,
+            &effort.all_obs_reported,
+            &effort.distance_miles,
+            "", // area (acres)
+            &checklist_comments,
+        ])
+        .context("Failed to write eBird record")?;
+        report.sightings += 1;
+    }
+    csv.flush().context("Failed to write eBird export")?;
+
+    Ok(report)
+}
+
+/// A checklist's effort columns in the record format
+struct EffortColumns {
+    start_time: String,
+    protocol: String,
+    observers: String,
+    duration: String,
+    all_obs_reported: String,
+    distance_miles: String,
+}
+
+/// Effort from the trip; checklists with no trip or no protocol go out as incidental
+fn effort_columns(effort: Option<&Effort>) -> EffortColumns {
+    let effort = effort.cloned().unwrap_or_default();
+    let text = |value: Option<String>| value.unwrap_or_default();
+    EffortColumns {
+        start_time: text(effort.start_time),
+        protocol: effort.protocol.unwrap_or(Protocol::Incidental).to_string(),
+        observers: text(effort.party_size.map(|size| size.to_string())),
+        duration: text(effort.duration_min.map(|minutes| minutes.to_string())),
+        all_obs_reported: if effort.complete { "Y" } else { "N" }.to_string(),
+        distance_miles: text(effort.distance_km.map(|km| format!("{:.2}", km / KM_PER_MILE))),
+    }
+}
+
+fn select_sightings(conn: &Connection, filter: &EbirdExportFilter) -> Result<Vec<Sighting>> {
+    let mut sightings = match (filter.trip_id, filter.taxon_id) {
+        (Some(trip_id), _) => {
+            get_trip_by_id(conn, trip_id)?;
+            get_sightings_by_trip_id(conn, trip_id)?
+        }
+        (None, Some(taxon_id)) => get_sightings_by_taxon(conn, &get_taxon_by_id(conn, taxon_id)?)?,
+        (None, None) => {
+            let everything = DateRange { from: None, to: None };
+            get_sightings_by_date_range(conn, filter.dates.as_ref().unwrap_or(&everything))?
+        }
+    };
+
+    if let (Some(_), Some(taxon_id)) = (filter.trip_id, filter.taxon_id) {
+        let taxon = get_taxon_by_id(conn, taxon_id)?;
+        let ids: HashSet<i64> = get_sightings_by_taxon(conn, &taxon)?.iter().map(|sighting| sighting.id).collect();
+        sightings.retain(|sighting| ids.contains(&sighting.id));
+    }
+    if let Some(ref range) = filter.dates {
+        sightings.retain(|sighting| sighting.date.is_some_and(|date| range.contains(&date.to_iso())));
+    }
+
+    Ok(sightings)
+}
+
+/// Resolve a sighting's checklist date, location and names, or why eBird can't take it
+fn record_row<'a>(sighting: &'a Sighting, taxon: &Taxon, trip: Option<&'a Trip>) -> Result<RecordRow<'a>, &'static str> {
+    let date = sighting.date.or(trip.and_then(|trip| trip.date));
+    let date = date.and_then(record_date).ok_or("no full date")?;
+
+    let location = sighting
+        .location
+        .as_deref()
+        .or(trip.and_then(|trip| trip.location.as_deref()))
+        .or(trip.map(|trip| trip.name.as_str()))
+        .ok_or("no location")?;
+
+    let names = record_names(sighting, taxon).ok_or("identified above family level")?;
+
+    Ok(RecordRow { sighting, trip, date, location: location.to_string(), names })
+}
+
+fn checklist_key<'a>(row: &'a RecordRow) -> (String, Option<i64>, &'a str) {
+    // MM/DD/YYYY doesn't sort; compare on the ISO day instead, leaving out any time
+    let iso = row.sighting.date.or(row.trip.and_then(|trip| trip.date)).map(|date| date.to_iso()).unwrap_or_default();
+    (iso.get(..10).unwrap_or(&iso).to_string(), row.sighting.trip_id, &row.location)
+}
+
+/// Common name, genus and species columns. The species column carries the rest of the
+/// scientific name as eBird writes it ("hyemalis oreganus", "viduata/autumnalis",
+/// "platyrhynchos x rubripes"). Genus and family sightings become spuhs ("Dendrocygna" /
+/// "sp."), the form eBird uses for birds not identified to species.
+fn record_names(sighting: &Sighting, taxon: &Taxon) -> Option<(String, String, String)> {
+    if matches!(taxon.rank, Rank::Species | Rank::Subspecies | Rank::Hybrid | Rank::Slash) {
+        let name = taxon.scientific_name();
+        if let Some((genus, rest)) = name.split_once(' ') {
+            return Some((sighting.common_name.clone(), genus.to_string(), rest.to_string()));
+        }
+    }
+
+    let higher = sighting.genus.as_ref().or(sighting.family.as_ref())?;
+    let common_name = if sighting.common_name.eq_ignore_ascii_case(higher) {
+        format!("{} sp.", higher)
+    } else {
+        sighting.common_name.clone()
+    };
+    Some((common_name, higher.clone(), "sp.".to_string()))
+}
+
+/// eBird Record Format dates are MM/DD/YYYY; partial dates can't be submitted
+fn record_date(date: ObservedDate) -> Option<String> {
+    if date.precision() < DatePrecision::Day {
+        return None;
+    }
+    let iso = date.to_iso();
+    Some(format!("{}/{}/{}", &iso[5..7], &iso[8..10], &iso[..4]))
+}
+
+/// The record format has no age/sex or breeding columns, so they ride along in the
+/// species comments after the notes
+fn species_comments(sighting: &Sighting) -> String {
+    let mut parts: Vec<String> = sighting.notes.as_deref().map(single_line).into_iter().collect();
+    if !sighting.age_sex.is_empty() {
+        parts.push(format!("Age/sex: {}", format_age_sex(&sighting.age_sex)));
+    }
+    if let Some(code) = sighting.breeding_code {
+        parts.push(format!("Breeding code: {}", code));
+    }
+    parts.retain(|part| !part.is_empty());
+    parts.join(". ")
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::search::parse_date_range;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+        crate::core::migrations::migrate(&conn).unwrap();
+
+        // Load test taxa (first 100 species + parent taxa)
+        let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
+        conn.execute_batch(&test_taxa).unwrap();
+
+        conn
+    }
+
+    const EXPORT: &str = "\
+Submission ID,Common Name,Scientific Name,Taxonomic Order,Count,State/Province,County,Location ID,Location,Latitude,Longitude,Date,Time,Protocol,Duration (Min),All Obs Reported,Distance Traveled (km),Area Covered (ha),Number of Observers,Breeding Code,Observation Details,Checklist Comments,ML Catalog Numbers
+S100,White-faced Whistling-Duck,Dendrocygna viduata,200,3,US-TX,Hidalgo,L1,Estero Llano Grande SP,26.12,-97.95,2024-03-02,07:30 AM,Traveling,90,1,2.1,,2,P Pair in Suitable Habitat,\"Pair on the pond, one calling\",Windy morning,
+S100,Black-bellied Whistling-Duck,Dendrocygna autumnalis,201,X,US-TX,Hidalgo,L1,Estero Llano Grande SP,26.12,-97.95,2024-03-02,07:30 AM,Traveling,90,1,2.1,,2,,,Windy morning,
+S100,whistling-duck sp.,Dendrocygna sp.,202,1,US-TX,Hidalgo,L1,Estero Llano Grande SP,26.12,-97.95,2024-03-02,07:30 AM,Traveling,90,1,2.1,,2,,Flyover,Windy morning,
+S100,Mallard x American Black Duck (hybrid),Anas platyrhynchos x rubripes,300,1,US-TX,Hidalgo,L1,Estero Llano Grande SP,26.12,-97.95,2024-03-02,07:30 AM,Traveling,90,1,2.1,,2,,,Windy morning,
+S200,Black-bellied Whistling-Duck,Dendrocygna autumnalis,201,12,US-TX,Cameron,L2,Resaca de la Palma SP,26.0,-97.5,03-05-2024,08:00 AM,Stationary,30,1,,,1,,,,
+S200,Mallard x American Black Duck (hybrid),Anas platyrhynchos x rubripes,300,2,US-TX,Cameron,L2,Resaca de la Palma SP,26.0,-97.5,03-05-2024,08:00 AM,Stationary,30,1,,,1,,,,
+";
+
+    fn trip_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM trips", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_import_creates_trips_and_sightings() {
+        let conn = setup_test_db();
+
+        let report = import_ebird_csv(&conn, EXPORT.as_bytes(), false).unwrap();
+        assert_eq!(report.trips_created, 2);
+        assert_eq!(report.sightings_created, 4);
+        assert!(report.already_imported.is_empty());
+        assert_eq!(
+            report.unmatched,
+            vec![UnmatchedName {
+                scientific_name: "Anas platyrhynchos x rubripes".to_string(),
+                common_name: "Mallard x American Black Duck (hybrid)".to_string(),
+                rows: 2,
+            }]
+        );
+
+        let trip_id: i64 = conn
+            .query_row("SELECT trip_id FROM ebird_checklists WHERE submission_id = 'S100'", [], |row| row.get(0))
+            .unwrap();
+        let trip = get_trip_by_id(&conn, trip_id).unwrap();
+        assert_eq!(trip.name, "Estero Llano Grande SP, Hidalgo, US-TX");
+        assert_eq!(trip.date, Some("2024-03-02".parse().unwrap()));
+        assert_eq!(trip.notes, Some("Windy morning".to_string()));
+        assert_eq!(trip.coordinates, Some(Coordinates::new(26.12, -97.95, None).unwrap()));
+        assert_eq!(
+            trip.effort,
+            Effort {
+                start_time: Some("07:30".to_string()),
+                duration_min: Some(90),
+                distance_km: Some(2.1),
+                party_size: Some(2),
+                protocol: Some(Protocol::Traveling),
+                complete: true,
+            }
+        );
+
+        let sightings = get_sightings_by_trip_id(&conn, trip_id).unwrap();
+        assert_eq!(sightings.len(), 3);
+        let duck = sightings.iter().find(|s| s.common_name == "White-faced Whistling-Duck").unwrap();
+        assert_eq!(duck.notes, Some("Pair on the pond, one calling".to_string()));
+        assert_eq!(duck.count, Some(Count::Exact(3)));
+        assert_eq!(duck.breeding_code, Some(BreedingCode::Pair));
+        assert!(sightings.iter().any(|s| s.count == Some(Count::Present)));
+        assert_eq!(duck.date, Some("2024-03-02".parse().unwrap()));
+        assert_eq!(duck.location, Some("Estero Llano Grande SP, Hidalgo, US-TX".to_string()));
+
+        // "Dendrocygna sp." lands on the genus
+        assert!(sightings.iter().any(|s| s.species_epithet.is_none() && s.genus == Some("Dendrocygna".to_string())));
+    }
+
+    #[test]
+    fn test_import_normalizes_old_date_format() {
+        let conn = setup_test_db();
+        import_ebird_csv(&conn, EXPORT.as_bytes(), false).unwrap();
+
+        let date: String = conn
+            .query_row(
+                "SELECT t.date FROM trips t JOIN ebird_checklists c ON c.trip_id = t.id WHERE c.submission_id = 'S200'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(date, "2024-03-05");
+    }
+
+    #[test]
+    fn test_dry_run_reports_without_writing() {
+        let conn = setup_test_db();
+
+        let report = import_ebird_csv(&conn, EXPORT.as_bytes(), true).unwrap();
+        assert_eq!(report.trips_created, 2);
+        assert_eq!(report.sightings_created, 4);
+        assert_eq!(report.unmatched.len(), 1);
+        assert_eq!(trip_count(&conn), 0);
+    }
+
+    #[test]
+    fn test_reimport_skips_known_checklists() {
+        let conn = setup_test_db();
+
+        import_ebird_csv(&conn, EXPORT.as_bytes(), false).unwrap();
+        let report = import_ebird_csv(&conn, EXPORT.as_bytes(), false).unwrap();
+
+        assert_eq!(report.trips_created, 0);
+        assert_eq!(report.sightings_created, 0);
+        assert_eq!(report.already_imported, vec!["S100".to_string(), "S200".to_string()]);
+        assert_eq!(trip_count(&conn), 2);
+    }
+
+    #[test]
+    fn test_import_rejects_bad_files() {
+        let conn = setup_test_db();
+
+        let missing_column = "Common Name,Scientific Name,Date\nMallard,Anas platyrhynchos,2024-01-01\n";
+        let err = import_ebird_csv(&conn, missing_column.as_bytes(), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("Submission ID"));
+
+        let bad_date = "Submission ID,Scientific Name,Date\nS1,Dendrocygna viduata,March 2\n";
+        let err = import_ebird_csv(&conn, bad_date.as_bytes(), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("line 2"));
+
+        let bad_count = "Submission ID,Scientific Name,Date,Count\nS1,Dendrocygna viduata,2024-01-01,lots\n";
+        let err = import_ebird_csv(&conn, bad_count.as_bytes(), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("invalid count"));
+        assert_eq!(trip_count(&conn), 0);
+    }
+
+    #[test]
+    fn test_taxon_lookup_name() {
+        assert_eq!(taxon_lookup_name("Turdus migratorius"), Some("Turdus migratorius".to_string()));
+        assert_eq!(taxon_lookup_name("Buteo sp."), Some("Buteo".to_string()));
+        assert_eq!(taxon_lookup_name("Anatidae sp."), Some("Anatidae".to_string()));
+        assert_eq!(taxon_lookup_name("Junco hyemalis [oreganus Group]"), Some("Junco hyemalis".to_string()));
+        assert_eq!(taxon_lookup_name("Larus argentatus smithsonianus"), Some("Larus argentatus".to_string()));
+        assert_eq!(taxon_lookup_name("Empidonax alnorum/traillii"), None);
+        assert_eq!(taxon_lookup_name("Anser/Branta sp."), None);
+        assert_eq!(taxon_lookup_name("Anas platyrhynchos x rubripes"), None);
+        assert_eq!(taxon_lookup_name("Anas platyrhynchos (Domestic type)"), None);
+    }
+
+    fn trip_for(conn: &Connection, submission_id: &str) -> i64 {
+        conn.query_row("SELECT trip_id FROM ebird_checklists WHERE submission_id = ?1", params![submission_id], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn export(conn: &Connection, filter: &EbirdExportFilter) -> (EbirdExportReport, Vec<csv::StringRecord>) {
+        let mut out = Vec::new();
+        let report = export_ebird_csv(conn, filter, &mut out).unwrap();
+        let records = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(out.as_slice())
+            .records()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        (report, records)
+    }
+
+    fn columns(record: &csv::StringRecord, n: usize) -> Vec<&str> {
+        record.iter().take(n).collect()
+    }
+
+    #[test]
+    fn test_export_trip_in_record_format() {
+        let conn = setup_test_db();
+        import_ebird_csv(&conn, EXPORT.as_bytes(), false).unwrap();
+
+        let filter = EbirdExportFilter { trip_id: Some(trip_for(&conn, "S100")), ..Default::default() };
+        let (report, records) = export(&conn, &filter);
+        assert_eq!(report.checklists, 1);
+        assert_eq!(report.sightings, 3);
+        assert!(report.skipped.is_empty());
+        assert!(records.iter().all(|record| record.len() == 19));
+
+        let duck = &records[0];
+        assert_eq!(columns(duck, 5), [
+                "White-faced Whistling-Duck",
+                "Dendrocygna",
+                "viduata",
+                "3",
+                "Pair on the pond, one calling. Breeding code: P Pair in suitable habitat",
+            ]);
+        assert_eq!(&duck[5], "Estero Llano Grande SP, Hidalgo, US-TX");
+        assert_eq!(columns(duck, 8)[6..], ["26.12", "-97.95"]);
+        assert_eq!(&duck[8], "03/02/2024");
+        assert_eq!(duck.iter().skip(9).take(9).collect::<Vec<_>>(), ["07:30", "", "", "traveling", "2", "90", "Y", "1.30", ""]);
+        assert_eq!(&duck[18], "Windy morning");
+
+        // "X" survives the round trip
+        assert_eq!(&records[1][3], "X");
+
+        // The genus-level sighting goes back out as a spuh
+        assert_eq!(columns(&records[2], 5), ["Dendrocygna sp.", "Dendrocygna", "sp.", "1", "Flyover"]);
+
+        // A sighting's own point wins over its trip's
+        conn.execute("UPDATE sightings SET latitude = 26.13, longitude = -97.96 WHERE notes = 'Flyover'", []).unwrap();
+        let (_, records) = export(&conn, &filter);
+        assert_eq!(columns(&records[0], 8)[6..], ["26.12", "-97.95"]);
+        assert_eq!(columns(&records[2], 8)[6..], ["26.13", "-97.96"]);
+    }
+
+    #[test]
+    fn test_export_filters() {
+        let conn = setup_test_db();
+        import_ebird_csv(&conn, EXPORT.as_bytes(), false).unwrap();
+
+        let (report, _) = export(&conn, &EbirdExportFilter::default());
+        assert_eq!((report.checklists, report.sightings), (2, 4));
+
+        let march_5 = EbirdExportFilter { dates: Some(parse_date_range("2024-03-05").unwrap()), ..Default::default() };
+        let (report, records) = export(&conn, &march_5);
+        assert_eq!((report.checklists, report.sightings), (1, 1));
+        assert_eq!(&records[0][8], "03/05/2024");
+
+        // The genus includes both species and the spuh
+        let genus = find_taxon_by_scientific_name(&conn, "Dendrocygna").unwrap().unwrap();
+        let (report, _) = export(&conn, &EbirdExportFilter { taxon_id: Some(genus.id), ..Default::default() });
+        assert_eq!(report.sightings, 4);
+
+        let viduata = find_taxon_by_scientific_name(&conn, "Dendrocygna viduata").unwrap().unwrap();
+        let both = EbirdExportFilter {
+            trip_id: Some(trip_for(&conn, "S200")),
+            taxon_id: Some(viduata.id),
+            dates: None,
+        };
+        assert_eq!(export(&conn, &both).0.sightings, 0);
+
+        let missing = EbirdExportFilter { trip_id: Some(9999), ..Default::default() };
+        assert!(export_ebird_csv(&conn, &missing, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_export_skips_what_ebird_cannot_take() {
+        let conn = setup_test_db();
+
+        let species = find_taxon_by_scientific_name(&conn, "Dendrocygna viduata").unwrap().unwrap();
+        let order: i64 = conn.query_row("SELECT id FROM taxa WHERE rank = 'order' LIMIT 1", [], |row| row.get(0)).unwrap();
+
+        let trip_id = create_trip(&conn, "Backyard", Some("2024-05"), None, None, None, &Effort::default()).unwrap();
+        create_sighting(&conn, Some(trip_id), species.id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, order, None, None, Some("2024-05-01"), Some("Pond"), None, &[], None, None).unwrap();
+        create_sighting(&conn, None, species.id, None, None, Some("2024-05-01"), None, None, &[], None, None).unwrap();
+        create_sighting(
+            &conn,
+            None,
+            species.id,
+            None,
+            None,
+            Some("2024-05-01"),
+            Some("Pond"),
+            Some(Count::Exact(2)),
+            &[],
+            None,
+            None,
+        )
+        .unwrap();
+
+        let (report, records) = export(&conn, &EbirdExportFilter::default());
+        let reasons: Vec<&str> = report.skipped.iter().map(|skipped| skipped.reason).collect();
+        assert_eq!(reasons, vec!["identified above family level", "no location"]);
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][3], "2");
+        assert_eq!(columns(&records[0], 8)[6..], ["", ""]);
+
+        // Only a partial trip date to fall back on
+        let (report, _) = export(&conn, &EbirdExportFilter { trip_id: Some(trip_id), ..Default::default() });
+        assert_eq!(report.skipped[0].reason, "no full date");
+    }
+
+    #[test]
+    fn test_export_identification_categories() {
+        let conn = setup_test_db();
+
+        let create = |rank: Rank, epithet: Option<&str>, subspecies: Option<&str>, name: &str| {
+            let taxon = crate::core::taxon::create_taxon(&conn, rank, "Animalia", Some("Chordata"), Some("Aves"), Some("Anseriformes"), Some("Anatidae"), Some("Dendrocygninae"), Some("Dendrocygna"), epithet, subspecies, name).unwrap();
+            create_sighting(&conn, None, taxon, None, None, Some("2024-03-02"), Some("Pond"), None, &[], None, None).unwrap();
+        };
+        create(Rank::Subspecies, Some("autumnalis"), Some("fulgens"), "Black-bellied Whistling-Duck (fulgens)");
+        create(Rank::Slash, Some("viduata/autumnalis"), None, "White-faced/Black-bellied Whistling-Duck");
+        create(Rank::Hybrid, Some("viduata x autumnalis"), None, "White-faced x Black-bellied Whistling-Duck (hybrid)");
+        create(Rank::Spuh, None, None, "whistling-duck sp.");
+
+        let (report, records) = export(&conn, &EbirdExportFilter::default());
+        assert!(report.skipped.is_empty());
+        let names: Vec<Vec<&str>> = records.iter().map(|record| columns(record, 3)).collect();
+        assert_eq!(
+            names,
+            vec![
+                vec!["Black-bellied Whistling-Duck (fulgens)", "Dendrocygna", "autumnalis fulgens"],
+                vec!["White-faced/Black-bellied Whistling-Duck", "Dendrocygna", "viduata/autumnalis"],
+                vec!["White-faced x Black-bellied Whistling-Duck (hybrid)", "Dendrocygna", "viduata x autumnalis"],
+                vec!["whistling-duck sp.", "Dendrocygna", "sp."],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_species_comments() {
+        let conn = setup_test_db();
+        let species = find_taxon_by_scientific_name(&conn, "Dendrocygna viduata").unwrap().unwrap();
+        let age_sex = crate::models::parse_age_sex("1 male, 1 female").unwrap();
+
+        let id = create_sighting(
+            &conn,
+            None,
+            species.id,
+            Some("In the\nreeds"),
+            None,
+            None,
+            None,
+            Some(Count::Exact(2)),
+            &age_sex,
+            Some(BreedingCode::Courtship),
+            None,
+        )
+        .unwrap();
+        let sighting = crate::core::sighting::get_sighting_by_id(&conn, id).unwrap();
+        assert_eq!(
+            species_comments(&sighting),
+            "In the reeds. Age/sex: 1 male, 1 female. Breeding code: C Courtship, display or copulation"
+        );
+
+        let id = create_sighting(&conn, None, species.id, None, None, None, None, None, &[], None, None).unwrap();
+        let sighting = crate::core::sighting::get_sighting_by_id(&conn, id).unwrap();
+        assert_eq!(species_comments(&sighting), "");
+    }
+}
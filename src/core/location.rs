@@ -0,0 +1,411 @@
+use crate::core::db::{coordinate_values, coordinates_at, optional_text};
+use crate::models::{Coordinates, Location, LocationUpdate};
+use anyhow::{Context, Result, bail};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+
+const LOCATION_COLUMNS: &str =
+    "id, name, locality, county, state, country, latitude, longitude, accuracy_m, hotspot";
+
+/// SQL folding `expr` the way the `name_key` and `locality_key` columns are folded:
+/// lowercase, with commas, periods and hyphens as spaces, apostrophes dropped and runs of spaces collapsed
+fn location_key(expr: &str) -> String {
+    format!(
+        "trim(replace(replace(replace(replace(replace(replace(replace(lower({}), ',', ' '), '.', ' '), '-', ' '), '''', ''), '  ', ' '), '  ', ' '), '  ', ' '))",
+        expr
+    )
+}
+
+/// Create a new location
+pub fn create_location(
+    conn: &Connection,
+    locality: &str,
+    county: Option<&str>,
+    state: Option<&str>,
+    country: Option<&str>,
+    coordinates: Option<Coordinates>,
+    hotspot: bool,
+) -> Result<i64> {
+    if locality.trim().is_empty() {
+        bail!("location locality cannot be empty");
+    }
+
+    let sql = r#"
+        INSERT INTO locations (locality, county, state, country, latitude, longitude, accuracy_m, hotspot)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+    "#;
+
+    let (latitude, longitude, accuracy_m) = coordinate_values(coordinates);
+    conn.execute(
+        sql,
+        params![locality.trim(), county, state, country, latitude, longitude, accuracy_m, hotspot],
+    )
+    .context("Failed to insert location")?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Map a row of [`LOCATION_COLUMNS`] to a `Location`
+pub(crate) fn location_from_row(row: &Row) -> rusqlite::Result<Location> {
+    Ok(Location {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        locality: row.get(2)?,
+        county: row.get(3)?,
+        state: row.get(4)?,
+        country: row.get(5)?,
+        coordinates: coordinates_at(row, 6)?,
+        hotspot: row.get(9)?,
+    })
+}
+
+/// Get a location by ID
+pub fn get_location_by_id(conn: &Connection, id: i64) -> Result<Location> {
+    let sql = format!("SELECT {} FROM locations WHERE id = ?1", LOCATION_COLUMNS);
+
+    let location = conn.query_row(&sql, params![id], location_from_row)
+        .context("Failed to fetch location")?;
+
+    Ok(location)
+}
+
+/// Get every location, by country, state, county and locality
+pub fn get_all_locations(conn: &Connection) -> Result<Vec<Location>> {
+    let sql = format!(
+        "SELECT {} FROM locations ORDER BY country, state, county, locality_key, id",
+        LOCATION_COLUMNS
+    );
+
+    let mut stmt = conn.prepare(&sql)
+        .context("Failed to prepare get all locations query")?;
+
+    let rows = stmt.query_map([], location_from_row)
+        .context("Failed to execute get all locations query")?;
+
+    let results: Vec<Location> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse location rows")?;
+    Ok(results)
+}
+
+/// Find the location a free-text name refers to, ignoring case, punctuation and spacing.
+/// A match on the full name ("Central Park, NY") wins over a match on the locality alone
+/// ("Central Park"); among equals the oldest location wins.
+pub fn find_location(conn: &Connection, name: &str) -> Result<Option<Location>> {
+    let key = location_key("?1");
+    let sql = format!(
+        r#"
+        SELECT {columns}
+        FROM locations
+        WHERE name_key = {key} OR locality_key = {key}
+        ORDER BY name_key = {key} DESC, id
+        LIMIT 1
+        "#,
+        columns = LOCATION_COLUMNS,
+        key = key
+    );
+
+    let location = conn.query_row(&sql, params![name.trim()], location_from_row)
+        .optional()
+        .context("Failed to look up location")?;
+    Ok(location)
+}
+
+/// The location id and name a trip or sighting named `name` should carry, creating a
+/// location if none matches. A blank name means no location.
+pub(crate) fn resolve_location(conn: &Connection, name: &str) -> Result<Option<(i64, String)>> {
+    if name.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let location = match find_location(conn, name)? {
+        Some(location) => location,
+        None => {
+            let id = create_location(conn, name, None, None, None, None, false)?;
+            get_location_by_id(conn, id)?
+        }
+    };
+    Ok(Some((location.id, location.name)))
+}
+
+/// Column assignments for a trip or sighting update that sets `location` by name or `location_id` directly
+pub(crate) fn location_assignments(
+    conn: &Connection,
+    location: Option<&str>,
+    location_id: Option<Option<i64>>,
+    assignments: &mut Vec<&'static str>,
+    values: &mut Vec<Value>,
+) -> Result<()> {
+    let resolved = match (location, location_id) {
+        (Some(_), Some(_)) => bail!("set either a location name or a location id, not both"),
+        (Some(name), None) => resolve_location(conn, name)?,
+        (None, Some(Some(id))) => {
+            let location = get_location_by_id(conn, id)
+                .with_context(|| format!("Location {} not found", id))?;
+            Some((location.id, location.name))
+        }
+        (None, Some(None)) => None,
+        (None, None) => return Ok(()),
+    };
+
+    let (id, name) = resolved.unzip();
+    assignments.extend(["location_id = ?", "location = ?"]);
+    values.extend([Value::from(id), Value::from(name)]);
+    Ok(())
+}
+
+/// Update a location (only the fields set in `changes` are written). Trips and sightings
+/// there pick up the new name.
+pub fn update_location(conn: &Connection, id: i64, changes: &LocationUpdate) -> Result<usize> {
+    let mut assignments: Vec<&str> = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(ref locality) = changes.locality {
+        if locality.trim().is_empty() {
+            bail!("location locality cannot be empty");
+        }
+        assignments.push("locality = ?");
+        values.push(Value::from(locality.trim().to_string()));
+    }
+
+    for (assignment, value) in [
+        ("county = ?", &changes.county),
+        ("state = ?", &changes.state),
+        ("country = ?", &changes.country),
+    ] {
+        if let Some(v) = value {
+            assignments.push(assignment);
+            values.push(optional_text(v.trim()));
+        }
+    }
+
+    if let Some(coordinates) = changes.coordinates {
+        let (latitude, longitude, accuracy_m) = coordinate_values(coordinates);
+        assignments.extend(["latitude = ?", "longitude = ?", "accuracy_m = ?"]);
+        values.extend([Value::from(latitude), Value::from(longitude), Value::from(accuracy_m)]);
+    }
+
+    if let Some(hotspot) = changes.hotspot {
+        assignments.push("hotspot = ?");
+        values.push(Value::from(hotspot));
+    }
+
+    if assignments.is_empty() {
+        bail!("no location fields to update");
+    }
+
+    let sql = format!("UPDATE locations SET {} WHERE id = ?", assignments.join(", "));
+    values.push(Value::from(id));
+
+    let rows_affected = conn.execute(&sql, rusqlite::params_from_iter(values))
+        .context("Failed to update location")?;
+    Ok(rows_affected)
+}
+
+/// Delete a location by ID. Its trips and sightings keep the name as plain text.
+pub fn delete_location(conn: &Connection, id: i64) -> Result<usize> {
+    let sql = "DELETE FROM locations WHERE id = ?1";
+    let rows_affected = conn.execute(sql, params![id])
+        .context("Failed to delete location")?;
+    Ok(rows_affected)
+}
+
+/// Fold `duplicates` into `keep`: their trips and sightings move over and they are deleted.
+/// Returns how many trips and sightings moved.
+pub fn merge_locations(conn: &Connection, keep: i64, duplicates: &[i64]) -> Result<usize> {
+    let tx = conn.unchecked_transaction().context("Failed to start merge")?;
+
+    let target = get_location_by_id(&tx, keep)
+        .with_context(|| format!("Location {} not found", keep))?;
+
+    let mut moved = 0;
+    for &duplicate in duplicates.iter().filter(|&&duplicate| duplicate != keep) {
+        get_location_by_id(&tx, duplicate)
+            .with_context(|| format!("Location {} not found", duplicate))?;
+
+        for table in ["trips", "sightings"] {
+            let sql = format!("UPDATE {} SET location_id = ?1, location = ?2 WHERE location_id = ?3", table);
+            moved += tx.execute(&sql, params![keep, target.name, duplicate])
+                .with_context(|| format!("Failed to move {} to location {}", table, keep))?;
+        }
+        delete_location(&tx, duplicate)?;
+    }
+
+    tx.commit().context("Failed to commit merge")?;
+    Ok(moved)
+}
+
+/// Link trips and sightings that have a location name but no location (rows written by
+/// plain SQL, such as the seed files) to a matching or new location. Returns how many were linked.
+pub fn link_locations(conn: &Connection) -> Result<usize> {
+    let mut linked = 0;
+
+    for table in ["trips", "sightings"] {
+        let sql = format!(
+            "SELECT DISTINCT location FROM {} WHERE location_id IS NULL AND trim(location) <> ''",
+            table
+        );
+        let names: Vec<String> = conn
+            .prepare(&sql)
+            .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect())
+            .with_context(|| format!("Failed to find unlinked {} locations", table))?;
+
+        for name in names {
+            if let Some((id, canonical)) = resolve_location(conn, &name)? {
+                let sql = format!(
+                    "UPDATE {} SET location_id = ?1, location = ?2 WHERE location_id IS NULL AND location = ?3",
+                    table
+                );
+                linked += conn.execute(&sql, params![id, canonical, name])
+                    .with_context(|| format!("Failed to link {} to location {}", table, id))?;
+            }
+        }
+    }
+
+    Ok(linked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Effort;
+    use crate::core::sighting::{create_sighting, get_sighting_by_id, get_sightings_by_location_id};
+    use crate::core::trip::{create_trip, get_trip_by_id, get_trips_by_location_id, update_trip};
+    use crate::models::TripUpdate;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+        crate::core::migrations::migrate(&conn).unwrap();
+
+        // Load test taxa (first 100 species + parent taxa)
+        let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
+        conn.execute_batch(&test_taxa).unwrap();
+
+        conn
+    }
+
+    fn any_taxon(conn: &Connection) -> i64 {
+        conn.query_row("SELECT id FROM taxa WHERE rank = 'species' LIMIT 1", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_create_and_get_location() {
+        let conn = setup_test_db();
+
+        let point = Coordinates::new(40.7825, -73.9655, Some(250.0)).unwrap();
+        let id = create_location(&conn, " Central Park ", Some("New York"), Some("NY"), Some("USA"), Some(point), true).unwrap();
+
+        let location = get_location_by_id(&conn, id).unwrap();
+        assert_eq!(location.locality, "Central Park");
+        assert_eq!(location.name, "Central Park, New York, NY, USA");
+        assert_eq!(location.coordinates, Some(point));
+        assert!(location.hotspot);
+        assert_eq!(location.to_string(), format!("{}: Central Park, New York, NY, USA [hotspot]", id));
+
+        assert!(create_location(&conn, "  ", None, None, None, None, false).is_err());
+    }
+
+    #[test]
+    fn test_find_location_ignores_case_and_punctuation() {
+        let conn = setup_test_db();
+
+        let park = create_location(&conn, "Central Park", None, Some("NY"), None, None, false).unwrap();
+        let other = create_location(&conn, "Central Park", None, Some("CO"), None, None, false).unwrap();
+
+        assert_eq!(find_location(&conn, "central  park.").unwrap().unwrap().id, park);
+        assert_eq!(find_location(&conn, "Central Park, CO").unwrap().unwrap().id, other);
+        assert_eq!(find_location(&conn, "O'Hare").unwrap().map(|location| location.id), None);
+    }
+
+    #[test]
+    fn test_trips_and_sightings_share_locations() {
+        let conn = setup_test_db();
+        let taxon = any_taxon(&conn);
+
+        let trip = create_trip(&conn, "Morning Walk", None, Some("Central Park"), None, None, &Effort::default()).unwrap();
+        let sighting = create_sighting(&conn, Some(trip), taxon, None, None, None, Some("central park"), None, &[], None, None).unwrap();
+        create_trip(&conn, "No Place", None, Some(" "), None, None, &Effort::default()).unwrap();
+
+        let trip = get_trip_by_id(&conn, trip).unwrap();
+        let sighting = get_sighting_by_id(&conn, sighting).unwrap();
+        assert!(trip.location_id.is_some());
+        assert_eq!(sighting.location_id, trip.location_id);
+        assert_eq!(sighting.location, Some("Central Park".to_string()));
+        assert_eq!(get_all_locations(&conn).unwrap().len(), 1);
+
+        let location_id = trip.location_id.unwrap();
+        assert_eq!(get_trips_by_location_id(&conn, location_id).unwrap().len(), 1);
+        assert_eq!(get_sightings_by_location_id(&conn, location_id).unwrap().len(), 1);
+
+        // Renaming the place renames it everywhere
+        let changes = LocationUpdate { state: Some("NY".to_string()), ..Default::default() };
+        update_location(&conn, location_id, &changes).unwrap();
+        assert_eq!(get_trip_by_id(&conn, trip.id).unwrap().location, Some("Central Park, NY".to_string()));
+        assert_eq!(get_sighting_by_id(&conn, sighting.id).unwrap().location, Some("Central Park, NY".to_string()));
+
+        // Deleting it leaves the name behind as text
+        delete_location(&conn, location_id).unwrap();
+        let trip = get_trip_by_id(&conn, trip.id).unwrap();
+        assert_eq!((trip.location_id, trip.location), (None, Some("Central Park, NY".to_string())));
+    }
+
+    #[test]
+    fn test_update_trip_location_by_name_or_id() {
+        let conn = setup_test_db();
+
+        let trip = create_trip(&conn, "Morning Walk", None, Some("Riverside"), None, None, &Effort::default()).unwrap();
+        let lake = create_location(&conn, "Lakeshore", None, None, Some("USA"), None, false).unwrap();
+
+        let changes = TripUpdate { location_id: Some(Some(lake)), ..Default::default() };
+        update_trip(&conn, trip, &changes).unwrap();
+        let updated = get_trip_by_id(&conn, trip).unwrap();
+        assert_eq!((updated.location_id, updated.location), (Some(lake), Some("Lakeshore, USA".to_string())));
+
+        let changes = TripUpdate { location: Some(String::new()), ..Default::default() };
+        update_trip(&conn, trip, &changes).unwrap();
+        let updated = get_trip_by_id(&conn, trip).unwrap();
+        assert_eq!((updated.location_id, updated.location), (None, None));
+
+        let changes = TripUpdate { location_id: Some(Some(9999)), ..Default::default() };
+        assert!(update_trip(&conn, trip, &changes).is_err());
+        let changes = TripUpdate { location: Some("Lakeshore".to_string()), location_id: Some(Some(lake)), ..Default::default() };
+        assert!(update_trip(&conn, trip, &changes).is_err());
+    }
+
+    #[test]
+    fn test_merge_locations() {
+        let conn = setup_test_db();
+        let taxon = any_taxon(&conn);
+
+        let park = create_location(&conn, "Central Park", None, None, None, None, false).unwrap();
+        let trip = create_trip(&conn, "Walk", None, Some("Central Park NYC"), None, None, &Effort::default()).unwrap();
+        create_sighting(&conn, Some(trip), taxon, None, None, None, Some("Central Park NYC"), None, &[], None, None).unwrap();
+        let duplicate = get_trip_by_id(&conn, trip).unwrap().location_id.unwrap();
+        assert_ne!(duplicate, park);
+
+        assert_eq!(merge_locations(&conn, park, &[duplicate, park]).unwrap(), 2);
+        assert!(get_location_by_id(&conn, duplicate).is_err());
+        let trip = get_trip_by_id(&conn, trip).unwrap();
+        assert_eq!((trip.location_id, trip.location), (Some(park), Some("Central Park".to_string())));
+
+        assert!(merge_locations(&conn, park, &[9999]).is_err());
+    }
+
+    #[test]
+    fn test_link_locations() {
+        let conn = setup_test_db();
+
+        conn.execute_batch(
+            "INSERT INTO trips (name, location) VALUES ('A', 'Forest Park'), ('B', 'forest park'), ('C', NULL);",
+        )
+        .unwrap();
+
+        assert_eq!(link_locations(&conn).unwrap(), 2);
+        let linked: i64 = conn
+            .query_row("SELECT COUNT(DISTINCT location_id) FROM trips WHERE location = 'Forest Park'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(linked, 1);
+        assert_eq!(link_locations(&conn).unwrap(), 0);
+    }
+}
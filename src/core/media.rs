@@ -0,0 +1,580 @@
+use crate::core::photos::read_exif;
+use crate::models::{Media, MediaOwner};
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const MEDIA_COLUMNS: &str = "id, sighting_id, trip_id, path, content_hash, mime_type, captured_at, caption";
+
+/// A file about to be attached, with what could be learned from reading it
+#[derive(Debug, Clone)]
+pub(crate) struct MediaFile {
+    path: String,
+    content_hash: Option<String>,
+    mime_type: &'static str,
+    /// When a photo's EXIF says it was taken
+    taken_at: Option<String>,
+    /// Last modified, in seconds since the Unix epoch
+    modified: Option<i64>,
+}
+
+impl MediaFile {
+    /// Read the file at `path`, which must exist; the stored path is made absolute
+    pub(crate) fn read(path: &str) -> Result<MediaFile> {
+        let path = fs::canonicalize(path.trim())
+            .with_context(|| format!("Media file {} not found", path.trim()))?;
+        if !path.is_file() {
+            bail!("{} is not a file", path.display());
+        }
+
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|age| age.as_secs() as i64);
+
+        let mime_type = mime_type_for(&path.to_string_lossy());
+        let taken_at = if mime_type.starts_with("image/") { read_exif(&path).taken_at } else { None };
+
+        Ok(MediaFile {
+            content_hash: Some(hash_file(&path)?),
+            mime_type,
+            path: path.to_string_lossy().into_owned(),
+            taken_at,
+            modified,
+        })
+    }
+
+    /// A file that can't be read here, such as one named in an import; only its name is recorded
+    pub(crate) fn unread(path: &str) -> MediaFile {
+        MediaFile {
+            path: path.trim().to_string(),
+            content_hash: None,
+            mime_type: mime_type_for(path.trim()),
+            taken_at: None,
+            modified: None,
+        }
+    }
+}
+
+/// SHA-256 of a file's contents, in hex
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// MIME type for a file name's extension, `application/octet-stream` when it isn't a known media type
+pub fn mime_type_for(path: &str) -> &'static str {
+    let extension = Path::new(path).extension().map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("heic") => "image/heic",
+        Some("tif" | "tiff") => "image/tiff",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("m4a") => "audio/mp4",
+        Some("flac") => "audio/flac",
+        Some("ogg") => "audio/ogg",
+        Some("mp4") => "video/mp4",
+        Some("mov") => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Record `file` as attached to `owner`. Without a capture time, a photo's EXIF date is used,
+/// then the file's modification time.
+pub(crate) fn attach_media(
+    conn: &Connection,
+    owner: MediaOwner,
+    file: &MediaFile,
+    caption: Option<&str>,
+    captured_at: Option<&str>,
+) -> Result<i64> {
+    let (sighting_id, trip_id) = match owner {
+        MediaOwner::Sighting(id) => (Some(id), None),
+        MediaOwner::Trip(id) => (None, Some(id)),
+    };
+
+    let sql = r#"
+        INSERT INTO media (sighting_id, trip_id, path, content_hash, mime_type, captured_at, caption)
+        VALUES (?1, ?2, ?3, ?4, ?5, COALESCE(?6, ?7, datetime(?8, 'unixepoch', 'localtime')), ?9)
+    "#;
+
+    conn.execute(
+        sql,
+        params![
+            sighting_id,
+            trip_id,
+            file.path,
+            file.content_hash,
+            file.mime_type,
+            captured_at,
+            file.taken_at,
+            file.modified,
+            caption
+        ],
+    )
+    .context("Failed to insert media")?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Whether a file with the same contents is already attached to anything
+pub(crate) fn is_attached(conn: &Connection, file: &MediaFile) -> Result<bool> {
+    let Some(ref hash) = file.content_hash else {
+        return Ok(false);
+    };
+    let attached = conn
+        .query_row("SELECT 1 FROM media WHERE content_hash = ?1 LIMIT 1", params![hash], |_| Ok(()))
+        .optional()
+        .context("Failed to look up media by contents")?
+        .is_some();
+    Ok(attached)
+}
+
+/// Attach the file at `path` to a sighting or trip; the file must exist
+pub fn add_media(
+    conn: &Connection,
+    owner: MediaOwner,
+    path: &str,
+    caption: Option<&str>,
+    captured_at: Option<&str>,
+) -> Result<i64> {
+    let (table, id) = match owner {
+        MediaOwner::Sighting(id) => ("sightings", id),
+        MediaOwner::Trip(id) => ("trips", id),
+    };
+    let exists = conn
+        .query_row(&format!("SELECT 1 FROM {} WHERE id = ?1", table), params![id], |_| Ok(()))
+        .optional()
+        .context("Failed to look up media owner")?
+        .is_some();
+    if !exists {
+        match owner {
+            MediaOwner::Sighting(id) => bail!("Sighting {} not found", id),
+            MediaOwner::Trip(id) => bail!("Trip {} not found", id),
+        }
+    }
+
+    attach_media(conn, owner, &MediaFile::read(path)?, caption, captured_at)
+}
+
+/// Map a row of [`MEDIA_COLUMNS`] to a `Media`
+fn media_from_row(row: &Row) -> rusqlite::Result<Media> {
+    let sighting_id: Option<i64> = row.get(1)?;
+    let owner = match sighting_id {
+        Some(id) => MediaOwner::Sighting(id),
+        None => MediaOwner::Trip(row.get(2)?),
+    };
+
+    Ok(Media {
+        id: row.get(0)?,
+        owner,
+        path: row.get(3)?,
+        content_hash: row.get(4)?,
+        mime_type: row.get(5)?,
+        captured_at: row.get(6)?,
+        caption: row.get(7)?,
+    })
+}
+
+/// Get a media record by ID
+pub fn get_media_by_id(conn: &Connection, id: i64) -> Result<Media> {
+    let sql = format!("SELECT {} FROM media WHERE id = ?1", MEDIA_COLUMNS);
+
+    let media = conn.query_row(&sql, params![id], media_from_row)
+        .context("Failed to fetch media")?;
+
+    Ok(media)
+}
+
+/// Get the files attached to a sighting or trip, in capture order
+pub fn get_media(conn: &Connection, owner: MediaOwner) -> Result<Vec<Media>> {
+    let (column, id) = match owner {
+        MediaOwner::Sighting(id) => ("sighting_id", id),
+        MediaOwner::Trip(id) => ("trip_id", id),
+    };
+    let sql = format!(
+        "SELECT {} FROM media WHERE {} = ?1 ORDER BY captured_at IS NULL, captured_at, id",
+        MEDIA_COLUMNS, column
+    );
+
+    let mut stmt = conn.prepare(&sql)
+        .context("Failed to prepare media query")?;
+    let media = stmt.query_map(params![id], media_from_row)
+        .context("Failed to execute media query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse media")?;
+
+    Ok(media)
+}
+
+/// Get every media record, by ID
+pub(crate) fn get_all_media(conn: &Connection) -> Result<Vec<Media>> {
+    let sql = format!("SELECT {} FROM media ORDER BY id", MEDIA_COLUMNS);
+
+    let mut stmt = conn.prepare(&sql)
+        .context("Failed to prepare media query")?;
+    let media = stmt.query_map([], media_from_row)
+        .context("Failed to execute media query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse media")?;
+
+    Ok(media)
+}
+
+/// Detach a media file (the file itself is left alone)
+pub fn remove_media(conn: &Connection, id: i64) -> Result<usize> {
+    let sql = "DELETE FROM media WHERE id = ?1";
+    let rows_affected = conn.execute(sql, params![id])
+        .context("Failed to delete media")?;
+    Ok(rows_affected)
+}
+
+/// Point a media record at the file's new location. The file must exist and,
+/// if the record has a hash, have the same contents.
+pub fn relocate_media(conn: &Connection, id: i64, new_path: &str) -> Result<Media> {
+    let media = get_media_by_id(conn, id)
+        .with_context(|| format!("Media {} not found", id))?;
+    let file = MediaFile::read(new_path)?;
+
+    if media.content_hash.is_some() && media.content_hash != file.content_hash {
+        bail!("{} is not the same file as {}", file.path, media.path);
+    }
+
+    conn.execute(
+        "UPDATE media SET path = ?1, content_hash = ?2 WHERE id = ?3",
+        params![file.path, file.content_hash, id],
+    )
+    .context("Failed to update media")?;
+
+    get_media_by_id(conn, id)
+}
+
+/// Rewrite the paths of every media file under directory `from` to be under `to` instead,
+/// e.g. after moving a photo library. Returns how many records changed.
+pub fn relocate_media_dir(conn: &Connection, from: &str, to: &str) -> Result<usize> {
+    let from = from.trim().trim_end_matches('/');
+    let to = to.trim().trim_end_matches('/');
+    if from.is_empty() {
+        bail!("directory to move from cannot be empty");
+    }
+
+    let sql = r#"
+        UPDATE media
+        SET path = ?2 || substr(path, length(?1) + 1)
+        WHERE substr(path, 1, length(?1) + 1) = ?1 || '/'
+    "#;
+    let rows_affected = conn.execute(sql, params![from, to])
+        .context("Failed to move media directory")?;
+    Ok(rows_affected)
+}
+
+/// What's wrong with an attached file
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaProblem {
+    /// Nothing is at the recorded path
+    Missing,
+    /// The file at the recorded path has different contents than when it was attached
+    Changed,
+    /// The file isn't at the recorded path but was found here
+    Moved(String),
+    /// Something is at the recorded path but it can't be read, and why
+    Unreadable(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaIssue {
+    pub media: Media,
+    pub problem: MediaProblem,
+}
+
+/// Check that every attached file is still where it was recorded, unchanged.
+/// Missing files are looked for, by name and contents, under `search_dirs`.
+pub fn check_media(conn: &Connection, search_dirs: &[PathBuf]) -> Result<Vec<MediaIssue>> {
+    let mut issues = Vec::new();
+    let mut candidates: Option<HashMap<String, Vec<PathBuf>>> = None;
+
+    for media in get_all_media(conn)? {
+        let path = Path::new(&media.path);
+        if path.exists() && !path.is_dir() {
+            if let Some(ref hash) = media.content_hash {
+                match hash_file(path) {
+                    Ok(found) if found == *hash => {}
+                    Ok(_) => issues.push(MediaIssue { media, problem: MediaProblem::Changed }),
+                    Err(e) => issues.push(MediaIssue { media, problem: MediaProblem::Unreadable(format!("{:#}", e)) }),
+                }
+            }
+            continue;
+        }
+
+        // only walk the search directories once something turns out to be missing
+        let candidates = match candidates {
+            Some(ref candidates) => candidates,
+            None => candidates.insert(files_by_name(search_dirs)?),
+        };
+        let found = path
+            .file_name()
+            .and_then(|name| candidates.get(&*name.to_string_lossy()))
+            .and_then(|paths| find_moved(&media, paths));
+
+        let problem = match found {
+            Some(moved) => MediaProblem::Moved(moved.to_string_lossy().into_owned()),
+            None => MediaProblem::Missing,
+        };
+        issues.push(MediaIssue { media, problem });
+    }
+
+    Ok(issues)
+}
+
+/// Which of the same-named `paths` is the missing file: the one with the same contents,
+/// or without a hash to compare, the only one. A file that can't be read isn't it.
+fn find_moved(media: &Media, paths: &[PathBuf]) -> Option<PathBuf> {
+    match media.content_hash {
+        Some(ref hash) => paths.iter().find(|path| hash_file(path).is_ok_and(|found| found == *hash)).cloned(),
+        None => match paths {
+            [path] => Some(path.clone()),
+            _ => None,
+        },
+    }
+}
+
+/// Every file under `dirs`, by file name. Symlinked directories aren't followed, so a
+/// link back up the tree can't loop.
+fn files_by_name(dirs: &[PathBuf]) -> Result<HashMap<String, Vec<PathBuf>>> {
+    let mut files: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let mut pending: Vec<PathBuf> = dirs.to_vec();
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+        for entry in entries {
+            let entry = entry.with_context(|| format!("Failed to read directory {}", dir.display()))?;
+            let path = entry.path();
+            let file_type = entry.file_type().with_context(|| format!("Failed to read {}", path.display()))?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_symlink() && path.is_dir() {
+                continue;
+            } else if let Some(name) = path.file_name() {
+                let name = name.to_string_lossy().into_owned();
+                let path = fs::canonicalize(&path).unwrap_or(path);
+                files.entry(name).or_default().push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Effort;
+    use crate::core::sighting::{create_sighting, delete_sighting};
+    use crate::core::trip::create_trip;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+        crate::core::migrations::migrate(&conn).unwrap();
+
+        // Load test taxa (first 100 species + parent taxa)
+        let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
+        conn.execute_batch(&test_taxa).unwrap();
+
+        conn
+    }
+
+    /// A fresh scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fastwatcher-media-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::canonicalize(dir).unwrap()
+    }
+
+    fn sighting(conn: &Connection) -> i64 {
+        create_sighting(conn, None, 1, None, None, None, None, None, &[], None, None).unwrap()
+    }
+
+    #[test]
+    fn test_add_and_list_media() {
+        let conn = setup_test_db();
+        let dir = scratch_dir("add");
+        let photo = dir.join("heron.JPG");
+        let call = dir.join("wren.m4a");
+        fs::write(&photo, b"heron").unwrap();
+        fs::write(&call, b"wren").unwrap();
+
+        let sighting_id = sighting(&conn);
+        let trip_id = create_trip(&conn, "Marsh walk", None, None, None, None, &Effort::default()).unwrap();
+        let owner = MediaOwner::Sighting(sighting_id);
+
+        let photo_id = add_media(&conn, owner, photo.to_str().unwrap(), Some("In flight"), Some("2025-05-01 07:30:00")).unwrap();
+        add_media(&conn, owner, call.to_str().unwrap(), None, Some("2025-05-01 07:10:00")).unwrap();
+        add_media(&conn, MediaOwner::Trip(trip_id), photo.to_str().unwrap(), None, None).unwrap();
+
+        let media = get_media(&conn, owner).unwrap();
+        assert_eq!(media.len(), 2);
+        assert_eq!(media[0].mime_type, "audio/mp4");
+        assert_eq!(media[0].kind(), "audio");
+        assert_eq!(media[1].id, photo_id);
+        assert_eq!(media[1].mime_type, "image/jpeg");
+        assert_eq!(media[1].caption.as_deref(), Some("In flight"));
+        assert_eq!(
+            media[1].content_hash.as_deref(),
+            Some("8bf140db34c3fc03ca732867002d7396b59434818c0b5cbaa65aace6c4dd3b81")
+        );
+
+        // without a capture time, the file's modification time is used
+        let trip_media = get_media(&conn, MediaOwner::Trip(trip_id)).unwrap();
+        assert_eq!(trip_media.len(), 1);
+        assert!(trip_media[0].captured_at.is_some());
+
+        assert!(add_media(&conn, owner, dir.join("missing.jpg").to_str().unwrap(), None, None).is_err());
+        assert!(add_media(&conn, MediaOwner::Sighting(999_999), photo.to_str().unwrap(), None, None).is_err());
+
+        assert_eq!(remove_media(&conn, photo_id).unwrap(), 1);
+        assert_eq!(get_media(&conn, owner).unwrap().len(), 1);
+
+        // attachments go with their sighting
+        delete_sighting(&conn, sighting_id).unwrap();
+        assert!(get_media(&conn, owner).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_create_sighting_attaches_media() {
+        let conn = setup_test_db();
+        let dir = scratch_dir("create");
+        let photo = dir.join("duck.png");
+        fs::write(&photo, b"duck").unwrap();
+
+        let id = create_sighting(&conn, None, 1, None, photo.to_str(), None, None, None, &[], None, None).unwrap();
+        let media = get_media(&conn, MediaOwner::Sighting(id)).unwrap();
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0].path, photo.to_str().unwrap());
+        assert_eq!(media[0].mime_type, "image/png");
+
+        // a missing file stops the sighting from being created
+        let missing = dir.join("gone.png");
+        assert!(create_sighting(&conn, None, 1, None, missing.to_str(), None, None, None, &[], None, None).is_err());
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM sightings", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_relocate_media() {
+        let conn = setup_test_db();
+        let dir = scratch_dir("relocate");
+        let old = dir.join("a.jpg");
+        fs::write(&old, b"owl").unwrap();
+
+        let id = add_media(&conn, MediaOwner::Sighting(sighting(&conn)), old.to_str().unwrap(), None, None).unwrap();
+
+        let other = dir.join("other.jpg");
+        fs::write(&other, b"not the owl").unwrap();
+        assert!(relocate_media(&conn, id, other.to_str().unwrap()).is_err());
+
+        let new = dir.join("owl.jpg");
+        fs::rename(&old, &new).unwrap();
+        let media = relocate_media(&conn, id, new.to_str().unwrap()).unwrap();
+        assert_eq!(media.path, new.to_str().unwrap());
+
+        let moved = dir.join("library");
+        assert_eq!(relocate_media_dir(&conn, dir.to_str().unwrap(), moved.to_str().unwrap()).unwrap(), 1);
+        assert_eq!(get_media_by_id(&conn, id).unwrap().path, moved.join("owl.jpg").to_str().unwrap());
+        assert_eq!(relocate_media_dir(&conn, "/nowhere", "/elsewhere").unwrap(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_media_finds_missing_moved_and_changed_files() {
+        let conn = setup_test_db();
+        let dir = scratch_dir("check");
+        let owner = MediaOwner::Sighting(sighting(&conn));
+        for name in ["fine.jpg", "changed.jpg", "moved.jpg", "gone.jpg"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        for name in ["fine.jpg", "changed.jpg", "moved.jpg", "gone.jpg"] {
+            add_media(&conn, owner, dir.join(name).to_str().unwrap(), None, None).unwrap();
+        }
+        let unhashed = attach_media(&conn, owner, &MediaFile::unread("old/imported.jpg"), None, None).unwrap();
+
+        fs::write(dir.join("changed.jpg"), "edited").unwrap();
+        fs::create_dir_all(dir.join("sorted")).unwrap();
+        fs::rename(dir.join("moved.jpg"), dir.join("sorted").join("moved.jpg")).unwrap();
+        fs::remove_file(dir.join("gone.jpg")).unwrap();
+        // same name, different contents: not the missing file
+        fs::write(dir.join("sorted").join("gone.jpg"), "another").unwrap();
+        fs::write(dir.join("sorted").join("imported.jpg"), "imported").unwrap();
+
+        let issues = check_media(&conn, std::slice::from_ref(&dir)).unwrap();
+        let problems: Vec<(String, MediaProblem)> = issues
+            .iter()
+            .map(|issue| (issue.media.path.rsplit('/').next().unwrap().to_string(), issue.problem.clone()))
+            .collect();
+        let sorted = |name: &str| dir.join("sorted").join(name).to_string_lossy().into_owned();
+        assert_eq!(
+            problems,
+            vec![
+                ("changed.jpg".to_string(), MediaProblem::Changed),
+                ("moved.jpg".to_string(), MediaProblem::Moved(sorted("moved.jpg"))),
+                ("gone.jpg".to_string(), MediaProblem::Missing),
+                ("imported.jpg".to_string(), MediaProblem::Moved(sorted("imported.jpg"))),
+            ]
+        );
+        assert_eq!(issues[3].media.id, unhashed);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_check_media_survives_symlink_loops_and_unreadable_files() {
+        use std::os::unix::fs::symlink;
+        use std::os::unix::net::UnixListener;
+
+        let conn = setup_test_db();
+        let dir = scratch_dir("check_unreadable");
+        let owner = MediaOwner::Sighting(sighting(&conn));
+        for name in ["socket.jpg", "moved.jpg"] {
+            fs::write(dir.join(name), name).unwrap();
+            add_media(&conn, owner, dir.join(name).to_str().unwrap(), None, None).unwrap();
+        }
+
+        // a socket can't be opened, wherever it turns up
+        fs::remove_file(dir.join("socket.jpg")).unwrap();
+        let _socket = UnixListener::bind(dir.join("socket.jpg")).unwrap();
+        let search = dir.join("search");
+        fs::create_dir_all(search.join("nested")).unwrap();
+        let _decoy = UnixListener::bind(search.join("moved.jpg")).unwrap();
+        fs::rename(dir.join("moved.jpg"), search.join("nested").join("moved.jpg")).unwrap();
+        symlink(&search, search.join("nested").join("loop")).unwrap();
+
+        let issues = check_media(&conn, std::slice::from_ref(&search)).unwrap();
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(issues[0].problem, MediaProblem::Unreadable(ref why) if why.contains("socket.jpg")));
+        let moved = search.join("nested").join("moved.jpg").to_string_lossy().into_owned();
+        assert_eq!(issues[1].problem, MediaProblem::Moved(moved));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,449 @@
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+
+/// A schema change compiled into the binary
+#[derive(Debug)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    sql: &'static str,
+}
+
+/// Every migration in order. A database whose `user_version` is N has had the
+/// first N applied. Append new migrations; never edit or reorder shipped ones.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial schema",
+        sql: include_str!("../../migrations/0001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "sync sighting taxonomy on taxon updates",
+        sql: include_str!("../../migrations/0002_taxa_sync_trigger.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "full-text search",
+        sql: include_str!("../../migrations/0003_full_text_search.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "eBird checklist tracking",
+        sql: include_str!("../../migrations/0004_ebird_checklists.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "Darwin Core occurrence tracking",
+        sql: include_str!("../../migrations/0005_dwc_occurrences.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "sighting counts, age/sex and breeding codes",
+        sql: include_str!("../../migrations/0006_sighting_details.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "coordinates and spatial index",
+        sql: include_str!("../../migrations/0007_coordinates.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "locations",
+        sql: include_str!("../../migrations/0008_locations.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "subspecies and identification categories",
+        sql: include_str!("../../migrations/0009_identification_categories.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "taxon tree",
+        sql: include_str!("../../migrations/0010_taxon_tree.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "media library",
+        sql: include_str!("../../migrations/0011_media.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "trip effort",
+        sql: include_str!("../../migrations/0012_trip_effort.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "typed dates",
+        sql: include_str!("../../migrations/0013_observed_dates.sql"),
+    },
+];
+
+/// The schema version this build creates
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// The database's schema version (`PRAGMA user_version`)
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let version: u32 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .context("Failed to read schema version")?;
+    Ok(version)
+}
+
+/// Migrations not yet applied to the database
+pub fn pending_migrations(conn: &Connection) -> Result<&'static [Migration]> {
+    let version = schema_version(conn)?;
+    check_supported(version)?;
+    Ok(&MIGRATIONS[version as usize..])
+}
+
+fn check_supported(version: u32) -> Result<()> {
+    if version > latest_version() {
+        bail!(
+            "database schema version {} is newer than this build supports ({}); upgrade fast-watcher",
+            version,
+            latest_version()
+        );
+    }
+    Ok(())
+}
+
+/// Brings the schema up to date in one transaction and returns the migrations applied.
+/// On failure nothing is applied and the version is unchanged.
+pub fn migrate(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    // IMMEDIATE takes the write lock before reading the version, so two processes can't both migrate
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .context("Failed to start migration")?;
+
+    let version = schema_version(&tx)?;
+    check_supported(version)?;
+
+    let pending = &MIGRATIONS[version as usize..];
+    for migration in pending {
+        tx.execute_batch(migration.sql)
+            .with_context(|| format!("Migration {} ({}) failed", migration.version, migration.name))?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+    }
+
+    tx.commit().context("Failed to commit migrations")?;
+    Ok(pending.iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_names(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name IN ('taxa', 'trips', 'sightings', 'sightings_fts') ORDER BY name")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn test_versions_are_sequential() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, index + 1);
+        }
+    }
+
+    #[test]
+    fn test_migrate_new_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        assert_eq!(pending_migrations(&conn).unwrap().len(), MIGRATIONS.len());
+
+        let applied = migrate(&conn).unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert_eq!(table_names(&conn), vec!["sightings", "sightings_fts", "taxa", "trips"]);
+
+        // Already current: nothing to do
+        assert!(migrate(&conn).unwrap().is_empty());
+        assert!(pending_migrations(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_upgrades_older_database_keeping_data() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // A database from before versioning: baseline tables, user_version 0
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute("INSERT INTO trips (name, notes) VALUES ('Old Trip', 'Heron rookery')", []).unwrap();
+
+        let applied = migrate(&conn).unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+
+        // Existing rows are indexed by the full-text migration
+        let found: i64 = conn
+            .query_row("SELECT COUNT(*) FROM trips_fts WHERE trips_fts MATCH 'heron'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(found, 1);
+    }
+
+    #[test]
+    fn test_counts_move_out_of_notes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO taxa (id, rank, kingdom) VALUES (1, 'kingdom', 'Animalia');
+             INSERT INTO sightings (id, taxon_id, notes) VALUES
+                 (1, 1, 'Count: 12' || char(10) || 'In the reeds'),
+                 (2, 1, 'Count: X'),
+                 (3, 1, 'Count: many'),
+                 (4, 1, 'Two by the gate');",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        let rows: Vec<(Option<String>, Option<String>)> = conn
+            .prepare("SELECT CAST(count AS TEXT), notes FROM sightings ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let text = |value: &str| Some(value.to_string());
+        assert_eq!(
+            rows,
+            vec![
+                (text("12"), text("In the reeds")),
+                (text("X"), None),
+                (None, text("Count: many")),
+                (None, text("Two by the gate")),
+            ]
+        );
+
+        // Counts are numbers or X
+        assert!(conn.execute("UPDATE sightings SET count = 'lots' WHERE id = 1", []).is_err());
+        assert!(conn.execute("UPDATE sightings SET count = -1 WHERE id = 1", []).is_err());
+        assert!(conn.execute("UPDATE sightings SET breeding_code = 'ZZ' WHERE id = 1", []).is_err());
+    }
+
+    #[test]
+    fn test_dates_are_normalized() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO taxa (id, rank, kingdom) VALUES (1, 'kingdom', 'Animalia');
+             INSERT INTO trips (id, name, date, notes) VALUES
+                 (1, 'A', '2025/05/14', NULL),
+                 (2, 'B', '05-14-2025', NULL),
+                 (3, 'C', ' 2025-05 ', NULL),
+                 (4, 'D', '2025-05-14 07:30:15', NULL),
+                 (5, 'E', 'Spring 1998', NULL),
+                 (6, 'F', 'yesterday', 'Owls'),
+                 (7, 'G', '', NULL),
+                 (8, 'H', '2025-05-14T07:30:15-05:00', NULL),
+                 (9, 'I', '2025-05-14 07:30+05:30', NULL);
+             INSERT INTO sightings (id, taxon_id, date) VALUES
+                 (1, 1, '1998 '),
+                 (2, 1, '2025-02-30'),
+                 (3, 1, 'winter 1998'),
+                 (4, 1, '2025-05-14T07:30+16:00');",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        let rows = |sql: &str| -> Vec<(Option<String>, Option<String>, Option<String>)> {
+            conn.prepare(sql)
+                .unwrap()
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        let text = |value: &str| Some(value.to_string());
+        assert_eq!(
+            rows("SELECT date, date_precision, notes FROM trips ORDER BY id"),
+            vec![
+                (text("2025-05-14"), text("day"), None),
+                (text("2025-05-14"), text("day"), None),
+                (text("2025-05"), text("month"), None),
+                (text("2025-05-14T07:30"), text("time"), None),
+                (text("1998-03/1998-05"), text("season"), None),
+                // Unreadable dates move to the notes
+                (None, None, text("Owls\nDate: yesterday")),
+                (None, None, None),
+                (text("2025-05-14T07:30-05:00"), text("time"), None),
+                (text("2025-05-14T07:30+05:30"), text("time"), None),
+            ]
+        );
+        assert_eq!(
+            rows("SELECT date, date_precision, notes FROM sightings ORDER BY id"),
+            vec![
+                (text("1998"), text("year"), None),
+                (None, None, text("Date: 2025-02-30")),
+                (text("1998-12/1999-02"), text("season"), None),
+                // No offset is more than 14 hours from UTC
+                (None, None, text("Date: 2025-05-14T07:30+16:00")),
+            ]
+        );
+
+        // Only stored forms are accepted from now on
+        conn.execute("UPDATE trips SET date = '2025-05-14T07:30-05:00' WHERE id = 1", []).unwrap();
+        for bad in ["tomorrow", "2025-13", "2025-04-31", "2025-05-14 07:30", "2025-05-14T07:30+14:30", "1998-04/1998-06"] {
+            assert!(conn.execute("UPDATE trips SET date = ?1 WHERE id = 1", [bad]).is_err(), "{}", bad);
+            assert!(conn.execute("INSERT INTO sightings (taxon_id, date) VALUES (1, ?1)", [bad]).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_location_strings_cluster_into_locations() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO taxa (id, rank, kingdom) VALUES (1, 'kingdom', 'Animalia');
+             INSERT INTO trips (id, name, location) VALUES
+                 (1, 'A', 'central park'),
+                 (2, 'B', 'Central Park'),
+                 (3, 'C', 'Central  Park.'),
+                 (4, 'D', 'Forest Park'),
+                 (5, 'E', NULL);
+             INSERT INTO sightings (id, taxon_id, location) VALUES (1, 1, 'Central Park'), (2, 1, 'Near the pond');",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        // The most common spelling names each place
+        let names: Vec<String> = conn
+            .prepare("SELECT locality FROM locations ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(names, vec!["Central Park", "Forest Park", "Near the pond"]);
+
+        let rows: Vec<(Option<i64>, Option<String>)> = conn
+            .prepare("SELECT location_id, location FROM trips ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let park = (Some(1), Some("Central Park".to_string()));
+        assert_eq!(rows, vec![park.clone(), park.clone(), park, (Some(2), Some("Forest Park".to_string())), (None, None)]);
+
+        let sighting_location: Option<i64> = conn
+            .query_row("SELECT location_id FROM sightings WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sighting_location, Some(1));
+    }
+
+    #[test]
+    fn test_taxa_rebuild_keeps_sightings_and_search() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO taxa (id, rank, kingdom, genus, species_epithet, common_name)
+                 VALUES (7, 'species', 'Animalia', 'Tringa', 'melanoleuca', 'Greater Yellowlegs');
+             INSERT INTO sightings (id, taxon_id) VALUES (1, 7);",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        let violations: i64 = conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0)).unwrap();
+        assert_eq!(violations, 0);
+        let found: i64 = conn
+            .query_row("SELECT rowid FROM taxa_fts WHERE taxa_fts MATCH 'yellowlegs'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(found, 7);
+
+        // New categories are allowed, and sightings still can't point at missing taxa
+        conn.execute(
+            "INSERT INTO taxa (rank, kingdom, genus, species_epithet, common_name)
+             VALUES ('slash', 'Animalia', 'Tringa', 'melanoleuca/flavipes', 'Greater/Lesser Yellowlegs')",
+            [],
+        )
+        .unwrap();
+        assert!(conn.execute("DELETE FROM taxa WHERE id = 7", []).is_err());
+    }
+
+    #[test]
+    fn test_existing_taxa_are_linked_into_a_tree() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO taxa (id, rank, kingdom, family, genus, species_epithet, common_name)
+                 VALUES (1, 'species', 'Animalia', 'Corvidae', 'Corvus', 'corax', 'Common Raven'),
+                        (2, 'genus', 'Animalia', 'Corvidae', 'Corvus', NULL, 'Crows'),
+                        (3, 'species', 'Plantae', 'Moraceae', 'Corvus', 'corax', 'Not a raven'),
+                        (4, 'family', 'Animalia', 'Corvidae', NULL, NULL, 'Crows and Jays');",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        let parents: Vec<(i64, Option<i64>)> = conn
+            .prepare("SELECT id, parent_id FROM taxa ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        // The plant has no genus of its own yet; the core API creates missing ancestors
+        assert_eq!(parents, vec![(1, Some(2)), (2, Some(4)), (3, None), (4, None)]);
+    }
+
+    #[test]
+    fn test_media_paths_become_attachments() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO taxa (id, rank, kingdom, common_name) VALUES (1, 'kingdom', 'Animalia', 'Animals');
+             INSERT INTO sightings (id, taxon_id, kingdom, common_name, media_path)
+                 VALUES (1, 1, 'Animalia', 'Animals', 'photos/2025/heron.v2.JPG'),
+                        (2, 1, 'Animalia', 'Animals', '  '),
+                        (3, 1, 'Animalia', 'Animals', 'recordings/wren');",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        let media: Vec<(i64, String, String, Option<String>)> = conn
+            .prepare("SELECT sighting_id, path, mime_type, content_hash FROM media ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            media,
+            vec![
+                (1, "photos/2025/heron.v2.JPG".to_string(), "image/jpeg".to_string(), None),
+                (3, "recordings/wren".to_string(), "application/octet-stream".to_string(), None),
+            ]
+        );
+        assert!(conn.prepare("SELECT media_path FROM sightings").is_err());
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+
+        // Rewind to before full-text search (3), but block it with a conflicting object
+        conn.pragma_update(None, "user_version", 2).unwrap();
+        conn.execute_batch("DROP TABLE taxa_fts; CREATE VIEW taxa_fts AS SELECT 1").unwrap();
+
+        assert!(migrate(&conn).is_err());
+        assert_eq!(schema_version(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rejects_newer_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        assert!(migrate(&conn).is_err());
+        assert!(pending_migrations(&conn).is_err());
+    }
+}
@@ -1,4 +1,12 @@
 pub mod db;
+pub mod dwca;
+pub mod ebird;
+pub mod location;
+pub mod media;
+pub mod migrations;
+pub mod photos;
+pub mod preview;
+pub mod reports;
 pub mod search;
 pub mod sighting;
 pub mod taxon;
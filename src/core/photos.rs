@@ -0,0 +1,568 @@
+use crate::core::media::{MediaFile, attach_media, is_attached};
+use crate::core::sighting::create_sighting;
+use crate::core::taxon::find_taxon_by_name;
+use crate::core::trip::create_trip;
+use crate::models::{Coordinates, Effort, MediaOwner, Taxon};
+use anyhow::{Context, Result, bail};
+use exif::{In, Reader, Tag, Value};
+use rusqlite::Connection;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Files `scan_photos` picks up, by extension
+const PHOTO_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "heic", "tif", "tiff"];
+
+/// When and where a photo's EXIF says it was taken
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct PhotoExif {
+    /// "YYYY-MM-DD HH:MM:SS", camera local time
+    pub taken_at: Option<String>,
+    pub coordinates: Option<Coordinates>,
+}
+
+/// Read a photo's EXIF date and GPS position; a file without EXIF yields neither
+pub(crate) fn read_exif(path: &Path) -> PhotoExif {
+    let Ok(file) = File::open(path) else {
+        return PhotoExif::default();
+    };
+    let Ok(exif) = Reader::new().read_from_container(&mut BufReader::new(file)) else {
+        return PhotoExif::default();
+    };
+    let field = |tag: Tag| exif.get_field(tag, In::PRIMARY).map(|field| &field.value);
+
+    let taken_at = [Tag::DateTimeOriginal, Tag::DateTimeDigitized, Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| match field(tag)? {
+            Value::Ascii(lines) => exif::DateTime::from_ascii(lines.first()?).ok(),
+            _ => None,
+        })
+        .map(|time| {
+            format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                time.year, time.month, time.day, time.hour, time.minute, time.second
+            )
+        });
+
+    // degrees, minutes and seconds, negated for the south or west hemisphere
+    let degrees = |tag: Tag, hemisphere: Tag, negative: u8| -> Option<f64> {
+        let Value::Rational(parts) = field(tag)? else {
+            return None;
+        };
+        let degrees: f64 = parts.iter().zip([1.0, 60.0, 3600.0]).map(|(part, per_degree)| part.to_f64() / per_degree).sum();
+        let negated = matches!(field(hemisphere), Some(Value::Ascii(lines)) if lines.first().and_then(|line| line.first()) == Some(&negative));
+        Some(if negated { -degrees } else { degrees })
+    };
+    let accuracy_m = match field(Tag::GPSHPositioningError) {
+        Some(Value::Rational(parts)) => parts.first().map(|part| part.to_f64()).filter(|accuracy| accuracy.is_finite()),
+        _ => None,
+    };
+    let coordinates = match (degrees(Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S'), degrees(Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W')) {
+        (Some(latitude), Some(longitude)) => Coordinates::new(latitude, longitude, accuracy_m).ok(),
+        _ => None,
+    };
+
+    PhotoExif { taken_at, coordinates }
+}
+
+/// A photo found by [`scan_photos`], with what it says about itself
+#[derive(Debug, Clone)]
+pub struct Photo {
+    pub path: PathBuf,
+    /// "YYYY-MM-DD HH:MM:SS" from EXIF; a photo whose time isn't in this form is imported as undated
+    pub taken_at: Option<String>,
+    pub coordinates: Option<Coordinates>,
+    /// The taxon name given by a sidecar file or the file name
+    pub label: Option<String>,
+    /// Any further lines of the sidecar file
+    pub notes: Option<String>,
+    /// The taxon `label` names, if one matched; can also be set before importing
+    pub taxon: Option<Taxon>,
+}
+
+/// Every photo under `dir`, oldest first (undated ones last), with its EXIF date and
+/// position. A photo's taxon is named by a sidecar text file with the same stem
+/// (`IMG_0001.txt`: the name, then optional notes) or after `--` in its file name
+/// (`IMG_0001--great-blue-heron.jpg`), and matched by common or scientific name.
+pub fn scan_photos(conn: &Connection, dir: &Path) -> Result<Vec<Photo>> {
+    let mut photos = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+        for entry in entries {
+            let path = entry.with_context(|| format!("Failed to read directory {}", dir.display()))?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase());
+            if !extension.is_some_and(|ext| PHOTO_EXTENSIONS.contains(&ext.as_str())) {
+                continue;
+            }
+
+            let exif = read_exif(&path);
+            let (label, notes) = photo_label(&path)?;
+            let taxon = label.as_deref().map(|label| find_taxon_by_name(conn, label)).transpose()?.flatten();
+            photos.push(Photo { path, taken_at: exif.taken_at, coordinates: exif.coordinates, label, notes, taxon });
+        }
+    }
+
+    photos.sort_by(|a, b| {
+        (a.taken_at.is_none(), &a.taken_at, &a.path).cmp(&(b.taken_at.is_none(), &b.taken_at, &b.path))
+    });
+    Ok(photos)
+}
+
+/// The taxon name and notes for a photo, from its sidecar file or else its file name
+fn photo_label(path: &Path) -> Result<(Option<String>, Option<String>)> {
+    let sidecar = path.with_extension("txt");
+    if sidecar.is_file() {
+        let text = fs::read_to_string(&sidecar)
+            .with_context(|| format!("Failed to read {}", sidecar.display()))?;
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let label = lines.next().map(str::to_string);
+        let notes = lines.collect::<Vec<_>>().join(" ");
+        return Ok((label, (!notes.is_empty()).then_some(notes)));
+    }
+
+    let label = path
+        .file_stem()
+        .and_then(|stem| stem.to_string_lossy().rsplit_once("--").map(|(_, name)| name.replace(['-', '_'], " ")))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    Ok((label, None))
+}
+
+/// One trip an import made (or would make)
+#[derive(Debug)]
+pub struct PhotoTrip {
+    pub name: String,
+    /// When its first and last photos were taken
+    pub first: String,
+    pub last: String,
+    /// Each sighting's common name and number of photos
+    pub sightings: Vec<(String, usize)>,
+    /// Photos with no taxon; they're attached to the trip
+    pub unidentified: Vec<PathBuf>,
+}
+
+/// Outcome of a photo import (or what a dry run would do)
+#[derive(Debug, Default)]
+pub struct PhotoImportReport {
+    pub trips: Vec<PhotoTrip>,
+    pub sightings_created: usize,
+    pub photos_attached: usize,
+    /// Photos attached by an earlier import (same contents); they're skipped
+    pub already_imported: Vec<PathBuf>,
+    /// Photos without an EXIF date, or with one that can't be read; they're skipped
+    pub undated: Vec<PathBuf>,
+}
+
+/// Seconds since 1970-01-01 for a "YYYY-MM-DD HH:MM:SS" time, ignoring time zones
+fn timestamp_seconds(time: &str) -> Option<i64> {
+    let separators = [(4, b'-'), (7, b'-'), (10, b' '), (13, b':'), (16, b':')];
+    if time.len() != 19 || separators.iter().any(|&(at, separator)| time.as_bytes()[at] != separator) {
+        return None;
+    }
+    let number = |start: usize, end: usize| {
+        let digits = time.get(start..end)?;
+        digits.bytes().all(|b| b.is_ascii_digit()).then(|| digits.parse::<i64>().ok())?
+    };
+    let (year, month, day) = (number(0, 4)?, number(5, 7)?, number(8, 10)?);
+    let (hour, minute, second) = (number(11, 13)?, number(14, 16)?, number(17, 19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // days from the civil date, counting years from March so leap days come last
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// A photo to import, its "YYYY-MM-DD HH:MM:SS" time checked by [`timestamp_seconds`]
+struct DatedPhoto<'a> {
+    photo: &'a Photo,
+    taken_at: &'a str,
+    seconds: i64,
+    file: MediaFile,
+}
+
+impl DatedPhoto<'_> {
+    /// "YYYY-MM-DD"
+    fn day(&self) -> &str {
+        &self.taken_at[..10]
+    }
+
+    /// "YYYY-MM-DD HH:MM"
+    fn minute(&self) -> &str {
+        &self.taken_at[..16]
+    }
+}
+
+/// Split dated photos, oldest first, wherever more than `gap_seconds` passes between two
+fn split_by_gap<'a, 'p>(photos: &'a [DatedPhoto<'p>], gap_seconds: i64) -> Vec<&'a [DatedPhoto<'p>]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for i in 1..photos.len() {
+        if photos[i].seconds - photos[i - 1].seconds > gap_seconds {
+            groups.push(&photos[start..i]);
+            start = i;
+        }
+    }
+    if start < photos.len() {
+        groups.push(&photos[start..]);
+    }
+    groups
+}
+
+/// Turn scanned photos into trips and sightings. Photos more than `gap_seconds` apart
+/// start a new trip, named for `location` (or "Photos") and the day. Within a trip each
+/// taxon becomes one sighting with its photos attached; photos with no taxon are attached
+/// to the trip. Photos without a date, or already attached, are skipped. Everything runs
+/// in one transaction; a dry run rolls it back and only reports.
+pub fn import_photos(
+    conn: &Connection,
+    photos: &[Photo],
+    gap_seconds: i64,
+    location: Option<&str>,
+    dry_run: bool,
+) -> Result<PhotoImportReport> {
+    if gap_seconds <= 0 {
+        bail!("the gap between trips must be positive");
+    }
+
+    let tx = conn.unchecked_transaction().context("Failed to start import")?;
+    let mut report = PhotoImportReport::default();
+
+    let mut new_photos = Vec::new();
+    for photo in photos {
+        let Some((taken_at, seconds)) =
+            photo.taken_at.as_deref().and_then(|time| Some((time, timestamp_seconds(time)?)))
+        else {
+            report.undated.push(photo.path.clone());
+            continue;
+        };
+        let file = MediaFile::read(&photo.path.to_string_lossy())?;
+        if is_attached(&tx, &file)? {
+            report.already_imported.push(photo.path.clone());
+            continue;
+        }
+        new_photos.push(DatedPhoto { photo, taken_at, seconds, file });
+    }
+    // photos built by a caller may not be in time order
+    new_photos.sort_by_key(|dated| dated.seconds);
+
+    for group in split_by_gap(&new_photos, gap_seconds) {
+        let (first, last) = (&group[0], &group[group.len() - 1]);
+        let day = first.day();
+        let name = format!("{} {}", location.unwrap_or("Photos"), day);
+        let coordinates = group.iter().find_map(|dated| dated.photo.coordinates);
+        // the photos span the trip, so they give its start time and (at least a minute of) duration
+        let effort = Effort {
+            start_time: Some(first.minute()[11..].to_string()),
+            duration_min: Some(((last.seconds - first.seconds) / 60).max(1) as u32),
+            ..Default::default()
+        };
+
+        let trip_id = create_trip(&tx, &name, Some(day), location, None, coordinates, &effort)
+            .with_context(|| format!("Failed to create trip {}", name))?;
+        let mut trip = PhotoTrip {
+            name,
+            first: first.taken_at.to_string(),
+            last: last.taken_at.to_string(),
+            sightings: Vec::new(),
+            unidentified: Vec::new(),
+        };
+
+        // one sighting per taxon, in the order they were first photographed
+        let mut taxa: Vec<&Taxon> = Vec::new();
+        for dated in group {
+            if let Some(ref taxon) = dated.photo.taxon {
+                if !taxa.iter().any(|seen| seen.id == taxon.id) {
+                    taxa.push(taxon);
+                }
+            }
+        }
+        for taxon in taxa {
+            let shots: Vec<&DatedPhoto> =
+                group.iter().filter(|dated| dated.photo.taxon.as_ref().is_some_and(|t| t.id == taxon.id)).collect();
+            let mut notes: Vec<&str> = Vec::new();
+            for note in shots.iter().filter_map(|dated| dated.photo.notes.as_deref()) {
+                if !notes.contains(&note) {
+                    notes.push(note);
+                }
+            }
+            let notes = notes.join("; ");
+
+            let sighting_id = create_sighting(
+                &tx,
+                Some(trip_id),
+                taxon.id,
+                (!notes.is_empty()).then_some(notes.as_str()),
+                None,
+                // to the minute of its first photo
+                Some(shots[0].minute()),
+                None,
+                None,
+                &[],
+                None,
+                shots.iter().find_map(|dated| dated.photo.coordinates),
+            )
+            .with_context(|| format!("Failed to create sighting of {}", taxon.common_name))?;
+            for dated in &shots {
+                attach_media(&tx, MediaOwner::Sighting(sighting_id), &dated.file, None, None)?;
+            }
+
+            report.sightings_created += 1;
+            report.photos_attached += shots.len();
+            trip.sightings.push((taxon.common_name.clone(), shots.len()));
+        }
+
+        for dated in group.iter().filter(|dated| dated.photo.taxon.is_none()) {
+            attach_media(&tx, MediaOwner::Trip(trip_id), &dated.file, None, None)?;
+            report.photos_attached += 1;
+            trip.unidentified.push(dated.photo.path.clone());
+        }
+        report.trips.push(trip);
+    }
+
+    if dry_run {
+        tx.rollback().context("Failed to roll back dry run")?;
+    } else {
+        tx.commit().context("Failed to commit import")?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::media::get_media;
+    use crate::core::sighting::get_sightings_by_trip_id;
+    use crate::core::trip::get_all_trips;
+    use exif::experimental::Writer;
+    use exif::{Field, Rational};
+    use image::{Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+        crate::core::migrations::migrate(&conn).unwrap();
+
+        // Load test taxa (first 100 species + parent taxa)
+        let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
+        conn.execute_batch(&test_taxa).unwrap();
+
+        conn
+    }
+
+    /// A fresh scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fastwatcher-photos-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A small JPEG, distinct per `shade`, with an EXIF date and optionally a GPS position
+    fn write_photo(path: &Path, shade: u8, taken_at: Option<&str>, position: Option<(f64, f64)>) {
+        let mut jpeg = Cursor::new(Vec::new());
+        RgbImage::from_pixel(8, 8, Rgb([shade, shade, shade])).write_to(&mut jpeg, image::ImageFormat::Jpeg).unwrap();
+        let jpeg = jpeg.into_inner();
+
+        let dms = |degrees: f64| {
+            let degrees = degrees.abs();
+            let whole = degrees.trunc();
+            let minutes = (degrees - whole) * 60.0;
+            Value::Rational(vec![
+                Rational { num: whole as u32, denom: 1 },
+                Rational { num: minutes.trunc() as u32, denom: 1 },
+                Rational { num: ((minutes.fract() * 60.0) * 100.0).round() as u32, denom: 100 },
+            ])
+        };
+        let ascii = |text: &str| Value::Ascii(vec![text.as_bytes().to_vec()]);
+        let mut fields = Vec::new();
+        if let Some(taken_at) = taken_at {
+            fields.push(Field { tag: Tag::DateTimeOriginal, ifd_num: In::PRIMARY, value: ascii(&taken_at.replace('-', ":")) });
+        }
+        if let Some((latitude, longitude)) = position {
+            fields.push(Field { tag: Tag::GPSLatitudeRef, ifd_num: In::PRIMARY, value: ascii(if latitude < 0.0 { "S" } else { "N" }) });
+            fields.push(Field { tag: Tag::GPSLatitude, ifd_num: In::PRIMARY, value: dms(latitude) });
+            fields.push(Field { tag: Tag::GPSLongitudeRef, ifd_num: In::PRIMARY, value: ascii(if longitude < 0.0 { "W" } else { "E" }) });
+            fields.push(Field { tag: Tag::GPSLongitude, ifd_num: In::PRIMARY, value: dms(longitude) });
+        }
+        if fields.is_empty() {
+            fs::write(path, jpeg).unwrap();
+            return;
+        }
+
+        let mut writer = Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+
+        // an APP1 segment right after the start-of-image marker
+        let payload = [b"Exif\0\0".as_slice(), tiff.get_ref()].concat();
+        let mut bytes = jpeg[..2].to_vec();
+        bytes.extend_from_slice(&[0xFF, 0xE1]);
+        bytes.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&jpeg[2..]);
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_read_exif() {
+        let dir = scratch_dir("exif");
+        let photo = dir.join("heron.jpg");
+        write_photo(&photo, 10, Some("2025-05-01 07:30:15"), Some((40.5, -73.25)));
+
+        let exif = read_exif(&photo);
+        assert_eq!(exif.taken_at.as_deref(), Some("2025-05-01 07:30:15"));
+        let point = exif.coordinates.unwrap();
+        assert!((point.latitude - 40.5).abs() < 1e-6);
+        assert!((point.longitude + 73.25).abs() < 1e-6);
+
+        let plain = dir.join("plain.jpg");
+        write_photo(&plain, 20, None, None);
+        assert_eq!(read_exif(&plain), PhotoExif::default());
+        assert_eq!(read_exif(&dir.join("missing.jpg")), PhotoExif::default());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_timestamp_seconds() {
+        assert_eq!(timestamp_seconds("1970-01-01 00:00:00"), Some(0));
+        assert_eq!(timestamp_seconds("2000-03-01 00:00:00"), Some(951_868_800));
+        assert_eq!(timestamp_seconds("2024-12-31 23:59:59"), Some(1_735_689_599));
+        assert_eq!(timestamp_seconds("2024-12-31"), None);
+        assert_eq!(timestamp_seconds("2024:12:31 23:59:59"), None);
+        assert_eq!(timestamp_seconds("2024-13-31 23:59:59"), None);
+        assert_eq!(timestamp_seconds("2024-12-31 23:59:59 extra"), None);
+        assert_eq!(timestamp_seconds("2024-12-31 +3:59:59"), None);
+        assert_eq!(timestamp_seconds("2024-12-31 23:5é:59"), None);
+    }
+
+    #[test]
+    fn test_scan_photos_reads_labels() {
+        let conn = setup_test_db();
+        let dir = scratch_dir("scan");
+        fs::create_dir_all(dir.join("card")).unwrap();
+        write_photo(&dir.join("card").join("IMG_0002--white-faced-whistling-duck.JPG"), 1, Some("2025-05-01 08:00:00"), None);
+        write_photo(&dir.join("IMG_0001.jpg"), 2, Some("2025-05-01 07:00:00"), None);
+        fs::write(dir.join("IMG_0001.txt"), "Dendrocygna viduata\n\nPair on the pond\nflushed at 7:05\n").unwrap();
+        write_photo(&dir.join("IMG_0003--purple-penguin.jpg"), 3, None, None);
+        fs::write(dir.join("notes.md"), "not a photo").unwrap();
+
+        let photos = scan_photos(&conn, &dir).unwrap();
+        let names: Vec<String> = photos.iter().map(|photo| photo.path.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, vec!["IMG_0001.jpg", "IMG_0002--white-faced-whistling-duck.JPG", "IMG_0003--purple-penguin.jpg"]);
+
+        assert_eq!(photos[0].label.as_deref(), Some("Dendrocygna viduata"));
+        assert_eq!(photos[0].notes.as_deref(), Some("Pair on the pond flushed at 7:05"));
+        assert_eq!(photos[0].taxon.as_ref().unwrap().common_name, "White-faced Whistling-Duck");
+        assert_eq!(photos[1].taxon.as_ref().unwrap().common_name, "White-faced Whistling-Duck");
+        assert_eq!(photos[2].label.as_deref(), Some("purple penguin"));
+        assert!(photos[2].taxon.is_none());
+        assert!(photos[2].taken_at.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_import_photos_groups_trips_and_sightings() {
+        let conn = setup_test_db();
+        let dir = scratch_dir("import");
+        write_photo(&dir.join("a--white-faced-whistling-duck.jpg"), 1, Some("2025-05-01 07:00:00"), Some((26.1, -97.9)));
+        write_photo(&dir.join("b--white-faced-whistling-duck.jpg"), 2, Some("2025-05-01 07:20:00"), None);
+        write_photo(&dir.join("c--dendrocygna.jpg"), 3, Some("2025-05-01 09:00:00"), None);
+        write_photo(&dir.join("d.jpg"), 4, Some("2025-05-01 09:30:00"), None);
+        // more than three hours later: a second trip
+        write_photo(&dir.join("e--white-faced-whistling-duck.jpg"), 5, Some("2025-05-01 16:00:00"), None);
+        write_photo(&dir.join("f.jpg"), 6, None, None);
+
+        let photos = scan_photos(&conn, &dir).unwrap();
+        assert!(import_photos(&conn, &photos, 0, None, false).is_err());
+
+        let dry = import_photos(&conn, &photos, 3 * 3600, None, true).unwrap();
+        assert_eq!(dry.trips.len(), 2);
+        assert!(get_all_trips(&conn).unwrap().is_empty());
+
+        let report = import_photos(&conn, &photos, 3 * 3600, Some("Estero Llano Grande"), false).unwrap();
+        assert_eq!(report.sightings_created, 3);
+        assert_eq!(report.photos_attached, 5);
+        assert_eq!(report.undated, vec![dir.join("f.jpg")]);
+        let morning = &report.trips[0];
+        assert_eq!(morning.name, "Estero Llano Grande 2025-05-01");
+        assert_eq!((morning.first.as_str(), morning.last.as_str()), ("2025-05-01 07:00:00", "2025-05-01 09:30:00"));
+        assert_eq!(
+            morning.sightings,
+            vec![("White-faced Whistling-Duck".to_string(), 2), ("Dendrocygna".to_string(), 1)]
+        );
+        assert_eq!(morning.unidentified, vec![dir.join("d.jpg")]);
+
+        let trips = get_all_trips(&conn).unwrap();
+        assert_eq!(trips.len(), 2);
+        let trip = trips.iter().find(|trip| trip.name == morning.name && trip.coordinates.is_some()).unwrap();
+        assert_eq!(trip.date.unwrap().to_string(), "2025-05-01");
+        assert_eq!(trip.effort.start_time.as_deref(), Some("07:00"));
+        assert_eq!(trip.effort.duration_min, Some(150));
+        assert_eq!(get_media(&conn, MediaOwner::Trip(trip.id)).unwrap().len(), 1);
+
+        let sightings = get_sightings_by_trip_id(&conn, trip.id).unwrap();
+        let duck = sightings.iter().find(|sighting| sighting.common_name == "White-faced Whistling-Duck").unwrap();
+        assert_eq!(duck.date.unwrap().to_string(), "2025-05-01T07:00");
+        assert!(duck.coordinates.is_some());
+        let shots = get_media(&conn, MediaOwner::Sighting(duck.id)).unwrap();
+        assert_eq!(shots.len(), 2);
+        assert_eq!(shots[0].captured_at.as_deref(), Some("2025-05-01 07:00:00"));
+
+        // importing the same folder again skips what's already attached
+        let again = import_photos(&conn, &photos, 3 * 3600, None, false).unwrap();
+        assert!(again.trips.is_empty());
+        assert_eq!(again.already_imported.len(), 5);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_import_photos_skips_unreadable_times() {
+        let conn = setup_test_db();
+        let dir = scratch_dir("bad_times");
+        let photo = |name: &str, shade: u8, taken_at: &str| {
+            let path = dir.join(name);
+            write_photo(&path, shade, None, None);
+            Photo { path, taken_at: Some(taken_at.to_string()), coordinates: None, label: None, notes: None, taxon: None }
+        };
+        // built by hand: out of order, and with times EXIF wouldn't give
+        let photos = [
+            photo("late.jpg", 1, "2025-05-01 09:00:00"),
+            photo("short.jpg", 2, "2025-05"),
+            photo("month.jpg", 3, "2025-13-01 07:00:00"),
+            photo("wide.jpg", 4, "2025-05-01 07:0é:00"),
+            photo("early.jpg", 5, "2025-05-01 07:00:00"),
+        ];
+
+        let report = import_photos(&conn, &photos, 3 * 3600, None, false).unwrap();
+        assert_eq!(report.undated, ["short.jpg", "month.jpg", "wide.jpg"].map(|name| dir.join(name)));
+        assert_eq!(report.trips.len(), 1);
+        assert_eq!(report.trips[0].first, "2025-05-01 07:00:00");
+        assert_eq!(report.trips[0].unidentified, vec![dir.join("early.jpg"), dir.join("late.jpg")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,340 @@
+use crate::models::Media;
+use anyhow::{Context, Result, bail};
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+use symphonia::core::errors::Error as AudioError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Longest side of a cached thumbnail, in pixels
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// Audio frames folded into each waveform peak before it's fitted to the requested width
+const WAVEFORM_WINDOW: usize = 1024;
+
+/// Where thumbnails and waveforms are kept: `fastwatcher/thumbnails` in `$XDG_CACHE_HOME` or `~/.cache`
+pub fn thumbnail_dir() -> Result<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).map(PathBuf::from);
+    thumbnail_dir_from(var("XDG_CACHE_HOME"), var("HOME"))
+}
+
+fn thumbnail_dir_from(xdg_cache_home: Option<PathBuf>, home: Option<PathBuf>) -> Result<PathBuf> {
+    let non_empty = |path: Option<PathBuf>| path.filter(|p| !p.as_os_str().is_empty());
+
+    // as with the data directory, relative XDG values are ignored
+    let cache_home = match non_empty(xdg_cache_home).filter(|p| p.is_absolute()) {
+        Some(dir) => dir,
+        None => match non_empty(home) {
+            Some(home) => home.join(".cache"),
+            None => bail!("could not find a cache directory for thumbnails"),
+        },
+    };
+
+    Ok(cache_home.join("fastwatcher").join("thumbnails"))
+}
+
+/// Decode an image, turned upright according to its EXIF orientation
+pub fn load_image(path: &Path) -> Result<DynamicImage> {
+    let mut decoder = ImageReader::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?
+        .with_guessed_format()
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .into_decoder()
+        .with_context(|| format!("Unsupported image {}", path.display()))?;
+    let orientation = decoder.orientation()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let mut image = DynamicImage::from_decoder(decoder)
+        .with_context(|| format!("Failed to decode {}", path.display()))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Where a preview of `media` is cached, named by its content hash (or, for files attached
+/// without a hash, its path), and whether it is at least as new as the file
+fn cached_preview(media: &Media, cache_dir: &Path, suffix: &str) -> (PathBuf, bool) {
+    let key = match media.content_hash {
+        Some(ref hash) => hash.clone(),
+        None => format!("{:x}", Sha256::digest(media.path.as_bytes())),
+    };
+    let cached = cache_dir.join(format!("{}{}", key, suffix));
+
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let fresh = match (modified(&cached), modified(Path::new(&media.path))) {
+        (Some(made), Some(changed)) => made >= changed,
+        _ => false,
+    };
+    (cached, fresh)
+}
+
+/// A thumbnail of an image attachment, made in `cache_dir` the first time it's asked for
+/// and again whenever the image is newer than it. Returns the thumbnail's path.
+pub fn thumbnail(media: &Media, cache_dir: &Path) -> Result<PathBuf> {
+    let (cached, fresh) = cached_preview(media, cache_dir, ".jpg");
+    if fresh {
+        return Ok(cached);
+    }
+
+    let image = load_image(Path::new(&media.path))?;
+    fs::create_dir_all(cache_dir)
+        .with_context(|| format!("Failed to create thumbnail directory {}", cache_dir.display()))?;
+    fit_within(image, THUMBNAIL_SIZE)
+        .to_rgb8()
+        .save(&cached)
+        .with_context(|| format!("Failed to save thumbnail {}", cached.display()))?;
+
+    Ok(cached)
+}
+
+/// Scale an image down so neither side is longer than `size`; smaller images are left alone
+pub fn fit_within(image: DynamicImage, size: u32) -> DynamicImage {
+    if image.width() <= size && image.height() <= size {
+        image
+    } else {
+        image.thumbnail(size, size)
+    }
+}
+
+/// The [`waveform`] of an audio attachment, cached in `cache_dir` like [`thumbnail`] so a
+/// recording is only decoded once
+pub fn cached_waveform(media: &Media, cache_dir: &Path, width: usize) -> Result<Vec<f32>> {
+    let (cached, fresh) = cached_preview(media, cache_dir, &format!("-{}.peaks", width));
+    if fresh {
+        if let Ok(bytes) = fs::read(&cached) {
+            return Ok(bytes.chunks_exact(4).map(|peak| f32::from_le_bytes(peak.try_into().unwrap())).collect());
+        }
+    }
+
+    let peaks = waveform(Path::new(&media.path), width)?;
+    fs::create_dir_all(cache_dir)
+        .with_context(|| format!("Failed to create thumbnail directory {}", cache_dir.display()))?;
+    let bytes: Vec<u8> = peaks.iter().flat_map(|peak| peak.to_le_bytes()).collect();
+    fs::write(&cached, bytes).with_context(|| format!("Failed to save waveform {}", cached.display()))?;
+
+    Ok(peaks)
+}
+
+/// The loudness of an audio file across `width` equal slices, each the slice's
+/// peak from 0.0 to 1.0 relative to the loudest
+pub fn waveform(path: &Path, width: usize) -> Result<Vec<f32>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .with_context(|| format!("Unsupported audio {}", path.display()))?;
+    let mut format = probed.format;
+    let track = format.tracks().iter().find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .with_context(|| format!("No audio in {}", path.display()))?;
+    let track_id = track.id;
+    let channels = track.codec_params.channels.map(|channels| channels.count()).unwrap_or(1).max(1);
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .with_context(|| format!("Unsupported audio {}", path.display()))?;
+
+    let mut peaks = Vec::new();
+    let (mut peak, mut frames) = (0.0f32, 0);
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(AudioError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        // a damaged packet is skipped rather than losing the whole waveform
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(AudioError::DecodeError(_)) => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to decode {}", path.display())),
+        };
+        let mut samples = SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+        samples.copy_interleaved_ref(decoded);
+
+        for frame in samples.samples().chunks(channels) {
+            peak = frame.iter().fold(peak, |peak, sample| peak.max(sample.abs()));
+            frames += 1;
+            if frames == WAVEFORM_WINDOW {
+                peaks.push(peak);
+                (peak, frames) = (0.0, 0);
+            }
+        }
+    }
+    if frames > 0 {
+        peaks.push(peak);
+    }
+
+    Ok(fit_peaks(&peaks, width))
+}
+
+/// Fold `peaks` into at most `width` slices and scale them so the loudest is 1.0
+fn fit_peaks(peaks: &[f32], width: usize) -> Vec<f32> {
+    let slices: Vec<f32> = if peaks.len() <= width {
+        peaks.to_vec()
+    } else {
+        (0..width)
+            .map(|i| peaks[i * peaks.len() / width..(i + 1) * peaks.len() / width].iter().fold(0.0, |a: f32, &b| a.max(b)))
+            .collect()
+    };
+
+    let loudest = slices.iter().fold(0.0, |a: f32, &b| a.max(b));
+    if loudest > 0.0 {
+        slices.iter().map(|peak| peak / loudest).collect()
+    } else {
+        slices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MediaOwner;
+    use image::{Rgb, RgbImage};
+
+    /// A fresh scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fastwatcher-preview-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn media_at(path: &Path) -> Media {
+        Media {
+            id: 1,
+            owner: MediaOwner::Sighting(1),
+            path: path.to_string_lossy().into_owned(),
+            content_hash: None,
+            mime_type: "image/png".to_string(),
+            captured_at: None,
+            caption: None,
+        }
+    }
+
+    /// A mono 16-bit WAV file of `samples`
+    fn write_wav(path: &Path, samples: &[i16]) {
+        let data_len = (samples.len() * 2) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&16000u32.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_thumbnail_dir() {
+        let home = Some(PathBuf::from("/home/birder"));
+
+        assert_eq!(
+            thumbnail_dir_from(Some(PathBuf::from("/cache")), home.clone()).unwrap(),
+            PathBuf::from("/cache/fastwatcher/thumbnails")
+        );
+        assert_eq!(
+            thumbnail_dir_from(Some(PathBuf::from("relative")), home.clone()).unwrap(),
+            PathBuf::from("/home/birder/.cache/fastwatcher/thumbnails")
+        );
+        assert!(thumbnail_dir_from(None, None).is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_is_cached() {
+        let dir = scratch_dir("thumbnail");
+        let photo = dir.join("egret.png");
+        RgbImage::from_pixel(600, 300, Rgb([200, 200, 190])).save(&photo).unwrap();
+        let cache = dir.join("cache");
+
+        let made = thumbnail(&media_at(&photo), &cache).unwrap();
+        let small = image::open(&made).unwrap();
+        assert_eq!((small.width(), small.height()), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+
+        // asking again reuses the cached file
+        let made_at = fs::metadata(&made).unwrap().modified().unwrap();
+        assert_eq!(thumbnail(&media_at(&photo), &cache).unwrap(), made);
+        assert_eq!(fs::metadata(&made).unwrap().modified().unwrap(), made_at);
+
+        // small images aren't scaled up
+        let icon = dir.join("icon.png");
+        RgbImage::new(40, 30).save(&icon).unwrap();
+        let small = image::open(thumbnail(&media_at(&icon), &cache).unwrap()).unwrap();
+        assert_eq!((small.width(), small.height()), (40, 30));
+
+        assert!(thumbnail(&media_at(&dir.join("missing.png")), &cache).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_waveform() {
+        let dir = scratch_dir("waveform");
+        let call = dir.join("call.wav");
+        // a quiet half, then a loud one, each four peak windows long
+        let samples: Vec<i16> = (0..8192).map(|i| if i < 4096 { 1000 } else { -16000 }).collect();
+        write_wav(&call, &samples);
+
+        let peaks = waveform(&call, 4).unwrap();
+        assert_eq!(peaks.len(), 4);
+        assert!(peaks[0] < 0.1 && peaks[1] < 0.1);
+        assert_eq!(peaks[3], 1.0);
+
+        fs::write(dir.join("noise.wav"), b"not audio").unwrap();
+        assert!(waveform(&dir.join("noise.wav"), 4).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_waveform_is_cached() {
+        let dir = scratch_dir("cached-waveform");
+        let call = dir.join("call.wav");
+        let samples: Vec<i16> = (0..8192).map(|i| if i < 4096 { 1000 } else { -16000 }).collect();
+        write_wav(&call, &samples);
+        let cache = dir.join("cache");
+        let media = Media { mime_type: "audio/wav".to_string(), ..media_at(&call) };
+
+        let peaks = cached_waveform(&media, &cache, 4).unwrap();
+        assert_eq!(peaks, waveform(&call, 4).unwrap());
+
+        // the cached peaks are read back without decoding the recording again
+        let (cached, fresh) = cached_preview(&media, &cache, "-4.peaks");
+        assert!(fresh);
+        fs::write(&cached, [0.5f32, 0.25].iter().flat_map(|peak| peak.to_le_bytes()).collect::<Vec<u8>>()).unwrap();
+        assert_eq!(cached_waveform(&media, &cache, 4).unwrap(), vec![0.5, 0.25]);
+
+        // each width has its own entry
+        assert_eq!(cached_waveform(&media, &cache, 2).unwrap().len(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fit_peaks() {
+        assert_eq!(fit_peaks(&[0.1, 0.2], 10), vec![0.5, 1.0]);
+        assert_eq!(fit_peaks(&[0.1, 0.4, 0.2, 0.2], 2), vec![1.0, 0.5]);
+        assert_eq!(fit_peaks(&[0.0, 0.0], 1), vec![0.0]);
+        assert!(fit_peaks(&[], 10).is_empty());
+    }
+}
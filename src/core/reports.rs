@@ -0,0 +1,328 @@
+use crate::models::{BreedingCode, ObservedDate, Rank};
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, params};
+
+/// One species on a life or year list
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListEntry {
+    pub taxon_id: i64,
+    pub common_name: String,
+    pub scientific_name: String,
+    /// Earliest and latest sighting dates (within the year, for a year list)
+    pub first_date: Option<ObservedDate>,
+    pub last_date: Option<ObservedDate>,
+    pub sightings: i64,
+    /// Birds counted across those sightings; "X" counts add nothing
+    pub individuals: i64,
+    /// Strongest breeding evidence recorded
+    pub breeding_code: Option<BreedingCode>,
+    /// Year lists only: the first sighting ever was this year
+    pub new_species: bool,
+    /// Complete checklists (trips with all species reported) it was on
+    pub complete_checklists: i64,
+}
+
+/// Birding effort across the trips on a life or year list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffortSummary {
+    pub trips: i64,
+    /// Trips where every species seen was reported
+    pub complete_checklists: i64,
+    /// Recorded durations and distances; trips without them add nothing
+    pub minutes: i64,
+    pub distance_km: f64,
+}
+
+/// Every species with at least one sighting. Subspecies sightings count toward their
+/// species; hybrids, slashes and spuhs don't count (see `Rank::counts_as_species`).
+pub fn life_list(conn: &Connection) -> Result<Vec<ListEntry>> {
+    species_list(conn, None)
+}
+
+/// Species sighted in `year` (YYYY), with their first-of-year and last dates
+pub fn year_list(conn: &Connection, year: &str) -> Result<Vec<ListEntry>> {
+    check_year(year)?;
+    species_list(conn, Some(year))
+}
+
+/// Trips, complete checklists, time and distance, for every trip or those in `year`
+pub fn effort_summary(conn: &Connection, year: Option<&str>) -> Result<EffortSummary> {
+    if let Some(year) = year {
+        check_year(year)?;
+    }
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(complete), 0), COALESCE(SUM(duration_min), 0), COALESCE(SUM(distance_km), 0.0)
+         FROM trips
+         WHERE ?1 IS NULL OR substr(date, 1, 4) = ?1",
+        params![year],
+        |row| {
+            Ok(EffortSummary {
+                trips: row.get(0)?,
+                complete_checklists: row.get(1)?,
+                minutes: row.get(2)?,
+                distance_km: row.get(3)?,
+            })
+        },
+    )
+    .context("Failed to summarize trip effort")
+}
+
+fn check_year(year: &str) -> Result<()> {
+    if year.len() != 4 || !year.chars().all(|c| c.is_ascii_digit()) {
+        bail!("invalid year '{}' (expected YYYY)", year);
+    }
+    Ok(())
+}
+
+/// The current local year, as SQLite sees it
+pub fn current_year(conn: &Connection) -> Result<String> {
+    conn.query_row("SELECT strftime('%Y', 'now', 'localtime')", [], |row| row.get(0))
+        .context("Failed to read the current year")
+}
+
+/// A sighting without its own date takes its trip's. Taxa are listed in taxonomic
+/// order, which is the order the checklist was loaded in (taxon ID). A subspecies
+/// counts as its parent species.
+fn species_list(conn: &Connection, year: Option<&str>) -> Result<Vec<ListEntry>> {
+    let counted: Vec<String> = Rank::ALL
+        .into_iter()
+        .filter(|rank| rank.counts_as_species())
+        .map(|rank| format!("'{}'", rank))
+        .collect();
+
+    let sql = format!(
+        r#"
+        WITH species AS (
+            SELECT t.id,
+                   CASE WHEN t.rank = 'subspecies' AND parent.rank = 'species' THEN parent.id ELSE t.id END AS species_id
+            FROM taxa t
+            LEFT JOIN taxa parent ON parent.id = t.parent_id
+            WHERE t.rank IN ({})
+        ),
+        dated AS (
+            SELECT sp.species_id AS taxon_id, COALESCE(s.date, tr.date) AS date, s.count, s.breeding_code,
+                   s.trip_id, tr.complete
+            FROM sightings s
+            JOIN species sp ON sp.id = s.taxon_id
+            LEFT JOIN trips tr ON tr.id = s.trip_id
+        ),
+        life AS (
+            SELECT taxon_id, MIN(date) AS first_date
+            FROM dated
+            GROUP BY taxon_id
+        )
+        SELECT t.id, t.common_name, t.genus, t.species_epithet,
+               MIN(d.date), MAX(d.date), COUNT(*),
+               ?1 IS NOT NULL AND substr(life.first_date, 1, 4) = ?1,
+               COALESCE(SUM(CASE WHEN typeof(d.count) = 'integer' THEN d.count END), 0),
+               group_concat(DISTINCT d.breeding_code),
+               COUNT(DISTINCT CASE WHEN d.complete THEN d.trip_id END)
+        FROM dated d
+        JOIN taxa t ON t.id = d.taxon_id
+        JOIN life ON life.taxon_id = d.taxon_id
+        WHERE ?1 IS NULL OR substr(d.date, 1, 4) = ?1
+        GROUP BY t.id
+        ORDER BY t.id
+        "#,
+        counted.join(", ")
+    );
+
+    let mut stmt = conn.prepare(&sql)
+        .context("Failed to prepare species list query")?;
+
+    let rows = stmt.query_map(params![year], |row| {
+        let genus: Option<String> = row.get(2)?;
+        let epithet: Option<String> = row.get(3)?;
+        let codes: Option<String> = row.get(9)?;
+        Ok(ListEntry {
+            taxon_id: row.get(0)?,
+            common_name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            scientific_name: [genus, epithet].into_iter().flatten().collect::<Vec<_>>().join(" "),
+            first_date: row.get(4)?,
+            last_date: row.get(5)?,
+            sightings: row.get(6)?,
+            new_species: row.get(7)?,
+            individuals: row.get(8)?,
+            breeding_code: codes.as_deref().and_then(strongest_breeding_code),
+            complete_checklists: row.get(10)?,
+        })
+    }).context("Failed to execute species list query")?;
+
+    let results: Vec<ListEntry> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse species list rows")?;
+    Ok(results)
+}
+
+/// The strongest of a comma-separated list of codes
+fn strongest_breeding_code(codes: &str) -> Option<BreedingCode> {
+    let codes: Vec<BreedingCode> = codes.split(',').filter_map(|code| code.parse().ok()).collect();
+    BreedingCode::ALL.into_iter().find(|code| codes.contains(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::sighting::create_sighting;
+    use crate::core::taxon::{create_taxon, find_taxon_by_scientific_name, get_taxon_by_id};
+    use crate::core::trip::create_trip;
+    use crate::models::{Count, Effort, Protocol};
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+        crate::core::migrations::migrate(&conn).unwrap();
+
+        // Load test taxa (first 100 species + parent taxa)
+        let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
+        conn.execute_batch(&test_taxa).unwrap();
+
+        conn
+    }
+
+    fn taxon_id(conn: &Connection, name: &str) -> i64 {
+        find_taxon_by_scientific_name(conn, name).unwrap().unwrap().id
+    }
+
+    fn populate(conn: &Connection) {
+        let duck = taxon_id(conn, "Dendrocygna viduata");
+        let tinamou = taxon_id(conn, "Nothocercus bonapartei");
+        let genus = taxon_id(conn, "Dendrocygna");
+
+        create_sighting(conn, None, duck, None, None, Some("2024-03-02"), None, Some(Count::Exact(4)), &[], Some(BreedingCode::Pair), None).unwrap();
+        create_sighting(conn, None, duck, None, None, Some("2025-01-10"), None, Some(Count::Present), &[], Some(BreedingCode::Singing), None).unwrap();
+        create_sighting(conn, None, duck, None, None, Some("2025-06-01"), None, Some(Count::Exact(7)), &[], Some(BreedingCode::FeedingYoung), None).unwrap();
+        // Undated sighting on a dated trip
+        let trip = create_trip(conn, "Cloud forest", Some("2025-02-14"), None, None, None, &Effort::default()).unwrap();
+        create_sighting(conn, Some(trip), tinamou, None, None, None, None, None, &[], None, None).unwrap();
+        // Not identified to species: not on any list
+        create_sighting(conn, None, genus, None, None, Some("2025-03-01"), None, None, &[], None, None).unwrap();
+    }
+
+    #[test]
+    fn test_life_list() {
+        let conn = setup_test_db();
+        populate(&conn);
+
+        let list = life_list(&conn).unwrap();
+        assert_eq!(list.len(), 2);
+
+        // Taxonomic order: tinamous before ducks
+        assert_eq!(list[0].scientific_name, "Nothocercus bonapartei");
+        assert_eq!(list[0].first_date, Some("2025-02-14".parse().unwrap()));
+        assert_eq!(list[1].common_name, "White-faced Whistling-Duck");
+        assert_eq!(list[1].first_date, Some("2024-03-02".parse().unwrap()));
+        assert_eq!(list[1].last_date, Some("2025-06-01".parse().unwrap()));
+        assert_eq!(list[1].sightings, 3);
+        assert_eq!(list[1].individuals, 11);
+        assert_eq!(list[1].breeding_code, Some(BreedingCode::FeedingYoung));
+        assert_eq!((list[0].individuals, list[0].breeding_code), (0, None));
+        assert!(list.iter().all(|entry| !entry.new_species));
+    }
+
+    #[test]
+    fn test_year_list() {
+        let conn = setup_test_db();
+        populate(&conn);
+
+        let list = year_list(&conn, "2025").unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list[0].new_species);
+        assert_eq!(list[1].first_date, Some("2025-01-10".parse().unwrap()));
+        assert_eq!(list[1].sightings, 2);
+        assert_eq!(list[1].individuals, 7);
+        assert!(!list[1].new_species);
+
+        let list = year_list(&conn, "2024").unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].new_species);
+        assert_eq!(list[0].breeding_code, Some(BreedingCode::Pair));
+
+        assert!(year_list(&conn, "2023").unwrap().is_empty());
+        assert!(year_list(&conn, "25").is_err());
+    }
+
+    #[test]
+    fn test_subspecies_count_toward_their_species() {
+        let conn = setup_test_db();
+        populate(&conn);
+
+        let duck = get_taxon_by_id(&conn, taxon_id(&conn, "Dendrocygna viduata")).unwrap();
+        let category = |rank: Rank, epithet: Option<&str>, subspecies: Option<&str>, name: &str| {
+            create_taxon(
+                &conn,
+                rank,
+                &duck.kingdom,
+                duck.phylum.as_deref(),
+                duck.class.as_deref(),
+                duck.order.as_deref(),
+                duck.family.as_deref(),
+                duck.subfamily.as_deref(),
+                duck.genus.as_deref(),
+                epithet,
+                subspecies,
+                name,
+            )
+            .unwrap()
+        };
+        let subspecies = category(Rank::Subspecies, Some("viduata"), Some("orientalis"), "White-faced Whistling-Duck (Eastern)");
+        let slash = category(Rank::Slash, Some("viduata/bicolor"), None, "White-faced/Fulvous Whistling-Duck");
+        let hybrid = category(Rank::Hybrid, Some("viduata x bicolor"), None, "White-faced x Fulvous Whistling-Duck (hybrid)");
+        let spuh = category(Rank::Spuh, None, None, "whistling-duck sp.");
+
+        create_sighting(&conn, None, subspecies, None, None, Some("2025-07-01"), None, Some(Count::Exact(2)), &[], None, None).unwrap();
+        for taxon in [slash, hybrid, spuh] {
+            create_sighting(&conn, None, taxon, None, None, Some("2025-08-01"), None, Some(Count::Exact(1)), &[], None, None).unwrap();
+        }
+
+        let list = life_list(&conn).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].taxon_id, duck.id);
+        assert_eq!(list[1].sightings, 4);
+        assert_eq!(list[1].individuals, 13);
+        assert_eq!(list[1].last_date, Some("2025-07-01".parse().unwrap()));
+
+        let list = year_list(&conn, "2025").unwrap();
+        assert_eq!(list[1].sightings, 3);
+    }
+
+    #[test]
+    fn test_effort() {
+        let conn = setup_test_db();
+        populate(&conn);
+
+        let duck = taxon_id(&conn, "Dendrocygna viduata");
+        let effort = |duration_min, distance_km, complete| Effort {
+            duration_min: Some(duration_min),
+            distance_km,
+            protocol: Some(if distance_km.is_some() { Protocol::Traveling } else { Protocol::Stationary }),
+            complete,
+            ..Effort::default()
+        };
+        let walk = create_trip(&conn, "Marsh walk", Some("2025-04-05"), None, None, None, &effort(120, Some(3.5), true)).unwrap();
+        let watch = create_trip(&conn, "Seawatch", Some("2025-04-06"), None, None, None, &effort(60, None, true)).unwrap();
+        let stroll = create_trip(&conn, "Stroll", Some("2024-09-01"), None, None, None, &effort(30, Some(1.0), false)).unwrap();
+        for trip in [walk, watch, stroll] {
+            create_sighting(&conn, Some(trip), duck, None, None, None, None, None, &[], None, None).unwrap();
+        }
+
+        let list = life_list(&conn).unwrap();
+        assert_eq!((list[0].complete_checklists, list[1].complete_checklists), (0, 2));
+
+        let summary = effort_summary(&conn, None).unwrap();
+        assert_eq!(
+            summary,
+            EffortSummary { trips: 4, complete_checklists: 2, minutes: 210, distance_km: 4.5 }
+        );
+        let summary = effort_summary(&conn, Some("2024")).unwrap();
+        assert_eq!((summary.trips, summary.complete_checklists, summary.minutes), (1, 0, 30));
+        assert!(effort_summary(&conn, Some("24")).is_err());
+    }
+
+    #[test]
+    fn test_current_year() {
+        let conn = setup_test_db();
+        let year = current_year(&conn).unwrap();
+        assert!(year_list(&conn, &year).is_ok());
+    }
+}
@@ -1,151 +1,383 @@
-use crate::models::{Sighting, Taxon, Trip};
-use anyhow::{Context, Result, bail};
-use rusqlite::{Connection, params};
-
-/// Performs a basic search over the sightings table.
-pub fn run_search_sightings(conn: &Connection, query: &str) -> Result<Vec<Sighting>> {
-    let trimmed = query.trim();
-    if trimmed.is_empty() {
-        bail!("empty query not allowed");
-    }
-
-    let sql = r#"
-        SELECT id, trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name, notes, media_path, date, location
-        FROM sightings
-        WHERE kingdom LIKE ?1
-            OR phylum LIKE ?1
-            OR class LIKE ?1
-            OR "order" LIKE ?1
-            OR family LIKE ?1
-            OR subfamily LIKE ?1
-            OR genus LIKE ?1
-            OR species_epithet LIKE ?1
-            OR common_name LIKE ?1
-            OR date LIKE ?1
-            OR location LIKE ?1
-        LIMIT 100
-    "#;
-
-    let pattern = format!("%{}%", trimmed);
-    let mut stmt = conn.prepare(sql).context("Failed to prepare sightings search query")?;
-    let rows = stmt.query_map(params![pattern], |row| {
-        Ok(Sighting {
-            id: row.get(0)?,
-            trip_id: row.get(1)?,
-            taxon_id: row.get(2)?,
-            kingdom: row.get(3)?,
-            phylum: row.get(4)?,
-            class: row.get(5)?,
-            order: row.get(6)?,
-            family: row.get(7)?,
-            subfamily: row.get(8)?,
-            genus: row.get(9)?,
-            species_epithet: row.get(10)?,
-            common_name: row.get(11)?,
-            notes: row.get(12)?,
-            media_path: row.get(13)?,
-            date: row.get(14)?,
-            location: row.get(15)?,
-        })
-    }).context("Failed to execute sightings search")?;
-
-    let results: Vec<Sighting> = rows.collect::<Result<Vec<_>, _>>()
-        .context("Failed to parse sighting rows")?;
-    Ok(results)
+pub mod geo;
+pub mod query;
+
+pub use query::{DateRange, SearchQuery, SearchTarget, SqlFilter, parse_date_range, parse_query};
+
+use crate::core::location::location_from_row;
+use crate::core::sighting::sighting_from_row;
+use crate::core::taxon::taxon_from_row;
+use crate::core::trip::trip_from_row;
+use crate::models::{Location, Sighting, Taxon, Trip};
+use anyhow::{Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{Connection, Row, params_from_iter};
+
+/// Maximum number of results returned by any search
+const SEARCH_LIMIT: i64 = 100;
+
+/// Markers wrapped around matched terms in snippets
+pub const HIGHLIGHT_START: &str = "[";
+pub const HIGHLIGHT_END: &str = "]";
+
+/// A ranked search result with a highlighted excerpt of the matching text
+#[derive(Debug)]
+pub struct SearchHit<T> {
+    pub item: T,
+    /// bm25 relevance (lower is better); 0 for filter-only queries
+    pub score: f64,
+    /// Best-matching column excerpt, matches wrapped in `HIGHLIGHT_START`/`HIGHLIGHT_END`;
+    /// empty for filter-only queries
+    pub snippet: String,
 }
 
-pub fn run_search_trips(conn: &Connection, query: &str) -> Result<Vec<Trip>> {
-    let trimmed = query.trim();
-    if trimmed.is_empty() {
-        bail!("empty query not allowed");
-    }
-
-    let sql = r#"
-        SELECT DISTINCT trips.id, trips.name, trips.date, trips.location, trips.notes
-            FROM trips
-            LEFT JOIN sightings ON sightings.trip_id = trips.id
-            WHERE
-            (
-                trips.name LIKE ?1 OR
-                trips.location LIKE ?1 OR
-                sightings.kingdom LIKE ?1 OR
-                sightings.phylum LIKE ?1 OR
-                sightings.class LIKE ?1 OR
-                sightings."order" LIKE ?1 OR
-                sightings.family LIKE ?1 OR
-                sightings.subfamily LIKE ?1 OR
-                sightings.genus LIKE ?1 OR
-                sightings.species_epithet LIKE ?1 OR
-                sightings.common_name LIKE ?1
-                OR trips.date LIKE ?1
-            )
-        LIMIT 100
-"#;
-
-    let pattern = format!("%{}%", trimmed);
-    let mut stmt = conn.prepare(sql).context("Failed to prepare trips search query")?;
-    let rows = stmt.query_map(params![pattern], |row| {
-        Ok(Trip {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            date: row.get(2)?,
-            location: row.get(3)?,
-            notes: row.get(4)?,
-        })
-    }).context("Failed to execute trips search")?;
-
-    let results: Vec<Trip> = rows.collect::<Result<Vec<_>, _>>()
-        .context("Failed to parse trip rows")?;
+/// Results of one query across every record type it applies to
+#[derive(Debug, Default)]
+pub struct SearchResults {
+    pub sightings: Vec<SearchHit<Sighting>>,
+    pub trips: Vec<SearchHit<Trip>>,
+    pub taxa: Vec<SearchHit<Taxon>>,
+    pub locations: Vec<SearchHit<Location>>,
+}
+
+/// Runs a query against sightings, trips, taxa and places, skipping any type the
+/// query can't apply to (a `trip:` prefix, or a `rank:` filter on trips)
+pub fn run_search(conn: &Connection, query: &str) -> Result<SearchResults> {
+    let query = parse_query(query)?;
+    let mut results = SearchResults::default();
+
+    if query.applies_to(SearchTarget::Sightings) {
+        results.sightings = search_sightings(conn, &query)?;
+    }
+    if query.applies_to(SearchTarget::Trips) {
+        results.trips = search_trips(conn, &query)?;
+    }
+    if query.applies_to(SearchTarget::Taxa) {
+        results.taxa = search_taxa(conn, &query)?;
+    }
+    if query.applies_to(SearchTarget::Locations) {
+        results.locations = search_locations(conn, &query)?;
+    }
+
     Ok(results)
 }
 
-pub fn run_search_taxa(conn: &Connection, query: &str) -> Result<Vec<Taxon>> {
-    let trimmed = query.trim();
-    if trimmed.is_empty() {
-        bail!("empty query not allowed");
-    }
-
-    let sql = r#"
-        SELECT id, rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
-        FROM taxa
-        WHERE kingdom LIKE ?1
-           OR phylum LIKE ?1
-           OR class LIKE ?1
-           OR "order" LIKE ?1
-           OR family LIKE ?1
-           OR subfamily LIKE ?1
-           OR genus LIKE ?1
-           OR species_epithet LIKE ?1
-           OR common_name LIKE ?1
-        LIMIT 100
-    "#;
-
-    let pattern = format!("%{}%", trimmed);
-    let mut stmt = conn.prepare(sql).context("Failed to prepare taxa search query")?;
-    let rows = stmt.query_map(params![pattern], |row| {
-        Ok(Taxon {
-            id: row.get(0)?,
-            rank: row.get(1)?,
-            kingdom: row.get(2)?,
-            phylum: row.get(3)?,
-            class: row.get(4)?,
-            order: row.get(5)?,
-            family: row.get(6)?,
-            subfamily: row.get(7)?,
-            genus: row.get(8)?,
-            species_epithet: row.get(9)?,
-            common_name: row.get(10)?,
-        })
-    }).context("Failed to execute taxa search")?;
-
-    let results: Vec<Taxon> = rows.collect::<Result<Vec<_>, _>>()
-        .context("Failed to parse taxon rows")?;
+/// Search sightings (taxonomy, notes, location and date)
+pub fn run_search_sightings(conn: &Connection, query: &str) -> Result<Vec<SearchHit<Sighting>>> {
+    let query = parse_query(query)?;
+    query.check_target(SearchTarget::Sightings)?;
+    search_sightings(conn, &query)
+}
+
+/// Search trips; see [`search_trips`]
+pub fn run_search_trips(conn: &Connection, query: &str) -> Result<Vec<SearchHit<Trip>>> {
+    let query = parse_query(query)?;
+    query.check_target(SearchTarget::Trips)?;
+    search_trips(conn, &query)
+}
+
+/// Search taxa (common name and every rank)
+pub fn run_search_taxa(conn: &Connection, query: &str) -> Result<Vec<SearchHit<Taxon>>> {
+    let query = parse_query(query)?;
+    query.check_target(SearchTarget::Taxa)?;
+    search_taxa(conn, &query)
+}
+
+/// Search places (locality, county, state and country)
+pub fn run_search_locations(conn: &Connection, query: &str) -> Result<Vec<SearchHit<Location>>> {
+    let query = parse_query(query)?;
+    query.check_target(SearchTarget::Locations)?;
+    search_locations(conn, &query)
+}
+
+/// Excludes rows whose FTS entry matches any negated word or phrase
+fn exclude_matches(filter: &mut SqlFilter, query: &SearchQuery, id_column: &str, fts_table: &str) {
+    for expression in query.excluded_fts() {
+        filter.push(
+            format!("{} NOT IN (SELECT rowid FROM {} WHERE {} MATCH ?)", id_column, fts_table, fts_table),
+            [Value::from(expression)],
+        );
+    }
+}
+
+fn highlight_params() -> [Value; 2] {
+    [Value::from(HIGHLIGHT_START.to_string()), Value::from(HIGHLIGHT_END.to_string())]
+}
+
+fn sighting_hit(row: &Row) -> rusqlite::Result<SearchHit<Sighting>> {
+    Ok(SearchHit {
+        item: sighting_from_row(row)?,
+        score: row.get(22)?,
+        snippet: row.get(23)?,
+    })
+}
+
+fn trip_hit(row: &Row) -> rusqlite::Result<SearchHit<Trip>> {
+    Ok(SearchHit {
+        item: trip_from_row(row)?,
+        score: row.get(15)?,
+        snippet: row.get(16)?,
+    })
+}
+
+fn taxon_hit(row: &Row) -> rusqlite::Result<SearchHit<Taxon>> {
+    Ok(SearchHit {
+        item: taxon_from_row(row)?,
+        score: row.get(13)?,
+        snippet: row.get(14)?,
+    })
+}
+
+fn location_hit(row: &Row) -> rusqlite::Result<SearchHit<Location>> {
+    Ok(SearchHit {
+        item: location_from_row(row)?,
+        score: row.get(10)?,
+        snippet: row.get(11)?,
+    })
+}
+
+fn run_hits<T>(
+    conn: &Connection,
+    sql: &str,
+    params: Vec<Value>,
+    map: fn(&Row) -> rusqlite::Result<SearchHit<T>>,
+) -> Result<Vec<SearchHit<T>>> {
+    let mut stmt = conn.prepare(sql).context("Failed to prepare search query")?;
+    let rows = stmt.query_map(params_from_iter(params), map).context("Invalid search query")?;
+
+    let results: Vec<SearchHit<T>> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Invalid search query")?;
     Ok(results)
 }
 
+/// Search sightings with a parsed query
+pub fn search_sightings(conn: &Connection, query: &SearchQuery) -> Result<Vec<SearchHit<Sighting>>> {
+    let mut filter = query.filter(SearchTarget::Sightings, "s")?;
+    exclude_matches(&mut filter, query, "s.id", "sightings_fts");
+
+    let columns = r#"s.id, s.trip_id, s.taxon_id, s.kingdom, s.phylum, s.class, s."order", s.family, s.subfamily,
+               s.genus, s.species_epithet, s.common_name, s.notes, s.date, s.location,
+               s.count, s.age_sex, s.breeding_code, s.latitude, s.longitude, s.accuracy_m, s.location_id"#;
+    let mut params: Vec<Value> = Vec::new();
+
+    let sql = match query.fts_match() {
+        Some(fts_query) => {
+            params.extend(highlight_params());
+            params.push(Value::from(fts_query));
+            format!(
+                r#"
+                SELECT {},
+                       bm25(sightings_fts, 10.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 1.0, 2.0, 1.0) AS score,
+                       snippet(sightings_fts, -1, ?, ?, '…', 12)
+                FROM sightings_fts
+                INNER JOIN sightings s ON s.id = sightings_fts.rowid
+                WHERE sightings_fts MATCH ? AND {}
+                ORDER BY score
+                LIMIT ?
+                "#,
+                columns,
+                filter.where_clause()
+            )
+        }
+        None => format!(
+            r#"
+            SELECT {}, 0.0, ''
+            FROM sightings s
+            WHERE {}
+            ORDER BY s.date DESC, s.id DESC
+            LIMIT ?
+            "#,
+            columns,
+            filter.where_clause()
+        ),
+    };
+
+    params.extend(filter.params);
+    params.push(Value::from(SEARCH_LIMIT));
+
+    run_hits(conn, &sql, params, sighting_hit)
+}
+
+/// Search trips with a parsed query. Trips whose own fields match the text come
+/// first, followed by trips that only match through one of their sightings.
+pub fn search_trips(conn: &Connection, query: &SearchQuery) -> Result<Vec<SearchHit<Trip>>> {
+    let mut filter = query.filter(SearchTarget::Trips, "t")?;
+    exclude_matches(&mut filter, query, "t.id", "trips_fts");
+    for expression in query.excluded_fts() {
+        filter.push(
+            r#"t.id NOT IN (
+                SELECT s.trip_id FROM sightings_fts
+                INNER JOIN sightings s ON s.id = sightings_fts.rowid
+                WHERE sightings_fts MATCH ? AND s.trip_id IS NOT NULL
+            )"#
+            .to_string(),
+            [Value::from(expression)],
+        );
+    }
+
+    let mut params: Vec<Value> = Vec::new();
+
+    // MATERIALIZED keeps bm25()/snippet() inside plain FTS queries; they can't run under GROUP BY or windows
+    let sql = match query.fts_match() {
+        Some(fts_query) => {
+            params.extend(highlight_params());
+            params.push(Value::from(fts_query.clone()));
+            params.extend(highlight_params());
+            params.push(Value::from(fts_query));
+            format!(
+                r#"
+                WITH direct AS MATERIALIZED (
+                    SELECT rowid AS trip_id,
+                           bm25(trips_fts, 10.0, 2.0, 1.0, 1.0) AS score,
+                           snippet(trips_fts, -1, ?, ?, '…', 12) AS snippet
+                    FROM trips_fts
+                    WHERE trips_fts MATCH ?
+                ),
+                via_sightings AS MATERIALIZED (
+                    SELECT s.trip_id AS trip_id,
+                           bm25(sightings_fts, 10.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 1.0, 2.0, 1.0) AS score,
+                           snippet(sightings_fts, -1, ?, ?, '…', 12) AS snippet
+                    FROM sightings_fts
+                    INNER JOIN sightings s ON s.id = sightings_fts.rowid
+                    WHERE sightings_fts MATCH ? AND s.trip_id IS NOT NULL
+                ),
+                ranked AS (
+                    SELECT trip_id, tier, score, snippet,
+                           ROW_NUMBER() OVER (PARTITION BY trip_id ORDER BY tier, score) AS position
+                    FROM (
+                        SELECT trip_id, 0 AS tier, score, snippet FROM direct
+                        UNION ALL
+                        SELECT trip_id, 1 AS tier, score, snippet FROM via_sightings
+                    )
+                )
+                SELECT t.id, t.name, t.date, t.location, t.notes, t.latitude, t.longitude, t.accuracy_m, t.location_id,
+                       t.start_time, t.duration_min, t.distance_km, t.party_size, t.protocol, t.complete,
+                       ranked.score, ranked.snippet
+                FROM ranked
+                INNER JOIN trips t ON t.id = ranked.trip_id
+                WHERE ranked.position = 1 AND {}
+                ORDER BY ranked.tier, ranked.score
+                LIMIT ?
+                "#,
+                filter.where_clause()
+            )
+        }
+        None => format!(
+            r#"
+            SELECT t.id, t.name, t.date, t.location, t.notes, t.latitude, t.longitude, t.accuracy_m, t.location_id,
+                   t.start_time, t.duration_min, t.distance_km, t.party_size, t.protocol, t.complete, 0.0, ''
+            FROM trips t
+            WHERE {}
+            ORDER BY t.date DESC, t.id DESC
+            LIMIT ?
+            "#,
+            filter.where_clause()
+        ),
+    };
+
+    params.extend(filter.params);
+    params.push(Value::from(SEARCH_LIMIT));
+
+    run_hits(conn, &sql, params, trip_hit)
+}
+
+/// Search taxa with a parsed query
+pub fn search_taxa(conn: &Connection, query: &SearchQuery) -> Result<Vec<SearchHit<Taxon>>> {
+    let mut filter = query.filter(SearchTarget::Taxa, "t")?;
+    exclude_matches(&mut filter, query, "t.id", "taxa_fts");
+
+    let columns = r#"t.id, t.rank, t.kingdom, t.phylum, t.class, t."order", t.family, t.subfamily, t.genus, t.species_epithet, t.common_name, t.subspecies, t.parent_id"#;
+    let mut params: Vec<Value> = Vec::new();
+
+    let sql = match query.fts_match() {
+        Some(fts_query) => {
+            params.extend(highlight_params());
+            params.push(Value::from(fts_query));
+            format!(
+                r#"
+                SELECT {},
+                       bm25(taxa_fts, 10.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0) AS score,
+                       snippet(taxa_fts, -1, ?, ?, '…', 12)
+                FROM taxa_fts
+                INNER JOIN taxa t ON t.id = taxa_fts.rowid
+                WHERE taxa_fts MATCH ? AND {}
+                ORDER BY score
+                LIMIT ?
+                "#,
+                columns,
+                filter.where_clause()
+            )
+        }
+        None => format!(
+            r#"
+            SELECT {}, 0.0, ''
+            FROM taxa t
+            WHERE {}
+            ORDER BY t.id
+            LIMIT ?
+            "#,
+            columns,
+            filter.where_clause()
+        ),
+    };
+
+    params.extend(filter.params);
+    params.push(Value::from(SEARCH_LIMIT));
+
+    run_hits(conn, &sql, params, taxon_hit)
+}
+
+/// Search places with a parsed query
+pub fn search_locations(conn: &Connection, query: &SearchQuery) -> Result<Vec<SearchHit<Location>>> {
+    let mut filter = query.filter(SearchTarget::Locations, "l")?;
+    exclude_matches(&mut filter, query, "l.id", "locations_fts");
+
+    let columns = "l.id, l.name, l.locality, l.county, l.state, l.country, l.latitude, l.longitude, l.accuracy_m, l.hotspot";
+    let mut params: Vec<Value> = Vec::new();
+
+    let sql = match query.fts_match() {
+        Some(fts_query) => {
+            params.extend(highlight_params());
+            params.push(Value::from(fts_query));
+            format!(
+                r#"
+                SELECT {},
+                       bm25(locations_fts, 10.0, 2.0, 2.0, 1.0) AS score,
+                       snippet(locations_fts, -1, ?, ?, '…', 12)
+                FROM locations_fts
+                INNER JOIN locations l ON l.id = locations_fts.rowid
+                WHERE locations_fts MATCH ? AND {}
+                ORDER BY score
+                LIMIT ?
+                "#,
+                columns,
+                filter.where_clause()
+            )
+        }
+        None => format!(
+            r#"
+            SELECT {}, 0.0, ''
+            FROM locations l
+            WHERE {}
+            ORDER BY l.name
+            LIMIT ?
+            "#,
+            columns,
+            filter.where_clause()
+        ),
+    };
+
+    params.extend(filter.params);
+    params.push(Value::from(SEARCH_LIMIT));
+
+    run_hits(conn, &sql, params, location_hit)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Effort;
+    use crate::models::Rank;
+    use crate::core::location::create_location;
     use crate::core::taxon::create_taxon;
     use crate::core::trip::create_trip;
     use crate::core::sighting::create_sighting;
@@ -154,8 +386,7 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         conn.pragma_update(None, "foreign_keys", "ON").unwrap();
 
-        let schema = std::fs::read_to_string("init.sql").unwrap();
-        conn.execute_batch(&schema).unwrap();
+        crate::core::migrations::migrate(&conn).unwrap();
 
         // Load test taxa (first 100 species + parent taxa)
         let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
@@ -168,94 +399,94 @@ mod tests {
     fn test_search_taxa_by_common_name() {
         let conn = setup_test_db();
 
-        create_taxon(&conn, "species", "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), "American Robin").unwrap();
+        create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
 
         let results = run_search_taxa(&conn, "Robin").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].common_name, "American Robin");
+        assert_eq!(results[0].item.common_name, "American Robin");
     }
 
     #[test]
     fn test_search_taxa_by_family() {
         let conn = setup_test_db();
 
-        create_taxon(&conn, "family", "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, None, None, "Crow Family").unwrap();
+        create_taxon(&conn, Rank::Family, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, None, None, None, "Crow Family").unwrap();
 
         let results = run_search_taxa(&conn, "Corvidae").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].family, Some("Corvidae".to_string()));
-        assert_eq!(results[0].rank, "family");
+        assert_eq!(results[0].item.family, Some("Corvidae".to_string()));
+        assert_eq!(results[0].item.rank, Rank::Family);
     }
 
     #[test]
     fn test_search_sightings_by_species() {
         let conn = setup_test_db();
 
-        let taxon_id = create_taxon(&conn, "species", "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), "American Robin").unwrap();
-        create_sighting(&conn, None, taxon_id, Some("Test note"), None, None, None).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        create_sighting(&conn, None, taxon_id, Some("Test note"), None, None, None, None, &[], None, None).unwrap();
 
         let results = run_search_sightings(&conn, "Robin").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].common_name, "American Robin");
+        assert_eq!(results[0].item.common_name, "American Robin");
     }
 
     #[test]
     fn test_search_sightings_by_family() {
         let conn = setup_test_db();
 
-        let taxon_id = create_taxon(&conn, "family", "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, None, None, "Crow Family").unwrap();
-        create_sighting(&conn, None, taxon_id, None, None, None, None).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Family, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, None, None, None, "Crow Family").unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
         let results = run_search_sightings(&conn, "Corvidae").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].family, Some("Corvidae".to_string()));
+        assert_eq!(results[0].item.family, Some("Corvidae".to_string()));
     }
 
     #[test]
     fn test_search_sightings_by_location() {
         let conn = setup_test_db();
 
-        let taxon_id = create_taxon(&conn, "species", "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), "American Robin").unwrap();
-        create_sighting(&conn, None, taxon_id, None, None, None, Some("Near the pond")).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, None, Some("Near the pond"), None, &[], None, None).unwrap();
 
         let results = run_search_sightings(&conn, "pond").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].location, Some("Near the pond".to_string()));
+        assert_eq!(results[0].item.location, Some("Near the pond".to_string()));
     }
 
     #[test]
     fn test_search_trips_by_name() {
         let conn = setup_test_db();
 
-        create_trip(&conn, "Morning Birding", Some("2025-01-15"), Some("Central Park"), None).unwrap();
+        create_trip(&conn, "Morning Birding", Some("2025-01-15"), Some("Central Park"), None, None, &Effort::default()).unwrap();
 
         let results = run_search_trips(&conn, "Birding").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "Morning Birding");
+        assert_eq!(results[0].item.name, "Morning Birding");
     }
 
     #[test]
     fn test_search_trips_by_location() {
         let conn = setup_test_db();
 
-        create_trip(&conn, "Morning Walk", None, Some("Central Park"), None).unwrap();
+        create_trip(&conn, "Morning Walk", None, Some("Central Park"), None, None, &Effort::default()).unwrap();
 
         let results = run_search_trips(&conn, "Central Park").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].location, Some("Central Park".to_string()));
+        assert_eq!(results[0].item.location, Some("Central Park".to_string()));
     }
 
     #[test]
     fn test_search_trips_by_sighting_taxonomy() {
         let conn = setup_test_db();
 
-        let trip_id = create_trip(&conn, "Birdwatching", None, None, None).unwrap();
-        let taxon_id = create_taxon(&conn, "family", "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, None, None, "Crow Family").unwrap();
-        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None).unwrap();
+        let trip_id = create_trip(&conn, "Birdwatching", None, None, None, None, &Effort::default()).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Family, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, None, None, None, "Crow Family").unwrap();
+        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
         let results = run_search_trips(&conn, "Corvidae").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "Birdwatching");
+        assert_eq!(results[0].item.name, "Birdwatching");
     }
 
     #[test]
@@ -276,7 +507,7 @@ mod tests {
         // Create subfamily-level taxon
         create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -285,13 +516,14 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae Subfamily",
         ).unwrap();
 
         let results = run_search_taxa(&conn, "Corvinae").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].rank, "subfamily");
-        assert_eq!(results[0].subfamily, Some("Corvinae".to_string()));
+        assert_eq!(results[0].item.rank, Rank::Subfamily);
+        assert_eq!(results[0].item.subfamily, Some("Corvinae".to_string()));
     }
 
     #[test]
@@ -301,7 +533,7 @@ mod tests {
         // Create subfamily-level taxon
         let taxon_id = create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -310,25 +542,26 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae Subfamily",
         ).unwrap();
 
-        create_sighting(&conn, None, taxon_id, None, None, None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
         let results = run_search_sightings(&conn, "Corvinae").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].subfamily, Some("Corvinae".to_string()));
+        assert_eq!(results[0].item.subfamily, Some("Corvinae".to_string()));
     }
 
     #[test]
     fn test_search_trips_by_subfamily() {
         let conn = setup_test_db();
 
-        let trip_id = create_trip(&conn, "Corvid Watch", None, None, None).unwrap();
+        let trip_id = create_trip(&conn, "Corvid Watch", None, None, None, None, &Effort::default()).unwrap();
 
         let taxon_id = create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -337,13 +570,293 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae",
         ).unwrap();
 
-        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None).unwrap();
+        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
         let results = run_search_trips(&conn, "Corvinae").unwrap();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "Corvid Watch");
+        assert_eq!(results[0].item.name, "Corvid Watch");
+    }
+
+    #[test]
+    fn test_search_sightings_by_notes_with_snippet() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        create_sighting(&conn, None, taxon_id, Some("Pulling earthworms from the wet lawn"), None, None, None, None, &[], None, None).unwrap();
+
+        let results = run_search_sightings(&conn, "earthworms").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.common_name, "American Robin");
+        assert!(results[0].snippet.contains("[earthworms]"));
+    }
+
+    #[test]
+    fn test_search_sightings_advanced_syntax() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Accipitriformes"), Some("Accipitridae"), None, Some("Buteo"), Some("jamaicensis"), None, "Red-tailed Hawk").unwrap();
+        create_sighting(&conn, None, taxon_id, Some("Perched on a light pole by the highway"), None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, Some("Soaring over the highway, tail glowing red"), None, None, None, None, &[], None, None).unwrap();
+
+        // Phrase
+        assert_eq!(run_search_sightings(&conn, "\"light pole\"").unwrap().len(), 1);
+        assert_eq!(run_search_sightings(&conn, "\"pole light\"").unwrap().len(), 0);
+
+        // Boolean operators
+        assert_eq!(run_search_sightings(&conn, "highway AND (perched OR soaring)").unwrap().len(), 2);
+        assert_eq!(run_search_sightings(&conn, "highway NOT soaring").unwrap().len(), 1);
+
+        // Prefix
+        assert_eq!(run_search_sightings(&conn, "soar*").unwrap().len(), 1);
+
+        // Near
+        assert_eq!(run_search_sightings(&conn, "NEAR(tail red, 2)").unwrap().len(), 1);
+        assert_eq!(run_search_sightings(&conn, "NEAR(perched highway, 2)").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_search_invalid_fts_syntax() {
+        let conn = setup_test_db();
+
+        assert!(run_search_sightings(&conn, "\"unbalanced").is_err());
+        assert!(run_search_taxa(&conn, "hawk AND").is_err());
+    }
+
+    #[test]
+    fn test_search_ranks_common_name_matches_first() {
+        let conn = setup_test_db();
+
+        let jay_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Cyanocitta"), Some("cristata"), None, "Blue Jay").unwrap();
+        let robin_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        create_sighting(&conn, None, robin_id, Some("Chased off by a jay"), None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, jay_id, None, None, None, None, None, &[], None, None).unwrap();
+
+        let results = run_search_sightings(&conn, "jay").unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].item.common_name, "Blue Jay");
+        assert!(results[0].score <= results[1].score);
+    }
+
+    #[test]
+    fn test_search_trips_by_notes() {
+        let conn = setup_test_db();
+
+        create_trip(&conn, "Morning Walk", None, None, Some("Fog lifted around nine"), None, &Effort::default()).unwrap();
+
+        let results = run_search_trips(&conn, "fog").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.name, "Morning Walk");
+        assert!(results[0].snippet.contains("[Fog]"));
+    }
+
+    #[test]
+    fn test_search_trips_direct_matches_before_sighting_matches() {
+        let conn = setup_test_db();
+
+        let direct = create_trip(&conn, "Owl Prowl", None, None, None, None, &Effort::default()).unwrap();
+        let via_sighting = create_trip(&conn, "Night Walk", None, None, None, None, &Effort::default()).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Strigiformes"), Some("Strigidae"), None, Some("Bubo"), Some("virginianus"), None, "Great Horned Owl").unwrap();
+        create_sighting(&conn, Some(via_sighting), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, Some(via_sighting), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+
+        let results = run_search_trips(&conn, "owl").unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].item.id, direct);
+        assert_eq!(results[1].item.id, via_sighting);
+    }
+
+    #[test]
+    fn test_search_index_follows_updates_and_deletes() {
+        let conn = setup_test_db();
+
+        let trip_id = create_trip(&conn, "Morning Walk", None, Some("Riverside"), None, None, &Effort::default()).unwrap();
+
+        let changes = crate::models::TripUpdate {
+            location: Some("Lakeshore".to_string()),
+            ..Default::default()
+        };
+        crate::core::trip::update_trip(&conn, trip_id, &changes).unwrap();
+        assert_eq!(run_search_trips(&conn, "Riverside").unwrap().len(), 0);
+        assert_eq!(run_search_trips(&conn, "Lakeshore").unwrap().len(), 1);
+
+        crate::core::trip::delete_trip(&conn, trip_id).unwrap();
+        assert_eq!(run_search_trips(&conn, "Lakeshore").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_search_sightings_with_field_and_date_filters() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Accipitriformes"), Some("Accipitridae"), None, Some("Buteo"), Some("jamaicensis"), None, "Red-tailed Hawk").unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, Some("2025-03-14"), Some("Forest Park"), None, &[], None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, Some("2025-07-02"), Some("Forest Park"), None, &[], None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, Some("2024-11-30"), Some("Riverfront"), None, &[], None, None).unwrap();
+
+        assert_eq!(run_search_sightings(&conn, "hawk location:park").unwrap().len(), 2);
+        assert_eq!(run_search_sightings(&conn, "hawk date:2025").unwrap().len(), 2);
+        assert_eq!(run_search_sightings(&conn, "hawk date:2025-01..2025-06").unwrap().len(), 1);
+        assert_eq!(run_search_sightings(&conn, "hawk date:..2025-03-14").unwrap().len(), 2);
+        assert_eq!(run_search_sightings(&conn, "sighting:hawk date:2025-01-01..2025-12-31 location:park").unwrap().len(), 2);
+
+        // Filters alone list every match, without scores or snippets
+        let results = run_search_sightings(&conn, "location:riverfront").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.date, Some("2024-11-30".parse().unwrap()));
+        assert!(results[0].snippet.is_empty());
+    }
+
+    #[test]
+    fn test_search_negation_includes_empty_fields() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        create_sighting(&conn, None, taxon_id, Some("Singing in the rain"), None, None, Some("Backyard"), None, &[], None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, Some("Feeding fledglings"), None, None, None, None, &[], None, None).unwrap();
+
+        let results = run_search_sightings(&conn, "robin -location:backyard").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.notes, Some("Feeding fledglings".to_string()));
+
+        let results = run_search_sightings(&conn, "robin -rain").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.notes, Some("Feeding fledglings".to_string()));
+
+        assert_eq!(run_search_sightings(&conn, "robin -\"in the rain\"").unwrap().len(), 1);
+        assert_eq!(run_search_sightings(&conn, "-fledglings").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_search_trips_negation_covers_sightings() {
+        let conn = setup_test_db();
+
+        let owl_trip = create_trip(&conn, "Night Walk", Some("2025-02-01"), None, None, None, &Effort::default()).unwrap();
+        create_trip(&conn, "Day Walk", Some("2025-02-02"), None, None, None, &Effort::default()).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Strigiformes"), Some("Strigidae"), None, Some("Bubo"), Some("virginianus"), None, "Great Horned Owl").unwrap();
+        create_sighting(&conn, Some(owl_trip), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+
+        let results = run_search_trips(&conn, "walk -owl").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.name, "Day Walk");
+
+        assert_eq!(run_search_trips(&conn, "walk date:2025-02-01").unwrap()[0].item.id, owl_trip);
+    }
+
+    #[test]
+    fn test_search_near_filter() {
+        let conn = setup_test_db();
+
+        let point = |value: &str| Some(value.parse::<crate::models::Coordinates>().unwrap());
+        let park = create_trip(&conn, "Park Walk", None, None, None, point("40.7825,-73.9655"), &Effort::default()).unwrap();
+        create_trip(&conn, "Harbor Walk", None, None, None, point("40.6892,-74.0445"), &Effort::default()).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        create_sighting(&conn, Some(park), taxon_id, Some("By the trip"), None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, Some(park), taxon_id, Some("Wandered off"), None, None, None, None, &[], None, point("40.6602,-73.9690")).unwrap();
+        create_sighting(&conn, None, taxon_id, Some("Nowhere"), None, None, None, None, &[], None, None).unwrap();
+
+        let results = run_search_sightings(&conn, "robin near:40.78,-73.97,2km").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.notes, Some("By the trip".to_string()));
+
+        assert_eq!(run_search_sightings(&conn, "near:40.78,-73.97,20").unwrap().len(), 2);
+        assert_eq!(run_search_sightings(&conn, "robin -near:40.78,-73.97,2km").unwrap().len(), 2);
+
+        let results = run_search_trips(&conn, "walk near:40.78,-73.97,2km").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.id, park);
+        assert_eq!(run_search_trips(&conn, "near:40.78,-73.97,20").unwrap().len(), 2);
+
+        assert!(run_search_taxa(&conn, "robin near:40.78,-73.97,2km").is_err());
+    }
+
+    #[test]
+    fn test_search_near_across_the_antimeridian() {
+        let conn = setup_test_db();
+
+        let point = |value: &str| Some(value.parse::<crate::models::Coordinates>().unwrap());
+        let east = create_trip(&conn, "East", None, None, None, point("10,179.95"), &Effort::default()).unwrap();
+        let west = create_trip(&conn, "West", None, None, None, point("10,-179.95"), &Effort::default()).unwrap();
+        create_trip(&conn, "Far", None, None, None, point("10,179"), &Effort::default()).unwrap();
+
+        for center in ["10,-179.95,50", "10,179.95,50", "10,180,50"] {
+            let mut ids: Vec<i64> = run_search_trips(&conn, &format!("near:{}", center)).unwrap().iter().map(|hit| hit.item.id).collect();
+            ids.sort();
+            assert_eq!(ids, vec![east, west], "{}", center);
+
+            let radius = geo::parse_radius(center).unwrap();
+            let mut near: Vec<i64> = geo::trips_near(&conn, &radius).unwrap().iter().map(|hit| hit.item.id).collect();
+            near.sort();
+            assert_eq!(near, ids, "{}", center);
+        }
+    }
+
+    #[test]
+    fn test_search_locations_by_hierarchy() {
+        let conn = setup_test_db();
+
+        let point = |value: &str| Some(value.parse::<crate::models::Coordinates>().unwrap());
+        create_location(&conn, "Central Park", Some("New York"), Some("New York"), Some("US"), point("40.7825,-73.9655"), true).unwrap();
+        create_location(&conn, "Forest Park", Some("Queens"), Some("New York"), Some("US"), None, false).unwrap();
+        create_location(&conn, "Forest Park", Some("St. Louis"), Some("Missouri"), Some("US"), None, false).unwrap();
+
+        let results = run_search_locations(&conn, "forest").unwrap();
+        assert_eq!(results.len(), 2);
+
+        let results = run_search_locations(&conn, "park state:missouri").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.name, "Forest Park, St. Louis, Missouri, US");
+
+        assert_eq!(run_search_locations(&conn, "place: county:queens").unwrap().len(), 1);
+        assert_eq!(run_search_locations(&conn, "park near:40.78,-73.97,2km").unwrap().len(), 1);
+        assert!(run_search_locations(&conn, "park date:2025").is_err());
+        assert!(run_search_trips(&conn, "place:park").is_err());
+    }
+
+    #[test]
+    fn test_search_taxa_by_rank_filter() {
+        let conn = setup_test_db();
+
+        create_taxon(&conn, Rank::Genus, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Corvus"), None, None, "Crows").unwrap();
+        create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Corvus"), Some("corax"), None, "Common Raven").unwrap();
+
+        let results = run_search_taxa(&conn, "corvus rank:genus").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.common_name, "Crows");
+
+        // The raven, and the family created above the genus
+        assert_eq!(run_search_taxa(&conn, "family:corvidae -rank:genus").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_search_rejects_mismatched_targets_and_fields() {
+        let conn = setup_test_db();
+
+        assert!(run_search_sightings(&conn, "trip:owl").is_err());
+        assert!(run_search_taxa(&conn, "location:park").is_err());
+        assert!(run_search_trips(&conn, "rank:species").is_err());
+    }
+
+    #[test]
+    fn test_run_search_respects_type_prefix_and_fields() {
+        let conn = setup_test_db();
+
+        let trip_id = create_trip(&conn, "Owl Prowl", None, Some("Forest Park"), None, None, &Effort::default()).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Strigiformes"), Some("Strigidae"), None, Some("Bubo"), Some("virginianus"), None, "Great Horned Owl").unwrap();
+        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, Some("Forest Park"), None, &[], None, None).unwrap();
+
+        let results = run_search(&conn, "owl").unwrap();
+        assert_eq!((results.sightings.len(), results.trips.len(), results.taxa.len()), (1, 1, 1));
+
+        let results = run_search(&conn, "trip:owl").unwrap();
+        assert_eq!((results.sightings.len(), results.trips.len(), results.taxa.len()), (0, 1, 0));
+
+        // Taxa have no location, so they drop out rather than erroring
+        let results = run_search(&conn, "owl location:park").unwrap();
+        assert_eq!((results.sightings.len(), results.trips.len(), results.taxa.len()), (1, 1, 0));
+
+        assert!(run_search(&conn, "owl colour:brown").is_err());
     }
 }
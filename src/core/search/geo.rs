@@ -0,0 +1,357 @@
+//! Spatial queries over trip and sighting coordinates, backed by the `trips_geo` and
+//! `sightings_geo` R*Tree indexes. A sighting without coordinates of its own is placed
+//! at its trip's.
+
+use crate::core::sighting::sighting_from_row;
+use crate::core::trip::trip_from_row;
+use crate::models::coordinates::EARTH_RADIUS_KM;
+use crate::models::{Coordinates, Sighting, Trip};
+use anyhow::{Context, Result, anyhow, bail};
+use rusqlite::types::Value;
+use rusqlite::{Connection, params_from_iter};
+
+/// Kilometers per degree of latitude
+const KM_PER_DEGREE: f64 = EARTH_RADIUS_KM * std::f64::consts::PI / 180.0;
+
+/// A latitude/longitude box, edges included
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lon: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    /// The smallest box holding every point within `radius_km` of `center`. Near a pole,
+    /// or where the circle crosses the antimeridian, it spans every longitude.
+    pub fn around(center: &Coordinates, radius_km: f64) -> Self {
+        let dlat = radius_km / KM_PER_DEGREE;
+        let min_lat = (center.latitude - dlat).max(-90.0);
+        let max_lat = (center.latitude + dlat).min(90.0);
+
+        let widest = center.latitude.abs() + dlat;
+        let (min_lon, max_lon) = if widest >= 90.0 {
+            (-180.0, 180.0)
+        } else {
+            let dlon = radius_km / (KM_PER_DEGREE * widest.to_radians().cos());
+            if center.longitude - dlon < -180.0 || center.longitude + dlon > 180.0 {
+                (-180.0, 180.0)
+            } else {
+                (center.longitude - dlon, center.longitude + dlon)
+            }
+        };
+
+        BoundingBox { min_lat, max_lat, min_lon, max_lon }
+    }
+
+    pub fn contains(&self, point: &Coordinates) -> bool {
+        (self.min_lat..=self.max_lat).contains(&point.latitude) && (self.min_lon..=self.max_lon).contains(&point.longitude)
+    }
+
+    /// R*Tree condition on a geo table, with its parameters
+    fn rtree_condition(&self) -> (&'static str, [Value; 4]) {
+        (
+            "max_lat >= ? AND min_lat <= ? AND max_lon >= ? AND min_lon <= ?",
+            [Value::from(self.min_lat), Value::from(self.max_lat), Value::from(self.min_lon), Value::from(self.max_lon)],
+        )
+    }
+}
+
+/// Everything within `radius_km` of `center`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Radius {
+    pub center: Coordinates,
+    pub radius_km: f64,
+}
+
+impl Radius {
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::around(&self.center, self.radius_km)
+    }
+
+    /// SQL condition that the point in `lat`/`lon` lies inside the circle: an R*Tree lookup
+    /// on `geo_table` for `id_column`, then a flat-earth distance check that is close enough
+    /// at search scales. [`sightings_near`] and [`trips_near`] use the exact great-circle distance.
+    pub(crate) fn sql_condition(&self, geo_table: &str, id_column: &str, lat: &str, lon: &str) -> (String, Vec<Value>) {
+        let (rtree, params) = self.bounding_box().rtree_condition();
+        let mut params = params.to_vec();
+        let lon_scale = KM_PER_DEGREE * self.center.latitude.to_radians().cos();
+
+        // The longitude difference the short way round, so circles crossing the antimeridian work
+        let dlon = format!("min(abs({lon} - ?), 360 - abs({lon} - ?))", lon = lon);
+        let condition = format!(
+            "({id} IN (SELECT id FROM {table} WHERE {rtree}) \
+             AND (({lat} - ?) * ?) * (({lat} - ?) * ?) + ({dlon} * ?) * ({dlon} * ?) <= ?)",
+            id = id_column,
+            table = geo_table,
+            rtree = rtree,
+            lat = lat,
+            dlon = dlon,
+        );
+        let (latitude, longitude) = (self.center.latitude, self.center.longitude);
+        params.extend([Value::from(latitude), Value::from(KM_PER_DEGREE), Value::from(latitude), Value::from(KM_PER_DEGREE)]);
+        for _ in 0..2 {
+            params.extend([Value::from(longitude), Value::from(longitude), Value::from(lon_scale)]);
+        }
+        params.push(Value::from(self.radius_km * self.radius_km));
+
+        (condition, params)
+    }
+}
+
+/// Parse `LAT,LON,RADIUS`; the radius is in kilometers unless it ends in `m` (`500m`)
+pub fn parse_radius(value: &str) -> Result<Radius> {
+    let invalid = || anyhow!("invalid radius '{}' (expected LAT,LON,RADIUS, e.g. 40.78,-73.97,10km)", value);
+
+    let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+    let [latitude, longitude, radius] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let center: Coordinates = format!("{},{}", latitude, longitude).parse().map_err(|_| invalid())?;
+
+    let radius = radius.to_ascii_lowercase();
+    let radius_km = match (radius.strip_suffix("km"), radius.strip_suffix('m')) {
+        (Some(km), _) => km.trim().parse::<f64>().ok(),
+        (None, Some(meters)) => meters.trim().parse::<f64>().ok().map(|meters| meters / 1000.0),
+        (None, None) => radius.parse::<f64>().ok(),
+    }
+    .filter(|km| km.is_finite() && *km > 0.0)
+    .ok_or_else(invalid)?;
+
+    Ok(Radius { center, radius_km })
+}
+
+/// Parse `SOUTH,WEST,NORTH,EAST` in degrees
+pub fn parse_bounding_box(value: &str) -> Result<BoundingBox> {
+    let invalid = || anyhow!("invalid box '{}' (expected SOUTH,WEST,NORTH,EAST)", value);
+
+    let numbers = value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>().ok().filter(|number| number.is_finite()))
+        .collect::<Option<Vec<f64>>>()
+        .ok_or_else(invalid)?;
+    let [min_lat, min_lon, max_lat, max_lon] = numbers.as_slice() else {
+        return Err(invalid());
+    };
+
+    // Both corners must be valid points
+    Coordinates::new(*min_lat, *min_lon, None)?;
+    Coordinates::new(*max_lat, *max_lon, None)?;
+    if min_lat > max_lat || min_lon > max_lon {
+        bail!("box '{}' has its south-west corner above or east of its north-east corner", value);
+    }
+
+    Ok(BoundingBox { min_lat: *min_lat, max_lat: *max_lat, min_lon: *min_lon, max_lon: *max_lon })
+}
+
+/// A spatial query result, nearest first
+#[derive(Debug)]
+pub struct NearbyHit<T> {
+    pub item: T,
+    /// Where the record was placed (a sighting's own coordinates, or else its trip's)
+    pub position: Coordinates,
+    /// Great-circle distance from the search center; None for box queries
+    pub distance_km: Option<f64>,
+}
+
+/// Sightings placed inside `bounds`, by their own coordinates or else their trip's
+pub fn sightings_in_box(conn: &Connection, bounds: &BoundingBox) -> Result<Vec<NearbyHit<Sighting>>> {
+    let (rtree, bounds_params) = bounds.rtree_condition();
+    let sql = format!(
+        r#"
+        SELECT s.id, s.trip_id, s.taxon_id, s.kingdom, s.phylum, s.class, s."order", s.family, s.subfamily,
+               s.genus, s.species_epithet, s.common_name, s.notes, s.date, s.location,
+               s.count, s.age_sex, s.breeding_code, s.latitude, s.longitude, s.accuracy_m, s.location_id,
+               CASE WHEN s.latitude IS NOT NULL THEN s.latitude ELSE t.latitude END,
+               CASE WHEN s.latitude IS NOT NULL THEN s.longitude ELSE t.longitude END,
+               CASE WHEN s.latitude IS NOT NULL THEN s.accuracy_m ELSE t.accuracy_m END
+        FROM sightings s
+        LEFT JOIN trips t ON t.id = s.trip_id
+        WHERE s.id IN (SELECT id FROM sightings_geo WHERE {rtree})
+           OR (s.latitude IS NULL AND s.trip_id IN (SELECT id FROM trips_geo WHERE {rtree}))
+        ORDER BY s.id
+        "#,
+        rtree = rtree
+    );
+
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare sightings in box query")?;
+    let params = bounds_params.iter().chain(bounds_params.iter());
+    let rows = stmt
+        .query_map(params_from_iter(params), |row| {
+            let position = crate::core::db::coordinates_at(row, 22)?;
+            Ok((sighting_from_row(row)?, position))
+        })
+        .context("Failed to execute sightings in box query")?;
+
+    let mut hits = Vec::new();
+    for row in rows {
+        let (item, position) = row.context("Failed to parse sighting rows")?;
+        // The R*Tree stores 32-bit floats, so recheck against the exact columns
+        if let Some(position) = position.filter(|position| bounds.contains(position)) {
+            hits.push(NearbyHit { item, position, distance_km: None });
+        }
+    }
+    Ok(hits)
+}
+
+/// Trips whose coordinates fall inside `bounds`
+pub fn trips_in_box(conn: &Connection, bounds: &BoundingBox) -> Result<Vec<NearbyHit<Trip>>> {
+    let (rtree, params) = bounds.rtree_condition();
+    let sql = format!(
+        r#"
+        SELECT id, name, date, location, notes, latitude, longitude, accuracy_m, location_id,
+               start_time, duration_min, distance_km, party_size, protocol, complete
+        FROM trips
+        WHERE id IN (SELECT id FROM trips_geo WHERE {})
+        ORDER BY id
+        "#,
+        rtree
+    );
+
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare trips in box query")?;
+    let rows = stmt
+        .query_map(params_from_iter(params), trip_from_row)
+        .context("Failed to execute trips in box query")?;
+
+    let mut hits = Vec::new();
+    for row in rows {
+        let item = row.context("Failed to parse trip rows")?;
+        if let Some(position) = item.coordinates.filter(|position| bounds.contains(position)) {
+            hits.push(NearbyHit { item, position, distance_km: None });
+        }
+    }
+    Ok(hits)
+}
+
+/// Sightings within the radius, nearest first
+pub fn sightings_near(conn: &Connection, radius: &Radius) -> Result<Vec<NearbyHit<Sighting>>> {
+    let hits = sightings_in_box(conn, &radius.bounding_box())?;
+    Ok(nearest_first(hits, radius))
+}
+
+/// Trips within the radius, nearest first
+pub fn trips_near(conn: &Connection, radius: &Radius) -> Result<Vec<NearbyHit<Trip>>> {
+    let hits = trips_in_box(conn, &radius.bounding_box())?;
+    Ok(nearest_first(hits, radius))
+}
+
+fn nearest_first<T>(hits: Vec<NearbyHit<T>>, radius: &Radius) -> Vec<NearbyHit<T>> {
+    let mut hits: Vec<(f64, NearbyHit<T>)> = hits
+        .into_iter()
+        .map(|hit| (radius.center.distance_km(&hit.position), hit))
+        .filter(|(distance, _)| *distance <= radius.radius_km)
+        .collect();
+    hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+    hits.into_iter()
+        .map(|(distance, hit)| NearbyHit { distance_km: Some(distance), ..hit })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Effort;
+    use crate::core::sighting::create_sighting;
+    use crate::core::trip::create_trip;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+        crate::core::migrations::migrate(&conn).unwrap();
+
+        // Load test taxa (first 100 species + parent taxa)
+        let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
+        conn.execute_batch(&test_taxa).unwrap();
+
+        conn
+    }
+
+    fn point(latitude: f64, longitude: f64) -> Option<Coordinates> {
+        Some(Coordinates::new(latitude, longitude, None).unwrap())
+    }
+
+    #[test]
+    fn test_parse_radius() {
+        let radius = parse_radius("40.78,-73.97,10").unwrap();
+        assert_eq!(radius.center, point(40.78, -73.97).unwrap());
+        assert_eq!(radius.radius_km, 10.0);
+        assert_eq!(parse_radius("40.78, -73.97, 10km").unwrap().radius_km, 10.0);
+        assert_eq!(parse_radius("40.78,-73.97,500m").unwrap().radius_km, 0.5);
+
+        for bad in ["40.78,-73.97", "40.78,-73.97,0", "40.78,-73.97,ten", "95,0,1", "a,b,1"] {
+            assert!(parse_radius(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse_bounding_box() {
+        let bounds = parse_bounding_box("40.5,-74.3,40.9,-73.7").unwrap();
+        assert_eq!(bounds, BoundingBox { min_lat: 40.5, max_lat: 40.9, min_lon: -74.3, max_lon: -73.7 });
+        assert!(parse_bounding_box("40.9,-74.3,40.5,-73.7").is_err());
+        assert!(parse_bounding_box("40.5,-74.3,40.9").is_err());
+        assert!(parse_bounding_box("40.5,-74.3,40.9,200").is_err());
+    }
+
+    #[test]
+    fn test_bounding_box_around() {
+        let center = point(0.0, 0.0).unwrap();
+        let bounds = BoundingBox::around(&center, KM_PER_DEGREE);
+        assert!((bounds.max_lat - 1.0).abs() < 1e-9);
+        assert!((bounds.max_lon - 1.0).abs() < 1e-3);
+
+        // Wraps to every longitude at the antimeridian and near the poles
+        let bounds = BoundingBox::around(&point(10.0, 179.9).unwrap(), 50.0);
+        assert_eq!((bounds.min_lon, bounds.max_lon), (-180.0, 180.0));
+        let bounds = BoundingBox::around(&point(89.9, 0.0).unwrap(), 50.0);
+        assert_eq!((bounds.min_lon, bounds.max_lon, bounds.max_lat), (-180.0, 180.0, 90.0));
+    }
+
+    #[test]
+    fn test_sightings_and_trips_near() {
+        let conn = setup_test_db();
+        let taxon: i64 = conn.query_row("SELECT id FROM taxa WHERE rank = 'species' LIMIT 1", [], |row| row.get(0)).unwrap();
+
+        // Central Park trip; one sighting placed by the trip, one with its own point in Prospect Park
+        let park = create_trip(&conn, "Central Park", None, None, None, point(40.7825, -73.9655), &Effort::default()).unwrap();
+        let by_trip = create_sighting(&conn, Some(park), taxon, None, None, None, None, None, &[], None, None).unwrap();
+        let prospect = create_sighting(&conn, Some(park), taxon, None, None, None, None, None, &[], None, point(40.6602, -73.9690)).unwrap();
+        // Far away, and nowhere at all
+        create_sighting(&conn, None, taxon, None, None, None, None, None, &[], None, point(51.5, -0.12)).unwrap();
+        create_sighting(&conn, None, taxon, None, None, None, None, None, &[], None, None).unwrap();
+
+        let home = Radius { center: point(40.7812, -73.9665).unwrap(), radius_km: 5.0 };
+        let hits = sightings_near(&conn, &home).unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.item.id).collect::<Vec<_>>(), vec![by_trip]);
+        assert!(hits[0].distance_km.unwrap() < 0.2);
+        assert_eq!(hits[0].item.coordinates, None);
+
+        let wider = Radius { radius_km: 20.0, ..home };
+        let hits = sightings_near(&conn, &wider).unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.item.id).collect::<Vec<_>>(), vec![by_trip, prospect]);
+
+        let trips = trips_near(&conn, &wider).unwrap();
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].item.id, park);
+
+        // Moving the trip moves the sightings that use its point
+        conn.execute("UPDATE trips SET latitude = 10, longitude = 10 WHERE id = ?1", [park]).unwrap();
+        let hits = sightings_near(&conn, &wider).unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.item.id).collect::<Vec<_>>(), vec![prospect]);
+
+        let nyc = parse_bounding_box("40.5,-74.3,40.9,-73.7").unwrap();
+        assert_eq!(sightings_in_box(&conn, &nyc).unwrap().len(), 1);
+        assert!(trips_in_box(&conn, &nyc).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_geo_index_follows_deletes() {
+        let conn = setup_test_db();
+        let trip = create_trip(&conn, "Point", None, None, None, point(1.0, 1.0), &Effort::default()).unwrap();
+        conn.execute("DELETE FROM trips WHERE id = ?1", [trip]).unwrap();
+
+        let indexed: i64 = conn.query_row("SELECT COUNT(*) FROM trips_geo", [], |row| row.get(0)).unwrap();
+        assert_eq!(indexed, 0);
+    }
+}
@@ -0,0 +1,770 @@
+//! Structured search syntax, e.g. `sighting:hawk date:2025-01..2025-06 location:park -"red tailed"`.
+//!
+//! Input is parsed into a [`SearchQuery`]; free text becomes an FTS5 MATCH expression
+//! and everything else compiles to parameterized WHERE conditions.
+
+use super::geo::{Radius, parse_radius};
+use crate::models::Rank;
+use anyhow::{Result, bail};
+use rusqlite::types::Value;
+use std::fmt;
+
+/// The kind of record a query searches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTarget {
+    Sightings,
+    Trips,
+    Taxa,
+    Locations,
+}
+
+impl SearchTarget {
+    fn from_prefix(key: &str) -> Option<Self> {
+        match key {
+            "sighting" | "sightings" => Some(SearchTarget::Sightings),
+            "trip" | "trips" => Some(SearchTarget::Trips),
+            "taxon" | "taxa" => Some(SearchTarget::Taxa),
+            "place" | "places" => Some(SearchTarget::Locations),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SearchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SearchTarget::Sightings => "sightings",
+            SearchTarget::Trips => "trips",
+            SearchTarget::Taxa => "taxa",
+            SearchTarget::Locations => "locations",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Columns that can be filtered with `field:value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Rank,
+    Kingdom,
+    Phylum,
+    Class,
+    Order,
+    Family,
+    Subfamily,
+    Genus,
+    Species,
+    Notes,
+    Location,
+    County,
+    State,
+    Country,
+}
+
+const FIELDS: [Field; 15] = [
+    Field::Name,
+    Field::Rank,
+    Field::Kingdom,
+    Field::Phylum,
+    Field::Class,
+    Field::Order,
+    Field::Family,
+    Field::Subfamily,
+    Field::Genus,
+    Field::Species,
+    Field::Notes,
+    Field::Location,
+    Field::County,
+    Field::State,
+    Field::Country,
+];
+
+impl Field {
+    pub fn key(self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Rank => "rank",
+            Field::Kingdom => "kingdom",
+            Field::Phylum => "phylum",
+            Field::Class => "class",
+            Field::Order => "order",
+            Field::Family => "family",
+            Field::Subfamily => "subfamily",
+            Field::Genus => "genus",
+            Field::Species => "species",
+            Field::Notes => "notes",
+            Field::Location => "location",
+            Field::County => "county",
+            Field::State => "state",
+            Field::Country => "country",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        FIELDS.into_iter().find(|field| field.key() == key)
+    }
+
+    /// Column backing this field on `target`, or None if the target has no such field
+    pub fn column(self, target: SearchTarget) -> Option<&'static str> {
+        use SearchTarget::*;
+
+        match (self, target) {
+            (Field::Name, Trips) => Some("name"),
+            (Field::Name, Locations) => Some("locality"),
+            (Field::Name, Sightings | Taxa) => Some("common_name"),
+            (Field::Rank, Taxa) => Some("rank"),
+            (Field::Rank, _) => None,
+            (Field::Kingdom, Sightings | Taxa) => Some("kingdom"),
+            (Field::Phylum, Sightings | Taxa) => Some("phylum"),
+            (Field::Class, Sightings | Taxa) => Some("class"),
+            (Field::Order, Sightings | Taxa) => Some("\"order\""),
+            (Field::Family, Sightings | Taxa) => Some("family"),
+            (Field::Subfamily, Sightings | Taxa) => Some("subfamily"),
+            (Field::Genus, Sightings | Taxa) => Some("genus"),
+            (Field::Species, Sightings | Taxa) => Some("species_epithet"),
+            (Field::Notes | Field::Location, Sightings | Trips) => Some(self.key()),
+            (Field::Location, Locations) => Some("name"),
+            (Field::County | Field::State | Field::Country, Locations) => Some(self.key()),
+            _ => None,
+        }
+    }
+}
+
+/// Inclusive date range; bounds are `YYYY`, `YYYY-MM` or `YYYY-MM-DD` and either may be open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl DateRange {
+    /// Whether `date` falls in the range, comparing on each bound's own precision
+    pub fn contains(&self, date: &str) -> bool {
+        let after_start = self.from.as_ref().is_none_or(|from| date >= from.as_str());
+        let before_end = self.to.as_ref().is_none_or(|to| date.get(..to.len()).unwrap_or(date) <= to.as_str());
+        after_start && before_end
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// Bare word, prefix-matched unless it uses FTS5 syntax
+    Word(String),
+    /// Quoted phrase
+    Phrase(String),
+    /// `field:value`; a case-insensitive substring match (`rank:` matches exactly and must name a [`Rank`])
+    Field(Field, String),
+    /// `date:from..to`
+    Date(DateRange),
+    /// `near:lat,lon,radius`; sightings without coordinates use their trip's
+    Near(Radius),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub negated: bool,
+    pub term: Term,
+}
+
+/// A parsed search query
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchQuery {
+    /// Set by a `sighting:`, `trip:`, `taxon:` or `place:` prefix
+    pub target: Option<SearchTarget>,
+    pub clauses: Vec<Clause>,
+}
+
+/// WHERE conditions and their parameters, in placeholder order
+#[derive(Debug, Default)]
+pub struct SqlFilter {
+    pub conditions: Vec<String>,
+    pub params: Vec<Value>,
+}
+
+impl SqlFilter {
+    pub fn push(&mut self, condition: String, params: impl IntoIterator<Item = Value>) {
+        self.conditions.push(condition);
+        self.params.extend(params);
+    }
+
+    /// Conditions joined with AND (`1` when there are none)
+    pub fn where_clause(&self) -> String {
+        if self.conditions.is_empty() {
+            "1".to_string()
+        } else {
+            self.conditions.join(" AND ")
+        }
+    }
+}
+
+/// Parses the search syntax into a [`SearchQuery`]
+pub fn parse_query(input: &str) -> Result<SearchQuery> {
+    if input.trim().is_empty() {
+        bail!("empty query not allowed");
+    }
+
+    let mut query = SearchQuery::default();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut negated = false;
+        if chars.peek() == Some(&'-') {
+            chars.next();
+            match chars.peek() {
+                Some(c) if !c.is_whitespace() => negated = true,
+                _ => continue,
+            }
+        }
+
+        let token = read_token(&mut chars)?;
+
+        if let Some(target) = type_prefix(&token) {
+            if negated {
+                bail!("'{}' can't be negated", token);
+            }
+            if query.target.is_some_and(|existing| existing != target) {
+                bail!("query can only search one of sighting:, trip:, taxon: or place:");
+            }
+            query.target = Some(target);
+
+            let rest = &token[token.find(':').unwrap() + 1..];
+            if let Some(term) = text_term(rest) {
+                query.clauses.push(Clause { negated: false, term });
+            }
+            continue;
+        }
+
+        let term = match filter_key(&token) {
+            Some((key, value)) => parse_filter(&key, value)?,
+            None => match text_term(&token) {
+                Some(term) => term,
+                None => continue,
+            },
+        };
+        query.clauses.push(Clause { negated, term });
+    }
+
+    if query.target.is_none() && query.clauses.is_empty() {
+        bail!("query has no searchable words");
+    }
+
+    Ok(query)
+}
+
+/// Reads one token, keeping quoted text and parenthesized groups together
+fn read_token(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String> {
+    let mut token = String::new();
+    let mut in_quotes = false;
+    let mut depth = 0usize;
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() && !in_quotes && depth == 0 {
+            break;
+        }
+        chars.next();
+        token.push(c);
+
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '(' if !in_quotes => depth += 1,
+            ')' if !in_quotes => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    if in_quotes {
+        bail!("unterminated quote in '{}'", token);
+    }
+
+    Ok(token)
+}
+
+/// Splits `key:value` when the key is a plain word (so times like `6:30` stay text)
+fn filter_key(token: &str) -> Option<(String, &str)> {
+    let (key, value) = token.split_once(':')?;
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic() || c == '_') {
+        return None;
+    }
+    Some((key.to_ascii_lowercase(), value))
+}
+
+fn type_prefix(token: &str) -> Option<SearchTarget> {
+    filter_key(token).and_then(|(key, _)| SearchTarget::from_prefix(&key))
+}
+
+fn parse_filter(key: &str, value: &str) -> Result<Term> {
+    if key == "date" {
+        return Ok(Term::Date(parse_date_range(value)?));
+    }
+    if key == "near" {
+        return Ok(Term::Near(parse_radius(value)?));
+    }
+
+    let Some(field) = Field::from_key(key) else {
+        let known: Vec<&str> = FIELDS.iter().map(|field| field.key()).collect();
+        bail!(
+            "unknown filter '{}:' (expected sighting:, trip:, taxon:, place:, date:, near: or one of {})",
+            key,
+            known.join(", ")
+        );
+    };
+
+    let value = unquote(value);
+    if value.trim().is_empty() {
+        bail!("'{}:' needs a value", key);
+    }
+    if field == Field::Rank {
+        return Ok(Term::Field(field, value.parse::<Rank>()?.to_string()));
+    }
+
+    Ok(Term::Field(field, value.to_string()))
+}
+
+/// Parse `FROM..TO`, `FROM..`, `..TO` or a single date (the whole day, month or year)
+pub fn parse_date_range(value: &str) -> Result<DateRange> {
+    let (from, to) = match value.split_once("..") {
+        Some((from, to)) => (from, to),
+        None => (value, value),
+    };
+
+    let from = parse_date_bound(from)?;
+    let to = parse_date_bound(to)?;
+
+    if from.is_none() && to.is_none() {
+        bail!("'date:' needs a date or range, e.g. date:2025-01-01..2025-12-31");
+    }
+    if let (Some(start), Some(end)) = (&from, &to) {
+        // compare on the shorter precision: 2025-06..2025 is June through December
+        let precision = start.len().min(end.len());
+        if start[..precision] > end[..precision] {
+            bail!("date range starts after it ends: {}..{}", start, end);
+        }
+    }
+
+    Ok(DateRange { from, to })
+}
+
+fn parse_date_bound(value: &str) -> Result<Option<String>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let parts: Vec<&str> = value.split('-').collect();
+    let widths_ok = parts.len() <= 3
+        && parts.iter().zip([4, 2, 2]).all(|(part, width)| {
+            part.len() == width && part.chars().all(|c| c.is_ascii_digit())
+        });
+    let in_range = |index: usize, max: u32| {
+        parts.get(index).is_none_or(|part| (1..=max).contains(&part.parse::<u32>().unwrap_or(0)))
+    };
+
+    if !widths_ok || !in_range(1, 12) || !in_range(2, 31) {
+        bail!("invalid date '{}' (expected YYYY, YYYY-MM or YYYY-MM-DD)", value);
+    }
+
+    Ok(Some(value.to_string()))
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|inner| inner.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Free text as a term, or None if it has nothing searchable
+fn text_term(text: &str) -> Option<Term> {
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') && text[1..text.len() - 1].find('"').is_none() {
+        let phrase = text[1..text.len() - 1].trim();
+        return (!phrase.is_empty()).then(|| Term::Phrase(phrase.to_string()));
+    }
+
+    (text.chars().any(char::is_alphanumeric) || is_operator(text))
+        .then(|| Term::Word(text.to_string()))
+}
+
+fn is_operator(word: &str) -> bool {
+    matches!(word, "AND" | "OR" | "NOT")
+}
+
+/// Whether a word is FTS5 syntax (operators, prefixes, groups, NEAR) rather than plain text
+fn uses_fts_syntax(word: &str) -> bool {
+    is_operator(word) || word.contains(['"', '*', '(', ')'])
+}
+
+/// Plain words match as prefixes so search-as-you-type finds "robin" from "rob"
+fn prefix_terms(word: &str) -> Vec<String> {
+    word.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| format!("\"{}\"*", part))
+        .collect()
+}
+
+fn fts_term(term: &Term, raw: bool) -> Option<String> {
+    match term {
+        Term::Phrase(phrase) => Some(format!("\"{}\"", phrase)),
+        Term::Word(word) if raw || uses_fts_syntax(word) => Some(word.clone()),
+        Term::Word(word) => {
+            let parts = prefix_terms(word);
+            (!parts.is_empty()).then(|| parts.join(" "))
+        }
+        _ => None,
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('%');
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.push('%');
+    escaped
+}
+
+impl SearchQuery {
+    /// Whether the query can run against `target`: the type prefix allows it
+    /// and every field filter names a column the target has
+    pub fn applies_to(&self, target: SearchTarget) -> bool {
+        self.target.is_none_or(|only| only == target)
+            && self.clauses.iter().all(|clause| match &clause.term {
+                Term::Field(field, _) => field.column(target).is_some(),
+                Term::Date(_) => matches!(target, SearchTarget::Sightings | SearchTarget::Trips),
+                Term::Near(_) => target != SearchTarget::Taxa,
+                _ => true,
+            })
+    }
+
+    /// Fails if a type prefix limits the query to a different target
+    pub fn check_target(&self, target: SearchTarget) -> Result<()> {
+        match self.target {
+            Some(only) if only != target => {
+                bail!("query is limited to {} but this searches {}", only, target)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// FTS5 MATCH expression for the positive text, or None for a filter-only query.
+    ///
+    /// Once any word uses FTS5 syntax the text is passed through as written, so
+    /// `hawk AND (red OR tail)` and `NEAR(blue jay, 5)` keep working.
+    pub fn fts_match(&self) -> Option<String> {
+        let positive: Vec<&Term> = self
+            .clauses
+            .iter()
+            .filter(|clause| !clause.negated)
+            .map(|clause| &clause.term)
+            .collect();
+        let raw = positive
+            .iter()
+            .any(|term| matches!(term, Term::Word(word) if uses_fts_syntax(word)));
+
+        let parts: Vec<String> = positive.iter().filter_map(|term| fts_term(term, raw)).collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// FTS5 expressions for each negated word or phrase
+    pub fn excluded_fts(&self) -> Vec<String> {
+        self.clauses
+            .iter()
+            .filter(|clause| clause.negated)
+            .filter_map(|clause| fts_term(&clause.term, false))
+            .collect()
+    }
+
+    /// WHERE conditions for the field, date and distance filters against `alias` (a table alias for `target`)
+    pub fn filter(&self, target: SearchTarget, alias: &str) -> Result<SqlFilter> {
+        let mut filter = SqlFilter::default();
+
+        for clause in &self.clauses {
+            let (condition, params) = match &clause.term {
+                Term::Field(field, value) => {
+                    let Some(column) = field.column(target) else {
+                        bail!("'{}:' filter does not apply to {}", field.key(), target);
+                    };
+                    if *field == Field::Rank {
+                        (format!("{}.{} = ?", alias, column), vec![Value::from(value.clone())])
+                    } else {
+                        (format!("{}.{} LIKE ? ESCAPE '\\'", alias, column), vec![Value::from(escape_like(value))])
+                    }
+                }
+                Term::Date(range) => {
+                    if !matches!(target, SearchTarget::Sightings | SearchTarget::Trips) {
+                        bail!("'date:' filter does not apply to {}", target);
+                    }
+                    date_condition(&format!("{}.date", alias), range)
+                }
+                Term::Near(radius) => near_condition(target, alias, radius)?,
+                _ => continue,
+            };
+
+            if clause.negated {
+                filter.push(format!("NOT COALESCE({}, 0)", condition), params);
+            } else {
+                filter.push(condition, params);
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+/// Trips and places match on their own coordinates; sightings on theirs, or else their trip's
+fn near_condition(target: SearchTarget, alias: &str, radius: &Radius) -> Result<(String, Vec<Value>)> {
+    let own = |alias: &str, geo_table: &str| {
+        radius.sql_condition(
+            geo_table,
+            &format!("{}.id", alias),
+            &format!("{}.latitude", alias),
+            &format!("{}.longitude", alias),
+        )
+    };
+
+    match target {
+        SearchTarget::Trips => Ok(own(alias, "trips_geo")),
+        SearchTarget::Locations => Ok(own(alias, "locations_geo")),
+        SearchTarget::Sightings => {
+            let (sighting, mut params) = own(alias, "sightings_geo");
+            let (trip, trip_params) = own("near_trip", "trips_geo");
+            params.extend(trip_params);
+            Ok((
+                format!(
+                    "(CASE WHEN {a}.latitude IS NOT NULL THEN {sighting} \
+                     ELSE {a}.trip_id IN (SELECT near_trip.id FROM trips near_trip WHERE {trip}) END)",
+                    a = alias,
+                    sighting = sighting,
+                    trip = trip,
+                ),
+                params,
+            ))
+        }
+        SearchTarget::Taxa => bail!("'near:' filter does not apply to {}", target),
+    }
+}
+
+/// Compares on the bound's own precision, so `..2025-06` includes every day in June
+fn date_condition(column: &str, range: &DateRange) -> (String, Vec<Value>) {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+
+    if let Some(from) = &range.from {
+        conditions.push(format!("{} >= ?", column));
+        params.push(Value::from(from.clone()));
+    }
+    if let Some(to) = &range.to {
+        conditions.push(format!("substr({}, 1, ?) <= ?", column));
+        params.push(Value::from(to.len() as i64));
+        params.push(Value::from(to.clone()));
+    }
+
+    (format!("({})", conditions.join(" AND ")), params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clause(negated: bool, term: Term) -> Clause {
+        Clause { negated, term }
+    }
+
+    #[test]
+    fn test_parse_plain_words() {
+        let query = parse_query("blue jay").unwrap();
+        assert_eq!(query.target, None);
+        assert_eq!(
+            query.clauses,
+            vec![clause(false, Term::Word("blue".into())), clause(false, Term::Word("jay".into()))]
+        );
+        assert_eq!(query.fts_match().unwrap(), "\"blue\"* \"jay\"*");
+    }
+
+    #[test]
+    fn test_parse_combined_filters() {
+        let query = parse_query("sighting:hawk date:2025-01-01..2025-12-31 location:park").unwrap();
+        assert_eq!(query.target, Some(SearchTarget::Sightings));
+        assert_eq!(
+            query.clauses,
+            vec![
+                clause(false, Term::Word("hawk".into())),
+                clause(
+                    false,
+                    Term::Date(DateRange { from: Some("2025-01-01".into()), to: Some("2025-12-31".into()) })
+                ),
+                clause(false, Term::Field(Field::Location, "park".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_phrases_and_negation() {
+        let query = parse_query("-\"red tailed\" location:\"central park\" -notes:fog hawk").unwrap();
+        assert_eq!(
+            query.clauses,
+            vec![
+                clause(true, Term::Phrase("red tailed".into())),
+                clause(false, Term::Field(Field::Location, "central park".into())),
+                clause(true, Term::Field(Field::Notes, "fog".into())),
+                clause(false, Term::Word("hawk".into())),
+            ]
+        );
+        assert_eq!(query.fts_match().unwrap(), "\"hawk\"*");
+        assert_eq!(query.excluded_fts(), vec!["\"red tailed\"".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_type_prefix_alone_and_with_phrase() {
+        let query = parse_query("trip:").unwrap();
+        assert_eq!(query.target, Some(SearchTarget::Trips));
+        assert!(query.clauses.is_empty());
+        assert_eq!(query.fts_match(), None);
+
+        let query = parse_query("taxa:\"blue jay\"").unwrap();
+        assert_eq!(query.target, Some(SearchTarget::Taxa));
+        assert_eq!(query.clauses, vec![clause(false, Term::Phrase("blue jay".into()))]);
+    }
+
+    #[test]
+    fn test_parse_keeps_fts_syntax() {
+        for input in ["hawk AND (red OR tail)", "cor*", "NEAR(blue jay, 5)", "highway NOT soaring"] {
+            assert_eq!(parse_query(input).unwrap().fts_match().unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_parse_open_and_single_date_ranges() {
+        let range = |input: &str| match &parse_query(input).unwrap().clauses[0].term {
+            Term::Date(range) => range.clone(),
+            other => panic!("expected a date range, got {:?}", other),
+        };
+
+        assert_eq!(range("date:2025"), DateRange { from: Some("2025".into()), to: Some("2025".into()) });
+        assert_eq!(range("date:2025-06.."), DateRange { from: Some("2025-06".into()), to: None });
+        assert_eq!(range("date:..2024-12-31"), DateRange { from: None, to: Some("2024-12-31".into()) });
+    }
+
+    #[test]
+    fn test_date_range_contains() {
+        let range = parse_date_range("2025-06..2025").unwrap();
+        assert!(range.contains("2025-06-01"));
+        assert!(range.contains("2025-12-31"));
+        assert!(!range.contains("2025-05-31"));
+        assert!(!range.contains("2026-01-01"));
+
+        let open = parse_date_range("..2024-03").unwrap();
+        assert!(open.contains("1999-01-01"));
+        assert!(open.contains("2024-03-31"));
+        assert!(!open.contains("2024-04-01"));
+    }
+
+    #[test]
+    fn test_parse_near() {
+        let query = parse_query("hawk near:40.78,-73.97,500m").unwrap();
+        let Term::Near(radius) = &query.clauses[1].term else {
+            panic!("expected a near filter, got {:?}", query.clauses[1].term);
+        };
+        assert_eq!((radius.center.latitude, radius.center.longitude, radius.radius_km), (40.78, -73.97, 0.5));
+        assert_eq!(query.fts_match().unwrap(), "\"hawk\"*");
+
+        assert!(query.applies_to(SearchTarget::Sightings));
+        assert!(query.applies_to(SearchTarget::Trips));
+        assert!(!query.applies_to(SearchTarget::Taxa));
+
+        let filter = query.filter(SearchTarget::Trips, "t").unwrap();
+        assert!(filter.where_clause().starts_with("(t.id IN (SELECT id FROM trips_geo WHERE"));
+        assert_eq!(filter.params.len(), 15);
+        assert_eq!(query.filter(SearchTarget::Sightings, "s").unwrap().params.len(), 30);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for input in [
+            "",
+            "   ",
+            "--",
+            "\"unbalanced",
+            "colour:red",
+            "location:",
+            "date:",
+            "date:..",
+            "date:2025-13",
+            "date:2025-02-32",
+            "date:25",
+            "date:2025-12..2025-01",
+            "near:",
+            "near:40.78,-73.97",
+            "near:91,0,5",
+            "rank:tribe",
+            "-trip:owl",
+            "trip:owl taxon:owl",
+        ] {
+            assert!(parse_query(input).is_err(), "expected '{}' to fail", input);
+        }
+    }
+
+    #[test]
+    fn test_times_and_unknown_colons_stay_text() {
+        let query = parse_query("6:30 flyover").unwrap();
+        assert_eq!(query.clauses[0], clause(false, Term::Word("6:30".into())));
+        assert_eq!(query.fts_match().unwrap(), "\"6\"* \"30\"* \"flyover\"*");
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let query = parse_query("location:park").unwrap();
+        assert!(query.applies_to(SearchTarget::Sightings));
+        assert!(query.applies_to(SearchTarget::Trips));
+        assert!(!query.applies_to(SearchTarget::Taxa));
+
+        let query = parse_query("rank:genus").unwrap();
+        assert!(!query.applies_to(SearchTarget::Sightings));
+        assert!(query.applies_to(SearchTarget::Taxa));
+
+        let query = parse_query("state:york").unwrap();
+        assert!(query.applies_to(SearchTarget::Locations));
+        assert!(!query.applies_to(SearchTarget::Trips));
+
+        let query = parse_query("place:park date:2025").unwrap();
+        assert_eq!(query.target, Some(SearchTarget::Locations));
+        assert!(!query.applies_to(SearchTarget::Locations));
+
+        let query = parse_query("trip:owl").unwrap();
+        assert!(query.applies_to(SearchTarget::Trips));
+        assert!(!query.applies_to(SearchTarget::Sightings));
+        assert!(query.check_target(SearchTarget::Sightings).is_err());
+    }
+
+    #[test]
+    fn test_filter_compiles_to_parameters() {
+        let query = parse_query("location:50%_off -date:2025-01..2025-03 rank:Species").unwrap();
+
+        let filter = query.filter(SearchTarget::Sightings, "s");
+        assert!(filter.is_err(), "rank: does not apply to sightings");
+
+        let query = parse_query("location:50%_off -date:2025-01..2025-03").unwrap();
+        let filter = query.filter(SearchTarget::Trips, "t").unwrap();
+        assert_eq!(
+            filter.where_clause(),
+            "t.location LIKE ? ESCAPE '\\' AND NOT COALESCE((t.date >= ? AND substr(t.date, 1, ?) <= ?), 0)"
+        );
+        assert_eq!(
+            filter.params,
+            vec![
+                Value::from("%50\\%\\_off%".to_string()),
+                Value::from("2025-01".to_string()),
+                Value::from(7i64),
+                Value::from("2025-03".to_string()),
+            ]
+        );
+    }
+}
@@ -1,8 +1,56 @@
-use crate::models::Sighting;
-use anyhow::{Context, Result};
-use rusqlite::{Connection, params};
+use crate::core::db::{Paged, coordinate_values, coordinates_at, optional_date, optional_text, parse_date, query_page};
+use crate::core::location::{location_assignments, resolve_location};
+use crate::core::media::{MediaFile, attach_media};
+use crate::core::search::DateRange;
+use crate::core::taxon::SUBTREE_CTE;
+use crate::models::{
+    AgeSexCount, BreedingCode, Coordinates, Count, MediaOwner, Sighting, SightingUpdate, format_age_sex, parse_age_sex,
+};
+use anyhow::{Context, Result, bail};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Type, Value, ValueRef};
+use rusqlite::{Connection, Row, ToSql, params};
+
+/// Taxonomic fields copied from `taxa` into each sighting
+struct DenormalizedTaxonomy {
+    kingdom: String,
+    phylum: Option<String>,
+    class: Option<String>,
+    order: Option<String>,
+    family: Option<String>,
+    subfamily: Option<String>,
+    genus: Option<String>,
+    species_epithet: Option<String>,
+    common_name: String,
+}
+
+/// Look up the taxonomic fields a sighting of `taxon_id` should carry
+fn fetch_denormalized_taxonomy(conn: &Connection, taxon_id: i64) -> Result<DenormalizedTaxonomy> {
+    let sql = r#"
+        SELECT kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
+        FROM taxa
+        WHERE id = ?1
+    "#;
+
+    let taxonomy = conn.query_row(sql, params![taxon_id], |row| {
+        Ok(DenormalizedTaxonomy {
+            kingdom: row.get(0)?,
+            phylum: row.get(1)?,
+            class: row.get(2)?,
+            order: row.get(3)?,
+            family: row.get(4)?,
+            subfamily: row.get(5)?,
+            genus: row.get(6)?,
+            species_epithet: row.get(7)?,
+            common_name: row.get(8)?,
+        })
+    }).context("Failed to fetch taxon for sighting")?;
 
-/// Create a new sighting (looks up taxon data automatically)
+    Ok(taxonomy)
+}
+
+/// Create a new sighting (looks up taxon data automatically; a location name is matched or created as for trips).
+/// A `media_path` is attached to it and must name an existing file.
+#[allow(clippy::too_many_arguments)]
 pub fn create_sighting(
     conn: &Connection,
     trip_id: Option<i64>,
@@ -11,47 +59,28 @@ pub fn create_sighting(
     media_path: Option<&str>,
     date: Option<&str>,
     location: Option<&str>,
+    count: Option<Count>,
+    age_sex: &[AgeSexCount],
+    breeding_code: Option<BreedingCode>,
+    coordinates: Option<Coordinates>,
 ) -> Result<i64> {
-    // Look up the taxon to get taxonomic fields
-    let taxon_sql = r#"
-        SELECT kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
-        FROM taxa
-        WHERE id = ?1
-    "#;
+    check_age_sex(count, age_sex)?;
+    let date = parse_date(date)?;
+    let (latitude, longitude, accuracy_m) = coordinate_values(coordinates);
+    let media = media_path.map(MediaFile::read).transpose()?;
 
-    let (kingdom, phylum, class, order, family, subfamily, genus, species_epithet, common_name): (
-        String,
-        Option<String>,
-        Option<String>,
-        Option<String>,
-        Option<String>,
-        Option<String>,
-        Option<String>,
-        Option<String>,
-        String,
-    ) = conn
-        .query_row(taxon_sql, params![taxon_id], |row| {
-            Ok((
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-                row.get(3)?,
-                row.get(4)?,
-                row.get(5)?,
-                row.get(6)?,
-                row.get(7)?,
-                row.get(8)?,
-            ))
-        })
-        .context("Failed to fetch taxon for sighting")?;
+    // Look up the taxon to get taxonomic fields
+    let taxonomy = fetch_denormalized_taxonomy(conn, taxon_id)?;
+    let (location_id, location) = location.map(|name| resolve_location(conn, name)).transpose()?.flatten().unzip();
 
     // Insert sighting with duplicated taxonomic fields
     let sql = r#"
         INSERT INTO sightings (
             trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily,
-            genus, species_epithet, common_name, notes, media_path, date, location
+            genus, species_epithet, common_name, notes, date, location,
+            count, age_sex, breeding_code, latitude, longitude, accuracy_m, location_id
         )
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)
     "#;
 
     conn.execute(
@@ -59,207 +88,460 @@ pub fn create_sighting(
         params![
             trip_id,
             taxon_id,
-            kingdom,
-            phylum,
-            class,
-            order,
-            family,
-            subfamily,
-            genus,
-            species_epithet,
-            common_name,
+            taxonomy.kingdom,
+            taxonomy.phylum,
+            taxonomy.class,
+            taxonomy.order,
+            taxonomy.family,
+            taxonomy.subfamily,
+            taxonomy.genus,
+            taxonomy.species_epithet,
+            taxonomy.common_name,
             notes,
-            media_path,
             date,
-            location
+            location,
+            count,
+            optional_text(&format_age_sex(age_sex)),
+            breeding_code,
+            latitude,
+            longitude,
+            accuracy_m,
+            location_id
         ],
     )
     .context("Failed to insert sighting")?;
 
     let id = conn.last_insert_rowid();
+    if let Some(media) = media {
+        attach_media(conn, MediaOwner::Sighting(id), &media, None, None)?;
+    }
     Ok(id)
 }
 
+/// A sex/age breakdown can't add up to more birds than were counted
+fn check_age_sex(count: Option<Count>, age_sex: &[AgeSexCount]) -> Result<()> {
+    let total: u32 = age_sex.iter().map(|cell| cell.count).sum();
+    if let Some(Count::Exact(n)) = count {
+        if total > n {
+            bail!("sex/age breakdown adds up to {} but the count is {}", total, n);
+        }
+    }
+    Ok(())
+}
+
+/// Map a row selected with the sighting columns, in table order, to a `Sighting`
+pub(crate) fn sighting_from_row(row: &Row) -> rusqlite::Result<Sighting> {
+    let age_sex: Option<String> = row.get(16)?;
+    let age_sex = match age_sex {
+        Some(text) => parse_age_sex(&text)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(16, Type::Text, e.into()))?,
+        None => Vec::new(),
+    };
+
+    Ok(Sighting {
+        id: row.get(0)?,
+        trip_id: row.get(1)?,
+        taxon_id: row.get(2)?,
+        kingdom: row.get(3)?,
+        phylum: row.get(4)?,
+        class: row.get(5)?,
+        order: row.get(6)?,
+        family: row.get(7)?,
+        subfamily: row.get(8)?,
+        genus: row.get(9)?,
+        species_epithet: row.get(10)?,
+        common_name: row.get(11)?,
+        notes: row.get(12)?,
+        date: row.get(13)?,
+        location: row.get(14)?,
+        count: row.get(15)?,
+        age_sex,
+        breeding_code: row.get(17)?,
+        coordinates: coordinates_at(row, 18)?,
+        location_id: row.get(21)?,
+    })
+}
+
+impl ToSql for Count {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Count::Exact(n) => ToSqlOutput::from(*n),
+            Count::Present => ToSqlOutput::from("X"),
+        })
+    }
+}
+
+impl FromSql for Count {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Integer(n) => u32::try_from(n).map(Count::Exact).map_err(|_| FromSqlError::OutOfRange(n)),
+            ValueRef::Text(b"X") => Ok(Count::Present),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl ToSql for BreedingCode {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.code()))
+    }
+}
+
+impl FromSql for BreedingCode {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e: anyhow::Error| FromSqlError::Other(e.into()))
+    }
+}
+
 /// Get a sighting by ID
 pub fn get_sighting_by_id(conn: &Connection, id: i64) -> Result<Sighting> {
     let sql = r#"
         SELECT id, trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily,
-               genus, species_epithet, common_name, notes, media_path, date, location
+               genus, species_epithet, common_name, notes, date, location,
+               count, age_sex, breeding_code, latitude, longitude, accuracy_m, location_id
         FROM sightings
         WHERE id = ?1
     "#;
 
-    let sighting = conn.query_row(sql, params![id], |row| {
-        Ok(Sighting {
-            id: row.get(0)?,
-            trip_id: row.get(1)?,
-            taxon_id: row.get(2)?,
-            kingdom: row.get(3)?,
-            phylum: row.get(4)?,
-            class: row.get(5)?,
-            order: row.get(6)?,
-            family: row.get(7)?,
-            subfamily: row.get(8)?,
-            genus: row.get(9)?,
-            species_epithet: row.get(10)?,
-            common_name: row.get(11)?,
-            notes: row.get(12)?,
-            media_path: row.get(13)?,
-            date: row.get(14)?,
-            location: row.get(15)?,
-        })
-    }).context("Failed to fetch sighting")?;
+    let sighting = conn.query_row(sql, params![id], sighting_from_row)
+        .context("Failed to fetch sighting")?;
 
     Ok(sighting)
 }
 
-/// Delete a sighting by ID
-pub fn delete_sighting(conn: &Connection, id: i64) -> Result<usize> {
-    let sql = "DELETE FROM sightings WHERE id = ?1";
-    let rows_affected = conn.execute(sql, params![id])
-        .context("Failed to delete sighting")?;
-    Ok(rows_affected)
-}
+/// Update a sighting (only the fields set in `changes` are written)
+pub fn update_sighting(conn: &Connection, id: i64, changes: &SightingUpdate) -> Result<usize> {
+    let mut assignments: Vec<&str> = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
 
-/// Get all sightings of a specific taxon (matches based on taxonomic hierarchy)
-pub fn get_sightings_by_taxon(conn: &Connection, taxon: &crate::models::Taxon) -> Result<Vec<Sighting>> {
+    if let Some(trip_id) = changes.trip_id {
+        assignments.push("trip_id = ?");
+        values.push(Value::from(trip_id));
+    }
 
-    // Build WHERE clause based on taxon rank
-    let mut conditions = vec!["kingdom = ?1".to_string()];
-    let mut params: Vec<&str> = vec![&taxon.kingdom];
+    // A new taxon brings its taxonomy along with it
+    if let Some(taxon_id) = changes.taxon_id {
+        let taxonomy = fetch_denormalized_taxonomy(conn, taxon_id)?;
+        assignments.extend([
+            "taxon_id = ?",
+            "kingdom = ?",
+            "phylum = ?",
+            "class = ?",
+            "\"order\" = ?",
+            "family = ?",
+            "subfamily = ?",
+            "genus = ?",
+            "species_epithet = ?",
+            "common_name = ?",
+        ]);
+        values.extend([
+            Value::from(taxon_id),
+            Value::from(taxonomy.kingdom),
+            Value::from(taxonomy.phylum),
+            Value::from(taxonomy.class),
+            Value::from(taxonomy.order),
+            Value::from(taxonomy.family),
+            Value::from(taxonomy.subfamily),
+            Value::from(taxonomy.genus),
+            Value::from(taxonomy.species_epithet),
+            Value::from(taxonomy.common_name),
+        ]);
+    }
 
-    if taxon.rank != "kingdom" {
-        if let Some(ref p) = taxon.phylum {
-            conditions.push("phylum = ?".to_string());
-            params.push(p);
-        }
+    if let Some(ref notes) = changes.notes {
+        assignments.push("notes = ?");
+        values.push(optional_text(notes));
     }
 
-    if taxon.rank != "kingdom" && taxon.rank != "phylum" {
-        if let Some(ref c) = taxon.class {
-            conditions.push("class = ?".to_string());
-            params.push(c);
-        }
+    if let Some(ref date) = changes.date {
+        assignments.push("date = ?");
+        values.push(optional_date(date)?);
     }
 
-    if taxon.rank == "order" || taxon.rank == "family" || taxon.rank == "subfamily" || taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref o) = taxon.order {
-            conditions.push("\"order\" = ?".to_string());
-            params.push(o);
-        }
+    location_assignments(conn, changes.location.as_deref(), changes.location_id, &mut assignments, &mut values)?;
+
+    if let Some(count) = changes.count {
+        assignments.push("count = ?");
+        values.push(match count {
+            Some(Count::Exact(n)) => Value::from(n),
+            Some(Count::Present) => Value::from("X".to_string()),
+            None => Value::Null,
+        });
     }
 
-    if taxon.rank == "family" || taxon.rank == "subfamily" || taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref f) = taxon.family {
-            conditions.push("family = ?".to_string());
-            params.push(f);
-        }
+    if let Some(ref age_sex) = changes.age_sex {
+        assignments.push("age_sex = ?");
+        values.push(optional_text(&format_age_sex(age_sex)));
     }
 
-    if taxon.rank == "subfamily" || taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref sf) = taxon.subfamily {
-            conditions.push("subfamily = ?".to_string());
-            params.push(sf);
-        }
+    if let Some(breeding_code) = changes.breeding_code {
+        assignments.push("breeding_code = ?");
+        values.push(Value::from(breeding_code.map(|code| code.code().to_string())));
     }
 
-    if taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref g) = taxon.genus {
-            conditions.push("genus = ?".to_string());
-            params.push(g);
-        }
+    if let Some(coordinates) = changes.coordinates {
+        let (latitude, longitude, accuracy_m) = coordinate_values(coordinates);
+        assignments.extend(["latitude = ?", "longitude = ?", "accuracy_m = ?"]);
+        values.extend([Value::from(latitude), Value::from(longitude), Value::from(accuracy_m)]);
     }
 
-    if taxon.rank == "species" {
-        if let Some(ref s) = taxon.species_epithet {
-            conditions.push("species_epithet = ?".to_string());
-            params.push(s);
-        }
+    if assignments.is_empty() {
+        bail!("no sighting fields to update");
     }
 
-    let where_clause = conditions.join(" AND ");
-    let sql = format!(
+    // Check the breakdown against the count the sighting will end up with
+    if changes.count.is_some() || changes.age_sex.is_some() {
+        let current = get_sighting_by_id(conn, id)?;
+        let count = changes.count.unwrap_or(current.count);
+        check_age_sex(count, changes.age_sex.as_deref().unwrap_or(&current.age_sex))?;
+    }
+
+    let sql = format!("UPDATE sightings SET {} WHERE id = ?", assignments.join(", "));
+    values.push(Value::from(id));
+
+    let rows_affected = conn.execute(&sql, rusqlite::params_from_iter(values))
+        .context("Failed to update sighting")?;
+    Ok(rows_affected)
+}
+
+/// Delete a sighting by ID
+pub fn delete_sighting(conn: &Connection, id: i64) -> Result<usize> {
+    let sql = "DELETE FROM sightings WHERE id = ?1";
+    let rows_affected = conn.execute(sql, params![id])
+        .context("Failed to delete sighting")?;
+    Ok(rows_affected)
+}
+
+/// Sightings of the taxon bound to ?1 and of every taxon below it, newest first
+fn sightings_by_taxon_sql() -> String {
+    format!(
         r#"
+        {}
         SELECT id, trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily,
-               genus, species_epithet, common_name, notes, media_path, date, location
+               genus, species_epithet, common_name, notes, date, location,
+               count, age_sex, breeding_code, latitude, longitude, accuracy_m, location_id
         FROM sightings
-        WHERE {}
+        WHERE taxon_id IN (SELECT member_id FROM subtree)
         ORDER BY date DESC, id DESC
         "#,
-        where_clause
-    );
+        SUBTREE_CTE
+    )
+}
+
+/// Sightings from the trip bound to ?1, in the order they were added
+const SIGHTINGS_BY_TRIP_SQL: &str = r#"
+    SELECT id, trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily,
+           genus, species_epithet, common_name, notes, date, location,
+           count, age_sex, breeding_code, latitude, longitude, accuracy_m, location_id
+    FROM sightings
+    WHERE trip_id = ?1
+    ORDER BY id ASC
+"#;
+
+/// Sightings at the location bound to ?1, newest first
+const SIGHTINGS_BY_LOCATION_SQL: &str = r#"
+    SELECT id, trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily,
+           genus, species_epithet, common_name, notes, date, location,
+           count, age_sex, breeding_code, latitude, longitude, accuracy_m, location_id
+    FROM sightings
+    WHERE location_id = ?1
+    ORDER BY date DESC, id DESC
+"#;
+
+/// Get all sightings of a taxon and of every taxon below it in the tree
+pub fn get_sightings_by_taxon(conn: &Connection, taxon: &crate::models::Taxon) -> Result<Vec<Sighting>> {
+    let sql = sightings_by_taxon_sql();
 
     let mut stmt = conn.prepare(&sql)
         .context("Failed to prepare get sightings by taxon query")?;
 
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(Sighting {
-            id: row.get(0)?,
-            trip_id: row.get(1)?,
-            taxon_id: row.get(2)?,
-            kingdom: row.get(3)?,
-            phylum: row.get(4)?,
-            class: row.get(5)?,
-            order: row.get(6)?,
-            family: row.get(7)?,
-            subfamily: row.get(8)?,
-            genus: row.get(9)?,
-            species_epithet: row.get(10)?,
-            common_name: row.get(11)?,
-            notes: row.get(12)?,
-            media_path: row.get(13)?,
-            date: row.get(14)?,
-            location: row.get(15)?,
-        })
-    }).context("Failed to execute get sightings by taxon query")?;
+    let rows = stmt.query_map(params![taxon.id], sighting_from_row)
+        .context("Failed to execute get sightings by taxon query")?;
 
     let results: Vec<Sighting> = rows.collect::<Result<Vec<_>, _>>()
         .context("Failed to parse sighting rows")?;
     Ok(results)
 }
 
+/// One page of `get_sightings_by_taxon`, with the total number of sightings
+pub fn get_sightings_by_taxon_page(
+    conn: &Connection,
+    taxon: &crate::models::Taxon,
+    limit: usize,
+    offset: usize,
+) -> Result<Paged<Sighting>> {
+    query_page(conn, &sightings_by_taxon_sql(), &[&taxon.id], limit, offset, sighting_from_row)
+        .context("Failed to get a page of sightings by taxon")
+}
+
 /// Get all sightings from a specific trip
 pub fn get_sightings_by_trip_id(conn: &Connection, trip_id: i64) -> Result<Vec<Sighting>> {
+    let mut stmt = conn.prepare(SIGHTINGS_BY_TRIP_SQL)
+        .context("Failed to prepare get sightings by trip query")?;
+
+    let rows = stmt.query_map(params![trip_id], sighting_from_row)
+        .context("Failed to execute get sightings by trip query")?;
+
+    let results: Vec<Sighting> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse sighting rows")?;
+    Ok(results)
+}
+
+/// One page of `get_sightings_by_trip_id`, with the total number of sightings
+pub fn get_sightings_by_trip_id_page(conn: &Connection, trip_id: i64, limit: usize, offset: usize) -> Result<Paged<Sighting>> {
+    query_page(conn, SIGHTINGS_BY_TRIP_SQL, &[&trip_id], limit, offset, sighting_from_row)
+        .context("Failed to get a page of sightings by trip")
+}
+
+/// Get all sightings at a location, newest first
+pub fn get_sightings_by_location_id(conn: &Connection, location_id: i64) -> Result<Vec<Sighting>> {
+    let mut stmt = conn.prepare(SIGHTINGS_BY_LOCATION_SQL)
+        .context("Failed to prepare get sightings by location query")?;
+
+    let rows = stmt.query_map(params![location_id], sighting_from_row)
+        .context("Failed to execute get sightings by location query")?;
+
+    let results: Vec<Sighting> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse sighting rows")?;
+    Ok(results)
+}
+
+/// One page of `get_sightings_by_location_id`, with the total number of sightings
+pub fn get_sightings_by_location_id_page(
+    conn: &Connection,
+    location_id: i64,
+    limit: usize,
+    offset: usize,
+) -> Result<Paged<Sighting>> {
+    query_page(conn, SIGHTINGS_BY_LOCATION_SQL, &[&location_id], limit, offset, sighting_from_row)
+        .context("Failed to get a page of sightings by location")
+}
+
+/// Get every sighting, oldest first
+pub fn get_all_sightings(conn: &Connection) -> Result<Vec<Sighting>> {
     let sql = r#"
         SELECT id, trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily,
-               genus, species_epithet, common_name, notes, media_path, date, location
+               genus, species_epithet, common_name, notes, date, location,
+               count, age_sex, breeding_code, latitude, longitude, accuracy_m, location_id
         FROM sightings
-        WHERE trip_id = ?1
         ORDER BY id ASC
     "#;
 
     let mut stmt = conn.prepare(sql)
-        .context("Failed to prepare get sightings by trip query")?;
+        .context("Failed to prepare get all sightings query")?;
 
-    let rows = stmt.query_map(params![trip_id], |row| {
-        Ok(Sighting {
-            id: row.get(0)?,
-            trip_id: row.get(1)?,
-            taxon_id: row.get(2)?,
-            kingdom: row.get(3)?,
-            phylum: row.get(4)?,
-            class: row.get(5)?,
-            order: row.get(6)?,
-            family: row.get(7)?,
-            subfamily: row.get(8)?,
-            genus: row.get(9)?,
-            species_epithet: row.get(10)?,
-            common_name: row.get(11)?,
-            notes: row.get(12)?,
-            media_path: row.get(13)?,
-            date: row.get(14)?,
-            location: row.get(15)?,
-        })
-    }).context("Failed to execute get sightings by trip query")?;
+    let rows = stmt.query_map([], sighting_from_row)
+        .context("Failed to execute get all sightings query")?;
+
+    let results: Vec<Sighting> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse sighting rows")?;
+    Ok(results)
+}
+
+/// Get all dated sightings in a date range (an open range returns every dated sighting)
+pub fn get_sightings_by_date_range(conn: &Connection, range: &DateRange) -> Result<Vec<Sighting>> {
+    let mut conditions = vec!["date IS NOT NULL".to_string()];
+    let mut params: Vec<Value> = Vec::new();
+
+    if let Some(ref from) = range.from {
+        conditions.push("date >= ?".to_string());
+        params.push(Value::from(from.clone()));
+    }
+    if let Some(ref to) = range.to {
+        conditions.push("substr(date, 1, ?) <= ?".to_string());
+        params.push(Value::from(to.len() as i64));
+        params.push(Value::from(to.clone()));
+    }
+
+    let sql = format!(
+        r#"
+        SELECT id, trip_id, taxon_id, kingdom, phylum, class, "order", family, subfamily,
+               genus, species_epithet, common_name, notes, date, location,
+               count, age_sex, breeding_code, latitude, longitude, accuracy_m, location_id
+        FROM sightings
+        WHERE {}
+        ORDER BY date ASC, id ASC
+        "#,
+        conditions.join(" AND ")
+    );
+
+    let mut stmt = conn.prepare(&sql)
+        .context("Failed to prepare get sightings by date range query")?;
+
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), sighting_from_row)
+        .context("Failed to execute get sightings by date range query")?;
+
+    let results: Vec<Sighting> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse sighting rows")?;
+    Ok(results)
+}
+
+/// Find sightings whose copied taxonomy no longer matches their taxon
+pub fn find_taxonomy_drift(conn: &Connection) -> Result<Vec<Sighting>> {
+    let sql = r#"
+        SELECT s.id, s.trip_id, s.taxon_id, s.kingdom, s.phylum, s.class, s."order", s.family, s.subfamily,
+               s.genus, s.species_epithet, s.common_name, s.notes, s.date, s.location,
+               s.count, s.age_sex, s.breeding_code, s.latitude, s.longitude, s.accuracy_m, s.location_id
+        FROM sightings s
+        INNER JOIN taxa t ON t.id = s.taxon_id
+        WHERE s.kingdom IS NOT t.kingdom
+           OR s.phylum IS NOT t.phylum
+           OR s.class IS NOT t.class
+           OR s."order" IS NOT t."order"
+           OR s.family IS NOT t.family
+           OR s.subfamily IS NOT t.subfamily
+           OR s.genus IS NOT t.genus
+           OR s.species_epithet IS NOT t.species_epithet
+           OR s.common_name IS NOT t.common_name
+        ORDER BY s.id ASC
+    "#;
+
+    let mut stmt = conn.prepare(sql)
+        .context("Failed to prepare taxonomy drift query")?;
+
+    let rows = stmt.query_map([], sighting_from_row)
+        .context("Failed to execute taxonomy drift query")?;
 
     let results: Vec<Sighting> = rows.collect::<Result<Vec<_>, _>>()
         .context("Failed to parse sighting rows")?;
     Ok(results)
 }
 
+/// Re-copy taxonomy from `taxa` into every drifted sighting.
+/// Returns the number of sightings repaired.
+pub fn resync_denormalized_taxonomy(conn: &Connection) -> Result<usize> {
+    let sql = r#"
+        UPDATE sightings
+        SET (kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name) = (
+            SELECT kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
+            FROM taxa
+            WHERE taxa.id = sightings.taxon_id
+        )
+        WHERE EXISTS (
+            SELECT 1 FROM taxa t
+            WHERE t.id = sightings.taxon_id
+              AND (sightings.kingdom IS NOT t.kingdom
+                OR sightings.phylum IS NOT t.phylum
+                OR sightings.class IS NOT t.class
+                OR sightings."order" IS NOT t."order"
+                OR sightings.family IS NOT t.family
+                OR sightings.subfamily IS NOT t.subfamily
+                OR sightings.genus IS NOT t.genus
+                OR sightings.species_epithet IS NOT t.species_epithet
+                OR sightings.common_name IS NOT t.common_name)
+        )
+    "#;
+
+    let rows_affected = conn.execute(sql, [])
+        .context("Failed to resync sighting taxonomy")?;
+    Ok(rows_affected)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Effort;
+    use crate::models::{DatePrecision, Rank};
     use crate::core::taxon::{create_taxon, get_taxon_by_id};
     use crate::core::trip::create_trip;
 
@@ -267,8 +549,7 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         conn.pragma_update(None, "foreign_keys", "ON").unwrap();
 
-        let schema = std::fs::read_to_string("init.sql").unwrap();
-        conn.execute_batch(&schema).unwrap();
+        crate::core::migrations::migrate(&conn).unwrap();
 
         // Load test taxa (first 100 species + parent taxa)
         let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
@@ -284,7 +565,7 @@ mod tests {
         // Create taxon and trip first
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -293,10 +574,11 @@ mod tests {
             None,
             Some("Turdus"),
             Some("migratorius"),
+            None,
             "American Robin",
         ).unwrap();
 
-        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Park"), None).unwrap();
+        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Park"), None, None, &Effort::default()).unwrap();
 
         // Create sighting
         let sighting_id = create_sighting(
@@ -307,6 +589,10 @@ mod tests {
             None,
             Some("2025-01-15"),
             Some("Near pond"),
+            None,
+            &[],
+            None,
+            None,
         ).unwrap();
 
         assert!(sighting_id > 0);
@@ -325,7 +611,7 @@ mod tests {
 
         let taxon_id = create_taxon(
             &conn,
-            "family",
+            Rank::Family,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -334,6 +620,7 @@ mod tests {
             None,
             None,
             None,
+            None,
             "Crow Family",
         ).unwrap();
 
@@ -345,6 +632,10 @@ mod tests {
             None,
             None,
             None,
+            None,
+            &[],
+            None,
+            None,
         ).unwrap();
 
         let sighting = get_sighting_by_id(&conn, sighting_id).unwrap();
@@ -361,7 +652,7 @@ mod tests {
 
         let taxon_id = create_taxon(
             &conn,
-            "genus",
+            Rank::Genus,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -370,6 +661,7 @@ mod tests {
             None,
             Some("Buteo"),
             None,
+            None,
             "Buteo Hawks",
         ).unwrap();
 
@@ -381,6 +673,10 @@ mod tests {
             None,
             None,
             None,
+            None,
+            &[],
+            None,
+            None,
         ).unwrap();
 
         let sighting = get_sighting_by_id(&conn, sighting_id).unwrap();
@@ -400,6 +696,10 @@ mod tests {
             None,
             None,
             None,
+            None,
+            &[],
+            None,
+            None,
         );
 
         assert!(result.is_err());
@@ -411,7 +711,7 @@ mod tests {
 
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -420,10 +720,11 @@ mod tests {
             None,
             Some("Test"),
             Some("temp"),
+            None,
             "Test Bird",
         ).unwrap();
 
-        let sighting_id = create_sighting(&conn, None, taxon_id, None, None, None, None).unwrap();
+        let sighting_id = create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
         let rows = delete_sighting(&conn, sighting_id).unwrap();
         assert_eq!(rows, 1);
 
@@ -431,13 +732,179 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_update_sighting_partial() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Park"), None, None, &Effort::default()).unwrap();
+        let sighting_id = create_sighting(&conn, Some(trip_id), taxon_id, Some("Singing"), None, Some("2025-01-15"), Some("Near pnd"), None, &[], None, None).unwrap();
+
+        let changes = SightingUpdate {
+            location: Some("Near pond".to_string()),
+            notes: Some(String::new()),
+            ..Default::default()
+        };
+        let rows = update_sighting(&conn, sighting_id, &changes).unwrap();
+        assert_eq!(rows, 1);
+
+        let sighting = get_sighting_by_id(&conn, sighting_id).unwrap();
+        assert_eq!(sighting.location, Some("Near pond".to_string()));
+        assert_eq!(sighting.notes, None);
+        assert_eq!(sighting.date, Some("2025-01-15".parse().unwrap()));
+        assert_eq!(sighting.trip_id, Some(trip_id));
+    }
+
+    #[test]
+    fn test_update_sighting_taxon_recopies_taxonomy() {
+        let conn = setup_test_db();
+
+        let robin_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        let hawk_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Accipitriformes"), Some("Accipitridae"), None, Some("Buteo"), Some("jamaicensis"), None, "Red-tailed Hawk").unwrap();
+        let trip_id = create_trip(&conn, "Morning Walk", None, None, None, None, &Effort::default()).unwrap();
+        let sighting_id = create_sighting(&conn, Some(trip_id), robin_id, None, None, None, None, None, &[], None, None).unwrap();
+
+        let changes = SightingUpdate {
+            taxon_id: Some(hawk_id),
+            trip_id: Some(None),
+            ..Default::default()
+        };
+        update_sighting(&conn, sighting_id, &changes).unwrap();
+
+        let sighting = get_sighting_by_id(&conn, sighting_id).unwrap();
+        assert_eq!(sighting.taxon_id, hawk_id);
+        assert_eq!(sighting.order, Some("Accipitriformes".to_string()));
+        assert_eq!(sighting.genus, Some("Buteo".to_string()));
+        assert_eq!(sighting.common_name, "Red-tailed Hawk");
+        assert_eq!(sighting.trip_id, None);
+    }
+
+    #[test]
+    fn test_update_sighting_invalid_changes() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        let sighting_id = create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+
+        // Nothing to change
+        assert!(update_sighting(&conn, sighting_id, &SightingUpdate::default()).is_err());
+
+        // Unknown taxon
+        let changes = SightingUpdate {
+            taxon_id: Some(99999),
+            ..Default::default()
+        };
+        assert!(update_sighting(&conn, sighting_id, &changes).is_err());
+
+        // Unknown trip (foreign key)
+        let changes = SightingUpdate {
+            trip_id: Some(Some(99999)),
+            ..Default::default()
+        };
+        assert!(update_sighting(&conn, sighting_id, &changes).is_err());
+    }
+
+    #[test]
+    fn test_sighting_counts_age_sex_and_breeding() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        let age_sex = parse_age_sex("2 male adult, 1 juvenile").unwrap();
+        let id = create_sighting(&conn, None, taxon_id, None, None, None, None, Some(Count::Exact(4)), &age_sex, Some(BreedingCode::FeedingYoung), None).unwrap();
+
+        let sighting = get_sighting_by_id(&conn, id).unwrap();
+        assert_eq!(sighting.count, Some(Count::Exact(4)));
+        assert_eq!(sighting.age_sex, age_sex);
+        assert_eq!(sighting.breeding_code, Some(BreedingCode::FeedingYoung));
+
+        // A breakdown can't exceed the count, on create or update
+        let too_many = parse_age_sex("5 female").unwrap();
+        assert!(create_sighting(&conn, None, taxon_id, None, None, None, None, Some(Count::Exact(4)), &too_many, None, None).is_err());
+        let changes = SightingUpdate { count: Some(Some(Count::Exact(2))), ..Default::default() };
+        assert!(update_sighting(&conn, id, &changes).is_err());
+
+        // "X" puts no limit on the breakdown
+        let changes = SightingUpdate {
+            count: Some(Some(Count::Present)),
+            age_sex: Some(too_many.clone()),
+            breeding_code: Some(None),
+            ..Default::default()
+        };
+        update_sighting(&conn, id, &changes).unwrap();
+        let sighting = get_sighting_by_id(&conn, id).unwrap();
+        assert_eq!(sighting.count, Some(Count::Present));
+        assert_eq!(sighting.age_sex, too_many);
+        assert_eq!(sighting.breeding_code, None);
+
+        // Clearing
+        let changes = SightingUpdate { count: Some(None), age_sex: Some(Vec::new()), ..Default::default() };
+        update_sighting(&conn, id, &changes).unwrap();
+        let sighting = get_sighting_by_id(&conn, id).unwrap();
+        assert_eq!((sighting.count, sighting.age_sex.len()), (None, 0));
+    }
+
+    #[test]
+    fn test_taxon_update_propagates_to_sightings() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "Amercan Robin").unwrap();
+        let sighting_id = create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+
+        let changes = crate::models::TaxonUpdate {
+            common_name: Some("American Robin".to_string()),
+            family: Some("Muscicapidae".to_string()),
+            ..Default::default()
+        };
+        crate::core::taxon::update_taxon(&conn, taxon_id, &changes).unwrap();
+
+        // Trigger keeps the copy current
+        let sighting = get_sighting_by_id(&conn, sighting_id).unwrap();
+        assert_eq!(sighting.common_name, "American Robin");
+        assert_eq!(sighting.family, Some("Muscicapidae".to_string()));
+        assert!(find_taxonomy_drift(&conn).unwrap().is_empty());
+
+        // Hierarchy queries see the corrected family
+        let taxon = get_taxon_by_id(&conn, taxon_id).unwrap();
+        assert_eq!(get_sightings_by_taxon(&conn, &taxon).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_find_and_resync_taxonomy_drift() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        let stale_id = create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+
+        // Simulate drift written behind the trigger's back (e.g. an older database)
+        conn.execute(
+            "UPDATE sightings SET common_name = 'Robin', subfamily = 'Turdinae' WHERE id = ?1",
+            params![stale_id],
+        ).unwrap();
+
+        let drifted = find_taxonomy_drift(&conn).unwrap();
+        assert_eq!(drifted.len(), 1);
+        assert_eq!(drifted[0].id, stale_id);
+
+        let repaired = resync_denormalized_taxonomy(&conn).unwrap();
+        assert_eq!(repaired, 1);
+
+        let sighting = get_sighting_by_id(&conn, stale_id).unwrap();
+        assert_eq!(sighting.common_name, "American Robin");
+        assert_eq!(sighting.subfamily, None);
+        assert!(find_taxonomy_drift(&conn).unwrap().is_empty());
+
+        // Nothing left to repair
+        assert_eq!(resync_denormalized_taxonomy(&conn).unwrap(), 0);
+    }
+
     #[test]
     fn test_get_sightings_by_taxon() {
         let conn = setup_test_db();
 
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -446,22 +913,23 @@ mod tests {
             None,
             Some("Turdus"),
             Some("migratorius"),
+            None,
             "American Robin",
         ).unwrap();
 
         // Create 3 sightings of the same taxon
-        create_sighting(&conn, None, taxon_id, None, None, Some("2025-01-15"), None).unwrap();
-        create_sighting(&conn, None, taxon_id, None, None, Some("2025-01-20"), None).unwrap();
-        create_sighting(&conn, None, taxon_id, None, None, Some("2025-01-10"), None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, Some("2025-01-15"), None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, Some("2025-01-20"), None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, Some("2025-01-10"), None, None, &[], None, None).unwrap();
 
         let taxon = get_taxon_by_id(&conn, taxon_id).unwrap();
         let results = get_sightings_by_taxon(&conn, &taxon).unwrap();
         assert_eq!(results.len(), 3);
 
         // Should be ordered by date DESC
-        assert_eq!(results[0].date, Some("2025-01-20".to_string()));
-        assert_eq!(results[1].date, Some("2025-01-15".to_string()));
-        assert_eq!(results[2].date, Some("2025-01-10".to_string()));
+        assert_eq!(results[0].date, Some("2025-01-20".parse().unwrap()));
+        assert_eq!(results[1].date, Some("2025-01-15".parse().unwrap()));
+        assert_eq!(results[2].date, Some("2025-01-10".parse().unwrap()));
 
         // All should have same taxon
         for sighting in &results {
@@ -470,13 +938,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sighting_dates_are_validated_and_stored_in_order() {
+        let conn = setup_test_db();
+
+        let robin = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        for date in ["5/14/2025", "yesterday", "2025-02-29"] {
+            let error = create_sighting(&conn, None, robin, None, None, Some(date), None, None, &[], None, None).unwrap_err();
+            assert!(error.to_string().starts_with(&format!("invalid date '{}'", date)), "{}", error);
+        }
+
+        // Mixed precisions and spellings are stored in one form, so they sort by time
+        for date in ["2025-05-14 7:30 PM", "spring 2025", "2025", "2025-05-14", "2024-12"] {
+            create_sighting(&conn, None, robin, None, None, Some(date), None, None, &[], None, None).unwrap();
+        }
+        let taxon = get_taxon_by_id(&conn, robin).unwrap();
+        let dates: Vec<String> = get_sightings_by_taxon(&conn, &taxon)
+            .unwrap()
+            .iter()
+            .map(|sighting| sighting.date.unwrap().to_string())
+            .collect();
+        assert_eq!(dates, vec!["2025-05-14T19:30", "2025-05-14", "spring 2025", "2025", "2024-12"]);
+
+        let id = create_sighting(&conn, None, robin, None, None, None, None, None, &[], None, None).unwrap();
+        let changes = SightingUpdate { date: Some("2025-04-31".to_string()), ..Default::default() };
+        assert!(update_sighting(&conn, id, &changes).is_err());
+        let changes = SightingUpdate { date: Some("fall 1998".to_string()), ..Default::default() };
+        update_sighting(&conn, id, &changes).unwrap();
+        let date = get_sighting_by_id(&conn, id).unwrap().date.unwrap();
+        assert_eq!((date.to_iso(), date.precision()), ("1998-09/1998-11".to_string(), DatePrecision::Season));
+    }
+
+    #[test]
+    fn test_get_sightings_by_taxon_page() {
+        let conn = setup_test_db();
+
+        let robin = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        for date in ["2025-01-15", "2025-01-20", "2025-01-10"] {
+            create_sighting(&conn, None, robin, None, None, Some(date), None, None, &[], None, None).unwrap();
+        }
+
+        // Paging the genus runs the subtree query under the count as well
+        let genus = get_taxon_by_id(&conn, robin).unwrap().parent_id.unwrap();
+        let genus = get_taxon_by_id(&conn, genus).unwrap();
+        let dates = |page: &Paged<Sighting>| -> Vec<String> { page.items.iter().map(|s| s.date.unwrap().to_string()).collect() };
+
+        let first = get_sightings_by_taxon_page(&conn, &genus, 2, 0).unwrap();
+        assert_eq!(first.total, 3);
+        assert_eq!(dates(&first), vec!["2025-01-20", "2025-01-15"]);
+
+        let rest = get_sightings_by_taxon_page(&conn, &genus, 2, 2).unwrap();
+        assert_eq!(rest.total, 3);
+        assert_eq!(dates(&rest), vec!["2025-01-10"]);
+
+        assert!(get_sightings_by_taxon_page(&conn, &genus, 2, 4).unwrap().items.is_empty());
+    }
+
+    #[test]
+    fn test_get_sightings_by_taxon_places_subspecies_and_categories() {
+        let conn = setup_test_db();
+
+        let create = |rank: Rank, epithet: Option<&str>, subspecies: Option<&str>, name: &str| {
+            create_taxon(&conn, rank, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Parulidae"), None, Some("Setophaga"), epithet, subspecies, name).unwrap()
+        };
+        let genus = create(Rank::Genus, None, None, "Setophaga");
+        let species = create(Rank::Species, Some("coronata"), None, "Yellow-rumped Warbler");
+        let audubons = create(Rank::Subspecies, Some("coronata"), Some("auduboni"), "Yellow-rumped Warbler (Audubon's)");
+        let slash = create(Rank::Slash, Some("coronata/magnolia"), None, "Yellow-rumped/Magnolia Warbler");
+        let spuh = create(Rank::Spuh, None, None, "Setophaga sp.");
+
+        for taxon_id in [species, audubons, slash, spuh] {
+            create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        }
+
+        let taxon_ids = |id: i64| -> Vec<i64> {
+            let taxon = get_taxon_by_id(&conn, id).unwrap();
+            let mut ids: Vec<i64> = get_sightings_by_taxon(&conn, &taxon).unwrap().iter().map(|s| s.taxon_id).collect();
+            ids.sort();
+            ids
+        };
+
+        // The genus takes in everything identified within it; the species its subspecies
+        assert_eq!(taxon_ids(genus), vec![species, audubons, slash, spuh]);
+        assert_eq!(taxon_ids(species), vec![species, audubons]);
+        assert_eq!(taxon_ids(audubons), vec![audubons]);
+        assert_eq!(taxon_ids(slash), vec![slash]);
+        assert_eq!(taxon_ids(spuh), vec![spuh]);
+    }
+
     #[test]
     fn test_get_sightings_by_taxon_empty() {
         let conn = setup_test_db();
 
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -485,6 +1041,7 @@ mod tests {
             None,
             Some("Turdus"),
             Some("migratorius"),
+            None,
             "American Robin",
         ).unwrap();
 
@@ -498,14 +1055,14 @@ mod tests {
     fn test_get_sightings_by_trip_id() {
         let conn = setup_test_db();
 
-        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Park"), None).unwrap();
+        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Park"), None, None, &Effort::default()).unwrap();
 
-        let taxon1 = create_taxon(&conn, "species", "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), "American Robin").unwrap();
-        let taxon2 = create_taxon(&conn, "species", "Animalia", Some("Chordata"), Some("Aves"), Some("Accipitriformes"), Some("Accipitridae"), None, Some("Buteo"), Some("jamaicensis"), "Red-tailed Hawk").unwrap();
+        let taxon1 = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        let taxon2 = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Accipitriformes"), Some("Accipitridae"), None, Some("Buteo"), Some("jamaicensis"), None, "Red-tailed Hawk").unwrap();
 
         // Create 2 sightings for the trip
-        create_sighting(&conn, Some(trip_id), taxon1, None, None, None, None).unwrap();
-        create_sighting(&conn, Some(trip_id), taxon2, None, None, None, None).unwrap();
+        create_sighting(&conn, Some(trip_id), taxon1, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, Some(trip_id), taxon2, None, None, None, None, None, &[], None, None).unwrap();
 
         let results = get_sightings_by_trip_id(&conn, trip_id).unwrap();
         assert_eq!(results.len(), 2);
@@ -516,11 +1073,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_sightings_by_trip_and_location_pages() {
+        let conn = setup_test_db();
+
+        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), None, None, None, &Effort::default()).unwrap();
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+
+        let ids: Vec<i64> = (0..4)
+            .map(|_| create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, Some("Riverside Park"), None, &[], None, None).unwrap())
+            .collect();
+        let location_id = get_sighting_by_id(&conn, ids[0]).unwrap().location_id.unwrap();
+
+        let page = get_sightings_by_trip_id_page(&conn, trip_id, 3, 1).unwrap();
+        assert_eq!(page.total, 4);
+        assert_eq!(page.items.iter().map(|s| s.id).collect::<Vec<_>>(), ids[1..4].to_vec());
+
+        let page = get_sightings_by_location_id_page(&conn, location_id, 1, 0).unwrap();
+        assert_eq!((page.items.len(), page.total), (1, 4));
+    }
+
+    #[test]
+    fn test_get_sightings_by_date_range() {
+        let conn = setup_test_db();
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+        for date in [Some("2024-12-31"), Some("2025-03-01"), Some("2025-06-30"), None] {
+            create_sighting(&conn, None, taxon_id, None, None, date, None, None, &[], None, None).unwrap();
+        }
+
+        let range = |from: Option<&str>, to: Option<&str>| DateRange { from: from.map(String::from), to: to.map(String::from) };
+        let dates = |range: &DateRange| -> Vec<Option<String>> {
+            get_sightings_by_date_range(&conn, range).unwrap().into_iter().map(|s| s.date.map(|date| date.to_string())).collect()
+        };
+
+        assert_eq!(dates(&range(Some("2025"), Some("2025-06"))), vec![Some("2025-03-01".to_string()), Some("2025-06-30".to_string())]);
+        assert_eq!(dates(&range(None, Some("2025-03-01"))).len(), 2);
+        // Undated sightings are never included
+        assert_eq!(dates(&range(None, None)).len(), 3);
+    }
+
     #[test]
     fn test_get_sightings_by_trip_id_empty() {
         let conn = setup_test_db();
 
-        let trip_id = create_trip(&conn, "Morning Walk", None, None, None).unwrap();
+        let trip_id = create_trip(&conn, "Morning Walk", None, None, None, None, &Effort::default()).unwrap();
 
         // No sightings for this trip
         let results = get_sightings_by_trip_id(&conn, trip_id).unwrap();
@@ -534,7 +1131,7 @@ mod tests {
         // Create a subfamily-level taxon
         let taxon_id = create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -543,6 +1140,7 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae Subfamily",
         ).unwrap();
 
@@ -554,6 +1152,10 @@ mod tests {
             None,
             None,
             None,
+            None,
+            &[],
+            None,
+            None,
         ).unwrap();
 
         let sighting = get_sighting_by_id(&conn, sighting_id).unwrap();
@@ -571,7 +1173,7 @@ mod tests {
         // Create subfamily-level taxon
         let subfamily_id = create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -580,13 +1182,14 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae",
         ).unwrap();
 
         // Create species within that subfamily
         let species_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -595,13 +1198,14 @@ mod tests {
             Some("Corvinae"),
             Some("Corvus"),
             Some("corax"),
+            None,
             "Common Raven",
         ).unwrap();
 
         // Create sightings for both
-        create_sighting(&conn, None, subfamily_id, None, None, Some("2025-01-10"), None).unwrap();
-        create_sighting(&conn, None, species_id, None, None, Some("2025-01-15"), None).unwrap();
-        create_sighting(&conn, None, species_id, None, None, Some("2025-01-20"), None).unwrap();
+        create_sighting(&conn, None, subfamily_id, None, None, Some("2025-01-10"), None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, species_id, None, None, Some("2025-01-15"), None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, species_id, None, None, Some("2025-01-20"), None, None, &[], None, None).unwrap();
 
         // Query by subfamily should return all 3 sightings
         let subfamily_taxon = get_taxon_by_id(&conn, subfamily_id).unwrap();
@@ -621,7 +1225,7 @@ mod tests {
         // Create family-level taxon
         let family_id = create_taxon(
             &conn,
-            "family",
+            Rank::Family,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -630,13 +1234,14 @@ mod tests {
             None,
             None,
             None,
+            None,
             "Corvidae Family",
         ).unwrap();
 
         // Create subfamily within family
         let subfamily_id = create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -645,13 +1250,14 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae",
         ).unwrap();
 
         // Create species with subfamily
         let species_with_subfamily_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -660,13 +1266,14 @@ mod tests {
             Some("Corvinae"),
             Some("Corvus"),
             Some("corax"),
+            None,
             "Common Raven",
         ).unwrap();
 
         // Create species without subfamily (different subfamily or none)
         let species_no_subfamily_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -675,14 +1282,15 @@ mod tests {
             None,
             Some("Cyanocitta"),
             Some("cristata"),
+            None,
             "Blue Jay",
         ).unwrap();
 
         // Create sightings for all
-        create_sighting(&conn, None, family_id, None, None, None, None).unwrap();
-        create_sighting(&conn, None, subfamily_id, None, None, None, None).unwrap();
-        create_sighting(&conn, None, species_with_subfamily_id, None, None, None, None).unwrap();
-        create_sighting(&conn, None, species_no_subfamily_id, None, None, None, None).unwrap();
+        create_sighting(&conn, None, family_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, subfamily_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, species_with_subfamily_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, None, species_no_subfamily_id, None, None, None, None, None, &[], None, None).unwrap();
 
         // Query by family should return ALL 4 sightings (family includes all subfamilies and species)
         let family_taxon = get_taxon_by_id(&conn, family_id).unwrap();
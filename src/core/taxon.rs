@@ -1,11 +1,26 @@
-use crate::models::Taxon;
-use anyhow::{Context, Result};
-use rusqlite::{Connection, params};
+use crate::core::db::optional_text;
+use crate::models::{Rank, Taxon, TaxonUpdate};
+use anyhow::{Context, Result, bail};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef};
+use rusqlite::{Connection, OptionalExtension, Params, Row, ToSql, params};
+
+impl ToSql for Rank {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for Rank {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e: anyhow::Error| FromSqlError::Other(e.into()))
+    }
+}
 
 /// Create a new taxon
+#[allow(clippy::too_many_arguments)]
 pub fn create_taxon(
     conn: &Connection,
-    rank: &str,
+    rank: Rank,
     kingdom: &str,
     phylum: Option<&str>,
     class: Option<&str>,
@@ -14,50 +29,396 @@ pub fn create_taxon(
     subfamily: Option<&str>,
     genus: Option<&str>,
     species_epithet: Option<&str>,
+    subspecies: Option<&str>,
     common_name: &str,
 ) -> Result<i64> {
     let sql = r#"
-        INSERT INTO taxa (rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        INSERT INTO taxa (rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, subspecies, common_name)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
     "#;
 
     conn.execute(
         sql,
-        params![rank, kingdom, phylum, class, order, family, subfamily, genus, species_epithet, common_name],
+        params![rank, kingdom, phylum, class, order, family, subfamily, genus, species_epithet, subspecies, common_name],
     )
     .context("Failed to insert taxon")?;
 
     let id = conn.last_insert_rowid();
+    create_missing_parent(conn, id)?;
     Ok(id)
 }
 
+/// The rank of a taxon's parent: the narrowest broader rank it names. A hybrid's or
+/// slash's species epithet names no single species, so they hang off the rank above.
+fn parent_rank(taxon: &Taxon) -> Option<Rank> {
+    Rank::ALL
+        .into_iter()
+        .rev()
+        .filter(|&rank| rank < taxon.rank && rank <= Rank::Species)
+        .filter(|&rank| rank < Rank::Species || taxon.rank <= Rank::Subspecies)
+        .find(|&rank| taxon.name_at(rank).is_some())
+}
+
+/// Create the taxon's parent (and, in turn, its ancestors) when none exists yet. The
+/// schema links a new taxon to its parent, and a new parent adopts its children.
+fn create_missing_parent(conn: &Connection, id: i64) -> Result<()> {
+    let taxon = get_taxon_by_id(conn, id)?;
+    if let Some(rank) = parent_rank(&taxon).filter(|_| taxon.parent_id.is_none()) {
+        find_or_create_ancestor(conn, &taxon, rank)?;
+    }
+    Ok(())
+}
+
+/// The taxon a taxon names at `rank` (see `Taxon::lineage`): the taxon itself, an
+/// ancestor in the tree, or a taxon with the same names down to that rank. One is
+/// created, along with its own missing ancestors, when none exists.
+pub fn find_or_create_ancestor(conn: &Connection, taxon: &Taxon, rank: Rank) -> Result<Taxon> {
+    if rank == taxon.rank {
+        return Ok(taxon.clone());
+    }
+    let Some((_, name)) = taxon.lineage().into_iter().find(|&(at, _)| at == rank && at < taxon.rank) else {
+        bail!("{} names no {} above it", taxon.scientific_name(), rank);
+    };
+
+    if let Some(ancestor) = get_ancestors(conn, taxon.id)?.into_iter().find(|ancestor| ancestor.rank == rank) {
+        return Ok(ancestor);
+    }
+
+    let names = |at: Rank| taxon.name_at(at).filter(|_| at <= rank);
+    let sql = format!(
+        r#"
+        SELECT {}
+        FROM taxa
+        WHERE rank = ?1 AND kingdom = ?2 AND phylum IS ?3 AND class IS ?4 AND "order" IS ?5
+          AND family IS ?6 AND subfamily IS ?7 AND genus IS ?8 AND species_epithet IS ?9
+        ORDER BY id
+        LIMIT 1
+        "#,
+        TAXON_COLUMNS
+    );
+    let found = conn
+        .query_row(
+            &sql,
+            params![
+                rank,
+                taxon.kingdom,
+                names(Rank::Phylum),
+                names(Rank::Class),
+                names(Rank::Order),
+                names(Rank::Family),
+                names(Rank::Subfamily),
+                names(Rank::Genus),
+                names(Rank::Species),
+            ],
+            taxon_from_row,
+        )
+        .optional()
+        .context("Failed to look up ancestor taxon")?;
+    if let Some(ancestor) = found {
+        return Ok(ancestor);
+    }
+
+    let id = create_taxon(
+        conn,
+        rank,
+        &taxon.kingdom,
+        names(Rank::Phylum),
+        names(Rank::Class),
+        names(Rank::Order),
+        names(Rank::Family),
+        names(Rank::Subfamily),
+        names(Rank::Genus),
+        names(Rank::Species),
+        None,
+        name,
+    )
+    .with_context(|| format!("Failed to create the {} above taxon {}", rank, taxon.id))?;
+    get_taxon_by_id(conn, id)
+}
+
+/// Columns read by `taxon_from_row`, in order
+pub(crate) const TAXON_COLUMNS: &str =
+    r#"id, rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name, subspecies, parent_id"#;
+
+/// Map a row selected with `TAXON_COLUMNS`
+pub(crate) fn taxon_from_row(row: &Row) -> rusqlite::Result<Taxon> {
+    Ok(Taxon {
+        id: row.get(0)?,
+        rank: row.get(1)?,
+        kingdom: row.get(2)?,
+        phylum: row.get(3)?,
+        class: row.get(4)?,
+        order: row.get(5)?,
+        family: row.get(6)?,
+        subfamily: row.get(7)?,
+        genus: row.get(8)?,
+        species_epithet: row.get(9)?,
+        common_name: row.get(10)?,
+        subspecies: row.get(11)?,
+        parent_id: row.get(12)?,
+    })
+}
+
 /// Get a taxon by ID
 pub fn get_taxon_by_id(conn: &Connection, id: i64) -> Result<Taxon> {
-    let sql = r#"
-        SELECT id, rank, kingdom, phylum, class, "order", family, subfamily, genus, species_epithet, common_name
+    let sql = format!("SELECT {} FROM taxa WHERE id = ?1", TAXON_COLUMNS);
+
+    let taxon = conn.query_row(&sql, params![id], taxon_from_row)
+        .context("Failed to fetch taxon")?;
+
+    Ok(taxon)
+}
+
+/// Find a taxon by scientific name, ignoring case: "Genus epithet" for a species or a
+/// slash ("Tringa melanoleuca/flavipes"), "Genus epithet subspecies", "Genus a x b" for
+/// a hybrid, "Name sp." for a spuh, or a single name ("Buteo", "Corvidae") for the taxon
+/// at that rank
+pub fn find_taxon_by_scientific_name(conn: &Connection, name: &str) -> Result<Option<Taxon>> {
+    let words: Vec<&str> = name.split_whitespace().collect();
+
+    let (condition, values): (String, Vec<String>) = match words.as_slice() {
+        [higher, sp] if sp.eq_ignore_ascii_case("sp.") => (
+            r#"rank = 'spuh' AND COALESCE(genus, subfamily, family, "order", class, phylum, kingdom) = ?1 COLLATE NOCASE"#
+                .to_string(),
+            vec![higher.to_string()],
+        ),
+        [genus, epithet] => (
+            "rank IN ('species', 'slash') AND genus = ?1 COLLATE NOCASE AND species_epithet = ?2 COLLATE NOCASE"
+                .to_string(),
+            vec![genus.to_string(), epithet.to_string()],
+        ),
+        [genus, rest @ ..] if rest.iter().any(|word| word.eq_ignore_ascii_case("x")) => (
+            "rank = 'hybrid' AND genus = ?1 COLLATE NOCASE AND species_epithet = ?2 COLLATE NOCASE".to_string(),
+            vec![genus.to_string(), rest.join(" ")],
+        ),
+        [genus, epithet, subspecies] => (
+            "rank = 'subspecies' AND genus = ?1 COLLATE NOCASE AND species_epithet = ?2 COLLATE NOCASE \
+             AND subspecies = ?3 COLLATE NOCASE"
+                .to_string(),
+            vec![genus.to_string(), epithet.to_string(), subspecies.to_string()],
+        ),
+        [single] => (
+            Rank::ALL
+                .iter()
+                .filter(|&&rank| rank < Rank::Species)
+                .filter_map(|rank| rank.column().map(|column| format!("(rank = '{}' AND {} = ?1 COLLATE NOCASE)", rank, column)))
+                .collect::<Vec<_>>()
+                .join(" OR "),
+            vec![single.to_string()],
+        ),
+        _ => return Ok(None),
+    };
+
+    let sql = format!(
+        r#"
+        SELECT {}
         FROM taxa
-        WHERE id = ?1
-    "#;
+        WHERE {}
+        ORDER BY id
+        LIMIT 1
+        "#,
+        TAXON_COLUMNS, condition
+    );
 
-    let taxon = conn.query_row(sql, params![id], |row| {
-        Ok(Taxon {
-            id: row.get(0)?,
-            rank: row.get(1)?,
-            kingdom: row.get(2)?,
-            phylum: row.get(3)?,
-            class: row.get(4)?,
-            order: row.get(5)?,
-            family: row.get(6)?,
-            subfamily: row.get(7)?,
-            genus: row.get(8)?,
-            species_epithet: row.get(9)?,
-            common_name: row.get(10)?,
-        })
-    }).context("Failed to fetch taxon")?;
+    let taxon = conn.query_row(&sql, rusqlite::params_from_iter(values), taxon_from_row)
+        .optional().context("Failed to look up taxon by scientific name")?;
 
     Ok(taxon)
 }
 
+/// Find a taxon by common name, or failing that by scientific name (see
+/// [`find_taxon_by_scientific_name`]). Case, hyphens, underscores and apostrophes are
+/// ignored, so "black-crowned_night-heron" and "Coopers Hawk" match.
+pub fn find_taxon_by_name(conn: &Connection, name: &str) -> Result<Option<Taxon>> {
+    let key = |expr: &str| {
+        format!(
+            "trim(replace(replace(replace(replace(replace(lower({}), '-', ' '), '_', ' '), '''', ''), '  ', ' '), '  ', ' '))",
+            expr
+        )
+    };
+    let sql = format!(
+        "SELECT {} FROM taxa WHERE {} = {} ORDER BY id LIMIT 1",
+        TAXON_COLUMNS,
+        key("common_name"),
+        key("?1")
+    );
+
+    let taxon = conn.query_row(&sql, params![name.trim()], taxon_from_row)
+        .optional().context("Failed to look up taxon by common name")?;
+
+    match taxon {
+        Some(taxon) => Ok(Some(taxon)),
+        None => find_taxon_by_scientific_name(conn, &name.replace(['-', '_'], " ")),
+    }
+}
+
+/// Update a taxon (only the fields set in `changes` are written)
+pub fn update_taxon(conn: &Connection, id: i64, changes: &TaxonUpdate) -> Result<usize> {
+    let mut assignments: Vec<&str> = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(rank) = changes.rank {
+        assignments.push("rank = ?");
+        values.push(Value::from(rank.as_str().to_string()));
+    }
+
+    // Required columns cannot be cleared
+    for (assignment, label, value) in [
+        ("kingdom = ?", "kingdom", &changes.kingdom),
+        ("common_name = ?", "common name", &changes.common_name),
+    ] {
+        if let Some(v) = value {
+            if v.trim().is_empty() {
+                bail!("taxon {} cannot be empty", label);
+            }
+            assignments.push(assignment);
+            values.push(Value::from(v.clone()));
+        }
+    }
+
+    for (assignment, value) in [
+        ("phylum = ?", &changes.phylum),
+        ("class = ?", &changes.class),
+        ("\"order\" = ?", &changes.order),
+        ("family = ?", &changes.family),
+        ("subfamily = ?", &changes.subfamily),
+        ("genus = ?", &changes.genus),
+        ("species_epithet = ?", &changes.species_epithet),
+        ("subspecies = ?", &changes.subspecies),
+    ] {
+        if let Some(v) = value {
+            assignments.push(assignment);
+            values.push(optional_text(v));
+        }
+    }
+
+    if assignments.is_empty() {
+        bail!("no taxon fields to update");
+    }
+
+    let sql = format!("UPDATE taxa SET {} WHERE id = ?", assignments.join(", "));
+    values.push(Value::from(id));
+
+    let rows_affected = conn.execute(&sql, rusqlite::params_from_iter(values))
+        .context("Failed to update taxon")?;
+    if rows_affected > 0 {
+        create_missing_parent(conn, id)?;
+        rename_descendants(conn, id)?;
+    }
+    Ok(rows_affected)
+}
+
+/// Copy a taxon's names, from kingdom down to its own rank, into everything below it,
+/// so renaming a genus renames its species and their subspecies (and, through the sync
+/// trigger, their sightings). Parents go first so each child relinks under its parent.
+fn rename_descendants(conn: &Connection, id: i64) -> Result<()> {
+    let taxon = get_taxon_by_id(conn, id)?;
+    if taxon.rank > Rank::Species {
+        return Ok(());
+    }
+    let ranks: Vec<Rank> = Rank::ALL.into_iter().filter(|&rank| rank <= taxon.rank).collect();
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH RECURSIVE below(member_id, depth) AS (
+                SELECT id, 1 FROM taxa WHERE parent_id = ?1
+                UNION
+                SELECT taxa.id, depth + 1 FROM taxa JOIN below ON taxa.parent_id = below.member_id
+            )
+            SELECT member_id FROM below ORDER BY depth, member_id
+            "#,
+        )
+        .context("Failed to prepare descendants query")?;
+    let descendants: Vec<i64> = stmt
+        .query_map([id], |row| row.get(0))
+        .context("Failed to query descendants")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read descendants")?;
+
+    let assignments: Vec<String> = ranks.iter().filter_map(|rank| rank.column()).map(|column| format!("{} = ?", column)).collect();
+    let sql = format!("UPDATE taxa SET {} WHERE id = ?", assignments.join(", "));
+    for descendant in descendants {
+        let mut values: Vec<Value> = ranks.iter().map(|&rank| Value::from(taxon.name_at(rank).map(str::to_string))).collect();
+        values.push(Value::from(descendant));
+        conn.execute(&sql, rusqlite::params_from_iter(values))
+            .with_context(|| format!("Failed to rename taxon {}", descendant))?;
+    }
+    Ok(())
+}
+
+/// `subtree(member_id)`: the taxon bound to ?1 and everything below it in the tree
+pub(crate) const SUBTREE_CTE: &str = r#"
+    WITH RECURSIVE subtree(member_id) AS (
+        SELECT ?1
+        UNION
+        SELECT taxa.id FROM taxa JOIN subtree ON taxa.parent_id = subtree.member_id
+    )
+"#;
+
+fn query_taxa(conn: &Connection, sql: &str, params: impl Params) -> Result<Vec<Taxon>> {
+    let mut stmt = conn.prepare(sql)
+        .context("Failed to prepare taxa query")?;
+
+    let rows = stmt.query_map(params, taxon_from_row)
+        .context("Failed to execute taxa query")?;
+
+    let results: Vec<Taxon> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse taxon rows")?;
+    Ok(results)
+}
+
+/// The taxa above a taxon, broadest (its kingdom) first
+pub fn get_ancestors(conn: &Connection, id: i64) -> Result<Vec<Taxon>> {
+    let sql = format!(
+        r#"
+        WITH RECURSIVE ancestors(ancestor_id, depth) AS (
+            SELECT parent_id, 1 FROM taxa WHERE id = ?1
+            UNION ALL
+            SELECT taxa.parent_id, ancestors.depth + 1
+            FROM taxa JOIN ancestors ON taxa.id = ancestors.ancestor_id
+        )
+        SELECT {}
+        FROM taxa
+        JOIN ancestors ON id = ancestor_id
+        ORDER BY depth DESC
+        "#,
+        TAXON_COLUMNS
+    );
+    query_taxa(conn, &sql, params![id])
+}
+
+/// The taxa directly below a taxon, in taxonomic order
+pub fn get_children(conn: &Connection, id: i64) -> Result<Vec<Taxon>> {
+    let sql = format!("SELECT {} FROM taxa WHERE parent_id = ?1 ORDER BY id", TAXON_COLUMNS);
+    query_taxa(conn, &sql, params![id])
+}
+
+/// The taxa at the top of the tree: the kingdoms, and any taxon whose ancestors don't
+/// exist yet
+pub fn get_root_taxa(conn: &Connection) -> Result<Vec<Taxon>> {
+    let sql = format!("SELECT {} FROM taxa WHERE parent_id IS NULL ORDER BY id", TAXON_COLUMNS);
+    query_taxa(conn, &sql, [])
+}
+
+/// Every taxon below a taxon, at any depth, in taxonomic order
+pub fn get_descendants(conn: &Connection, id: i64) -> Result<Vec<Taxon>> {
+    let sql = format!(
+        "{} SELECT {} FROM taxa WHERE id IN (SELECT member_id FROM subtree) AND id <> ?1 ORDER BY id",
+        SUBTREE_CTE, TAXON_COLUMNS
+    );
+    query_taxa(conn, &sql, params![id])
+}
+
+/// The distinct taxa sighted on a trip, by common name
+pub fn get_taxa_by_trip_id(conn: &Connection, trip_id: i64) -> Result<Vec<Taxon>> {
+    let sql = format!(
+        "SELECT {} FROM taxa WHERE id IN (SELECT taxon_id FROM sightings WHERE trip_id = ?1) ORDER BY common_name, id",
+        TAXON_COLUMNS
+    );
+    query_taxa(conn, &sql, params![trip_id])
+}
+
 /// Delete a taxon by ID
 pub fn delete_taxon(conn: &Connection, id: i64) -> Result<usize> {
     let sql = "DELETE FROM taxa WHERE id = ?1";
@@ -69,14 +430,14 @@ pub fn delete_taxon(conn: &Connection, id: i64) -> Result<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Effort;
 
     fn setup_test_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
         conn.pragma_update(None, "foreign_keys", "ON").unwrap();
 
         // Create schema
-        let schema = std::fs::read_to_string("init.sql").unwrap();
-        conn.execute_batch(&schema).unwrap();
+        crate::core::migrations::migrate(&conn).unwrap();
 
         // Load test taxa (first 100 species + parent taxa)
         let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
@@ -91,7 +452,7 @@ mod tests {
 
         let id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -100,13 +461,14 @@ mod tests {
             None,
             Some("Turdus"),
             Some("migratorius"),
+            None,
             "American Robin",
         ).unwrap();
 
         assert!(id > 0);
 
         let taxon = get_taxon_by_id(&conn, id).unwrap();
-        assert_eq!(taxon.rank, "species");
+        assert_eq!(taxon.rank, Rank::Species);
         assert_eq!(taxon.kingdom, "Animalia");
         assert_eq!(taxon.phylum, Some("Chordata".to_string()));
         assert_eq!(taxon.species_epithet, Some("migratorius".to_string()));
@@ -119,7 +481,7 @@ mod tests {
 
         let id = create_taxon(
             &conn,
-            "family",
+            Rank::Family,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -128,11 +490,12 @@ mod tests {
             None,
             None,
             None,
+            None,
             "Crow Family",
         ).unwrap();
 
         let taxon = get_taxon_by_id(&conn, id).unwrap();
-        assert_eq!(taxon.rank, "family");
+        assert_eq!(taxon.rank, Rank::Family);
         assert_eq!(taxon.family, Some("Corvidae".to_string()));
         assert_eq!(taxon.genus, None);
         assert_eq!(taxon.species_epithet, None);
@@ -144,7 +507,7 @@ mod tests {
 
         let id = create_taxon(
             &conn,
-            "genus",
+            Rank::Genus,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -153,11 +516,12 @@ mod tests {
             None,
             Some("Buteo"),
             None,
+            None,
             "Buteo Hawks",
         ).unwrap();
 
         let taxon = get_taxon_by_id(&conn, id).unwrap();
-        assert_eq!(taxon.rank, "genus");
+        assert_eq!(taxon.rank, Rank::Genus);
         assert_eq!(taxon.genus, Some("Buteo".to_string()));
         assert_eq!(taxon.species_epithet, None);
     }
@@ -168,7 +532,7 @@ mod tests {
 
         let id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -177,6 +541,7 @@ mod tests {
             None,
             Some("Test"),
             Some("temp"),
+            None,
             "Temp Bird",
         ).unwrap();
 
@@ -192,21 +557,306 @@ mod tests {
     fn test_invalid_rank() {
         let conn = setup_test_db();
 
-        let result = create_taxon(
+        let error = "invalid_rank".parse::<Rank>().unwrap_err();
+        assert!(error.to_string().starts_with("unknown rank 'invalid_rank'"));
+
+        // The schema still guards against ranks written with plain SQL
+        let result = conn.execute(
+            "INSERT INTO taxa (rank, kingdom, common_name) VALUES ('invalid_rank', 'Animalia', 'Test')",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_update_taxon_partial() {
+        let conn = setup_test_db();
+
+        let id = create_taxon(
             &conn,
-            "invalid_rank",
+            Rank::Species,
             "Animalia",
+            Some("Chordata"),
+            Some("Aves"),
+            Some("Passeriformes"),
+            Some("Turdidae"),
             None,
+            Some("Turdus"),
+            Some("migratorus"),
             None,
+            "American Robbin",
+        ).unwrap();
+
+        let changes = TaxonUpdate {
+            species_epithet: Some("migratorius".to_string()),
+            common_name: Some("American Robin".to_string()),
+            ..Default::default()
+        };
+        let rows = update_taxon(&conn, id, &changes).unwrap();
+        assert_eq!(rows, 1);
+
+        let taxon = get_taxon_by_id(&conn, id).unwrap();
+        assert_eq!(taxon.rank, Rank::Species);
+        assert_eq!(taxon.genus, Some("Turdus".to_string()));
+        assert_eq!(taxon.species_epithet, Some("migratorius".to_string()));
+        assert_eq!(taxon.common_name, "American Robin");
+    }
+
+    #[test]
+    fn test_update_taxon_clears_optional_rank() {
+        let conn = setup_test_db();
+
+        let id = create_taxon(
+            &conn,
+            Rank::Genus,
+            "Animalia",
+            Some("Chordata"),
+            Some("Aves"),
+            Some("Passeriformes"),
+            Some("Corvidae"),
+            Some("Corvinae"),
+            Some("Corvus"),
             None,
             None,
+            "Crows",
+        ).unwrap();
+
+        let changes = TaxonUpdate {
+            subfamily: Some(String::new()),
+            ..Default::default()
+        };
+        update_taxon(&conn, id, &changes).unwrap();
+
+        let taxon = get_taxon_by_id(&conn, id).unwrap();
+        assert_eq!(taxon.subfamily, None);
+        assert_eq!(taxon.genus, Some("Corvus".to_string()));
+    }
+
+    #[test]
+    fn test_update_taxon_rejects_invalid_values() {
+        let conn = setup_test_db();
+
+        let id = create_taxon(
+            &conn,
+            Rank::Family,
+            "Animalia",
+            Some("Chordata"),
+            Some("Aves"),
+            Some("Passeriformes"),
+            Some("Corvidae"),
             None,
             None,
             None,
-            "Test",
-        );
+            None,
+            "Crow Family",
+        ).unwrap();
 
-        assert!(result.is_err());
+        // Nothing to change
+        assert!(update_taxon(&conn, id, &TaxonUpdate::default()).is_err());
+
+        // Required fields cannot be blanked
+        let changes = TaxonUpdate {
+            common_name: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(update_taxon(&conn, id, &changes).is_err());
+    }
+
+    #[test]
+    fn test_create_taxon_creates_missing_ancestors() {
+        let conn = setup_test_db();
+
+        let robin = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("migratorius"), None, "American Robin").unwrap();
+
+        // Passeriformes and below are new; Aves and above come from the seed
+        let ancestors = get_ancestors(&conn, robin).unwrap();
+        let names: Vec<String> = ancestors.iter().map(|taxon| taxon.scientific_name()).collect();
+        assert_eq!(names, vec!["Animalia", "Chordata", "Aves", "Passeriformes", "Turdidae", "Turdus"]);
+        assert_eq!(ancestors[3].rank, Rank::Order);
+        assert_eq!(ancestors[5].common_name, "Turdus");
+        assert_eq!(ancestors[0].parent_id, None);
+
+        let passeriformes = ancestors[3].id;
+        assert_eq!(get_children(&conn, passeriformes).unwrap().len(), 1);
+        let descendants: Vec<i64> = get_descendants(&conn, passeriformes).unwrap().iter().map(|taxon| taxon.id).collect();
+        // Each missing ancestor is created after the taxon that needed it
+        assert_eq!(descendants, vec![robin, ancestors[5].id, ancestors[4].id]);
+
+        // A second thrush reuses the ancestors
+        create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Turdidae"), None, Some("Turdus"), Some("merula"), None, "Eurasian Blackbird").unwrap();
+        assert_eq!(get_children(&conn, ancestors[5].id).unwrap().len(), 2);
+        assert_eq!(get_descendants(&conn, passeriformes).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_find_or_create_ancestor() {
+        let conn = setup_test_db();
+
+        // Inserted without the core API, so nothing above it exists yet
+        conn.execute(
+            r#"INSERT INTO taxa (rank, kingdom, "order", family, genus, species_epithet, common_name)
+               VALUES ('species', 'Plantae', 'Rosales', 'Moraceae', 'Ficus', 'carica', 'Common Fig')"#,
+            [],
+        )
+        .unwrap();
+        let fig = get_taxon_by_id(&conn, conn.last_insert_rowid()).unwrap();
+        assert_eq!(fig.parent_id, None);
+
+        let family = find_or_create_ancestor(&conn, &fig, Rank::Family).unwrap();
+        assert_eq!((family.rank, family.common_name.as_str()), (Rank::Family, "Moraceae"));
+        assert_eq!(family.genus, None);
+        let lineage: Vec<String> = get_ancestors(&conn, family.id).unwrap().iter().map(|t| t.scientific_name()).collect();
+        assert_eq!(lineage, vec!["Plantae", "Rosales"]);
+
+        // The new genus adopts the fig, and is found again rather than duplicated
+        let genus = find_or_create_ancestor(&conn, &fig, Rank::Genus).unwrap();
+        assert_eq!(genus.parent_id, Some(family.id));
+        assert_eq!(get_taxon_by_id(&conn, fig.id).unwrap().parent_id, Some(genus.id));
+        assert_eq!(find_or_create_ancestor(&conn, &fig, Rank::Genus).unwrap().id, genus.id);
+        assert_eq!(find_or_create_ancestor(&conn, &fig, Rank::Species).unwrap().id, fig.id);
+
+        // Only the ranks the taxon names, at or above its own
+        assert!(find_or_create_ancestor(&conn, &fig, Rank::Subfamily).is_err());
+        assert!(find_or_create_ancestor(&conn, &fig, Rank::Subspecies).is_err());
+        assert!(find_or_create_ancestor(&conn, &family, Rank::Genus).is_err());
+
+        let roots: Vec<String> = get_root_taxa(&conn).unwrap().iter().map(|t| t.scientific_name()).collect();
+        assert_eq!(roots, vec!["Animalia", "Plantae"]);
+    }
+
+    #[test]
+    fn test_lineage_names_categories_by_their_own_rank() {
+        let conn = setup_test_db();
+
+        let id = create_taxon(&conn, Rank::Hybrid, "Animalia", Some("Chordata"), Some("Aves"), Some("Anseriformes"), Some("Anatidae"), None, Some("Anas"), Some("platyrhynchos x rubripes"), None, "Mallard x American Black Duck (hybrid)").unwrap();
+        let hybrid = get_taxon_by_id(&conn, id).unwrap();
+        let lineage = hybrid.lineage();
+        assert_eq!(lineage.last(), Some(&(Rank::Hybrid, "platyrhynchos x rubripes")));
+        assert_eq!(lineage[lineage.len() - 2], (Rank::Genus, "Anas"));
+        assert_eq!(find_or_create_ancestor(&conn, &hybrid, Rank::Genus).unwrap().id, hybrid.parent_id.unwrap());
+        assert!(find_or_create_ancestor(&conn, &hybrid, Rank::Species).is_err());
+    }
+
+    #[test]
+    fn test_tree_separates_homonyms_and_places_categories() {
+        let conn = setup_test_db();
+
+        // "Morus" is both a gannet genus and the mulberry genus
+        let gannets = create_taxon(&conn, Rank::Genus, "Animalia", Some("Chordata"), Some("Aves"), Some("Suliformes"), Some("Sulidae"), None, Some("Morus"), None, None, "Morus").unwrap();
+        let mulberry = create_taxon(&conn, Rank::Species, "Plantae", None, None, Some("Rosales"), Some("Moraceae"), None, Some("Morus"), Some("alba"), None, "White Mulberry").unwrap();
+        let parent = get_taxon_by_id(&conn, mulberry).unwrap().parent_id.unwrap();
+        assert_ne!(parent, gannets);
+        assert_eq!(get_taxon_by_id(&conn, parent).unwrap().kingdom, "Plantae");
+        assert!(get_children(&conn, gannets).unwrap().is_empty());
+
+        // A subspecies hangs off its species, a slash or spuh off the genus
+        let create = |rank: Rank, epithet: Option<&str>, subspecies: Option<&str>, name: &str| {
+            create_taxon(&conn, rank, "Animalia", Some("Chordata"), Some("Aves"), Some("Suliformes"), Some("Sulidae"), None, Some("Morus"), epithet, subspecies, name).unwrap()
+        };
+        let form = create(Rank::Subspecies, Some("bassanus"), Some("exemplaris"), "Northern Gannet (test form)");
+        let slash = create(Rank::Slash, Some("bassanus/capensis"), None, "Northern/Cape Gannet");
+        let spuh = create(Rank::Spuh, None, None, "Morus sp.");
+
+        let parent_of = |id: i64| get_taxon_by_id(&conn, id).unwrap().parent_id.unwrap();
+        let species = parent_of(form);
+        assert_eq!(get_taxon_by_id(&conn, species).unwrap().scientific_name(), "Morus bassanus");
+        assert_eq!(parent_of(species), gannets);
+        assert_eq!((parent_of(slash), parent_of(spuh)), (gannets, gannets));
+        assert_eq!(get_taxon_by_id(&conn, spuh).unwrap().scientific_name(), "Morus sp.");
+    }
+
+    #[test]
+    fn test_editing_names_moves_a_taxon() {
+        let conn = setup_test_db();
+
+        let id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Corvus"), Some("corax"), None, "Common Raven").unwrap();
+        let changes = TaxonUpdate { genus: Some("Pica".to_string()), species_epithet: Some("pica".to_string()), ..Default::default() };
+        update_taxon(&conn, id, &changes).unwrap();
+
+        let ancestors = get_ancestors(&conn, id).unwrap();
+        assert_eq!(ancestors.last().unwrap().scientific_name(), "Pica");
+        assert_eq!(ancestors[ancestors.len() - 2].scientific_name(), "Corvidae");
+    }
+
+    #[test]
+    fn test_renaming_a_genus_renames_what_is_below_it() {
+        let conn = setup_test_db();
+
+        let species = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Corvus"), Some("corax"), None, "Common Raven").unwrap();
+        let subspecies = create_taxon(&conn, Rank::Subspecies, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Corvus"), Some("corax"), Some("principalis"), "Northern Raven").unwrap();
+        let spuh = create_taxon(&conn, Rank::Spuh, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Corvus"), None, None, "crow/raven sp.").unwrap();
+        let sighting = crate::core::sighting::create_sighting(&conn, None, subspecies, None, None, None, None, None, &[], None, None).unwrap();
+        let genus = find_taxon_by_scientific_name(&conn, "Corvus").unwrap().unwrap();
+
+        update_taxon(&conn, genus.id, &TaxonUpdate { genus: Some("Corvinus".to_string()), ..Default::default() }).unwrap();
+
+        assert_eq!(get_taxon_by_id(&conn, species).unwrap().scientific_name(), "Corvinus corax");
+        assert_eq!(get_taxon_by_id(&conn, subspecies).unwrap().scientific_name(), "Corvinus corax principalis");
+        assert_eq!(get_taxon_by_id(&conn, spuh).unwrap().scientific_name(), "Corvinus sp.");
+        assert!(find_taxon_by_scientific_name(&conn, "Corvus corax").unwrap().is_none());
+
+        // They stay where they were in the tree
+        assert_eq!(get_taxon_by_id(&conn, species).unwrap().parent_id, Some(genus.id));
+        assert_eq!(get_taxon_by_id(&conn, subspecies).unwrap().parent_id, Some(species));
+        assert_eq!(get_taxon_by_id(&conn, spuh).unwrap().parent_id, Some(genus.id));
+        let genus_of_sighting: Option<String> = conn.query_row("SELECT genus FROM sightings WHERE id = ?1", [sighting], |row| row.get(0)).unwrap();
+        assert_eq!(genus_of_sighting, Some("Corvinus".to_string()));
+
+        // Moving the family to another order moves the whole family
+        let family = find_taxon_by_scientific_name(&conn, "Corvidae").unwrap().unwrap();
+        update_taxon(&conn, family.id, &TaxonUpdate { order: Some("Corviformes".to_string()), ..Default::default() }).unwrap();
+        assert_eq!(get_taxon_by_id(&conn, subspecies).unwrap().order, Some("Corviformes".to_string()));
+        assert_eq!(get_taxon_by_id(&conn, subspecies).unwrap().parent_id, Some(species));
+        assert_eq!(get_taxon_by_id(&conn, genus.id).unwrap().parent_id, Some(family.id));
+    }
+
+    #[test]
+    fn test_find_subspecies_and_identification_categories() {
+        let conn = setup_test_db();
+
+        let create = |rank: Rank, genus: Option<&str>, epithet: Option<&str>, subspecies: Option<&str>, name: &str| {
+            create_taxon(&conn, rank, "Animalia", Some("Chordata"), Some("Aves"), Some("Charadriiformes"), Some("Scolopacidae"), None, genus, epithet, subspecies, name).unwrap()
+        };
+        let genus = create(Rank::Genus, Some("Tringa"), None, None, "Tringa");
+        let species = create(Rank::Species, Some("Tringa"), Some("melanoleuca"), None, "Greater Yellowlegs");
+        let subspecies = create(Rank::Subspecies, Some("Tringa"), Some("melanoleuca"), Some("exemplaris"), "Greater Yellowlegs (test form)");
+        let slash = create(Rank::Slash, Some("Tringa"), Some("melanoleuca/flavipes"), None, "Greater/Lesser Yellowlegs");
+        let hybrid = create(Rank::Hybrid, Some("Tringa"), Some("melanoleuca x flavipes"), None, "Greater x Lesser Yellowlegs (hybrid)");
+        let spuh = create(Rank::Spuh, Some("Tringa"), None, None, "yellowlegs sp.");
+
+        let find = |name: &str| find_taxon_by_scientific_name(&conn, name).unwrap().map(|taxon| taxon.id);
+        assert_eq!(find("Tringa melanoleuca"), Some(species));
+        assert_eq!(find("tringa melanoleuca EXEMPLARIS"), Some(subspecies));
+        assert_eq!(find("Tringa melanoleuca/flavipes"), Some(slash));
+        assert_eq!(find("Tringa melanoleuca x flavipes"), Some(hybrid));
+        assert_eq!(find("Tringa"), Some(genus));
+        assert_eq!(find("Tringa sp."), Some(spuh));
+        // A spuh is named after the narrowest rank it fills in
+        assert_eq!(find("Scolopacidae sp."), None);
+
+        let taxon = get_taxon_by_id(&conn, subspecies).unwrap();
+        assert_eq!(taxon.subspecies.as_deref(), Some("exemplaris"));
+        assert_eq!(taxon.name_at(Rank::Subspecies), Some("exemplaris"));
+        assert_eq!(get_taxon_by_id(&conn, spuh).unwrap().name_at(Rank::Spuh), None);
+    }
+
+    #[test]
+    fn test_get_taxa_by_trip_id() {
+        use crate::core::sighting::create_sighting;
+        use crate::core::trip::create_trip;
+
+        let conn = setup_test_db();
+        let trip = create_trip(&conn, "Marsh walk", None, None, None, None, &Effort::default()).unwrap();
+        let whistling = find_taxon_by_scientific_name(&conn, "Dendrocygna viduata").unwrap().unwrap();
+        let genus = find_taxon_by_scientific_name(&conn, "Dendrocygna").unwrap().unwrap();
+
+        for taxon_id in [whistling.id, genus.id, whistling.id] {
+            create_sighting(&conn, Some(trip), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        }
+
+        // Each taxon once, by common name
+        let names: Vec<String> = get_taxa_by_trip_id(&conn, trip).unwrap().into_iter().map(|t| t.common_name).collect();
+        assert_eq!(names, vec![genus.common_name, whistling.common_name]);
     }
 
     #[test]
@@ -215,4 +865,34 @@ mod tests {
         let result = get_taxon_by_id(&conn, 99999);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_find_taxon_by_scientific_name() {
+        let conn = setup_test_db();
+
+        let duck = find_taxon_by_scientific_name(&conn, "dendrocygna VIDUATA").unwrap().unwrap();
+        assert_eq!(duck.common_name, "White-faced Whistling-Duck");
+        assert_eq!(duck.rank, Rank::Species);
+
+        let genus = find_taxon_by_scientific_name(&conn, "Dendrocygna").unwrap().unwrap();
+        assert_eq!(genus.rank, Rank::Genus);
+
+        let family = find_taxon_by_scientific_name(&conn, "Anatidae").unwrap().unwrap();
+        assert_eq!(family.rank, Rank::Family);
+
+        assert!(find_taxon_by_scientific_name(&conn, "Dendrocygna nonexistens").unwrap().is_none());
+        assert!(find_taxon_by_scientific_name(&conn, "Anas platyrhynchos domesticus").unwrap().is_none());
+        assert!(find_taxon_by_scientific_name(&conn, "  ").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_taxon_by_name() {
+        let conn = setup_test_db();
+
+        for name in ["White-faced Whistling-Duck", "white faced whistling duck", "white-faced_whistling-duck", "Dendrocygna-viduata"] {
+            assert_eq!(find_taxon_by_name(&conn, name).unwrap().unwrap().common_name, "White-faced Whistling-Duck");
+        }
+        assert_eq!(find_taxon_by_name(&conn, "Anatidae").unwrap().unwrap().rank, Rank::Family);
+        assert!(find_taxon_by_name(&conn, "Purple Penguin").unwrap().is_none());
+    }
 }
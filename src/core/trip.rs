@@ -1,151 +1,312 @@
-use crate::models::Trip;
-use anyhow::{Context, Result};
-use rusqlite::{Connection, params};
-
-/// Create a new trip
+use crate::core::db::{Paged, coordinate_values, coordinates_at, optional_date, optional_text, parse_date, query_page};
+use crate::core::location::{location_assignments, resolve_location};
+use crate::core::taxon::SUBTREE_CTE;
+use crate::models::{Coordinates, Effort, Trip, TripUpdate};
+use anyhow::{Context, Result, bail};
+use rusqlite::types::Value;
+use rusqlite::{Connection, Row, params};
+
+/// Create a new trip. A location name is matched to an existing location, or a new one is created.
 pub fn create_trip(
     conn: &Connection,
     name: &str,
     date: Option<&str>,
     location: Option<&str>,
     notes: Option<&str>,
+    coordinates: Option<Coordinates>,
+    effort: &Effort,
 ) -> Result<i64> {
+    if name.trim().is_empty() {
+        bail!("trip name cannot be empty");
+    }
+    effort.check()?;
+    let date = parse_date(date)?;
+
     let sql = r#"
-        INSERT INTO trips (name, date, location, notes)
-        VALUES (?1, ?2, ?3, ?4)
+        INSERT INTO trips (
+            name, date, location, notes, latitude, longitude, accuracy_m, location_id,
+            start_time, duration_min, distance_km, party_size, protocol, complete
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
     "#;
 
-    conn.execute(sql, params![name, date, location, notes])
-        .context("Failed to insert trip")?;
+    let (location_id, location) = location.map(|name| resolve_location(conn, name)).transpose()?.flatten().unzip();
+    let (latitude, longitude, accuracy_m) = coordinate_values(coordinates);
+    conn.execute(
+        sql,
+        params![
+            name,
+            date,
+            location,
+            notes,
+            latitude,
+            longitude,
+            accuracy_m,
+            location_id,
+            effort.start_time,
+            effort.duration_min,
+            effort.distance_km,
+            effort.party_size,
+            effort.protocol.map(|protocol| protocol.as_str()),
+            effort.complete,
+        ],
+    )
+    .context("Failed to insert trip")?;
 
     let id = conn.last_insert_rowid();
     Ok(id)
 }
 
+/// Map a row of `id, name, date, location, notes, latitude, longitude, accuracy_m, location_id,
+/// start_time, duration_min, distance_km, party_size, protocol, complete` to a `Trip`
+pub(crate) fn trip_from_row(row: &Row) -> rusqlite::Result<Trip> {
+    let protocol: Option<String> = row.get(13)?;
+    Ok(Trip {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        date: row.get(2)?,
+        location: row.get(3)?,
+        notes: row.get(4)?,
+        coordinates: coordinates_at(row, 5)?,
+        location_id: row.get(8)?,
+        effort: Effort {
+            start_time: row.get(9)?,
+            duration_min: row.get(10)?,
+            distance_km: row.get(11)?,
+            party_size: row.get(12)?,
+            protocol: protocol.and_then(|protocol| protocol.parse().ok()),
+            complete: row.get(14)?,
+        },
+    })
+}
+
 /// Get a trip by ID
 pub fn get_trip_by_id(conn: &Connection, id: i64) -> Result<Trip> {
     let sql = r#"
-        SELECT id, name, date, location, notes
+        SELECT id, name, date, location, notes, latitude, longitude, accuracy_m, location_id,
+               start_time, duration_min, distance_km, party_size, protocol, complete
         FROM trips
         WHERE id = ?1
     "#;
 
-    let trip = conn.query_row(sql, params![id], |row| {
-        Ok(Trip {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            date: row.get(2)?,
-            location: row.get(3)?,
-            notes: row.get(4)?,
-        })
-    }).context("Failed to fetch trip")?;
+    let trip = conn.query_row(sql, params![id], trip_from_row)
+        .context("Failed to fetch trip")?;
 
     Ok(trip)
 }
 
-/// Delete a trip by ID
-pub fn delete_trip(conn: &Connection, id: i64) -> Result<usize> {
-    let sql = "DELETE FROM trips WHERE id = ?1";
-    let rows_affected = conn.execute(sql, params![id])
-        .context("Failed to delete trip")?;
-    Ok(rows_affected)
+/// Get every trip, oldest first
+pub fn get_all_trips(conn: &Connection) -> Result<Vec<Trip>> {
+    let sql = r#"
+        SELECT id, name, date, location, notes, latitude, longitude, accuracy_m, location_id,
+               start_time, duration_min, distance_km, party_size, protocol, complete
+        FROM trips
+        ORDER BY id ASC
+    "#;
+
+    let mut stmt = conn.prepare(sql)
+        .context("Failed to prepare get all trips query")?;
+
+    let rows = stmt.query_map([], trip_from_row)
+        .context("Failed to execute get all trips query")?;
+
+    let results: Vec<Trip> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse trip rows")?;
+    Ok(results)
 }
 
-/// Get all trips where a specific taxon was sighted (matches based on taxonomic hierarchy)
-pub fn get_trips_by_taxon(conn: &Connection, taxon: &crate::models::Taxon) -> Result<Vec<Trip>> {
-    // Build WHERE clause based on taxon rank
-    let mut conditions = vec!["sightings.kingdom = ?1".to_string()];
-    let mut params: Vec<&str> = vec![&taxon.kingdom];
-
-    if taxon.rank != "kingdom" {
-        if let Some(ref p) = taxon.phylum {
-            conditions.push("sightings.phylum = ?".to_string());
-            params.push(p);
+/// Update a trip (only the fields set in `changes` are written)
+pub fn update_trip(conn: &Connection, id: i64, changes: &TripUpdate) -> Result<usize> {
+    let mut assignments: Vec<&str> = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(ref name) = changes.name {
+        if name.trim().is_empty() {
+            bail!("trip name cannot be empty");
         }
+        assignments.push("name = ?");
+        values.push(Value::from(name.clone()));
     }
 
-    if taxon.rank != "kingdom" && taxon.rank != "phylum" {
-        if let Some(ref c) = taxon.class {
-            conditions.push("sightings.class = ?".to_string());
-            params.push(c);
-        }
+    if let Some(ref date) = changes.date {
+        assignments.push("date = ?");
+        values.push(optional_date(date)?);
     }
 
-    if taxon.rank == "order" || taxon.rank == "family" || taxon.rank == "subfamily" || taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref o) = taxon.order {
-            conditions.push("sightings.\"order\" = ?".to_string());
-            params.push(o);
-        }
+    if let Some(ref notes) = changes.notes {
+        assignments.push("notes = ?");
+        values.push(optional_text(notes));
     }
 
-    if taxon.rank == "family" || taxon.rank == "subfamily" || taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref f) = taxon.family {
-            conditions.push("sightings.family = ?".to_string());
-            params.push(f);
-        }
+    location_assignments(conn, changes.location.as_deref(), changes.location_id, &mut assignments, &mut values)?;
+
+    if let Some(coordinates) = changes.coordinates {
+        let (latitude, longitude, accuracy_m) = coordinate_values(coordinates);
+        assignments.extend(["latitude = ?", "longitude = ?", "accuracy_m = ?"]);
+        values.extend([Value::from(latitude), Value::from(longitude), Value::from(accuracy_m)]);
     }
 
-    if taxon.rank == "subfamily" || taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref sf) = taxon.subfamily {
-            conditions.push("sightings.subfamily = ?".to_string());
-            params.push(sf);
-        }
+    if effort_changed(changes) {
+        let effort = updated_effort(conn, id, changes)?;
+        effort.check()?;
+        assignments.extend([
+            "start_time = ?",
+            "duration_min = ?",
+            "distance_km = ?",
+            "party_size = ?",
+            "protocol = ?",
+            "complete = ?",
+        ]);
+        values.extend([
+            Value::from(effort.start_time),
+            Value::from(effort.duration_min),
+            Value::from(effort.distance_km),
+            Value::from(effort.party_size),
+            Value::from(effort.protocol.map(|protocol| protocol.as_str().to_string())),
+            Value::from(effort.complete),
+        ]);
     }
 
-    if taxon.rank == "genus" || taxon.rank == "species" {
-        if let Some(ref g) = taxon.genus {
-            conditions.push("sightings.genus = ?".to_string());
-            params.push(g);
-        }
+    if assignments.is_empty() {
+        bail!("no trip fields to update");
     }
 
-    if taxon.rank == "species" {
-        if let Some(ref s) = taxon.species_epithet {
-            conditions.push("sightings.species_epithet = ?".to_string());
-            params.push(s);
-        }
+    let sql = format!("UPDATE trips SET {} WHERE id = ?", assignments.join(", "));
+    values.push(Value::from(id));
+
+    let rows_affected = conn.execute(&sql, rusqlite::params_from_iter(values))
+        .context("Failed to update trip")?;
+    Ok(rows_affected)
+}
+
+fn effort_changed(changes: &TripUpdate) -> bool {
+    changes.start_time.is_some()
+        || changes.duration_min.is_some()
+        || changes.distance_km.is_some()
+        || changes.party_size.is_some()
+        || changes.protocol.is_some()
+        || changes.complete.is_some()
+}
+
+/// The trip's effort with `changes` applied, so the fields can be checked together
+/// (a stationary count can't gain a distance, for one). A missing trip keeps the defaults.
+fn updated_effort(conn: &Connection, id: i64, changes: &TripUpdate) -> Result<Effort> {
+    let mut effort = match get_trip_by_id(conn, id) {
+        Ok(trip) => trip.effort,
+        Err(_) => Effort::default(),
+    };
+
+    if let Some(ref time) = changes.start_time {
+        effort.start_time = (!time.is_empty()).then(|| time.clone());
+    }
+    if let Some(duration_min) = changes.duration_min {
+        effort.duration_min = duration_min;
+    }
+    if let Some(distance_km) = changes.distance_km {
+        effort.distance_km = distance_km;
     }
+    if let Some(party_size) = changes.party_size {
+        effort.party_size = party_size;
+    }
+    if let Some(protocol) = changes.protocol {
+        effort.protocol = protocol;
+    }
+    if let Some(complete) = changes.complete {
+        effort.complete = complete;
+    }
+    Ok(effort)
+}
 
-    let where_clause = conditions.join(" AND ");
-    let sql = format!(
+/// Delete a trip by ID
+pub fn delete_trip(conn: &Connection, id: i64) -> Result<usize> {
+    let sql = "DELETE FROM trips WHERE id = ?1";
+    let rows_affected = conn.execute(sql, params![id])
+        .context("Failed to delete trip")?;
+    Ok(rows_affected)
+}
+
+/// Trips at the location bound to ?1, newest first
+const TRIPS_BY_LOCATION_SQL: &str = r#"
+    SELECT id, name, date, location, notes, latitude, longitude, accuracy_m, location_id,
+           start_time, duration_min, distance_km, party_size, protocol, complete
+    FROM trips
+    WHERE location_id = ?1
+    ORDER BY date DESC, id DESC
+"#;
+
+/// Trips with a sighting of the taxon bound to ?1 or of any taxon below it, newest first
+fn trips_by_taxon_sql() -> String {
+    format!(
         r#"
-        SELECT DISTINCT trips.id, trips.name, trips.date, trips.location, trips.notes
+        {}
+        SELECT DISTINCT trips.id, trips.name, trips.date, trips.location, trips.notes,
+               trips.latitude, trips.longitude, trips.accuracy_m, trips.location_id,
+               trips.start_time, trips.duration_min, trips.distance_km, trips.party_size,
+               trips.protocol, trips.complete
         FROM trips
         INNER JOIN sightings ON sightings.trip_id = trips.id
-        WHERE {}
+        WHERE sightings.taxon_id IN (SELECT member_id FROM subtree)
         ORDER BY trips.date DESC, trips.id DESC
         "#,
-        where_clause
-    );
+        SUBTREE_CTE
+    )
+}
+
+/// Get all trips at a location, newest first
+pub fn get_trips_by_location_id(conn: &Connection, location_id: i64) -> Result<Vec<Trip>> {
+    let mut stmt = conn.prepare(TRIPS_BY_LOCATION_SQL)
+        .context("Failed to prepare get trips by location query")?;
+
+    let rows = stmt.query_map(params![location_id], trip_from_row)
+        .context("Failed to execute get trips by location query")?;
+
+    let results: Vec<Trip> = rows.collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse trip rows")?;
+    Ok(results)
+}
+
+/// One page of `get_trips_by_location_id`, with the total number of trips
+pub fn get_trips_by_location_id_page(conn: &Connection, location_id: i64, limit: usize, offset: usize) -> Result<Paged<Trip>> {
+    query_page(conn, TRIPS_BY_LOCATION_SQL, &[&location_id], limit, offset, trip_from_row)
+        .context("Failed to get a page of trips by location")
+}
+
+/// Get all trips where a taxon, or any taxon below it in the tree, was sighted
+pub fn get_trips_by_taxon(conn: &Connection, taxon: &crate::models::Taxon) -> Result<Vec<Trip>> {
+    let sql = trips_by_taxon_sql();
 
     let mut stmt = conn.prepare(&sql)
         .context("Failed to prepare get trips by taxon query")?;
 
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(Trip {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            date: row.get(2)?,
-            location: row.get(3)?,
-            notes: row.get(4)?,
-        })
-    }).context("Failed to execute get trips by taxon query")?;
+    let rows = stmt.query_map(params![taxon.id], trip_from_row)
+        .context("Failed to execute get trips by taxon query")?;
 
     let results: Vec<Trip> = rows.collect::<Result<Vec<_>, _>>()
         .context("Failed to parse trip rows")?;
     Ok(results)
 }
 
+/// One page of `get_trips_by_taxon`, with the total number of trips
+pub fn get_trips_by_taxon_page(
+    conn: &Connection,
+    taxon: &crate::models::Taxon,
+    limit: usize,
+    offset: usize,
+) -> Result<Paged<Trip>> {
+    query_page(conn, &trips_by_taxon_sql(), &[&taxon.id], limit, offset, trip_from_row)
+        .context("Failed to get a page of trips by taxon")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Rank;
 
     fn setup_test_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
         conn.pragma_update(None, "foreign_keys", "ON").unwrap();
 
-        let schema = std::fs::read_to_string("init.sql").unwrap();
-        conn.execute_batch(&schema).unwrap();
+        crate::core::migrations::migrate(&conn).unwrap();
 
         // Load test taxa (first 100 species + parent taxa)
         let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
@@ -164,13 +325,15 @@ mod tests {
             Some("2025-01-15"),
             Some("Central Park"),
             Some("Cold morning, lots of activity"),
+            None,
+            &Effort::default(),
         ).unwrap();
 
         assert!(id > 0);
 
         let trip = get_trip_by_id(&conn, id).unwrap();
         assert_eq!(trip.name, "Morning Birding");
-        assert_eq!(trip.date, Some("2025-01-15".to_string()));
+        assert_eq!(trip.date, Some("2025-01-15".parse().unwrap()));
         assert_eq!(trip.location, Some("Central Park".to_string()));
         assert_eq!(trip.notes, Some("Cold morning, lots of activity".to_string()));
     }
@@ -179,7 +342,7 @@ mod tests {
     fn test_create_trip_minimal_fields() {
         let conn = setup_test_db();
 
-        let id = create_trip(&conn, "Quick Walk", None, None, None).unwrap();
+        let id = create_trip(&conn, "Quick Walk", None, None, None, None, &Effort::default()).unwrap();
 
         let trip = get_trip_by_id(&conn, id).unwrap();
         assert_eq!(trip.name, "Quick Walk");
@@ -188,11 +351,18 @@ mod tests {
         assert_eq!(trip.notes, None);
     }
 
+    #[test]
+    fn test_create_trip_rejects_empty_name() {
+        let conn = setup_test_db();
+        let err = create_trip(&conn, "  ", Some("2025-01-15"), None, None, None, &Effort::default()).unwrap_err();
+        assert!(err.to_string().contains("name cannot be empty"));
+    }
+
     #[test]
     fn test_delete_trip() {
         let conn = setup_test_db();
 
-        let id = create_trip(&conn, "Test Trip", None, None, None).unwrap();
+        let id = create_trip(&conn, "Test Trip", None, None, None, None, &Effort::default()).unwrap();
         let rows = delete_trip(&conn, id).unwrap();
         assert_eq!(rows, 1);
 
@@ -200,6 +370,70 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_update_trip_partial() {
+        let conn = setup_test_db();
+
+        let id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Centrl Park"), Some("Windy"), None, &Effort::default()).unwrap();
+
+        let changes = TripUpdate {
+            location: Some("Central Park".to_string()),
+            ..Default::default()
+        };
+        let rows = update_trip(&conn, id, &changes).unwrap();
+        assert_eq!(rows, 1);
+
+        // Only location changed
+        let trip = get_trip_by_id(&conn, id).unwrap();
+        assert_eq!(trip.name, "Morning Walk");
+        assert_eq!(trip.date, Some("2025-01-15".parse().unwrap()));
+        assert_eq!(trip.location, Some("Central Park".to_string()));
+        assert_eq!(trip.notes, Some("Windy".to_string()));
+    }
+
+    #[test]
+    fn test_update_trip_clears_optional_field() {
+        let conn = setup_test_db();
+
+        let id = create_trip(&conn, "Morning Walk", None, None, Some("Windy"), None, &Effort::default()).unwrap();
+
+        let changes = TripUpdate {
+            notes: Some(String::new()),
+            ..Default::default()
+        };
+        update_trip(&conn, id, &changes).unwrap();
+
+        let trip = get_trip_by_id(&conn, id).unwrap();
+        assert_eq!(trip.notes, None);
+    }
+
+    #[test]
+    fn test_update_trip_rejects_empty_changes_and_name() {
+        let conn = setup_test_db();
+
+        let id = create_trip(&conn, "Morning Walk", None, None, None, None, &Effort::default()).unwrap();
+
+        assert!(update_trip(&conn, id, &TripUpdate::default()).is_err());
+
+        let changes = TripUpdate {
+            name: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert!(update_trip(&conn, id, &changes).is_err());
+    }
+
+    #[test]
+    fn test_update_nonexistent_trip() {
+        let conn = setup_test_db();
+
+        let changes = TripUpdate {
+            name: Some("Renamed".to_string()),
+            ..Default::default()
+        };
+        let rows = update_trip(&conn, 99999, &changes).unwrap();
+        assert_eq!(rows, 0);
+    }
+
     #[test]
     fn test_get_nonexistent_trip() {
         let conn = setup_test_db();
@@ -217,7 +451,7 @@ mod tests {
         // Create a taxon
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -226,17 +460,18 @@ mod tests {
             None,
             Some("Cyanocitta"),
             Some("cristata"),
+            None,
             "Blue Jay",
         ).unwrap();
 
         // Create 3 trips
-        let trip1 = create_trip(&conn, "Morning Walk", Some("2025-01-20"), Some("Park"), None).unwrap();
-        let trip2 = create_trip(&conn, "Afternoon Hike", Some("2025-01-15"), Some("Trail"), None).unwrap();
-        let trip3 = create_trip(&conn, "Evening Stroll", Some("2025-01-25"), Some("Beach"), None).unwrap();
+        let trip1 = create_trip(&conn, "Morning Walk", Some("2025-01-20"), Some("Park"), None, None, &Effort::default()).unwrap();
+        let trip2 = create_trip(&conn, "Afternoon Hike", Some("2025-01-15"), Some("Trail"), None, None, &Effort::default()).unwrap();
+        let trip3 = create_trip(&conn, "Evening Stroll", Some("2025-01-25"), Some("Beach"), None, None, &Effort::default()).unwrap();
 
         // Create sightings of the taxon on trip1 and trip2 (not trip3)
-        create_sighting(&conn, Some(trip1), taxon_id, None, None, None, None).unwrap();
-        create_sighting(&conn, Some(trip2), taxon_id, None, None, None, None).unwrap();
+        create_sighting(&conn, Some(trip1), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, Some(trip2), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
         let taxon = crate::core::taxon::get_taxon_by_id(&conn, taxon_id).unwrap();
         let results = get_trips_by_taxon(&conn, &taxon).unwrap();
@@ -250,6 +485,32 @@ mod tests {
         assert!(!results.iter().any(|t| t.id == trip3));
     }
 
+    #[test]
+    fn test_get_trips_by_taxon_and_location_pages() {
+        let conn = setup_test_db();
+
+        use crate::core::sighting::create_sighting;
+        use crate::core::taxon::{create_taxon, get_taxon_by_id};
+
+        let taxon_id = create_taxon(&conn, Rank::Species, "Animalia", Some("Chordata"), Some("Aves"), Some("Passeriformes"), Some("Corvidae"), None, Some("Cyanocitta"), Some("cristata"), None, "Blue Jay").unwrap();
+        let mut location_id = None;
+        for date in ["2025-01-20", "2025-01-15", "2025-01-25"] {
+            let trip = create_trip(&conn, date, Some(date), Some("Riverside Park"), None, None, &Effort::default()).unwrap();
+            location_id = get_trip_by_id(&conn, trip).unwrap().location_id;
+            // Two sightings on a trip still count it once
+            create_sighting(&conn, Some(trip), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+            create_sighting(&conn, Some(trip), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        }
+
+        let taxon = get_taxon_by_id(&conn, taxon_id).unwrap();
+        let page = get_trips_by_taxon_page(&conn, &taxon, 2, 1).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["2025-01-20", "2025-01-15"]);
+
+        let page = get_trips_by_location_id_page(&conn, location_id.unwrap(), 10, 0).unwrap();
+        assert_eq!((page.items.len(), page.total), (3, 3));
+    }
+
     #[test]
     fn test_get_trips_by_taxon_id_no_trips() {
         let conn = setup_test_db();
@@ -259,7 +520,7 @@ mod tests {
         // Create a taxon with no sightings
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -268,6 +529,7 @@ mod tests {
             None,
             Some("Turdus"),
             Some("migratorius"),
+            None,
             "American Robin",
         ).unwrap();
 
@@ -285,7 +547,7 @@ mod tests {
 
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -294,11 +556,12 @@ mod tests {
             None,
             Some("Turdus"),
             Some("migratorius"),
+            None,
             "American Robin",
         ).unwrap();
 
         // Create sighting without trip
-        create_sighting(&conn, None, taxon_id, None, None, None, None).unwrap();
+        create_sighting(&conn, None, taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
         let taxon = crate::core::taxon::get_taxon_by_id(&conn, taxon_id).unwrap();
         let results = get_trips_by_taxon(&conn, &taxon).unwrap();
@@ -314,7 +577,7 @@ mod tests {
 
         let taxon_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -323,15 +586,16 @@ mod tests {
             None,
             Some("Cyanocitta"),
             Some("cristata"),
+            None,
             "Blue Jay",
         ).unwrap();
 
-        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Park"), None).unwrap();
+        let trip_id = create_trip(&conn, "Morning Walk", Some("2025-01-15"), Some("Park"), None, None, &Effort::default()).unwrap();
 
         // Create multiple sightings of same taxon on same trip
-        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None).unwrap();
-        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None).unwrap();
-        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None).unwrap();
+        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
         // Should return trip only once (DISTINCT)
         let taxon = crate::core::taxon::get_taxon_by_id(&conn, taxon_id).unwrap();
@@ -350,7 +614,7 @@ mod tests {
         // Create subfamily taxon
         let subfamily_id = create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -359,13 +623,14 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae",
         ).unwrap();
 
         // Create species within that subfamily
         let species_id = create_taxon(
             &conn,
-            "species",
+            Rank::Species,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -374,16 +639,17 @@ mod tests {
             Some("Corvinae"),
             Some("Corvus"),
             Some("corax"),
+            None,
             "Common Raven",
         ).unwrap();
 
         // Create trips
-        let trip1 = create_trip(&conn, "Trip 1", Some("2025-01-15"), None, None).unwrap();
-        let trip2 = create_trip(&conn, "Trip 2", Some("2025-01-20"), None, None).unwrap();
+        let trip1 = create_trip(&conn, "Trip 1", Some("2025-01-15"), None, None, None, &Effort::default()).unwrap();
+        let trip2 = create_trip(&conn, "Trip 2", Some("2025-01-20"), None, None, None, &Effort::default()).unwrap();
 
         // Create sightings: trip1 has subfamily sighting, trip2 has species sighting, trip3 has neither
-        create_sighting(&conn, Some(trip1), subfamily_id, None, None, None, None).unwrap();
-        create_sighting(&conn, Some(trip2), species_id, None, None, None, None).unwrap();
+        create_sighting(&conn, Some(trip1), subfamily_id, None, None, None, None, None, &[], None, None).unwrap();
+        create_sighting(&conn, Some(trip2), species_id, None, None, None, None, None, &[], None, None).unwrap();
 
         // Query by subfamily should return both trip1 and trip2
         let subfamily_taxon = crate::core::taxon::get_taxon_by_id(&conn, subfamily_id).unwrap();
@@ -406,7 +672,7 @@ mod tests {
         // Create family taxon
         let family_id = create_taxon(
             &conn,
-            "family",
+            Rank::Family,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -415,13 +681,14 @@ mod tests {
             None,
             None,
             None,
+            None,
             "Corvidae",
         ).unwrap();
 
         // Create subfamily within that family
         let subfamily_id = create_taxon(
             &conn,
-            "subfamily",
+            Rank::Subfamily,
             "Animalia",
             Some("Chordata"),
             Some("Aves"),
@@ -430,12 +697,13 @@ mod tests {
             Some("Corvinae"),
             None,
             None,
+            None,
             "Corvinae",
         ).unwrap();
 
         // Create trip with subfamily sighting
-        let trip_id = create_trip(&conn, "Corvid Trip", Some("2025-01-15"), None, None).unwrap();
-        create_sighting(&conn, Some(trip_id), subfamily_id, None, None, None, None).unwrap();
+        let trip_id = create_trip(&conn, "Corvid Trip", Some("2025-01-15"), None, None, None, &Effort::default()).unwrap();
+        create_sighting(&conn, Some(trip_id), subfamily_id, None, None, None, None, None, &[], None, None).unwrap();
 
         // Query by family should return trip (family includes its subfamilies)
         let family_taxon = crate::core::taxon::get_taxon_by_id(&conn, family_id).unwrap();
@@ -3,109 +3,429 @@ mod core;
 mod models;
 mod ui;
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use clap::Parser;
 use cli::{Cli, Commands};
-use core::db::{connect, drop_all_tables, execute_sql_file};
-use core::search::{run_search_sightings, run_search_taxa, run_search_trips};
-use core::sighting::{create_sighting, delete_sighting, get_sighting_by_id};
-use core::taxon::{create_taxon, delete_taxon, get_taxon_by_id};
-use core::trip::{create_trip, delete_trip, get_trip_by_id};
+use core::db::{connect, drop_all_tables, execute_sql_file, open, resolve_db_path};
+use core::dwca::{export_dwca_file, import_dwca_file};
+use core::ebird::{EbirdExportFilter, export_ebird_csv, export_ebird_file, import_ebird_file};
+use core::migrations::{MIGRATIONS, latest_version, migrate, pending_migrations, schema_version};
+use core::photos::{Photo, import_photos, scan_photos};
+use core::reports::{EffortSummary, ListEntry, current_year, effort_summary, life_list, year_list};
+use core::search::geo::{NearbyHit, parse_bounding_box, parse_radius, sightings_in_box, sightings_near, trips_in_box, trips_near};
+use core::location::{
+    create_location, delete_location, get_all_locations, get_location_by_id, link_locations,
+    merge_locations, update_location,
+};
+use core::media::{MediaProblem, add_media, check_media, get_media, relocate_media, relocate_media_dir, remove_media};
+use core::search::{
+    SearchHit, parse_date_range, run_search, run_search_locations, run_search_sightings, run_search_taxa,
+    run_search_trips,
+};
+use core::sighting::{
+    create_sighting, delete_sighting, find_taxonomy_drift, get_sighting_by_id, get_sightings_by_location_id,
+    get_sightings_by_taxon, resync_denormalized_taxonomy, update_sighting,
+};
+use core::taxon::{create_taxon, delete_taxon, find_taxon_by_name, get_ancestors, get_children, get_descendants, get_taxon_by_id, update_taxon};
+use core::trip::{create_trip, delete_trip, get_trip_by_id, get_trips_by_location_id, get_trips_by_taxon, update_trip};
+use models::{
+    BreedingCode, Coordinates, Count, Effort, LocationUpdate, Media, MediaOwner, Protocol, SightingUpdate, TaxonUpdate,
+    TripUpdate, format_age_sex, parse_age_sex, parse_start_time,
+};
 
 fn main() -> Result<()> {
-    // Check if any CLI arguments were provided
-    let args: Vec<String> = std::env::args().collect();
+    let cli = Cli::parse();
+    let db_path = resolve_db_path(cli.db.as_deref())?;
 
-    // If no arguments (just the binary name), launch GUI
-    if args.len() == 1 {
-        ui::run_ui()?;
+    // No subcommand (just the binary name, optionally with --db): launch GUI
+    let Some(command) = cli.command else {
+        ui::run_ui(db_path)?;
         return Ok(());
-    }
+    };
 
     // Otherwise, run CLI
-    let cli = Cli::parse();
-
-    match cli.command {
-        Commands::SearchSightings { query } => {
-            let conn = connect()?;
-            let results = run_search_sightings(&conn, &query)?;
+    match command {
+        Commands::Search { query } => {
+            let conn = connect(&db_path)?;
+            let results = run_search(&conn, &query)?;
 
-            if results.is_empty() {
+            if results.sightings.is_empty()
+                && results.trips.is_empty()
+                && results.taxa.is_empty()
+                && results.locations.is_empty()
+            {
                 println!("No matches found.");
             } else {
-                for sighting in results {
-                    println!("{}", sighting);
-                }
+                print_section("Sightings", &results.sightings);
+                print_section("Trips", &results.trips);
+                print_section("Taxa", &results.taxa);
+                print_section("Places", &results.locations);
             }
         }
 
+        Commands::SearchSightings { query } => {
+            let conn = connect(&db_path)?;
+            let results = run_search_sightings(&conn, &query)?;
+
+            print_hits(&results);
+        }
+
         Commands::SearchTrips { query } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let results = run_search_trips(&conn, &query)?;
 
-            if results.is_empty() {
-                println!("No matches found.");
-            } else {
-                for trip in results {
-                    println!("{}", trip);
-                }
-            }
+            print_hits(&results);
         }
 
         Commands::SearchTaxa { query } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let results = run_search_taxa(&conn, &query)?;
 
-            if results.is_empty() {
-                println!("No matches found.");
+            print_hits(&results);
+        }
+
+        Commands::SearchLocations { query } => {
+            let conn = connect(&db_path)?;
+            let results = run_search_locations(&conn, &query)?;
+
+            print_hits(&results);
+        }
+
+        Commands::Near { radius } => {
+            let radius = parse_radius(&radius)?;
+            let conn = connect(&db_path)?;
+            let trips = trips_near(&conn, &radius)?;
+            let sightings = sightings_near(&conn, &radius)?;
+
+            if trips.is_empty() && sightings.is_empty() {
+                println!("Nothing recorded within {} km.", radius.radius_km);
             } else {
-                for taxon in results {
-                    println!("{}", taxon);
-                }
+                print_nearby("Trips", &trips);
+                print_nearby("Sightings", &sightings);
+            }
+        }
+
+        Commands::InBox { bounds } => {
+            let bounds = parse_bounding_box(&bounds)?;
+            let conn = connect(&db_path)?;
+            let trips = trips_in_box(&conn, &bounds)?;
+            let sightings = sightings_in_box(&conn, &bounds)?;
+
+            if trips.is_empty() && sightings.is_empty() {
+                println!("Nothing recorded in that box.");
+            } else {
+                print_nearby("Trips", &trips);
+                print_nearby("Sightings", &sightings);
             }
         }
 
         Commands::InitDb => {
-            let conn: rusqlite::Connection = connect()?;
-            execute_sql_file(&conn, "init.sql")?;
+            let conn: rusqlite::Connection = connect(&db_path)?;
             execute_sql_file(&conn, "seed_taxa_full.sql")?;
             execute_sql_file(&conn, "seed_trips.sql")?;
             execute_sql_file(&conn, "seed_sightings.sql")?;
+            link_locations(&conn)?;
             println!("Database initialized and seeded");
         }
 
+        Commands::ImportEbird { file, dry_run } => {
+            let conn = connect(&db_path)?;
+            let report = import_ebird_file(&conn, &file, dry_run)?;
+
+            if dry_run {
+                println!("Dry run: nothing was written.");
+            }
+            println!("Trips created: {}", report.trips_created);
+            println!("Sightings created: {}", report.sightings_created);
+            if !report.already_imported.is_empty() {
+                println!(
+                    "Skipped {} checklist(s) already imported: {}",
+                    report.already_imported.len(),
+                    report.already_imported.join(", ")
+                );
+            }
+            if !report.unmatched.is_empty() {
+                let rows: usize = report.unmatched.iter().map(|name| name.rows).sum();
+                println!("Unmatched scientific names ({} row(s) skipped):", rows);
+                for name in &report.unmatched {
+                    println!("  {} ({}): {} row(s)", name.scientific_name, name.common_name, name.rows);
+                }
+            }
+        }
+
+        Commands::ExportEbird { trip, taxon, date, output } => {
+            let conn = connect(&db_path)?;
+            let filter = EbirdExportFilter {
+                trip_id: trip,
+                taxon_id: taxon,
+                dates: date.as_deref().map(parse_date_range).transpose()?,
+            };
+
+            let report = match &output {
+                Some(path) => export_ebird_file(&conn, &filter, path)?,
+                None => export_ebird_csv(&conn, &filter, std::io::stdout().lock())?,
+            };
+
+            // Keep stdout clean for the CSV itself
+            eprintln!("Exported {} sighting(s) in {} checklist(s)", report.sightings, report.checklists);
+            if !report.skipped.is_empty() {
+                eprintln!("Skipped {} sighting(s):", report.skipped.len());
+                for skipped in &report.skipped {
+                    eprintln!("  #{} {}: {}", skipped.id, skipped.common_name, skipped.reason);
+                }
+            }
+        }
+
+        Commands::LifeList => {
+            let conn = connect(&db_path)?;
+            let list = life_list(&conn)?;
+            println!("Life list: {} species", list.len());
+            let effort = effort_summary(&conn, None)?;
+            print_effort_summary(&effort);
+            print_species_list(&list, &effort);
+        }
+
+        Commands::YearList { year } => {
+            let conn = connect(&db_path)?;
+            let year = match year {
+                Some(year) => year,
+                None => current_year(&conn)?,
+            };
+            let list = year_list(&conn, &year)?;
+            let new_species = list.iter().filter(|entry| entry.new_species).count();
+            println!("{} year list: {} species ({} new)", year, list.len(), new_species);
+            let effort = effort_summary(&conn, Some(&year))?;
+            print_effort_summary(&effort);
+            print_species_list(&list, &effort);
+        }
+
+        Commands::ExportDwca { output } => {
+            let conn = connect(&db_path)?;
+            let report = export_dwca_file(&conn, &output)?;
+            println!(
+                "Wrote {} occurrence(s) in {} event(s) to {}",
+                report.occurrences,
+                report.events,
+                output.display()
+            );
+        }
+
+        Commands::ImportDwca { file, dry_run } => {
+            let conn = connect(&db_path)?;
+            let report = import_dwca_file(&conn, &file, dry_run)?;
+
+            if dry_run {
+                println!("Dry run: nothing was written.");
+            }
+            println!("Trips created: {}", report.trips_created);
+            println!("Sightings created: {}", report.sightings_created);
+            if report.already_imported > 0 {
+                println!("Skipped {} occurrence(s) already imported", report.already_imported);
+            }
+            if !report.unmatched.is_empty() {
+                let rows: usize = report.unmatched.iter().map(|name| name.rows).sum();
+                println!("Unmatched scientific names ({} occurrence(s) skipped):", rows);
+                for name in &report.unmatched {
+                    println!("  {} ({}): {} occurrence(s)", name.scientific_name, name.common_name, name.rows);
+                }
+            }
+        }
+
+        Commands::ImportPhotos { dir, gap, location, interactive, dry_run } => {
+            if !gap.is_finite() || gap <= 0.0 {
+                bail!("--gap must be a positive number of hours");
+            }
+            let conn = connect(&db_path)?;
+            let mut photos = scan_photos(&conn, &dir)?;
+            if interactive {
+                prompt_photo_taxa(&conn, &mut photos)?;
+            }
+            let report = import_photos(&conn, &photos, (gap * 3600.0).round() as i64, location.as_deref(), dry_run)?;
+
+            if dry_run {
+                println!("Dry run: nothing was written.");
+            }
+            for trip in &report.trips {
+                println!("{} ({} to {})", trip.name, trip.first, trip.last);
+                for (common_name, photos) in &trip.sightings {
+                    println!("  {}: {} photo(s)", common_name, photos);
+                }
+                if !trip.unidentified.is_empty() {
+                    println!("  Unidentified, attached to the trip: {} photo(s)", trip.unidentified.len());
+                    for path in &trip.unidentified {
+                        println!("    {}", path.display());
+                    }
+                }
+            }
+            println!("Trips created: {}", report.trips.len());
+            println!("Sightings created: {}", report.sightings_created);
+            println!("Photos attached: {}", report.photos_attached);
+            if !report.already_imported.is_empty() {
+                println!("Skipped {} photo(s) already imported", report.already_imported.len());
+            }
+            if !report.undated.is_empty() {
+                println!("Skipped {} photo(s) without an EXIF date:", report.undated.len());
+                for path in &report.undated {
+                    println!("  {}", path.display());
+                }
+            }
+        }
+
+        Commands::Migrate { status } => {
+            let conn = open(&db_path)?;
+
+            if status {
+                let version = schema_version(&conn)?;
+                println!("Database: {}", db_path.display());
+                println!("Schema version: {} (latest {})", version, latest_version());
+                for migration in MIGRATIONS {
+                    let state = if migration.version <= version { "applied" } else { "pending" };
+                    println!("  {:>3}  {:<8} {}", migration.version, state, migration.name);
+                }
+
+                let pending = pending_migrations(&conn)?;
+                if !pending.is_empty() {
+                    println!("{} migration(s) pending; run `fast-watcher migrate` to apply", pending.len());
+                }
+            } else {
+                let applied = migrate(&conn)?;
+                for migration in &applied {
+                    println!("Applied migration {}: {}", migration.version, migration.name);
+                }
+                println!("Schema is at version {}", latest_version());
+            }
+        }
+
         Commands::DropDb => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             drop_all_tables(&conn)?;
             println!("All tables dropped. Use with caution!");
         }
 
+        Commands::ResyncDenormalized { check } => {
+            let conn = connect(&db_path)?;
+            let drifted = find_taxonomy_drift(&conn)?;
+
+            if drifted.is_empty() {
+                println!("All sightings match their taxa.");
+            } else {
+                for sighting in &drifted {
+                    println!("Drifted: {}", sighting);
+                }
+
+                if check {
+                    println!("{} sighting(s) out of sync. Run without --check to repair.", drifted.len());
+                } else {
+                    let repaired = resync_denormalized_taxonomy(&conn)?;
+                    println!("Resynced {} sighting(s)", repaired);
+                }
+            }
+        }
+
         // Trip commands
         Commands::AddTrip {
             name,
             date,
             location,
             notes,
+            coords,
+            start,
+            duration,
+            distance,
+            party_size,
+            protocol,
+            complete,
         } => {
-            let conn = connect()?;
+            let effort = Effort {
+                start_time: start,
+                duration_min: duration,
+                distance_km: distance,
+                party_size,
+                protocol,
+                complete,
+            };
+            let conn = connect(&db_path)?;
             let id = create_trip(
                 &conn,
                 &name,
-                date.as_deref(),
+                date.map(|date| date.to_iso()).as_deref(),
                 location.as_deref(),
                 notes.as_deref(),
+                coords,
+                &effort,
             )?;
             println!("Trip created with ID: {}", id);
         }
 
         Commands::ShowTrip { id } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let trip = get_trip_by_id(&conn, id)?;
             println!("{}", trip);
+            if let Some(date) = trip.date {
+                println!("  Date: {} ({})", date, date.precision());
+            }
+            if let Some(location_id) = trip.location_id {
+                println!("  Location ID: {}", location_id);
+            }
+            if let Some(coordinates) = trip.coordinates {
+                println!("  Coordinates: {}", coordinates);
+            }
+            if !trip.effort.is_empty() {
+                println!("  Effort: {}", trip.effort);
+            }
+            print_media(&get_media(&conn, MediaOwner::Trip(id))?);
+        }
+
+        Commands::EditTrip {
+            id,
+            name,
+            date,
+            location,
+            location_id,
+            notes,
+            coords,
+            start,
+            duration,
+            distance,
+            party_size,
+            protocol,
+            complete,
+            incomplete,
+        } => {
+            // An empty value clears the field
+            let coordinates = coords.as_deref().map(parse_coordinates).transpose()?;
+            let start_time = start
+                .map(|start| if start.trim().is_empty() { Ok(String::new()) } else { parse_start_time(&start) })
+                .transpose()?;
+            let protocol = protocol
+                .map(|protocol| if protocol.is_empty() { Ok(None) } else { protocol.parse::<Protocol>().map(Some) })
+                .transpose()?;
+
+            let conn = connect(&db_path)?;
+            let changes = TripUpdate {
+                name,
+                date,
+                location,
+                location_id: location_id.map(Some),
+                notes,
+                coordinates,
+                start_time,
+                duration_min: duration.as_deref().map(|value| parse_clearable(value, "duration")).transpose()?,
+                distance_km: distance.as_deref().map(|value| parse_clearable(value, "distance")).transpose()?,
+                party_size: party_size.as_deref().map(|value| parse_clearable(value, "party size")).transpose()?,
+                protocol,
+                complete: (complete || incomplete).then_some(complete),
+            };
+            let rows = update_trip(&conn, id, &changes)?;
+            if rows > 0 {
+                println!("Trip {} updated", id);
+            } else {
+                println!("Trip {} not found", id);
+            }
         }
 
         Commands::DeleteTrip { id } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let rows = delete_trip(&conn, id)?;
             if rows > 0 {
                 println!("Trip {} deleted", id);
@@ -126,11 +446,12 @@ fn main() -> Result<()> {
             subfamily,
             genus,
             species_epithet,
+            subspecies,
         } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let id = create_taxon(
                 &conn,
-                &rank,
+                rank,
                 &kingdom,
                 phylum.as_deref(),
                 class.as_deref(),
@@ -139,19 +460,85 @@ fn main() -> Result<()> {
                 subfamily.as_deref(),
                 genus.as_deref(),
                 species_epithet.as_deref(),
+                subspecies.as_deref(),
                 &common_name,
             )?;
             println!("Taxon created with ID: {}", id);
         }
 
         Commands::ShowTaxon { id } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let taxon = get_taxon_by_id(&conn, id)?;
             println!("{}", taxon);
+
+            let ancestors = get_ancestors(&conn, id)?;
+            if !ancestors.is_empty() {
+                let lineage: Vec<String> = ancestors.iter().map(|ancestor| ancestor.scientific_name()).collect();
+                println!("  Lineage: {}", lineage.join(" > "));
+            }
+            println!("  Descendants: {}", get_descendants(&conn, id)?.len());
+
+            let children = get_children(&conn, id)?;
+            if !children.is_empty() {
+                println!("== Children ({}) ==", children.len());
+                for child in &children {
+                    println!("{}", child);
+                }
+            }
+            let trips = get_trips_by_taxon(&conn, &taxon)?;
+            if !trips.is_empty() {
+                println!("== Trips ({}) ==", trips.len());
+                for trip in &trips {
+                    println!("{}", trip);
+                }
+            }
+            let sightings = get_sightings_by_taxon(&conn, &taxon)?;
+            if !sightings.is_empty() {
+                println!("== Sightings ({}) ==", sightings.len());
+                for sighting in &sightings {
+                    println!("{}", sighting);
+                }
+            }
+        }
+
+        Commands::EditTaxon {
+            id,
+            rank,
+            kingdom,
+            common_name,
+            phylum,
+            class,
+            order,
+            family,
+            subfamily,
+            genus,
+            species_epithet,
+            subspecies,
+        } => {
+            let conn = connect(&db_path)?;
+            let changes = TaxonUpdate {
+                rank,
+                kingdom,
+                phylum,
+                class,
+                order,
+                family,
+                subfamily,
+                genus,
+                species_epithet,
+                subspecies,
+                common_name,
+            };
+            let rows = update_taxon(&conn, id, &changes)?;
+            if rows > 0 {
+                println!("Taxon {} updated", id);
+            } else {
+                println!("Taxon {} not found", id);
+            }
         }
 
         Commands::DeleteTaxon { id } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let rows = delete_taxon(&conn, id)?;
             if rows > 0 {
                 println!("Taxon {} deleted", id);
@@ -168,28 +555,101 @@ fn main() -> Result<()> {
             media_path,
             date,
             location,
+            count,
+            age_sex,
+            breeding,
+            coords,
         } => {
-            let conn = connect()?;
+            let age_sex = parse_age_sex(age_sex.as_deref().unwrap_or(""))?;
+            let conn = connect(&db_path)?;
             let id = create_sighting(
                 &conn,
                 trip_id,
                 taxon_id,
                 notes.as_deref(),
                 media_path.as_deref(),
-                date.as_deref(),
+                date.map(|date| date.to_iso()).as_deref(),
                 location.as_deref(),
+                count,
+                &age_sex,
+                breeding,
+                coords,
             )?;
             println!("Sighting created with ID: {}", id);
         }
 
         Commands::ShowSighting { id } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let sighting = get_sighting_by_id(&conn, id)?;
             println!("{}", sighting);
+            if let Some(date) = sighting.date {
+                println!("  Date: {} ({})", date, date.precision());
+            }
+            if let Some(count) = sighting.count {
+                println!("  Count: {}", count);
+            }
+            if !sighting.age_sex.is_empty() {
+                println!("  Sex/age: {}", format_age_sex(&sighting.age_sex));
+            }
+            if let Some(code) = sighting.breeding_code {
+                println!("  Breeding: {} ({})", code, code.category());
+            }
+            if let Some(location_id) = sighting.location_id {
+                println!("  Location ID: {}", location_id);
+            }
+            if let Some(coordinates) = sighting.coordinates {
+                println!("  Coordinates: {}", coordinates);
+            }
+            print_media(&get_media(&conn, MediaOwner::Sighting(id))?);
+        }
+
+        Commands::EditSighting {
+            id,
+            trip_id,
+            no_trip,
+            taxon_id,
+            notes,
+            date,
+            location,
+            location_id,
+            count,
+            age_sex,
+            breeding,
+            coords,
+        } => {
+            // An empty value clears the field
+            let count = count
+                .map(|count| if count.is_empty() { Ok(None) } else { count.parse::<Count>().map(Some) })
+                .transpose()?;
+            let breeding_code = breeding
+                .map(|code| if code.is_empty() { Ok(None) } else { code.parse::<BreedingCode>().map(Some) })
+                .transpose()?;
+            let age_sex = age_sex.map(|age_sex| parse_age_sex(&age_sex)).transpose()?;
+            let coordinates = coords.as_deref().map(parse_coordinates).transpose()?;
+
+            let conn = connect(&db_path)?;
+            let changes = SightingUpdate {
+                trip_id: if no_trip { Some(None) } else { trip_id.map(Some) },
+                taxon_id,
+                notes,
+                date,
+                location,
+                location_id: location_id.map(Some),
+                count,
+                age_sex,
+                breeding_code,
+                coordinates,
+            };
+            let rows = update_sighting(&conn, id, &changes)?;
+            if rows > 0 {
+                println!("Sighting {} updated", id);
+            } else {
+                println!("Sighting {} not found", id);
+            }
         }
 
         Commands::DeleteSighting { id } => {
-            let conn = connect()?;
+            let conn = connect(&db_path)?;
             let rows = delete_sighting(&conn, id)?;
             if rows > 0 {
                 println!("Sighting {} deleted", id);
@@ -197,7 +657,309 @@ fn main() -> Result<()> {
                 println!("Sighting {} not found", id);
             }
         }
+
+        // Media commands
+        Commands::AddMedia { path, sighting, trip, caption, captured } => {
+            let owner = sighting
+                .map(MediaOwner::Sighting)
+                .or(trip.map(MediaOwner::Trip))
+                .context("pass --sighting or --trip")?;
+            let conn = connect(&db_path)?;
+            let id = add_media(&conn, owner, &path, caption.as_deref(), captured.as_deref())?;
+            println!("Media attached with ID: {}", id);
+        }
+
+        Commands::RemoveMedia { id } => {
+            let conn = connect(&db_path)?;
+            let rows = remove_media(&conn, id)?;
+            if rows > 0 {
+                println!("Media {} removed", id);
+            } else {
+                println!("Media {} not found", id);
+            }
+        }
+
+        Commands::MoveMedia { id, path } => {
+            let conn = connect(&db_path)?;
+            let media = relocate_media(&conn, id, &path)?;
+            println!("Media {} moved to {}", id, media.path);
+        }
+
+        Commands::MoveMediaDir { from, to } => {
+            let conn = connect(&db_path)?;
+            let moved = relocate_media_dir(&conn, &from, &to)?;
+            println!("Moved {} media file(s) from {} to {}", moved, from, to);
+        }
+
+        Commands::CheckMedia { search, fix } => {
+            let conn = connect(&db_path)?;
+            let issues = check_media(&conn, &search)?;
+            if issues.is_empty() {
+                println!("All media files are in place.");
+            }
+            for issue in issues {
+                match issue.problem {
+                    MediaProblem::Missing => println!("Missing: {}", issue.media),
+                    MediaProblem::Changed => println!("Changed: {}", issue.media),
+                    MediaProblem::Unreadable(why) => println!("Unreadable: {} ({})", issue.media, why),
+                    MediaProblem::Moved(path) => {
+                        println!("Moved: {} -> {}", issue.media, path);
+                        if fix {
+                            relocate_media(&conn, issue.media.id, &path)?;
+                            println!("  fixed");
+                        }
+                    }
+                }
+            }
+        }
+
+        // Location commands
+        Commands::AddLocation {
+            locality,
+            county,
+            state,
+            country,
+            coords,
+            hotspot,
+        } => {
+            let conn = connect(&db_path)?;
+            let id = create_location(
+                &conn,
+                &locality,
+                county.as_deref(),
+                state.as_deref(),
+                country.as_deref(),
+                coords,
+                hotspot,
+            )?;
+            println!("Location created with ID: {}", id);
+        }
+
+        Commands::ShowLocation { id } => {
+            let conn = connect(&db_path)?;
+            let location = get_location_by_id(&conn, id)?;
+            println!("{}", location);
+            if let Some(coordinates) = location.coordinates {
+                println!("  Coordinates: {}", coordinates);
+            }
+
+            let trips = get_trips_by_location_id(&conn, id)?;
+            if !trips.is_empty() {
+                println!("== Trips ({}) ==", trips.len());
+                for trip in &trips {
+                    println!("{}", trip);
+                }
+            }
+            let sightings = get_sightings_by_location_id(&conn, id)?;
+            if !sightings.is_empty() {
+                println!("== Sightings ({}) ==", sightings.len());
+                for sighting in &sightings {
+                    println!("{}", sighting);
+                }
+            }
+        }
+
+        Commands::ListLocations => {
+            let conn = connect(&db_path)?;
+            for location in get_all_locations(&conn)? {
+                println!("{}", location);
+            }
+        }
+
+        Commands::EditLocation {
+            id,
+            locality,
+            county,
+            state,
+            country,
+            coords,
+            hotspot,
+        } => {
+            let coordinates = coords.as_deref().map(parse_coordinates).transpose()?;
+            let conn = connect(&db_path)?;
+            let changes = LocationUpdate {
+                locality,
+                county,
+                state,
+                country,
+                coordinates,
+                hotspot,
+            };
+            let rows = update_location(&conn, id, &changes)?;
+            if rows > 0 {
+                println!("Location {} updated", id);
+            } else {
+                println!("Location {} not found", id);
+            }
+        }
+
+        Commands::DeleteLocation { id } => {
+            let conn = connect(&db_path)?;
+            let rows = delete_location(&conn, id)?;
+            if rows > 0 {
+                println!("Location {} deleted", id);
+            } else {
+                println!("Location {} not found", id);
+            }
+        }
+
+        Commands::MergeLocations { keep, duplicates } => {
+            let conn = connect(&db_path)?;
+            let moved = merge_locations(&conn, keep, &duplicates)?;
+            println!("Merged into location {}: moved {} trip(s) and sighting(s)", keep, moved);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_media(media: &[Media]) {
+    if !media.is_empty() {
+        println!("  Media:");
     }
+    for media in media {
+        println!("    {}", media);
+    }
+}
 
+/// Ask on stdin for the taxon of each dated photo that has none. A blank answer leaves
+/// it unidentified and "=" repeats the previous answer; end of input stops asking.
+fn prompt_photo_taxa(conn: &rusqlite::Connection, photos: &mut [Photo]) -> Result<()> {
+    use std::io::{BufRead, Write};
+
+    let mut lines = std::io::stdin().lock().lines();
+    let mut previous: Option<models::Taxon> = None;
+    for photo in photos.iter_mut().filter(|photo| photo.taxon.is_none() && photo.taken_at.is_some()) {
+        loop {
+            match photo.label {
+                Some(ref label) => print!("{} (no taxon named \"{}\") taxon? ", photo.path.display(), label),
+                None => print!("{} taxon? ", photo.path.display()),
+            }
+            std::io::stdout().flush()?;
+
+            let Some(line) = lines.next().transpose()? else {
+                println!();
+                return Ok(());
+            };
+            let answer = line.trim();
+            if answer.is_empty() {
+                break;
+            }
+            if answer == "=" {
+                photo.taxon = previous.clone();
+                break;
+            }
+            match find_taxon_by_name(conn, answer)? {
+                Some(taxon) => {
+                    println!("  {}", taxon.common_name);
+                    previous = Some(taxon.clone());
+                    photo.taxon = Some(taxon);
+                    break;
+                }
+                None => println!("  No taxon named \"{}\"", answer),
+            }
+        }
+    }
     Ok(())
 }
+
+fn print_hits<T: std::fmt::Display>(hits: &[SearchHit<T>]) {
+    if hits.is_empty() {
+        println!("No matches found.");
+        return;
+    }
+
+    for hit in hits {
+        println!("{}", hit.item);
+        // filter-only queries have no text to excerpt
+        if !hit.snippet.is_empty() {
+            println!("    {} (score {:.2})", hit.snippet, hit.score);
+        }
+    }
+}
+
+/// Parses a `--coords` value for an edit; an empty value clears the coordinates
+fn parse_coordinates(value: &str) -> Result<Option<Coordinates>> {
+    if value.trim().is_empty() {
+        Ok(None)
+    } else {
+        value.parse().map(Some)
+    }
+}
+
+/// Parses a number for an edit; an empty value clears it
+fn parse_clearable<T: std::str::FromStr>(value: &str, name: &str) -> Result<Option<T>> {
+    match value.trim() {
+        "" => Ok(None),
+        value => value.parse().map(Some).map_err(|_| anyhow::anyhow!("invalid {} '{}'", name, value)),
+    }
+}
+
+fn print_nearby<T: std::fmt::Display>(title: &str, hits: &[NearbyHit<T>]) {
+    if !hits.is_empty() {
+        println!("== {} ({}) ==", title, hits.len());
+        for hit in hits {
+            println!("{}", hit.item);
+            match hit.distance_km {
+                Some(distance) => println!("    {} ({:.2} km away)", hit.position, distance),
+                None => println!("    {}", hit.position),
+            }
+        }
+    }
+}
+
+fn print_section<T: std::fmt::Display>(title: &str, hits: &[SearchHit<T>]) {
+    if !hits.is_empty() {
+        println!("== {} ({}) ==", title, hits.len());
+        print_hits(hits);
+    }
+}
+
+/// One line per species: number, names, first/last dates and sighting count; "*" marks a new species
+fn print_effort_summary(effort: &EffortSummary) {
+    let mut summary = format!("{} trip(s), {} complete checklist(s)", effort.trips, effort.complete_checklists);
+    if effort.minutes > 0 {
+        summary.push_str(&format!(", {}h {:02}m", effort.minutes / 60, effort.minutes % 60));
+    }
+    if effort.distance_km > 0.0 {
+        summary.push_str(&format!(", {:.1} km", effort.distance_km));
+    }
+    println!("Effort: {}", summary);
+}
+
+/// Species on complete checklists also show how often they were reported on them
+fn print_species_list(list: &[ListEntry], effort: &EffortSummary) {
+    let width = list.len().to_string().len();
+    for (index, entry) in list.iter().enumerate() {
+        let dates = match (&entry.first_date, &entry.last_date) {
+            (Some(first), Some(last)) if first != last => format!("{} to {}", first, last),
+            (Some(first), _) => first.to_string(),
+            _ => "undated".to_string(),
+        };
+        let mut summary = format!("{}, {} sighting(s)", dates, entry.sightings);
+        if entry.individuals > 0 {
+            summary.push_str(&format!(", {} counted", entry.individuals));
+        }
+        if let Some(code) = entry.breeding_code {
+            summary.push_str(&format!(", breeding {} ({})", code.code(), code.category()));
+        }
+        if entry.complete_checklists > 0 {
+            summary.push_str(&format!(
+                ", on {} of {} complete checklist(s) ({:.0}%)",
+                entry.complete_checklists,
+                effort.complete_checklists,
+                100.0 * entry.complete_checklists as f64 / effort.complete_checklists as f64
+            ));
+        }
+        println!(
+            "{:>width$}. {}{} ({}) [taxon {}]  {}",
+            index + 1,
+            if entry.new_species { "* " } else { "" },
+            entry.common_name,
+            entry.scientific_name,
+            entry.taxon_id,
+            summary,
+            width = width
+        );
+    }
+}
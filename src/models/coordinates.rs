@@ -0,0 +1,107 @@
+use anyhow::{Result, anyhow, bail};
+use std::fmt;
+use std::str::FromStr;
+
+/// Mean Earth radius used for distances
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// A WGS84 point, with how far off it may be
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Accuracy radius in meters, if known
+    pub accuracy_m: Option<f64>,
+}
+
+impl Coordinates {
+    pub fn new(latitude: f64, longitude: f64, accuracy_m: Option<f64>) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            bail!("latitude {} is out of range (-90 to 90)", latitude);
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            bail!("longitude {} is out of range (-180 to 180)", longitude);
+        }
+        if accuracy_m.is_some_and(|accuracy| !(accuracy >= 0.0 && accuracy.is_finite())) {
+            bail!("accuracy must be a non-negative number of meters");
+        }
+        Ok(Coordinates { latitude, longitude, accuracy_m })
+    }
+
+    /// Great-circle (haversine) distance in kilometers
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+impl fmt::Display for Coordinates {
+    /// "40.78250, -73.96550 (±30 m)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.5}, {:.5}", self.latitude, self.longitude)?;
+        if let Some(accuracy) = self.accuracy_m {
+            write!(f, " (±{} m)", accuracy)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Coordinates {
+    type Err = anyhow::Error;
+
+    /// "LAT,LON" or "LAT,LON,ACCURACY_M"; the Display form also parses
+    fn from_str(s: &str) -> Result<Self> {
+        let cleaned = s.replace(['(', ')', '±'], " ").replace(" m", " ");
+        let parts: Vec<&str> = cleaned
+            .split([',', ' '])
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect();
+        let number = |part: &str| {
+            part.parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(|| anyhow!("invalid coordinates '{}' (expected LAT,LON or LAT,LON,ACCURACY_M)", s.trim()))
+        };
+
+        match parts.as_slice() {
+            [latitude, longitude] => Coordinates::new(number(latitude)?, number(longitude)?, None),
+            [latitude, longitude, accuracy] => {
+                Coordinates::new(number(latitude)?, number(longitude)?, Some(number(accuracy)?))
+            }
+            _ => bail!("invalid coordinates '{}' (expected LAT,LON or LAT,LON,ACCURACY_M)", s.trim()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coordinates() {
+        let point: Coordinates = "40.7825,-73.9655".parse().unwrap();
+        assert_eq!(point, Coordinates { latitude: 40.7825, longitude: -73.9655, accuracy_m: None });
+
+        let point: Coordinates = "40.7825, -73.9655, 30".parse().unwrap();
+        assert_eq!(point.accuracy_m, Some(30.0));
+        assert_eq!(point.to_string(), "40.78250, -73.96550 (±30 m)");
+        assert_eq!(point.to_string().parse::<Coordinates>().unwrap(), point);
+
+        for bad in ["", "40.7", "91,0", "0,181", "0,0,-5", "north,west", "1,2,3,4", "NaN,0"] {
+            assert!(bad.parse::<Coordinates>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_distance_km() {
+        let central_park = Coordinates::new(40.7825, -73.9655, None).unwrap();
+        let prospect_park = Coordinates::new(40.6602, -73.9690, None).unwrap();
+        let distance = central_park.distance_km(&prospect_park);
+        assert!((distance - 13.6).abs() < 0.1, "{}", distance);
+        assert_eq!(central_park.distance_km(&central_park), 0.0);
+    }
+}
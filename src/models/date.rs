@@ -0,0 +1,409 @@
+use crate::models::parse_start_time;
+use anyhow::{Result, anyhow, bail};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// How much of an [`ObservedDate`] is known, from coarsest to finest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DatePrecision {
+    Year,
+    Season,
+    Month,
+    Day,
+    /// A day and a time of day, to the minute
+    Time,
+}
+
+impl fmt::Display for DatePrecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DatePrecision::Year => "year",
+            DatePrecision::Season => "season",
+            DatePrecision::Month => "month",
+            DatePrecision::Day => "day",
+            DatePrecision::Time => "time",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A meteorological season in the northern hemisphere. Winter runs from December
+/// into the next year, so "winter 1998" is December 1998 to February 1999.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [Season::Spring, Season::Summer, Season::Autumn, Season::Winter];
+
+    fn first_month(self) -> u8 {
+        match self {
+            Season::Spring => 3,
+            Season::Summer => 6,
+            Season::Autumn => 9,
+            Season::Winter => 12,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Season> {
+        match name.to_lowercase().as_str() {
+            "fall" => Some(Season::Autumn),
+            name => Season::ALL.into_iter().find(|season| season.name() == name),
+        }
+    }
+}
+
+/// A time of day to the minute, with the UTC offset it was recorded in when known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+    /// Minutes east of UTC
+    pub utc_offset: Option<i16>,
+}
+
+/// When something was seen, to the precision it is known: a year, a season, a month,
+/// a day, or a day and time.
+///
+/// Stored as ISO 8601 text (`1998`, `1998-05`, `1998-05-14`, `1998-05-14T07:30`,
+/// `1998-05-14T07:30-05:00`), with a season as its months (`1998-03/1998-05`), so stored
+/// dates sort in order and a year or month prefix selects everything within it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObservedDate {
+    year: u16,
+    season: Option<Season>,
+    month: Option<u8>,
+    day: Option<u8>,
+    time: Option<TimeOfDay>,
+}
+
+impl ObservedDate {
+    pub fn precision(self) -> DatePrecision {
+        if self.time.is_some() {
+            DatePrecision::Time
+        } else if self.day.is_some() {
+            DatePrecision::Day
+        } else if self.month.is_some() {
+            DatePrecision::Month
+        } else if self.season.is_some() {
+            DatePrecision::Season
+        } else {
+            DatePrecision::Year
+        }
+    }
+
+    /// The stored form (see the type docs)
+    pub fn to_iso(self) -> String {
+        if let Some(season) = self.season {
+            let first = season.first_month();
+            let (end_year, end_month) = if first == 12 { (self.year + 1, 2) } else { (self.year, first + 2) };
+            return format!("{:04}-{:02}/{:04}-{:02}", self.year, first, end_year, end_month);
+        }
+
+        let mut iso = format!("{:04}", self.year);
+        if let Some(month) = self.month {
+            iso.push_str(&format!("-{:02}", month));
+        }
+        if let Some(day) = self.day {
+            iso.push_str(&format!("-{:02}", day));
+        }
+        if let Some(time) = self.time {
+            iso.push_str(&format!("T{:02}:{:02}", time.hour, time.minute));
+            if let Some(offset) = time.utc_offset {
+                let sign = if offset < 0 { '-' } else { '+' };
+                iso.push_str(&format!("{}{:02}:{:02}", sign, offset.abs() / 60, offset.abs() % 60));
+            }
+        }
+        iso
+    }
+
+    /// `YYYY[-MM[-DD]]`, checking the month and day exist
+    fn parse_calendar(text: &str, original: &str) -> Result<ObservedDate> {
+        let parts: Vec<&str> = text.split('-').collect();
+        let widths_ok = parts.len() <= 3
+            && parts.iter().zip([4, 2, 2]).all(|(part, width)| part.len() == width && part.chars().all(|c| c.is_ascii_digit()));
+        if !widths_ok {
+            return Err(invalid(original));
+        }
+
+        let numbers: Vec<u16> = parts.iter().map(|part| part.parse().unwrap()).collect();
+        let year = numbers[0];
+        let month = numbers.get(1).map(|&month| month as u8);
+        let day = numbers.get(2).map(|&day| day as u8);
+
+        if let Some(month) = month {
+            if !(1..=12).contains(&month) {
+                bail!("invalid date '{}': there is no month {}", original, month);
+            }
+            if let Some(day) = day {
+                let last = days_in_month(year, month);
+                if !(1..=last).contains(&day) {
+                    bail!("invalid date '{}': {:04}-{:02} has {} days", original, year, month, last);
+                }
+            }
+        }
+
+        Ok(ObservedDate { year, season: None, month, day, time: None })
+    }
+
+    /// "spring 1998", or its stored form "1998-03/1998-05"
+    fn parse_season(text: &str, original: &str) -> Result<Option<ObservedDate>> {
+        if let Some((start, _)) = text.split_once('/') {
+            let start = ObservedDate::parse_calendar(start, original)?;
+            let season = Season::ALL.into_iter().find(|&season| {
+                start.day.is_none() && start.month == Some(season.first_month())
+            });
+            let date = season.map(|season| ObservedDate { season: Some(season), month: None, ..start });
+            return match date {
+                Some(date) if date.to_iso() == text => Ok(Some(date)),
+                _ => bail!("invalid date '{}': only a season's months can be given as a range, e.g. 1998-03/1998-05", original),
+            };
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let [first, second] = words.as_slice() else {
+            return Ok(None);
+        };
+        let (season, year) = match (Season::from_name(first), Season::from_name(second)) {
+            (Some(season), _) => (season, *second),
+            (_, Some(season)) => (season, *first),
+            _ => return Ok(None),
+        };
+        if year.len() != 4 || !year.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid(original));
+        }
+        Ok(Some(ObservedDate { year: year.parse()?, season: Some(season), month: None, day: None, time: None }))
+    }
+}
+
+/// `HH:MM[:SS]` or a 12-hour clock, then an optional `Z` or `±HH[:MM]` offset
+fn parse_time(text: &str, original: &str) -> Result<TimeOfDay> {
+    let text = text.trim();
+    let (clock, utc_offset) = if let Some(clock) = text.strip_suffix('Z') {
+        (clock, Some(0))
+    } else if let Some(at) = text.rfind(['+', '-']) {
+        (&text[..at], Some(parse_utc_offset(&text[at..], original)?))
+    } else {
+        (text, None)
+    };
+
+    // Seconds (and any fraction of one) are dropped, keeping an AM/PM after them; times
+    // are kept to the minute
+    let not_a_time = || anyhow!("invalid date '{}': '{}' is not a time of day", original, text);
+    let clock = clock.trim();
+    let clock = match clock.splitn(3, ':').collect::<Vec<_>>().as_slice() {
+        [hour, minute, rest] => {
+            let seconds = rest.get(..2).filter(|seconds| seconds.chars().all(|c| c.is_ascii_digit()) && *seconds <= "59");
+            let seconds = seconds.ok_or_else(not_a_time)?;
+            let rest = &rest[seconds.len()..];
+            let meridiem = match rest.strip_prefix('.') {
+                Some(fraction) => fraction.trim_start_matches(|c: char| c.is_ascii_digit()),
+                None => rest,
+            };
+            format!("{}:{}{}", hour, minute, meridiem)
+        }
+        _ => clock.to_string(),
+    };
+    let clock = parse_start_time(&clock).map_err(|_| not_a_time())?;
+    let (hour, minute) = clock.split_once(':').unwrap();
+
+    Ok(TimeOfDay { hour: hour.parse()?, minute: minute.parse()?, utc_offset })
+}
+
+/// `±HH`, `±HHMM` or `±HH:MM` up to ±14:00, as minutes east of UTC
+fn parse_utc_offset(text: &str, original: &str) -> Result<i16> {
+    let bad = || anyhow!("invalid date '{}': '{}' is not a UTC offset (expected e.g. -05:00)", original, text);
+    let sign = if text.starts_with('-') { -1 } else { 1 };
+    let digits = text[1..].replace(':', "");
+    if !matches!(digits.len(), 2 | 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(bad());
+    }
+    let hours: i16 = digits[..2].parse()?;
+    let minutes: i16 = digits.get(2..).filter(|m| !m.is_empty()).map_or(Ok(0), str::parse)?;
+    if minutes > 59 || hours * 60 + minutes > 14 * 60 {
+        return Err(bad());
+    }
+    Ok(sign * (hours * 60 + minutes))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn invalid(text: &str) -> anyhow::Error {
+    anyhow!(
+        "invalid date '{}' (expected YYYY-MM-DD, YYYY-MM, YYYY or a season like 'spring 1998', \
+         optionally with a time like 2025-05-14T07:30 or '2025-05-14 7:30 PM -05:00')",
+        text
+    )
+}
+
+impl FromStr for ObservedDate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let text = s.trim();
+        if text.is_empty() {
+            bail!("date cannot be empty");
+        }
+        if let Some(date) = ObservedDate::parse_season(text, s.trim())? {
+            return Ok(date);
+        }
+
+        let (calendar, time) = match text.find(['T', ' ']) {
+            Some(at) => (&text[..at], Some(&text[at + 1..])),
+            None => (text, None),
+        };
+        let mut date = ObservedDate::parse_calendar(calendar, text)?;
+        if let Some(time) = time {
+            if date.day.is_none() {
+                bail!("invalid date '{}': a time of day needs a full date (YYYY-MM-DD)", text);
+            }
+            date.time = Some(parse_time(time, text)?);
+        }
+        Ok(date)
+    }
+}
+
+/// The stored form, except a season reads "spring 1998"
+impl fmt::Display for ObservedDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.season {
+            Some(season) => write!(f, "{} {}", season.name(), self.year),
+            None => write!(f, "{}", self.to_iso()),
+        }
+    }
+}
+
+/// Chronological by start (a season starts at its first month), coarser dates first when
+/// two start together, so spring 1998 comes before 1998-03
+impl Ord for ObservedDate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let start = |date: &ObservedDate| match date.season {
+            Some(season) => format!("{:04}-{:02}", date.year, season.first_month()),
+            None => date.to_iso(),
+        };
+        start(self).cmp(&start(other)).then(self.precision().cmp(&other.precision()))
+    }
+}
+
+impl PartialOrd for ObservedDate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iso(text: &str) -> String {
+        text.parse::<ObservedDate>().unwrap().to_iso()
+    }
+
+    #[test]
+    fn test_parse_dates_and_times() {
+        assert_eq!(iso("2025"), "2025");
+        assert_eq!(iso("2025-05"), "2025-05");
+        assert_eq!(iso(" 2024-02-29 "), "2024-02-29");
+        assert_eq!(iso("2025-05-14T07:30"), "2025-05-14T07:30");
+        assert_eq!(iso("2025-05-14 07:30:59"), "2025-05-14T07:30");
+        assert_eq!(iso("2025-05-14 7:30 PM"), "2025-05-14T19:30");
+        assert_eq!(iso("2025-05-14T07:30Z"), "2025-05-14T07:30+00:00");
+        assert_eq!(iso("2025-05-14T07:30:00-0500"), "2025-05-14T07:30-05:00");
+        assert_eq!(iso("2025-05-14 7:30 AM +05:30"), "2025-05-14T07:30+05:30");
+        assert_eq!(iso("2025-05-14T07:30+14:00"), "2025-05-14T07:30+14:00");
+        assert_eq!(iso("2025-05-14T07:30:15-12:00"), "2025-05-14T07:30-12:00");
+        assert_eq!(iso("2025-05-14 7:30:15 PM"), "2025-05-14T19:30");
+        assert_eq!(iso("2025-05-14 7:30:15pm -05:00"), "2025-05-14T19:30-05:00");
+        assert_eq!(iso("2025-05-14T07:30:15.250Z"), "2025-05-14T07:30+00:00");
+
+        let date: ObservedDate = "2025-05-14T07:30-05:00".parse().unwrap();
+        assert_eq!(date.precision(), DatePrecision::Time);
+        assert_eq!(date.to_string(), "2025-05-14T07:30-05:00");
+        assert_eq!("2025-05".parse::<ObservedDate>().unwrap().precision(), DatePrecision::Month);
+        assert_eq!("2025".parse::<ObservedDate>().unwrap().precision(), DatePrecision::Year);
+    }
+
+    #[test]
+    fn test_parse_seasons() {
+        let spring: ObservedDate = "spring 1998".parse().unwrap();
+        assert_eq!(spring.precision(), DatePrecision::Season);
+        assert_eq!(spring.to_iso(), "1998-03/1998-05");
+        assert_eq!(spring.to_string(), "spring 1998");
+        assert_eq!(iso("Fall 1998"), "1998-09/1998-11");
+        assert_eq!(iso("1998 winter"), "1998-12/1999-02");
+
+        // The stored form reads back as the same season
+        assert_eq!("1998-12/1999-02".parse::<ObservedDate>().unwrap().to_string(), "winter 1998");
+        assert!("1998-03/1998-04".parse::<ObservedDate>().is_err());
+        assert!("1998-04/1998-06".parse::<ObservedDate>().is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_dates() {
+        for bad in [
+            "",
+            "yesterday",
+            "15/01/2025",
+            "2025-1-5",
+            "98",
+            "2025-13",
+            "2025-00-10",
+            "spring",
+            "spring 98",
+            "2025-05-14T07:30+14:30",
+            "2025-05-14T07:30+16:00",
+            "2025-05-14 7:30:xx",
+            "2025-05-14T07:30:99",
+            "2025-05-14T07:30:5",
+            "2025-05-14T07:30:15 junk",
+        ] {
+            assert!(bad.parse::<ObservedDate>().is_err(), "{}", bad);
+        }
+
+        let error = |text: &str| text.parse::<ObservedDate>().unwrap_err().to_string();
+        assert!(error("yesterday").starts_with("invalid date 'yesterday' (expected YYYY-MM-DD"));
+        assert_eq!(error("2025-02-29"), "invalid date '2025-02-29': 2025-02 has 28 days");
+        assert_eq!(error("2025-04-31"), "invalid date '2025-04-31': 2025-04 has 30 days");
+        assert!(error("2025-05T07:30").contains("needs a full date"));
+        assert!(error("2025-05-14T25:00").contains("is not a time of day"));
+        assert!(error("2025-05-14T07:30+15:00").contains("is not a UTC offset"));
+    }
+
+    #[test]
+    fn test_order_follows_time() {
+        let mut dates: Vec<ObservedDate> = ["2025-05-14T07:30", "2024", "2025-05", "spring 2025", "2025-05-14", "2025-02"]
+            .into_iter()
+            .map(|text| text.parse().unwrap())
+            .collect();
+        dates.sort();
+        let sorted: Vec<String> = dates.iter().map(ToString::to_string).collect();
+        assert_eq!(sorted, ["2024", "2025-02", "spring 2025", "2025-05", "2025-05-14", "2025-05-14T07:30"]);
+
+        // A season and the month it starts with: the coarser season first
+        let spring: ObservedDate = "spring 1998".parse().unwrap();
+        let march: ObservedDate = "1998-03".parse().unwrap();
+        assert!(spring < march);
+        assert!(march < "1998-03-01".parse().unwrap());
+    }
+}
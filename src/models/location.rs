@@ -0,0 +1,39 @@
+use crate::models::Coordinates;
+use std::fmt;
+
+/// A place trips and sightings happen at: a locality inside an optional county, state and country
+#[derive(Debug)]
+pub struct Location {
+    pub id: i64,
+    /// Locality and every filled-in level above it, e.g. "Central Park, New York, NY, USA"
+    pub name: String,
+    pub locality: String,
+    pub county: Option<String>,
+    pub state: Option<String>,
+    pub country: Option<String>,
+    pub coordinates: Option<Coordinates>,
+    /// A shared birding spot (e.g. an eBird hotspot) rather than a private one
+    pub hotspot: bool,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.id, self.name)?;
+        if self.hotspot {
+            write!(f, " [hotspot]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Fields to change on an existing location. `None` leaves a field untouched;
+/// an empty string clears the county, state or country and `Some(None)` clears the coordinates.
+#[derive(Debug, Default)]
+pub struct LocationUpdate {
+    pub locality: Option<String>,
+    pub county: Option<String>,
+    pub state: Option<String>,
+    pub country: Option<String>,
+    pub coordinates: Option<Option<Coordinates>>,
+    pub hotspot: Option<bool>,
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+
+/// What a media file is attached to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaOwner {
+    Sighting(i64),
+    Trip(i64),
+}
+
+/// A photo, recording or video attached to a sighting or a trip
+#[derive(Debug, Clone)]
+pub struct Media {
+    pub id: i64,
+    pub owner: MediaOwner,
+    pub path: String,
+    /// SHA-256 of the file's contents, in hex; `None` if it couldn't be read when attached
+    pub content_hash: Option<String>,
+    pub mime_type: String,
+    pub captured_at: Option<String>,
+    pub caption: Option<String>,
+}
+
+impl Media {
+    /// "image", "audio", "video", or "application" when the type isn't known
+    pub fn kind(&self) -> &str {
+        self.mime_type.split('/').next().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.id, self.path, self.mime_type)?;
+        if let Some(ref captured_at) = self.captured_at {
+            write!(f, " {}", captured_at)?;
+        }
+        if let Some(ref caption) = self.caption {
+            write!(f, " - {}", caption)?;
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,20 @@
+pub mod coordinates;
+pub mod date;
+pub mod location;
+pub mod media;
+pub mod observation;
 pub mod sighting;
 pub mod taxon;
 pub mod trip;
 
 // optional re-exports so you can just `use crate::models::Sighting;`
-pub use sighting::Sighting;
-pub use taxon::Taxon;
-pub use trip::Trip;
+pub use coordinates::Coordinates;
+pub use date::{DatePrecision, ObservedDate};
+pub use location::{Location, LocationUpdate};
+pub use media::{Media, MediaOwner};
+pub use observation::{
+    Age, AgeSexCount, BreedingCode, Count, Sex, format_age_sex, parse_age_sex,
+};
+pub use sighting::{Sighting, SightingUpdate};
+pub use taxon::{Rank, Taxon, TaxonUpdate};
+pub use trip::{Effort, Protocol, Trip, TripUpdate, parse_start_time};
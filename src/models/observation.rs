@@ -0,0 +1,339 @@
+use anyhow::{Result, anyhow, bail};
+use std::fmt;
+use std::str::FromStr;
+
+/// How many birds a sighting records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Count {
+    Exact(u32),
+    /// Present but not counted (eBird's "X")
+    Present,
+}
+
+impl Count {
+    /// The number counted, if any
+    pub fn exact(self) -> Option<u32> {
+        match self {
+            Count::Exact(n) => Some(n),
+            Count::Present => None,
+        }
+    }
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Count::Exact(n) => write!(f, "{}", n),
+            Count::Present => write!(f, "X"),
+        }
+    }
+}
+
+impl FromStr for Count {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("x") {
+            return Ok(Count::Present);
+        }
+        s.parse::<u32>()
+            .map(Count::Exact)
+            .map_err(|_| anyhow!("invalid count '{}' (expected a number or X)", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Sex {
+    Male,
+    Female,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Age {
+    Adult,
+    Immature,
+    Juvenile,
+    Unknown,
+}
+
+impl Sex {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+            Sex::Unknown => "unknown",
+        }
+    }
+}
+
+impl Age {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Age::Adult => "adult",
+            Age::Immature => "immature",
+            Age::Juvenile => "juvenile",
+            Age::Unknown => "unknown",
+        }
+    }
+}
+
+/// One cell of a sighting's sex/age breakdown, e.g. 2 adult males
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgeSexCount {
+    pub sex: Sex,
+    pub age: Age,
+    pub count: u32,
+}
+
+impl fmt::Display for AgeSexCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.count)?;
+        if self.sex != Sex::Unknown {
+            write!(f, " {}", self.sex.as_str())?;
+        }
+        if self.age != Age::Unknown {
+            write!(f, " {}", self.age.as_str())?;
+        }
+        if self.sex == Sex::Unknown && self.age == Age::Unknown {
+            write!(f, " unknown")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a breakdown like "2 male adult, 1 female juvenile, 3 immature". Sex and age
+/// words may come in either order and default to unknown; repeated cells are added up.
+pub fn parse_age_sex(text: &str) -> Result<Vec<AgeSexCount>> {
+    let mut cells: Vec<AgeSexCount> = Vec::new();
+
+    for part in text.split([',', ';']).map(str::trim).filter(|part| !part.is_empty()) {
+        let mut words = part.split_whitespace();
+        let count = words
+            .next()
+            .and_then(|word| word.parse::<u32>().ok())
+            .filter(|count| *count > 0)
+            .ok_or_else(|| anyhow!("'{}' should start with a count, e.g. '2 male adult'", part))?;
+
+        let (mut sex, mut age) = (None, None);
+        for word in words {
+            let (slot, value) = match word.to_lowercase().as_str() {
+                "male" | "males" | "m" => (&mut sex, Some((Sex::Male, Age::Unknown))),
+                "female" | "females" | "f" => (&mut sex, Some((Sex::Female, Age::Unknown))),
+                "adult" | "adults" | "ad" => (&mut age, Some((Sex::Unknown, Age::Adult))),
+                "immature" | "immatures" | "imm" => (&mut age, Some((Sex::Unknown, Age::Immature))),
+                "juvenile" | "juveniles" | "juv" => (&mut age, Some((Sex::Unknown, Age::Juvenile))),
+                "unknown" => continue,
+                _ => bail!("unknown sex or age '{}' (use male, female, adult, immature or juvenile)", word),
+            };
+            if slot.is_some() {
+                bail!("'{}' gives more than one sex or age", part);
+            }
+            *slot = value;
+        }
+
+        let sex = sex.map_or(Sex::Unknown, |(sex, _)| sex);
+        let age = age.map_or(Age::Unknown, |(_, age)| age);
+        match cells.iter_mut().find(|cell| cell.sex == sex && cell.age == age) {
+            Some(cell) => cell.count += count,
+            None => cells.push(AgeSexCount { sex, age, count }),
+        }
+    }
+
+    cells.sort_by_key(|cell| (cell.sex, cell.age));
+    Ok(cells)
+}
+
+/// The canonical form `parse_age_sex` reads back
+pub fn format_age_sex(cells: &[AgeSexCount]) -> String {
+    cells.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Strength of breeding evidence, strongest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BreedingCategory {
+    Confirmed,
+    Probable,
+    Possible,
+    Observed,
+}
+
+impl fmt::Display for BreedingCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BreedingCategory::Confirmed => "Confirmed",
+            BreedingCategory::Probable => "Probable",
+            BreedingCategory::Possible => "Possible",
+            BreedingCategory::Observed => "Observed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// eBird breeding and atlas codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreedingCode {
+    NestWithYoung,
+    NestWithEggs,
+    CarryingFecalSac,
+    FeedingYoung,
+    CarryingFood,
+    RecentlyFledged,
+    OccupiedNest,
+    UsedNest,
+    DistractionDisplay,
+    NestBuilding,
+    CarryingNestingMaterial,
+    PhysiologicalEvidence,
+    CavityNestBuilding,
+    AgitatedBehavior,
+    VisitingProbableNestSite,
+    Courtship,
+    TerritorialDefense,
+    Pair,
+    MultipleSinging,
+    SingingSevenDays,
+    Singing,
+    InHabitat,
+    Flyover,
+}
+
+impl BreedingCode {
+    /// Every code, strongest evidence first
+    pub const ALL: [BreedingCode; 23] = [
+        BreedingCode::NestWithYoung,
+        BreedingCode::NestWithEggs,
+        BreedingCode::CarryingFecalSac,
+        BreedingCode::FeedingYoung,
+        BreedingCode::CarryingFood,
+        BreedingCode::RecentlyFledged,
+        BreedingCode::OccupiedNest,
+        BreedingCode::UsedNest,
+        BreedingCode::DistractionDisplay,
+        BreedingCode::NestBuilding,
+        BreedingCode::CarryingNestingMaterial,
+        BreedingCode::PhysiologicalEvidence,
+        BreedingCode::CavityNestBuilding,
+        BreedingCode::AgitatedBehavior,
+        BreedingCode::VisitingProbableNestSite,
+        BreedingCode::Courtship,
+        BreedingCode::TerritorialDefense,
+        BreedingCode::Pair,
+        BreedingCode::MultipleSinging,
+        BreedingCode::SingingSevenDays,
+        BreedingCode::Singing,
+        BreedingCode::InHabitat,
+        BreedingCode::Flyover,
+    ];
+
+    fn info(self) -> (&'static str, &'static str, BreedingCategory) {
+        use BreedingCategory::*;
+        match self {
+            BreedingCode::NestWithYoung => ("NY", "Nest with young", Confirmed),
+            BreedingCode::NestWithEggs => ("NE", "Nest with eggs", Confirmed),
+            BreedingCode::CarryingFecalSac => ("FS", "Carrying fecal sac", Confirmed),
+            BreedingCode::FeedingYoung => ("FY", "Feeding young", Confirmed),
+            BreedingCode::CarryingFood => ("CF", "Carrying food", Confirmed),
+            BreedingCode::RecentlyFledged => ("FL", "Recently fledged young", Confirmed),
+            BreedingCode::OccupiedNest => ("ON", "Occupied nest", Confirmed),
+            BreedingCode::UsedNest => ("UN", "Used nest", Confirmed),
+            BreedingCode::DistractionDisplay => ("DD", "Distraction display", Confirmed),
+            BreedingCode::NestBuilding => ("NB", "Nest building", Confirmed),
+            BreedingCode::CarryingNestingMaterial => ("CN", "Carrying nesting material", Probable),
+            BreedingCode::PhysiologicalEvidence => ("PE", "Physiological evidence", Probable),
+            BreedingCode::CavityNestBuilding => ("B", "Woodpecker/wren nest building", Probable),
+            BreedingCode::AgitatedBehavior => ("A", "Agitated behavior", Probable),
+            BreedingCode::VisitingProbableNestSite => ("N", "Visiting probable nest site", Probable),
+            BreedingCode::Courtship => ("C", "Courtship, display or copulation", Probable),
+            BreedingCode::TerritorialDefense => ("T", "Territorial defense", Probable),
+            BreedingCode::Pair => ("P", "Pair in suitable habitat", Probable),
+            BreedingCode::MultipleSinging => ("M", "Multiple (7+) singing birds", Probable),
+            BreedingCode::SingingSevenDays => ("S7", "Singing bird present 7+ days", Probable),
+            BreedingCode::Singing => ("S", "Singing bird", Possible),
+            BreedingCode::InHabitat => ("H", "In appropriate habitat", Possible),
+            BreedingCode::Flyover => ("F", "Flyover", Observed),
+        }
+    }
+
+    /// The one- or two-letter code, e.g. "FY"
+    pub fn code(self) -> &'static str {
+        self.info().0
+    }
+
+    pub fn description(self) -> &'static str {
+        self.info().1
+    }
+
+    pub fn category(self) -> BreedingCategory {
+        self.info().2
+    }
+}
+
+impl fmt::Display for BreedingCode {
+    /// "FY Feeding young"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code(), self.description())
+    }
+}
+
+impl FromStr for BreedingCode {
+    type Err = anyhow::Error;
+
+    /// Reads the code from the start, so both "FY" and "FY Feeding young" work
+    fn from_str(s: &str) -> Result<Self> {
+        let code = s.split_whitespace().next().unwrap_or("");
+        BreedingCode::ALL
+            .into_iter()
+            .find(|breeding| breeding.code().eq_ignore_ascii_case(code))
+            .ok_or_else(|| anyhow!("unknown breeding code '{}'", s.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count() {
+        assert_eq!("40".parse::<Count>().unwrap(), Count::Exact(40));
+        assert_eq!(" x ".parse::<Count>().unwrap(), Count::Present);
+        assert_eq!(Count::Present.to_string(), "X");
+        assert_eq!(Count::Exact(0).exact(), Some(0));
+        assert!("forty".parse::<Count>().is_err());
+        assert!("-1".parse::<Count>().is_err());
+    }
+
+    #[test]
+    fn test_parse_age_sex() {
+        let cells = parse_age_sex("1 female juv, 2 adult male; 3, 1 m ad").unwrap();
+        assert_eq!(
+            cells,
+            vec![
+                AgeSexCount { sex: Sex::Male, age: Age::Adult, count: 3 },
+                AgeSexCount { sex: Sex::Female, age: Age::Juvenile, count: 1 },
+                AgeSexCount { sex: Sex::Unknown, age: Age::Unknown, count: 3 },
+            ]
+        );
+        assert_eq!(format_age_sex(&cells), "3 male adult, 1 female juvenile, 3 unknown");
+        assert_eq!(parse_age_sex(&format_age_sex(&cells)).unwrap(), cells);
+        assert!(parse_age_sex("").unwrap().is_empty());
+
+        for bad in ["male", "0 male", "2 male female", "2 adult juvenile", "2 chick"] {
+            assert!(parse_age_sex(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_breeding_code() {
+        assert_eq!("fy".parse::<BreedingCode>().unwrap(), BreedingCode::FeedingYoung);
+        assert_eq!("S7 Singing bird present 7+ days".parse::<BreedingCode>().unwrap(), BreedingCode::SingingSevenDays);
+        assert_eq!(BreedingCode::FeedingYoung.to_string(), "FY Feeding young");
+        assert_eq!(BreedingCode::Pair.category(), BreedingCategory::Probable);
+        assert!("ZZ".parse::<BreedingCode>().is_err());
+
+        // ALL is ordered by strength of evidence
+        let categories: Vec<BreedingCategory> = BreedingCode::ALL.iter().map(|code| code.category()).collect();
+        assert!(categories.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
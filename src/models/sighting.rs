@@ -1,3 +1,4 @@
+use crate::models::{AgeSexCount, BreedingCode, Coordinates, Count, ObservedDate};
 use std::fmt;
 
 #[derive(Debug)]
@@ -15,9 +16,15 @@ pub struct Sighting {
     pub species_epithet: Option<String>,
     pub common_name: String,
     pub notes: Option<String>,
-    pub media_path: Option<String>,
-    pub date: Option<String>,
+    pub date: Option<ObservedDate>,
+    /// Name of the sighting's location, copied from `locations`
     pub location: Option<String>,
+    pub count: Option<Count>,
+    /// Sex/age breakdown; may cover fewer birds than `count`
+    pub age_sex: Vec<AgeSexCount>,
+    pub breeding_code: Option<BreedingCode>,
+    pub coordinates: Option<Coordinates>,
+    pub location_id: Option<i64>,
 }
 
 impl fmt::Display for Sighting {
@@ -41,3 +48,22 @@ impl fmt::Display for Sighting {
         )
     }
 }
+
+/// Fields to change on an existing sighting. `None` leaves a field untouched;
+/// an empty string clears an optional text field and `Some(None)` detaches
+/// the sighting from its trip or clears its count, breeding code,
+/// coordinates or location; an empty `age_sex` clears the breakdown. Changing `taxon_id`
+/// re-copies the taxonomy, and a `location` name is looked up (or created) as on insert.
+#[derive(Debug, Default)]
+pub struct SightingUpdate {
+    pub trip_id: Option<Option<i64>>,
+    pub taxon_id: Option<i64>,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+    pub location: Option<String>,
+    pub count: Option<Option<Count>>,
+    pub age_sex: Option<Vec<AgeSexCount>>,
+    pub breeding_code: Option<Option<BreedingCode>>,
+    pub coordinates: Option<Option<Coordinates>>,
+    pub location_id: Option<Option<i64>>,
+}
@@ -1,9 +1,103 @@
+use anyhow::{Result, anyhow};
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug)]
+/// A taxonomic rank; ranks compare from broadest (kingdom) to narrowest (subspecies).
+/// Hybrid, slash ("Greater/Lesser Yellowlegs") and spuh ("duck sp.") aren't ranks but
+/// identification categories, and sort after them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    Kingdom,
+    Phylum,
+    Class,
+    Order,
+    Family,
+    Subfamily,
+    Genus,
+    Species,
+    Subspecies,
+    Hybrid,
+    Slash,
+    Spuh,
+}
+
+impl Rank {
+    /// Every rank, broadest first, then the identification categories
+    pub const ALL: [Rank; 12] = [
+        Rank::Kingdom,
+        Rank::Phylum,
+        Rank::Class,
+        Rank::Order,
+        Rank::Family,
+        Rank::Subfamily,
+        Rank::Genus,
+        Rank::Species,
+        Rank::Subspecies,
+        Rank::Hybrid,
+        Rank::Slash,
+        Rank::Spuh,
+    ];
+
+    /// The name stored in the `rank` column, e.g. "subfamily"
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Rank::Kingdom => "kingdom",
+            Rank::Phylum => "phylum",
+            Rank::Class => "class",
+            Rank::Order => "order",
+            Rank::Family => "family",
+            Rank::Subfamily => "subfamily",
+            Rank::Genus => "genus",
+            Rank::Species => "species",
+            Rank::Subspecies => "subspecies",
+            Rank::Hybrid => "hybrid",
+            Rank::Slash => "slash",
+            Rank::Spuh => "spuh",
+        }
+    }
+
+    /// Column holding the name at this rank on `taxa` (quoted where needed); None for
+    /// the identification categories. Sightings copy every column but subspecies.
+    pub fn column(self) -> Option<&'static str> {
+        match self {
+            Rank::Order => Some("\"order\""),
+            Rank::Species => Some("species_epithet"),
+            Rank::Hybrid | Rank::Slash | Rank::Spuh => None,
+            rank => Some(rank.as_str()),
+        }
+    }
+
+    /// Whether a sighting of a taxon at this rank counts toward species totals: a
+    /// subspecies counts as its species, a hybrid, slash or spuh doesn't count
+    pub fn counts_as_species(self) -> bool {
+        matches!(self, Rank::Species | Rank::Subspecies)
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Rank {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Rank::ALL
+            .into_iter()
+            .find(|rank| rank.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Rank::ALL.iter().map(|rank| rank.as_str()).collect();
+                anyhow!("unknown rank '{}' (expected one of {})", s.trim(), names.join(", "))
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Taxon {
     pub id: i64,
-    pub rank: String,
+    pub rank: Rank,
     pub kingdom: String,
     pub phylum: Option<String>,
     pub class: Option<String>,
@@ -13,6 +107,66 @@ pub struct Taxon {
     pub genus: Option<String>,
     pub species_epithet: Option<String>,
     pub common_name: String,
+    pub subspecies: Option<String>,
+    /// The next broader taxon in the tree; None for a kingdom
+    pub parent_id: Option<i64>,
+}
+
+impl Taxon {
+    /// The taxon's name at `rank`, e.g. its family; None for ranks it doesn't fill in
+    /// and for the identification categories
+    pub fn name_at(&self, rank: Rank) -> Option<&str> {
+        match rank {
+            Rank::Kingdom => Some(&self.kingdom),
+            Rank::Phylum => self.phylum.as_deref(),
+            Rank::Class => self.class.as_deref(),
+            Rank::Order => self.order.as_deref(),
+            Rank::Family => self.family.as_deref(),
+            Rank::Subfamily => self.subfamily.as_deref(),
+            Rank::Genus => self.genus.as_deref(),
+            Rank::Species => self.species_epithet.as_deref(),
+            Rank::Subspecies => self.subspecies.as_deref(),
+            Rank::Hybrid | Rank::Slash | Rank::Spuh => None,
+        }
+    }
+
+    /// The names the taxon fills in, broadest first, each with the rank of the taxon
+    /// it names. A hybrid's or slash's species epithet names the taxon itself.
+    pub fn lineage(&self) -> Vec<(Rank, &str)> {
+        Rank::ALL
+            .into_iter()
+            .filter_map(|rank| self.name_at(rank).map(|name| (rank, name)))
+            .map(|(rank, name)| match self.rank {
+                Rank::Hybrid | Rank::Slash if rank == Rank::Species => (self.rank, name),
+                _ => (rank, name),
+            })
+            .collect()
+    }
+
+    /// The scientific name: "Genus epithet [subspecies]" at and below species,
+    /// "Name sp." for a spuh, otherwise the name at the taxon's rank
+    pub fn scientific_name(&self) -> String {
+        let deepest = || {
+            Rank::ALL
+                .iter()
+                .rev()
+                .filter(|&&rank| rank < Rank::Species)
+                .find_map(|&rank| self.name_at(rank))
+                .unwrap_or(&self.kingdom)
+        };
+
+        match self.rank {
+            Rank::Spuh => format!("{} sp.", deepest()),
+            Rank::Species | Rank::Subspecies | Rank::Hybrid | Rank::Slash => {
+                [self.genus.as_deref(), self.species_epithet.as_deref(), self.subspecies.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            rank => self.name_at(rank).unwrap_or(deepest()).to_string(),
+        }
+    }
 }
 
 impl fmt::Display for Taxon {
@@ -26,6 +180,7 @@ impl fmt::Display for Taxon {
         if let Some(ref sf) = self.subfamily { parts.push(sf.clone()); }
         if let Some(ref g) = self.genus { parts.push(g.clone()); }
         if let Some(ref s) = self.species_epithet { parts.push(s.clone()); }
+        if let Some(ref ssp) = self.subspecies { parts.push(ssp.clone()); }
 
         write!(
             f,
@@ -37,3 +192,45 @@ impl fmt::Display for Taxon {
         )
     }
 }
+
+/// Fields to change on an existing taxon. `None` leaves a field untouched;
+/// an empty string clears an optional rank.
+#[derive(Debug, Default)]
+pub struct TaxonUpdate {
+    pub rank: Option<Rank>,
+    pub kingdom: Option<String>,
+    pub phylum: Option<String>,
+    pub class: Option<String>,
+    pub order: Option<String>,
+    pub family: Option<String>,
+    pub subfamily: Option<String>,
+    pub genus: Option<String>,
+    pub species_epithet: Option<String>,
+    pub subspecies: Option<String>,
+    pub common_name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_parsing_and_order() {
+        assert_eq!(" Subfamily ".parse::<Rank>().unwrap(), Rank::Subfamily);
+        assert_eq!(Rank::Order.to_string(), "order");
+        assert_eq!(Rank::Order.column(), Some("\"order\""));
+        assert_eq!(Rank::Slash.column(), None);
+        assert!(Rank::Kingdom < Rank::Phylum && Rank::Genus < Rank::Species && Rank::Species < Rank::Subspecies);
+        assert_eq!("SPUH".parse::<Rank>().unwrap(), Rank::Spuh);
+
+        assert!(Rank::Subspecies.counts_as_species());
+        assert!(!Rank::Hybrid.counts_as_species() && !Rank::Genus.counts_as_species());
+
+        let error = "tribe".parse::<Rank>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown rank 'tribe' (expected one of kingdom, phylum, class, order, family, subfamily, genus, species, \
+             subspecies, hybrid, slash, spuh)"
+        );
+    }
+}
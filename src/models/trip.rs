@@ -1,12 +1,19 @@
+use crate::models::{Coordinates, ObservedDate};
+use anyhow::{Result, anyhow, bail};
 use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug)]
 pub struct Trip {
     pub id: i64,
     pub name: String,
-    pub date: Option<String>,
+    pub date: Option<ObservedDate>,
+    /// Name of the trip's location, copied from `locations`
     pub location: Option<String>,
     pub notes: Option<String>,
+    pub coordinates: Option<Coordinates>,
+    pub location_id: Option<i64>,
+    pub effort: Effort,
 }
 
 impl fmt::Display for Trip {
@@ -14,3 +21,203 @@ impl fmt::Display for Trip {
         write!(f, "{}: {}", self.id, self.name)
     }
 }
+
+/// Fields to change on an existing trip. `None` leaves a field untouched;
+/// an empty string clears an optional field and `Some(None)` clears the coordinates
+/// or location. A `location` name is looked up (or created) the same way as on insert.
+#[derive(Debug, Default)]
+pub struct TripUpdate {
+    pub name: Option<String>,
+    pub date: Option<String>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    pub coordinates: Option<Option<Coordinates>>,
+    pub location_id: Option<Option<i64>>,
+    /// "HH:MM"; an empty string clears it
+    pub start_time: Option<String>,
+    pub duration_min: Option<Option<u32>>,
+    pub distance_km: Option<Option<f64>>,
+    pub party_size: Option<Option<u32>>,
+    pub protocol: Option<Option<Protocol>>,
+    pub complete: Option<bool>,
+}
+
+/// How the birds on a trip were looked for, following eBird's protocols
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// Birding from one spot
+    Stationary,
+    /// Birding while moving along a route
+    Traveling,
+    /// Birds noted while doing something else
+    Incidental,
+}
+
+impl Protocol {
+    pub const ALL: [Protocol; 3] = [Protocol::Stationary, Protocol::Traveling, Protocol::Incidental];
+
+    /// The stored name, e.g. "traveling"
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Protocol::Stationary => "stationary",
+            Protocol::Traveling => "traveling",
+            Protocol::Incidental => "incidental",
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = anyhow::Error;
+
+    /// Case-insensitive, and accepts eBird's "Traveling" or the British "travelling"
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_lowercase();
+        let name = if name == "travelling" { "traveling" } else { name.as_str() };
+        Protocol::ALL
+            .into_iter()
+            .find(|protocol| protocol.as_str() == name)
+            .ok_or_else(|| anyhow!("unknown protocol '{}' (expected stationary, traveling or incidental)", s.trim()))
+    }
+}
+
+/// The effort behind a trip's list: when it started, for how long, how far and by how many
+/// people, and whether every species detected was reported (a complete checklist)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Effort {
+    /// "HH:MM", 24-hour
+    pub start_time: Option<String>,
+    pub duration_min: Option<u32>,
+    pub distance_km: Option<f64>,
+    pub party_size: Option<u32>,
+    pub protocol: Option<Protocol>,
+    pub complete: bool,
+}
+
+impl Effort {
+    /// Reject values a checklist can't have; `start_time` must already be "HH:MM"
+    pub fn check(&self) -> Result<()> {
+        if let Some(ref time) = self.start_time {
+            if parse_start_time(time).ok().as_ref() != Some(time) {
+                bail!("invalid start time '{}' (expected HH:MM)", time);
+            }
+        }
+        if self.duration_min == Some(0) {
+            bail!("duration must be at least one minute");
+        }
+        if self.party_size == Some(0) {
+            bail!("party size must be at least one");
+        }
+        if let Some(distance) = self.distance_km {
+            if !(distance >= 0.0 && distance.is_finite()) {
+                bail!("distance must be a non-negative number of kilometers");
+            }
+            if self.protocol == Some(Protocol::Stationary) && distance > 0.0 {
+                bail!("a stationary count can't cover a distance");
+            }
+        }
+        Ok(())
+    }
+
+    /// Nothing recorded beyond the defaults
+    pub fn is_empty(&self) -> bool {
+        *self == Effort::default()
+    }
+}
+
+impl fmt::Display for Effort {
+    /// "traveling, 07:30 for 90 min, 2.1 km, 2 observers, complete"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.protocol.iter().map(|protocol| protocol.to_string()).collect();
+        match (&self.start_time, self.duration_min) {
+            (Some(time), Some(minutes)) => parts.push(format!("{} for {} min", time, minutes)),
+            (Some(time), None) => parts.push(format!("from {}", time)),
+            (None, Some(minutes)) => parts.push(format!("{} min", minutes)),
+            (None, None) => {}
+        }
+        if let Some(distance) = self.distance_km {
+            parts.push(format!("{} km", distance));
+        }
+        if let Some(size) = self.party_size {
+            parts.push(format!("{} observer{}", size, if size == 1 { "" } else { "s" }));
+        }
+        parts.push(if self.complete { "complete" } else { "incomplete" }.to_string());
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// A start time as "HH:MM" from "7:30", "07:30", "19:05" or "7:30 PM"
+pub fn parse_start_time(text: &str) -> Result<String> {
+    let invalid = || anyhow!("invalid start time '{}' (expected HH:MM)", text.trim());
+    let upper = text.trim().to_uppercase();
+    let (clock, meridiem) = match upper.strip_suffix("AM").or(upper.strip_suffix("PM")) {
+        Some(clock) => (clock.trim_end(), Some(upper.ends_with("PM"))),
+        None => (upper.as_str(), None),
+    };
+
+    let (hour, minute) = clock.split_once(':').ok_or_else(invalid)?;
+    let digits = |part: &str| (1..=2).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit());
+    if !digits(hour) || minute.len() != 2 || !digits(minute) {
+        return Err(invalid());
+    }
+    let (mut hour, minute): (u32, u32) = (hour.parse()?, minute.parse()?);
+    if let Some(pm) = meridiem {
+        if !(1..=12).contains(&hour) {
+            return Err(invalid());
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    if hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok(format!("{:02}:{:02}", hour, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_start_time() {
+        assert_eq!(parse_start_time("7:30").unwrap(), "07:30");
+        assert_eq!(parse_start_time("19:05").unwrap(), "19:05");
+        assert_eq!(parse_start_time("07:30 AM").unwrap(), "07:30");
+        assert_eq!(parse_start_time("12:15 am").unwrap(), "00:15");
+        assert_eq!(parse_start_time("12:15 PM").unwrap(), "12:15");
+        assert_eq!(parse_start_time("7:30pm").unwrap(), "19:30");
+        for bad in ["", "730", "24:00", "7:3", "13:00 PM", "noon"] {
+            assert!(parse_start_time(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_effort_check_and_display() {
+        let effort = Effort {
+            start_time: Some("07:30".to_string()),
+            duration_min: Some(90),
+            distance_km: Some(2.1),
+            party_size: Some(2),
+            protocol: Some(Protocol::Traveling),
+            complete: true,
+        };
+        assert!(effort.check().is_ok());
+        assert_eq!(effort.to_string(), "traveling, 07:30 for 90 min, 2.1 km, 2 observers, complete");
+        assert_eq!(Effort::default().to_string(), "incomplete");
+        assert!(Effort::default().is_empty());
+
+        assert!(Effort { start_time: Some("7:30".to_string()), ..Default::default() }.check().is_err());
+        assert!(Effort { duration_min: Some(0), ..Default::default() }.check().is_err());
+        assert!(Effort { party_size: Some(0), ..Default::default() }.check().is_err());
+        assert!(Effort { distance_km: Some(-1.0), ..Default::default() }.check().is_err());
+        let stationary = Effort { protocol: Some(Protocol::Stationary), distance_km: Some(1.0), ..Default::default() };
+        assert!(stationary.check().is_err());
+
+        assert_eq!("Travelling".parse::<Protocol>().unwrap(), Protocol::Traveling);
+        assert!("pelagic".parse::<Protocol>().is_err());
+    }
+}
@@ -1,11 +1,48 @@
-use slint::{Timer, VecModel, ModelRc, SharedString};
+use slint::{Model, Timer, VecModel, ModelRc, SharedString};
 use std::time::Duration;
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::OnceLock;
+
+mod navigation;
+
+use crate::core::db::Paged;
+use crate::models::{Effort, ObservedDate, Sighting, Trip, parse_start_time};
+use navigation::{History, ListOwner, Page};
 
 slint::include_modules!();
 
-pub fn run_ui() -> Result<(), slint::PlatformError> {
+/// Database the GUI was launched with (resolved the same way as the CLI's `--db`)
+static DB_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Related sightings or trips a detail page shows before its "See all" link
+const RELATED_LIMIT: usize = 5;
+
+/// Rows a "See all" list loads at a time
+const LIST_PAGE_SIZE: usize = 50;
+
+/// Taxa offered as the new sighting form's taxon name is typed
+const TAXON_SUGGESTIONS: usize = 8;
+
+/// Bars in an audio attachment's waveform (the width of `MediaItem.waveform`'s viewbox)
+const WAVEFORM_WIDTH: usize = 120;
+
+/// Longest side a photo is shown at in the gallery, in pixels
+const GALLERY_SIZE: u32 = 2048;
+
+/// Files the media chooser offers first
+const MEDIA_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "heic", "webp", "tif", "tiff", "mp3", "wav", "m4a", "flac", "ogg", "mp4", "mov",
+];
+
+fn connect() -> anyhow::Result<rusqlite::Connection> {
+    let path = DB_PATH.get().expect("run_ui sets the database path");
+    crate::core::db::connect(path)
+}
+
+pub fn run_ui(db_path: PathBuf) -> Result<(), slint::PlatformError> {
+    DB_PATH.set(db_path).expect("run_ui is only called once");
     let ui = AppWindow::new()?;
 
     // Debounce timer setup
@@ -32,6 +69,8 @@ pub fn run_ui() -> Result<(), slint::PlatformError> {
                     ui.set_sightings_model(ModelRc::new(VecModel::from(vec![])));
                     ui.set_taxa_model(ModelRc::new(VecModel::from(vec![])));
                     ui.set_trips_model(ModelRc::new(VecModel::from(vec![])));
+                    ui.set_locations_model(ModelRc::new(VecModel::from(vec![])));
+                    ui.set_search_error(SharedString::new());
                 }
                 return;
             }
@@ -49,73 +88,250 @@ pub fn run_ui() -> Result<(), slint::PlatformError> {
         }
     });
 
-    // Navigation callbacks
-    ui.on_view_sighting_detail({
+    // Navigation callbacks; every page change goes through the history
+    let history = Rc::new(RefCell::new(History::new(Page::Search, "Search")));
+    update_history(&ui, &history.borrow());
+
+    let on_page = |page: fn(i32) -> Page| {
         let ui_weak = ui.as_weak();
-        move |id| {
+        let history = history.clone();
+        move |id: i32| {
             if let Some(ui) = ui_weak.upgrade() {
-                fetch_sighting_detail(&ui, id);
-                ui.set_current_view("sighting-detail".into());
+                navigate(&ui, &history, Move::Visit(page(id)));
             }
         }
-    });
+    };
+    ui.on_view_sighting_detail(on_page(Page::Sighting));
+    ui.on_view_taxon_detail(on_page(Page::Taxon));
+    ui.on_view_trip_detail(on_page(Page::Trip));
+    ui.on_view_location_detail(on_page(Page::Location));
+    ui.on_view_related_sighting(on_page(Page::Sighting));
+    ui.on_view_related_taxon(on_page(Page::Taxon));
+    ui.on_view_related_trip(on_page(Page::Trip));
+    ui.on_browse_taxa(on_page(Page::TaxonBrowser));
 
-    ui.on_view_taxon_detail({
+    // Taxonomy breadcrumbs open the taxon at that rank, creating it if needed
+    ui.on_view_ancestor({
         let ui_weak = ui.as_weak();
-        move |id| {
+        let history = history.clone();
+        move |taxon_id, rank| {
             if let Some(ui) = ui_weak.upgrade() {
-                fetch_taxon_detail(&ui, id);
-                ui.set_current_view("taxon-detail".into());
+                match find_ancestor(taxon_id, &rank) {
+                    Ok(id) => navigate(&ui, &history, Move::Visit(Page::Taxon(id))),
+                    Err(e) => eprintln!("Failed to open {}: {:#}", rank, e),
+                }
             }
         }
     });
 
-    ui.on_view_trip_detail({
+    let on_move = |to: fn() -> Move| {
+        let ui_weak = ui.as_weak();
+        let history = history.clone();
+        move || {
+            if let Some(ui) = ui_weak.upgrade() {
+                navigate(&ui, &history, to());
+            }
+        }
+    };
+    ui.on_go_back(on_move(|| Move::Back));
+    ui.on_go_forward(on_move(|| Move::Forward));
+    ui.on_go_home(on_move(|| Move::Visit(Page::Search)));
+    ui.on_show_life_list(on_move(|| Move::Visit(Page::SpeciesList { this_year: false })));
+    ui.on_show_year_list(on_move(|| Move::Visit(Page::SpeciesList { this_year: true })));
+
+    // "See all" links list everything related to the open detail page
+    let on_see_all = |list: fn(ListOwner) -> Page| {
+        let ui_weak = ui.as_weak();
+        let history = history.clone();
+        move || {
+            let Some(ui) = ui_weak.upgrade() else {
+                return;
+            };
+            let owner = history.borrow().current().page.list_owner();
+            if let Some(owner) = owner {
+                navigate(&ui, &history, Move::Visit(list(owner)));
+            }
+        }
+    };
+    ui.on_see_all_sightings(on_see_all(Page::AllSightings));
+    ui.on_see_all_trips(on_see_all(Page::AllTrips));
+
+    ui.on_load_more_list({
         let ui_weak = ui.as_weak();
-        move |id| {
+        let history = history.clone();
+        move || {
             if let Some(ui) = ui_weak.upgrade() {
-                fetch_trip_detail(&ui, id);
-                ui.set_current_view("trip-detail".into());
+                let page = history.borrow().current().page;
+                load_more_list(&ui, page);
             }
         }
     });
 
-    // Related entity navigation callbacks
-    ui.on_view_related_sighting({
+    // New record forms; a created record opens in place of its form
+    ui.on_add_trip(on_move(|| Move::Visit(Page::NewTrip)));
+    ui.on_add_sighting({
         let ui_weak = ui.as_weak();
-        move |id| {
+        let history = history.clone();
+        move |trip_id, taxon_id| {
             if let Some(ui) = ui_weak.upgrade() {
-                fetch_sighting_detail(&ui, id);
-                ui.set_current_view("sighting-detail".into());
+                navigate(&ui, &history, Move::Visit(Page::NewSighting { trip_id, taxon_id }));
             }
         }
     });
 
-    ui.on_view_related_taxon({
+    ui.on_search_form_taxa({
         let ui_weak = ui.as_weak();
-        move |id| {
+        move |text| {
             if let Some(ui) = ui_weak.upgrade() {
-                fetch_taxon_detail(&ui, id);
-                ui.set_current_view("taxon-detail".into());
+                suggest_taxa(&ui, &text);
             }
         }
     });
 
-    ui.on_view_related_trip({
+    ui.on_pick_form_trip({
         let ui_weak = ui.as_weak();
-        move |id| {
+        move |index| {
             if let Some(ui) = ui_weak.upgrade() {
-                fetch_trip_detail(&ui, id);
-                ui.set_current_view("trip-detail".into());
+                pick_form_trip(&ui, index);
             }
         }
     });
 
-    ui.on_back_to_search({
+    ui.on_choose_media({
         let ui_weak = ui.as_weak();
         move || {
+            let Some(ui) = ui_weak.upgrade() else {
+                return;
+            };
+            let file = rfd::FileDialog::new()
+                .set_title("Choose a photo or recording")
+                .add_filter("Photos and recordings", MEDIA_EXTENSIONS)
+                .add_filter("All files", &["*"])
+                .pick_file();
+            if let Some(file) = file {
+                ui.set_new_sighting_media(SharedString::from(file.display().to_string()));
+            }
+        }
+    });
+
+    ui.on_create_trip({
+        let ui_weak = ui.as_weak();
+        let history = history.clone();
+        move |form| {
             if let Some(ui) = ui_weak.upgrade() {
-                ui.set_current_view("search".into());
+                match create_new_trip(&form) {
+                    Ok(id) => navigate(&ui, &history, Move::Replace(Page::Trip(id as i32))),
+                    Err(e) => ui.set_edit_error(SharedString::from(format!("{:#}", e))),
+                }
+            }
+        }
+    });
+
+    ui.on_create_sighting({
+        let ui_weak = ui.as_weak();
+        let history = history.clone();
+        move |form| {
+            if let Some(ui) = ui_weak.upgrade() {
+                match create_new_sighting(&ui, &form) {
+                    Ok(id) => navigate(&ui, &history, Move::Replace(Page::Sighting(id as i32))),
+                    Err(e) => ui.set_edit_error(SharedString::from(format!("{:#}", e))),
+                }
+            }
+        }
+    });
+
+    // Attachments on the sighting page: photos open in the gallery, audio and video in the system's player
+    ui.on_open_gallery({
+        let ui_weak = ui.as_weak();
+        move |index| {
+            if let Some(ui) = ui_weak.upgrade() {
+                show_gallery_photo(&ui, index as usize);
+            }
+        }
+    });
+
+    ui.on_gallery_step({
+        let ui_weak = ui.as_weak();
+        move |step| {
+            if let Some(ui) = ui_weak.upgrade() {
+                step_gallery(&ui, step);
+            }
+        }
+    });
+
+    ui.on_open_media(|path| {
+        if let Err(e) = opener::open(path.as_str()) {
+            eprintln!("Failed to open {}: {}", path, e);
+        }
+    });
+
+    ui.on_go_to_history({
+        let ui_weak = ui.as_weak();
+        let history = history.clone();
+        move |index| {
+            if let Some(ui) = ui_weak.upgrade() {
+                navigate(&ui, &history, Move::To(index as usize));
+            }
+        }
+    });
+
+    // Edit form callbacks
+    ui.on_save_sighting({
+        let ui_weak = ui.as_weak();
+        move |detail| {
+            if let Some(ui) = ui_weak.upgrade() {
+                match save_sighting(&detail) {
+                    Ok(()) => {
+                        ui.set_editing(false);
+                        fetch_sighting_detail(&ui, detail.id);
+                    }
+                    Err(e) => ui.set_edit_error(SharedString::from(format!("{:#}", e))),
+                }
+            }
+        }
+    });
+
+    ui.on_save_taxon({
+        let ui_weak = ui.as_weak();
+        move |detail| {
+            if let Some(ui) = ui_weak.upgrade() {
+                match save_taxon(&detail) {
+                    Ok(()) => {
+                        ui.set_editing(false);
+                        fetch_taxon_detail(&ui, detail.id);
+                    }
+                    Err(e) => ui.set_edit_error(SharedString::from(format!("{:#}", e))),
+                }
+            }
+        }
+    });
+
+    ui.on_save_trip({
+        let ui_weak = ui.as_weak();
+        move |detail| {
+            if let Some(ui) = ui_weak.upgrade() {
+                match save_trip(&detail) {
+                    Ok(()) => {
+                        ui.set_editing(false);
+                        fetch_trip_detail(&ui, detail.id);
+                    }
+                    Err(e) => ui.set_edit_error(SharedString::from(format!("{:#}", e))),
+                }
+            }
+        }
+    });
+
+    ui.on_save_location({
+        let ui_weak = ui.as_weak();
+        move |detail| {
+            if let Some(ui) = ui_weak.upgrade() {
+                match save_location(&detail) {
+                    Ok(()) => {
+                        ui.set_editing(false);
+                        fetch_location_detail(&ui, detail.id);
+                    }
+                    Err(e) => ui.set_edit_error(SharedString::from(format!("{:#}", e))),
+                }
             }
         }
     });
@@ -123,8 +339,493 @@ pub fn run_ui() -> Result<(), slint::PlatformError> {
     ui.run()
 }
 
+/// A step through the navigation history
+enum Move {
+    Visit(Page),
+    /// Swap the current page for another, as when a form turns into the record it created
+    Replace(Page),
+    Back,
+    Forward,
+    To(usize),
+}
+
+/// Take a step through the history and show the page it lands on, scrolled to where
+/// it was left. Revisiting the current page reloads it in place.
+fn navigate(ui: &AppWindow, history: &RefCell<History>, to: Move) {
+    let scroll_y = ui.get_scroll_y();
+    let mut history = history.borrow_mut();
+    let target = match to {
+        Move::Visit(page) => Some((page, if history.visit(page, scroll_y) { 0.0 } else { scroll_y })),
+        Move::Replace(page) => {
+            history.replace(page);
+            Some((page, 0.0))
+        }
+        Move::Back => history.back(scroll_y).map(|visit| (visit.page, visit.scroll_y)),
+        Move::Forward => history.forward(scroll_y).map(|visit| (visit.page, visit.scroll_y)),
+        Move::To(index) => history.go_to(index, scroll_y).map(|visit| (visit.page, visit.scroll_y)),
+    };
+    let Some((page, scroll_y)) = target else {
+        return;
+    };
+
+    let title = load_page(ui, page);
+    history.set_title(&title);
+    ui.set_scroll_y(scroll_y);
+    update_history(ui, &history);
+}
+
+/// Fetch a page's data and switch to its view; returns the page's title
+fn load_page(ui: &AppWindow, page: Page) -> String {
+    match page {
+        Page::Search => {
+            show_view(ui, "search");
+            "Search".to_string()
+        }
+        Page::Sighting(id) => {
+            fetch_sighting_detail(ui, id);
+            show_view(ui, "sighting-detail");
+            ui.get_current_sighting().common_name.to_string()
+        }
+        Page::Taxon(id) => {
+            fetch_taxon_detail(ui, id);
+            show_view(ui, "taxon-detail");
+            ui.get_current_taxon().common_name.to_string()
+        }
+        Page::Trip(id) => {
+            fetch_trip_detail(ui, id);
+            show_view(ui, "trip-detail");
+            ui.get_current_trip().name.to_string()
+        }
+        Page::Location(id) => {
+            fetch_location_detail(ui, id);
+            show_view(ui, "location-detail");
+            ui.get_current_location().name.to_string()
+        }
+        Page::SpeciesList { this_year } => {
+            fetch_species_list(ui, this_year);
+            show_view(ui, "species-list");
+            ui.get_species_list_title().to_string()
+        }
+        Page::TaxonBrowser(id) => {
+            fetch_taxon_browser(ui, id);
+            show_view(ui, "taxon-browser");
+            ui.get_browser_title().to_string()
+        }
+        Page::AllSightings(_) | Page::AllTrips(_) => {
+            fetch_related_list(ui, page);
+            show_view(ui, "related-list");
+            ui.get_list_title().to_string()
+        }
+        Page::NewTrip => {
+            show_view(ui, "new-trip");
+            "New Trip".to_string()
+        }
+        Page::NewSighting { trip_id, taxon_id } => {
+            fetch_new_sighting_form(ui, trip_id, taxon_id);
+            show_view(ui, "new-sighting");
+            "New Sighting".to_string()
+        }
+    }
+}
+
+/// Show the back/forward state and the trail of the last few pages
+fn update_history(ui: &AppWindow, history: &History) {
+    let trail: Vec<HistoryCrumb> = history
+        .trail(5)
+        .into_iter()
+        .map(|(index, visit)| HistoryCrumb {
+            index: index as i32,
+            title: SharedString::from(visit.title.clone()),
+        })
+        .collect();
+
+    ui.set_can_go_back(history.can_go_back());
+    ui.set_can_go_forward(history.can_go_forward());
+    ui.set_history_trail(ModelRc::new(VecModel::from(trail)));
+}
+
+/// Switch to another view, leaving any open edit form
+fn show_view(ui: &AppWindow, view: &str) {
+    ui.set_editing(false);
+    ui.set_edit_error(SharedString::new());
+    ui.set_current_view(view.into());
+}
+
+fn save_sighting(detail: &SightingDetail) -> anyhow::Result<()> {
+    use crate::core::sighting::update_sighting;
+    use crate::models::{BreedingCode, Count, SightingUpdate, parse_age_sex};
+
+    let count = parse_field::<Count>(&detail.count)?;
+    let breeding_code = parse_field::<BreedingCode>(&detail.breeding)?;
+    let coordinates = parse_field(&detail.coordinates)?;
+
+    let conn = connect()?;
+    let changes = SightingUpdate {
+        date: Some(detail.date.trim().to_string()),
+        location: Some(detail.location.trim().to_string()),
+        notes: Some(detail.notes.trim().to_string()),
+        count: Some(count),
+        age_sex: Some(parse_age_sex(&detail.age_sex)?),
+        breeding_code: Some(breeding_code),
+        coordinates: Some(coordinates),
+        ..Default::default()
+    };
+    update_sighting(&conn, detail.id as i64, &changes)?;
+    Ok(())
+}
+
+fn save_taxon(detail: &TaxonDetail) -> anyhow::Result<()> {
+    use crate::core::taxon::update_taxon;
+    use crate::models::{Rank, TaxonUpdate};
+
+    let rank = detail.rank.parse::<Rank>()?;
+
+    let conn = connect()?;
+    let changes = TaxonUpdate {
+        rank: Some(rank),
+        kingdom: Some(detail.kingdom.trim().to_string()),
+        phylum: Some(detail.phylum.trim().to_string()),
+        class: Some(detail.class.trim().to_string()),
+        order: Some(detail.order.trim().to_string()),
+        family: Some(detail.family.trim().to_string()),
+        subfamily: Some(detail.subfamily.trim().to_string()),
+        genus: Some(detail.genus.trim().to_string()),
+        species_epithet: Some(detail.species_epithet.trim().to_string()),
+        subspecies: Some(detail.subspecies.trim().to_string()),
+        common_name: Some(detail.common_name.trim().to_string()),
+    };
+    update_taxon(&conn, detail.id as i64, &changes)?;
+    Ok(())
+}
+
+fn save_trip(detail: &TripDetail) -> anyhow::Result<()> {
+    use crate::core::trip::update_trip;
+    use crate::models::TripUpdate;
+
+    let coordinates = parse_field(&detail.coordinates)?;
+    let effort = form_effort(
+        &detail.start_time,
+        &detail.duration,
+        &detail.distance,
+        &detail.party_size,
+        &detail.protocol,
+        detail.complete,
+    )?;
+
+    let conn = connect()?;
+    let changes = TripUpdate {
+        name: Some(detail.name.trim().to_string()),
+        date: Some(detail.date.trim().to_string()),
+        location: Some(detail.location.trim().to_string()),
+        location_id: None,
+        notes: Some(detail.notes.trim().to_string()),
+        coordinates: Some(coordinates),
+        start_time: Some(effort.start_time.unwrap_or_default()),
+        duration_min: Some(effort.duration_min),
+        distance_km: Some(effort.distance_km),
+        party_size: Some(effort.party_size),
+        protocol: Some(effort.protocol),
+        complete: Some(effort.complete),
+    };
+    update_trip(&conn, detail.id as i64, &changes)?;
+    Ok(())
+}
+
+fn save_location(detail: &LocationDetail) -> anyhow::Result<()> {
+    use crate::core::location::update_location;
+    use crate::models::LocationUpdate;
+
+    let coordinates = parse_field(&detail.coordinates)?;
+
+    let conn = connect()?;
+    let changes = LocationUpdate {
+        locality: Some(detail.locality.trim().to_string()),
+        county: Some(detail.county.trim().to_string()),
+        state: Some(detail.state.trim().to_string()),
+        country: Some(detail.country.trim().to_string()),
+        coordinates: Some(coordinates),
+        hotspot: Some(detail.hotspot),
+    };
+    update_location(&conn, detail.id as i64, &changes)?;
+    Ok(())
+}
+
+/// Parse a form field; an empty field is `None`
+fn parse_field<T: std::str::FromStr<Err = anyhow::Error>>(value: &str) -> anyhow::Result<Option<T>> {
+    match value.trim() {
+        "" => Ok(None),
+        value => Ok(Some(value.parse()?)),
+    }
+}
+
+/// A trip's effort from its form fields; blank fields are left unset
+fn form_effort(
+    start_time: &str,
+    duration: &str,
+    distance: &str,
+    party_size: &str,
+    protocol: &str,
+    complete: bool,
+) -> anyhow::Result<Effort> {
+    use anyhow::Context;
+
+    let number = |value: &str, name: &str| -> anyhow::Result<Option<f64>> {
+        optional_field(value)
+            .map(|value| value.parse::<f64>().with_context(|| format!("invalid {} '{}'", name, value)))
+            .transpose()
+    };
+    let whole = |value: &str, name: &str| -> anyhow::Result<Option<u32>> {
+        optional_field(value)
+            .map(|value| value.parse::<u32>().with_context(|| format!("invalid {} '{}'", name, value)))
+            .transpose()
+    };
+
+    Ok(Effort {
+        start_time: optional_field(start_time).map(parse_start_time).transpose()?,
+        duration_min: whole(duration, "duration")?,
+        distance_km: number(distance, "distance")?,
+        party_size: whole(party_size, "party size")?,
+        protocol: parse_field(protocol)?,
+        complete,
+    })
+}
+
+/// A date as shown and edited in the UI, blank when unknown
+fn date_text(date: Option<ObservedDate>) -> SharedString {
+    date.map(|date| SharedString::from(date.to_string())).unwrap_or_default()
+}
+
+/// A form's text field, `None` when left blank
+fn optional_field(value: &str) -> Option<&str> {
+    Some(value.trim()).filter(|value| !value.is_empty())
+}
+
+fn create_new_trip(form: &NewTripForm) -> anyhow::Result<i64> {
+    use crate::core::trip::create_trip;
+
+    let coordinates = parse_field(&form.coordinates)?;
+    let effort = form_effort(&form.start_time, &form.duration, &form.distance, &form.party_size, &form.protocol, form.complete)?;
+
+    let conn = connect()?;
+    create_trip(
+        &conn,
+        form.name.trim(),
+        optional_field(&form.date),
+        optional_field(&form.location),
+        optional_field(&form.notes),
+        coordinates,
+        &effort,
+    )
+}
+
+fn create_new_sighting(ui: &AppWindow, form: &NewSightingForm) -> anyhow::Result<i64> {
+    use crate::core::sighting::create_sighting;
+    use crate::models::{BreedingCode, Count, parse_age_sex};
+
+    if form.taxon_id == 0 {
+        anyhow::bail!("choose a taxon for the sighting");
+    }
+    let trip_id = ui.get_trip_choices().row_data(form.trip_index as usize).map(|trip| trip.id as i64).filter(|&id| id != 0);
+    let count = parse_field::<Count>(&form.count)?;
+    let age_sex = parse_age_sex(&form.age_sex)?;
+    let breeding_code = parse_field::<BreedingCode>(&form.breeding)?;
+    let coordinates = parse_field(&form.coordinates)?;
+
+    let conn = connect()?;
+    create_sighting(
+        &conn,
+        trip_id,
+        form.taxon_id as i64,
+        optional_field(&form.notes),
+        optional_field(&form.media_path),
+        optional_field(&form.date),
+        optional_field(&form.location),
+        count,
+        &age_sex,
+        breeding_code,
+        coordinates,
+    )
+}
+
+/// An attachment for the sighting page, captioned with its file name if it has no caption.
+/// Its preview is filled in later by [`load_media_previews`].
+fn media_item(media: &crate::models::Media) -> MediaItem {
+    let path = std::path::Path::new(&media.path);
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+
+    MediaItem {
+        id: media.id as i32,
+        path: SharedString::from(media.path.clone()),
+        kind: SharedString::from(media.kind()),
+        captured_at: SharedString::from(media.captured_at.clone().unwrap_or_default()),
+        caption: SharedString::from(media.caption.clone().or(file_name).unwrap_or_else(|| media.path.clone())),
+        missing: !path.is_file(),
+        has_thumbnail: false,
+        thumbnail: slint::Image::default(),
+        waveform: SharedString::default(),
+    }
+}
+
+/// Make thumbnails for the sighting's photos and waveforms for its recordings on a worker
+/// thread, since a long recording takes a while to decode, and show each as it's ready.
+/// Both are cached in `thumbnail_dir`. Previews for a sighting that's no longer shown are dropped.
+fn load_media_previews(ui: &AppWindow, sighting_id: i32, media: Vec<crate::models::Media>) {
+    use crate::core::preview::{cached_waveform, thumbnail, thumbnail_dir};
+
+    let wanted = |media: &crate::models::Media| matches!(media.kind(), "image" | "audio") && std::path::Path::new(&media.path).is_file();
+    let media: Vec<(usize, crate::models::Media)> = media.into_iter().enumerate().filter(|(_, media)| wanted(media)).collect();
+    if media.is_empty() {
+        return;
+    }
+    let Some(cache_dir) = thumbnail_dir().map_err(|e| eprintln!("No thumbnails: {}", e)).ok() else {
+        return;
+    };
+
+    let ui_weak = ui.as_weak();
+    std::thread::spawn(move || {
+        for (row, media) in media {
+            let (thumbnail, waveform) = match media.kind() {
+                "image" => match thumbnail(&media, &cache_dir) {
+                    Ok(thumbnail) => (Some(thumbnail), String::new()),
+                    Err(e) => {
+                        eprintln!("No thumbnail for {}: {:#}", media.path, e);
+                        continue;
+                    }
+                },
+                _ => match cached_waveform(&media, &cache_dir, WAVEFORM_WIDTH) {
+                    Ok(peaks) => (None, waveform_commands(&peaks)),
+                    Err(e) => {
+                        eprintln!("No waveform for {}: {:#}", media.path, e);
+                        continue;
+                    }
+                },
+            };
+
+            let ui_weak = ui_weak.clone();
+            let shown = slint::invoke_from_event_loop(move || {
+                let Some(ui) = ui_weak.upgrade() else { return };
+                let model = ui.get_sighting_media();
+                let Some(mut item) = model.row_data(row) else { return };
+                if ui.get_current_sighting().id != sighting_id || item.id as i64 != media.id {
+                    return;
+                }
+
+                if let Some(thumbnail) = thumbnail {
+                    match slint::Image::load_from_path(&thumbnail) {
+                        Ok(image) => (item.thumbnail, item.has_thumbnail) = (image, true),
+                        Err(_) => eprintln!("No thumbnail for {}: unreadable thumbnail", media.path),
+                    }
+                }
+                item.waveform = SharedString::from(waveform);
+                model.set_row_data(row, item);
+            });
+            if shown.is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Path commands drawing one vertical bar per peak, centred in a 100-high viewbox
+fn waveform_commands(peaks: &[f32]) -> String {
+    peaks
+        .iter()
+        .enumerate()
+        .map(|(x, peak)| {
+            let half = (peak * 50.0).max(0.5);
+            format!("M {} {:.1} L {} {:.1}", x, 50.0 - half, x, 50.0 + half)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Positions in the sighting's attachments of the photos the gallery can show
+fn gallery_photos(ui: &AppWindow) -> Vec<usize> {
+    let media = ui.get_sighting_media();
+    (0..media.row_count())
+        .filter(|&row| media.row_data(row).is_some_and(|item| item.kind == "image" && !item.missing))
+        .collect()
+}
+
+/// Open the gallery at attachment `index`, loading the photo at gallery size
+fn show_gallery_photo(ui: &AppWindow, index: usize) {
+    use crate::core::preview::{fit_within, load_image};
+
+    let Some(item) = ui.get_sighting_media().row_data(index) else {
+        return;
+    };
+    let photo = match load_image(std::path::Path::new(item.path.as_str())) {
+        Ok(photo) => fit_within(photo, GALLERY_SIZE).to_rgba8(),
+        Err(e) => {
+            eprintln!("Failed to open photo: {:#}", e);
+            return;
+        }
+    };
+    let buffer = slint::SharedPixelBuffer::<slint::Rgba8Pixel>::clone_from_slice(photo.as_raw(), photo.width(), photo.height());
+
+    let photos = gallery_photos(ui);
+    let position = photos.iter().position(|&row| row == index).map(|n| n + 1).unwrap_or(1);
+    ui.set_gallery_image(slint::Image::from_rgba8(buffer));
+    ui.set_gallery_caption(SharedString::from(match item.captured_at.as_str() {
+        "" => item.caption.to_string(),
+        captured_at => format!("{} · {}", item.caption, captured_at),
+    }));
+    ui.set_gallery_position(SharedString::from(format!("{} of {}", position, photos.len())));
+    ui.set_gallery_index(index as i32);
+    ui.set_gallery_open(true);
+}
+
+/// Move `step` photos through the gallery, wrapping around at either end
+fn step_gallery(ui: &AppWindow, step: i32) {
+    let photos = gallery_photos(ui);
+    let current = photos.iter().position(|&row| row == ui.get_gallery_index() as usize).unwrap_or(0);
+    if !photos.is_empty() {
+        let next = (current as i32 + step).rem_euclid(photos.len() as i32) as usize;
+        show_gallery_photo(ui, photos[next]);
+    }
+}
+
+fn related_taxon_item(taxon: &crate::models::Taxon) -> RelatedTaxonItem {
+    RelatedTaxonItem {
+        id: taxon.id as i32,
+        common_name: SharedString::from(taxon.common_name.clone()),
+        rank: SharedString::from(taxon.rank.to_string()),
+        taxonomy: SharedString::from(taxonomy_text(taxon)),
+    }
+}
+
+/// A taxon's names, broadest first, as plain text
+fn taxonomy_text(taxon: &crate::models::Taxon) -> String {
+    taxon.lineage().iter().map(|(_, name)| *name).collect::<Vec<_>>().join(" / ")
+}
+
+/// Breadcrumb links for a taxon's names; `include_own` keeps the taxon's own name
+fn taxonomy_segments(taxon: &crate::models::Taxon, include_own: bool) -> ModelRc<TaxonomySegment> {
+    let segments: Vec<TaxonomySegment> = taxon
+        .lineage()
+        .into_iter()
+        .filter(|&(rank, _)| include_own || rank != taxon.rank)
+        .map(|(rank, name)| TaxonomySegment {
+            taxon_id: taxon.id as i32,
+            rank: SharedString::from(rank.to_string()),
+            name: SharedString::from(name),
+        })
+        .collect();
+    ModelRc::new(VecModel::from(segments))
+}
+
+/// Resolve a breadcrumb link to the taxon at that rank
+fn find_ancestor(taxon_id: i32, rank: &str) -> anyhow::Result<i32> {
+    use crate::core::taxon::{find_or_create_ancestor, get_taxon_by_id};
+    use crate::models::Rank;
+
+    let rank = rank.parse::<Rank>()?;
+    let conn = connect()?;
+    let taxon = get_taxon_by_id(&conn, taxon_id as i64)?;
+    Ok(find_or_create_ancestor(&conn, &taxon, rank)?.id as i32)
+}
+
 fn perform_search(ui: &AppWindow, query: &str) {
-    use crate::core::db::connect;
     use crate::core::search::*;
 
     let conn = match connect() {
@@ -135,41 +836,40 @@ fn perform_search(ui: &AppWindow, query: &str) {
         }
     };
 
-    // Run all 3 searches
-    let sightings = run_search_sightings(&conn, query).unwrap_or_default();
-    let taxa = run_search_taxa(&conn, query).unwrap_or_default();
-    let trips = run_search_trips(&conn, query).unwrap_or_default();
+    // Run the query against every type it applies to
+    let (results, error) = match run_search(&conn, query) {
+        Ok(results) => (results, String::new()),
+        Err(e) => (SearchResults::default(), format!("{:#}", e)),
+    };
+    let SearchResults { sightings, trips, taxa, locations } = results;
 
     // Convert sightings to Slint items
     let sighting_items: Vec<SightingItem> = sightings
         .iter()
-        .map(|s| SightingItem {
-            id: s.id as i32,
-            common_name: SharedString::from(s.common_name.clone()),
-            date: SharedString::from(s.date.as_ref().unwrap_or(&String::new()).clone()),
-            location: SharedString::from(s.location.as_ref().unwrap_or(&String::new()).clone()),
+        .map(|hit| {
+            let s = &hit.item;
+            SightingItem {
+                id: s.id as i32,
+                common_name: SharedString::from(s.common_name.clone()),
+                date: date_text(s.date),
+                location: SharedString::from(s.location.as_ref().unwrap_or(&String::new()).clone()),
+                snippet: SharedString::from(hit.snippet.clone()),
+            }
         })
         .collect();
 
     // Convert taxa to Slint items
     let taxon_items: Vec<TaxonItem> = taxa
         .iter()
-        .map(|t| {
-            // Build taxonomy string
-            let mut parts = vec![t.kingdom.clone()];
-            if let Some(ref p) = t.phylum { parts.push(p.clone()); }
-            if let Some(ref c) = t.class { parts.push(c.clone()); }
-            if let Some(ref o) = t.order { parts.push(o.clone()); }
-            if let Some(ref f) = t.family { parts.push(f.clone()); }
-            if let Some(ref sf) = t.subfamily { parts.push(sf.clone()); }
-            if let Some(ref g) = t.genus { parts.push(g.clone()); }
-            if let Some(ref s) = t.species_epithet { parts.push(s.clone()); }
+        .map(|hit| {
+            let t = &hit.item;
 
             TaxonItem {
                 id: t.id as i32,
-                rank: SharedString::from(t.rank.clone()),
+                rank: SharedString::from(t.rank.to_string()),
                 common_name: SharedString::from(t.common_name.clone()),
-                taxonomy: SharedString::from(parts.join(" / ")),
+                taxonomy: SharedString::from(taxonomy_text(t)),
+                snippet: SharedString::from(hit.snippet.clone()),
             }
         })
         .collect();
@@ -177,11 +877,26 @@ fn perform_search(ui: &AppWindow, query: &str) {
     // Convert trips to Slint items
     let trip_items: Vec<TripItem> = trips
         .iter()
-        .map(|t| TripItem {
-            id: t.id as i32,
-            name: SharedString::from(t.name.clone()),
-            date: SharedString::from(t.date.as_ref().unwrap_or(&String::new()).clone()),
-            location: SharedString::from(t.location.as_ref().unwrap_or(&String::new()).clone()),
+        .map(|hit| {
+            let t = &hit.item;
+            TripItem {
+                id: t.id as i32,
+                name: SharedString::from(t.name.clone()),
+                date: date_text(t.date),
+                location: SharedString::from(t.location.as_ref().unwrap_or(&String::new()).clone()),
+                snippet: SharedString::from(hit.snippet.clone()),
+            }
+        })
+        .collect();
+
+    // Convert places to Slint items
+    let location_items: Vec<LocationItem> = locations
+        .iter()
+        .map(|hit| LocationItem {
+            id: hit.item.id as i32,
+            name: SharedString::from(hit.item.name.clone()),
+            hotspot: hit.item.hotspot,
+            snippet: SharedString::from(hit.snippet.clone()),
         })
         .collect();
 
@@ -189,13 +904,16 @@ fn perform_search(ui: &AppWindow, query: &str) {
     ui.set_sightings_model(ModelRc::new(VecModel::from(sighting_items)));
     ui.set_taxa_model(ModelRc::new(VecModel::from(taxon_items)));
     ui.set_trips_model(ModelRc::new(VecModel::from(trip_items)));
+    ui.set_locations_model(ModelRc::new(VecModel::from(location_items)));
+    ui.set_search_error(SharedString::from(error));
 }
 
 fn fetch_sighting_detail(ui: &AppWindow, id: i32) {
-    use crate::core::db::connect;
+    use crate::core::media::get_media;
     use crate::core::sighting::get_sighting_by_id;
     use crate::core::taxon::get_taxon_by_id;
     use crate::core::trip::get_trip_by_id;
+    use crate::models::{MediaOwner, format_age_sex};
 
     let conn = match connect() {
         Ok(c) => c,
@@ -214,51 +932,33 @@ fn fetch_sighting_detail(ui: &AppWindow, id: i32) {
         }
     };
 
-    // Build taxonomy string
-    let mut tax_parts = vec![sighting.kingdom.clone()];
-    if let Some(ref p) = sighting.phylum { tax_parts.push(p.clone()); }
-    if let Some(ref c) = sighting.class { tax_parts.push(c.clone()); }
-    if let Some(ref o) = sighting.order { tax_parts.push(o.clone()); }
-    if let Some(ref f) = sighting.family { tax_parts.push(f.clone()); }
-    if let Some(ref sf) = sighting.subfamily { tax_parts.push(sf.clone()); }
-    if let Some(ref g) = sighting.genus { tax_parts.push(g.clone()); }
-    if let Some(ref s) = sighting.species_epithet { tax_parts.push(s.clone()); }
-
     // Create SightingDetail struct
     let detail = SightingDetail {
         id: sighting.id as i32,
         common_name: SharedString::from(sighting.common_name.clone()),
-        taxonomy: SharedString::from(tax_parts.join(" / ")),
-        date: SharedString::from(sighting.date.unwrap_or_default()),
+        date: date_text(sighting.date),
         location: SharedString::from(sighting.location.unwrap_or_default()),
         notes: SharedString::from(sighting.notes.unwrap_or_default()),
-        media_path: SharedString::from(sighting.media_path.unwrap_or_default()),
+        count: SharedString::from(sighting.count.map(|count| count.to_string()).unwrap_or_default()),
+        age_sex: SharedString::from(format_age_sex(&sighting.age_sex)),
+        breeding: SharedString::from(sighting.breeding_code.map(|code| code.to_string()).unwrap_or_default()),
+        coordinates: SharedString::from(sighting.coordinates.map(|point| point.to_string()).unwrap_or_default()),
         taxon_id: sighting.taxon_id as i32,
         trip_id: sighting.trip_id.map(|t| t as i32).unwrap_or(0),
         has_trip: sighting.trip_id.is_some(),
+        location_id: sighting.location_id.map(|l| l as i32).unwrap_or(0),
+        has_location: sighting.location_id.is_some(),
     };
 
     ui.set_current_sighting(detail);
 
-    // Get related taxon (always exists)
+    // Get related taxon (always exists); its names make up the breadcrumb
     let related_taxa = if let Ok(taxon) = get_taxon_by_id(&conn, sighting.taxon_id) {
-        // Build taxonomy string
-        let mut tax_parts = vec![taxon.kingdom.clone()];
-        if let Some(ref p) = taxon.phylum { tax_parts.push(p.clone()); }
-        if let Some(ref c) = taxon.class { tax_parts.push(c.clone()); }
-        if let Some(ref o) = taxon.order { tax_parts.push(o.clone()); }
-        if let Some(ref f) = taxon.family { tax_parts.push(f.clone()); }
-        if let Some(ref sf) = taxon.subfamily { tax_parts.push(sf.clone()); }
-        if let Some(ref g) = taxon.genus { tax_parts.push(g.clone()); }
-        if let Some(ref s) = taxon.species_epithet { tax_parts.push(s.clone()); }
-
-        vec![RelatedTaxonItem {
-            id: taxon.id as i32,
-            common_name: SharedString::from(taxon.common_name),
-            rank: SharedString::from(taxon.rank),
-            taxonomy: SharedString::from(tax_parts.join(" / ")),
-        }]
+        ui.set_taxonomy_path(taxonomy_segments(&taxon, true));
+
+        vec![related_taxon_item(&taxon)]
     } else {
+        ui.set_taxonomy_path(ModelRc::new(VecModel::from(vec![])));
         vec![]
     };
     ui.set_related_taxa(ModelRc::new(VecModel::from(related_taxa)));
@@ -269,7 +969,7 @@ fn fetch_sighting_detail(ui: &AppWindow, id: i32) {
             vec![RelatedTripItem {
                 id: trip.id as i32,
                 name: SharedString::from(trip.name),
-                date: SharedString::from(trip.date.unwrap_or_default()),
+                date: date_text(trip.date),
             }]
         } else {
             vec![]
@@ -278,13 +978,20 @@ fn fetch_sighting_detail(ui: &AppWindow, id: i32) {
         vec![]
     };
     ui.set_related_trips(ModelRc::new(VecModel::from(related_trips)));
+
+    let media = get_media(&conn, MediaOwner::Sighting(sighting.id)).unwrap_or_else(|e| {
+        eprintln!("Failed to fetch media: {}", e);
+        vec![]
+    });
+    let items: Vec<MediaItem> = media.iter().map(media_item).collect();
+    ui.set_sighting_media(ModelRc::new(VecModel::from(items)));
+    load_media_previews(ui, sighting.id as i32, media);
 }
 
 fn fetch_taxon_detail(ui: &AppWindow, id: i32) {
-    use crate::core::db::connect;
     use crate::core::taxon::get_taxon_by_id;
-    use crate::core::sighting::get_sightings_by_taxon;
-    use crate::core::trip::get_trips_by_taxon;
+    use crate::core::sighting::get_sightings_by_taxon_page;
+    use crate::core::trip::get_trips_by_taxon_page;
 
     let conn = match connect() {
         Ok(c) => c,
@@ -306,7 +1013,7 @@ fn fetch_taxon_detail(ui: &AppWindow, id: i32) {
     // Create TaxonDetail struct
     let detail = TaxonDetail {
         id: taxon.id as i32,
-        rank: SharedString::from(taxon.rank.clone()),
+        rank: SharedString::from(taxon.rank.to_string()),
         common_name: SharedString::from(taxon.common_name.clone()),
         kingdom: SharedString::from(taxon.kingdom.clone()),
         phylum: SharedString::from(taxon.phylum.clone().unwrap_or_default()),
@@ -316,41 +1023,21 @@ fn fetch_taxon_detail(ui: &AppWindow, id: i32) {
         subfamily: SharedString::from(taxon.subfamily.clone().unwrap_or_default()),
         genus: SharedString::from(taxon.genus.clone().unwrap_or_default()),
         species_epithet: SharedString::from(taxon.species_epithet.clone().unwrap_or_default()),
+        subspecies: SharedString::from(taxon.subspecies.clone().unwrap_or_default()),
     };
 
     ui.set_current_taxon(detail);
+    ui.set_taxonomy_path(taxonomy_segments(&taxon, false));
 
-    // Get related sightings (using hierarchy-based search)
-    let sightings = get_sightings_by_taxon(&conn, &taxon).unwrap_or_default();
-    let related_sightings: Vec<RelatedSightingItem> = sightings
-        .iter()
-        .map(|s| RelatedSightingItem {
-            id: s.id as i32,
-            common_name: SharedString::from(s.common_name.clone()),
-            date: SharedString::from(s.date.as_ref().unwrap_or(&String::new()).clone()),
-        })
-        .collect();
-    ui.set_related_sightings(ModelRc::new(VecModel::from(related_sightings)));
-
-    // Get related trips (using hierarchy-based search)
-    let trips = get_trips_by_taxon(&conn, &taxon).unwrap_or_default();
-    let related_trips: Vec<RelatedTripItem> = trips
-        .iter()
-        .map(|t| RelatedTripItem {
-            id: t.id as i32,
-            name: SharedString::from(t.name.clone()),
-            date: SharedString::from(t.date.as_ref().unwrap_or(&String::new()).clone()),
-        })
-        .collect();
-    ui.set_related_trips(ModelRc::new(VecModel::from(related_trips)));
+    // The first few related sightings and trips (using hierarchy-based search)
+    set_related_sightings(ui, get_sightings_by_taxon_page(&conn, &taxon, RELATED_LIMIT, 0));
+    set_related_trips(ui, get_trips_by_taxon_page(&conn, &taxon, RELATED_LIMIT, 0));
 }
 
 fn fetch_trip_detail(ui: &AppWindow, id: i32) {
-    use crate::core::db::connect;
     use crate::core::trip::get_trip_by_id;
-    use crate::core::sighting::get_sightings_by_trip_id;
-    use crate::core::taxon::get_taxon_by_id;
-    use std::collections::HashMap;
+    use crate::core::sighting::get_sightings_by_trip_id_page;
+    use crate::core::taxon::get_taxa_by_trip_id;
 
     let conn = match connect() {
         Ok(c) => c,
@@ -373,61 +1060,391 @@ fn fetch_trip_detail(ui: &AppWindow, id: i32) {
     let detail = TripDetail {
         id: trip.id as i32,
         name: SharedString::from(trip.name.clone()),
-        date: SharedString::from(trip.date.unwrap_or_default()),
+        date: date_text(trip.date),
         location: SharedString::from(trip.location.unwrap_or_default()),
         notes: SharedString::from(trip.notes.unwrap_or_default()),
+        coordinates: SharedString::from(trip.coordinates.map(|point| point.to_string()).unwrap_or_default()),
+        location_id: trip.location_id.map(|l| l as i32).unwrap_or(0),
+        has_location: trip.location_id.is_some(),
+        start_time: trip.effort.start_time.clone().unwrap_or_default().into(),
+        duration: trip.effort.duration_min.map(|d| d.to_string()).unwrap_or_default().into(),
+        distance: trip.effort.distance_km.map(|d| d.to_string()).unwrap_or_default().into(),
+        party_size: trip.effort.party_size.map(|p| p.to_string()).unwrap_or_default().into(),
+        protocol: trip.effort.protocol.map(|p| p.to_string()).unwrap_or_default().into(),
+        complete: trip.effort.complete,
+        effort: if trip.effort.is_empty() { String::new() } else { trip.effort.to_string() }.into(),
     };
 
     ui.set_current_trip(detail);
 
-    // Get related sightings
-    let sightings = get_sightings_by_trip_id(&conn, id as i64).unwrap_or_default();
-    let related_sightings: Vec<RelatedSightingItem> = sightings
+    // The first few sightings
+    set_related_sightings(ui, get_sightings_by_trip_id_page(&conn, id as i64, RELATED_LIMIT, 0));
+
+    // Every distinct taxon seen on the trip
+    let related_taxa: Vec<RelatedTaxonItem> = get_taxa_by_trip_id(&conn, id as i64)
+        .unwrap_or_default()
+        .iter()
+        .map(related_taxon_item)
+        .collect();
+    ui.set_related_taxa(ModelRc::new(VecModel::from(related_taxa)));
+
+    // Clear related trips (trips don't have related trips)
+    ui.set_related_trips(ModelRc::new(VecModel::from(vec![])));
+    ui.set_related_trips_total(0);
+}
+
+fn fetch_location_detail(ui: &AppWindow, id: i32) {
+    use crate::core::location::get_location_by_id;
+    use crate::core::sighting::get_sightings_by_location_id_page;
+    use crate::core::trip::get_trips_by_location_id_page;
+
+    let conn = match connect() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return;
+        }
+    };
+
+    // Get the location
+    let location = match get_location_by_id(&conn, id as i64) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Failed to fetch location: {}", e);
+            return;
+        }
+    };
+
+    let detail = LocationDetail {
+        id: location.id as i32,
+        name: SharedString::from(location.name),
+        locality: SharedString::from(location.locality),
+        county: SharedString::from(location.county.unwrap_or_default()),
+        state: SharedString::from(location.state.unwrap_or_default()),
+        country: SharedString::from(location.country.unwrap_or_default()),
+        coordinates: SharedString::from(location.coordinates.map(|point| point.to_string()).unwrap_or_default()),
+        hotspot: location.hotspot,
+    };
+
+    ui.set_current_location(detail);
+
+    // The first few trips and sightings recorded there
+    set_related_trips(ui, get_trips_by_location_id_page(&conn, id as i64, RELATED_LIMIT, 0));
+    set_related_sightings(ui, get_sightings_by_location_id_page(&conn, id as i64, RELATED_LIMIT, 0));
+}
+
+/// Show a detail page's first related sightings, and how many there are in all
+fn set_related_sightings(ui: &AppWindow, page: anyhow::Result<Paged<Sighting>>) {
+    let page = page.unwrap_or_else(|e| {
+        eprintln!("Failed to fetch sightings: {:#}", e);
+        Paged { items: Vec::new(), total: 0 }
+    });
+    let related_sightings: Vec<RelatedSightingItem> = page
+        .items
         .iter()
         .map(|s| RelatedSightingItem {
             id: s.id as i32,
             common_name: SharedString::from(s.common_name.clone()),
-            date: SharedString::from(s.date.as_ref().unwrap_or(&String::new()).clone()),
+            date: date_text(s.date),
         })
         .collect();
     ui.set_related_sightings(ModelRc::new(VecModel::from(related_sightings)));
+    ui.set_related_sightings_total(page.total as i32);
+}
 
-    // Build distinct taxa list from sightings
-    let mut taxa_map: HashMap<i64, RelatedTaxonItem> = HashMap::new();
-    for sighting in &sightings {
-        // Only add each taxon_id once
-        if !taxa_map.contains_key(&sighting.taxon_id) {
-            // Fetch the taxon to get rank info
-            if let Ok(taxon) = get_taxon_by_id(&conn, sighting.taxon_id) {
-                // Build taxonomy string
-                let mut tax_parts = vec![taxon.kingdom.clone()];
-                if let Some(ref p) = taxon.phylum { tax_parts.push(p.clone()); }
-                if let Some(ref c) = taxon.class { tax_parts.push(c.clone()); }
-                if let Some(ref o) = taxon.order { tax_parts.push(o.clone()); }
-                if let Some(ref f) = taxon.family { tax_parts.push(f.clone()); }
-                if let Some(ref sf) = taxon.subfamily { tax_parts.push(sf.clone()); }
-                if let Some(ref g) = taxon.genus { tax_parts.push(g.clone()); }
-                if let Some(ref s) = taxon.species_epithet { tax_parts.push(s.clone()); }
-
-                taxa_map.insert(
-                    sighting.taxon_id,
-                    RelatedTaxonItem {
-                        id: taxon.id as i32,
-                        common_name: SharedString::from(taxon.common_name),
-                        rank: SharedString::from(taxon.rank),
-                        taxonomy: SharedString::from(tax_parts.join(" / ")),
-                    }
-                );
-            }
+/// Show a detail page's first related trips, and how many there are in all
+fn set_related_trips(ui: &AppWindow, page: anyhow::Result<Paged<Trip>>) {
+    let page = page.unwrap_or_else(|e| {
+        eprintln!("Failed to fetch trips: {:#}", e);
+        Paged { items: Vec::new(), total: 0 }
+    });
+    let related_trips: Vec<RelatedTripItem> = page
+        .items
+        .iter()
+        .map(|t| RelatedTripItem {
+            id: t.id as i32,
+            name: SharedString::from(t.name.clone()),
+            date: date_text(t.date),
+        })
+        .collect();
+    ui.set_related_trips(ModelRc::new(VecModel::from(related_trips)));
+    ui.set_related_trips_total(page.total as i32);
+}
+
+/// One page of a "See all" list, with the list's title
+fn fetch_list_page(page: Page, offset: usize) -> anyhow::Result<(String, Paged<ListEntry>)> {
+    use crate::core::location::get_location_by_id;
+    use crate::core::sighting::{get_sightings_by_location_id_page, get_sightings_by_taxon_page, get_sightings_by_trip_id_page};
+    use crate::core::taxon::get_taxon_by_id;
+    use crate::core::trip::{get_trip_by_id, get_trips_by_location_id_page, get_trips_by_taxon_page};
+
+    let conn = connect()?;
+    let sighting_entry = |s: Sighting| ListEntry {
+        id: s.id as i32,
+        title: SharedString::from(s.common_name),
+        subtitle: SharedString::from([s.date.map(|date| date.to_string()), s.location].into_iter().flatten().collect::<Vec<_>>().join(" · ")),
+    };
+    let trip_entry = |t: Trip| ListEntry {
+        id: t.id as i32,
+        title: SharedString::from(t.name),
+        subtitle: SharedString::from([t.date.map(|date| date.to_string()), t.location].into_iter().flatten().collect::<Vec<_>>().join(" · ")),
+    };
+
+    Ok(match page {
+        Page::AllSightings(ListOwner::Taxon(id)) => {
+            let taxon = get_taxon_by_id(&conn, id as i64)?;
+            let entries = get_sightings_by_taxon_page(&conn, &taxon, LIST_PAGE_SIZE, offset)?.map(sighting_entry);
+            (format!("Sightings of {}", taxon.common_name), entries)
+        }
+        Page::AllSightings(ListOwner::Trip(id)) => {
+            let trip = get_trip_by_id(&conn, id as i64)?;
+            let entries = get_sightings_by_trip_id_page(&conn, trip.id, LIST_PAGE_SIZE, offset)?.map(sighting_entry);
+            (format!("Sightings on {}", trip.name), entries)
+        }
+        Page::AllSightings(ListOwner::Location(id)) => {
+            let location = get_location_by_id(&conn, id as i64)?;
+            let entries = get_sightings_by_location_id_page(&conn, location.id, LIST_PAGE_SIZE, offset)?.map(sighting_entry);
+            (format!("Sightings at {}", location.name), entries)
+        }
+        Page::AllTrips(ListOwner::Taxon(id)) => {
+            let taxon = get_taxon_by_id(&conn, id as i64)?;
+            let entries = get_trips_by_taxon_page(&conn, &taxon, LIST_PAGE_SIZE, offset)?.map(trip_entry);
+            (format!("Trips with {}", taxon.common_name), entries)
+        }
+        Page::AllTrips(ListOwner::Location(id)) => {
+            let location = get_location_by_id(&conn, id as i64)?;
+            let entries = get_trips_by_location_id_page(&conn, location.id, LIST_PAGE_SIZE, offset)?.map(trip_entry);
+            (format!("Trips at {}", location.name), entries)
+        }
+        _ => anyhow::bail!("{:?} is not a list page", page),
+    })
+}
+
+/// Reset the new sighting form, starting from a trip and taxon when given
+fn fetch_new_sighting_form(ui: &AppWindow, trip_id: i32, taxon_id: i32) {
+    use crate::core::taxon::get_taxon_by_id;
+    use crate::core::trip::get_all_trips;
+
+    let conn = match connect() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return;
+        }
+    };
+
+    // The picker lists trips newest first, after "No trip"
+    let mut trips = get_all_trips(&conn).unwrap_or_default();
+    trips.sort_by(|a, b| b.date.cmp(&a.date).then(b.id.cmp(&a.id)));
+    let mut choices = vec![TripItem { name: SharedString::from("No trip"), ..Default::default() }];
+    choices.extend(trips.into_iter().map(|trip| TripItem {
+        id: trip.id as i32,
+        name: SharedString::from(trip.name),
+        date: date_text(trip.date),
+        location: SharedString::from(trip.location.unwrap_or_default()),
+        snippet: SharedString::new(),
+    }));
+    let labels: Vec<SharedString> = choices
+        .iter()
+        .map(|trip| match trip.date.as_str() {
+            "" => trip.name.clone(),
+            date => SharedString::from(format!("{} · {}", trip.name, date)),
+        })
+        .collect();
+
+    let taxon = match taxon_id {
+        0 => None,
+        id => get_taxon_by_id(&conn, id as i64).ok(),
+    };
+    let trip_index = choices.iter().position(|trip| trip_id != 0 && trip.id == trip_id).unwrap_or(0);
+
+    ui.set_new_sighting_taxon_id(taxon.as_ref().map_or(0, |taxon| taxon.id as i32));
+    ui.set_new_sighting_taxon_name(SharedString::from(taxon.map(|taxon| taxon.common_name).unwrap_or_default()));
+    ui.set_new_sighting_trip_index(trip_index as i32);
+    ui.set_new_sighting_date(choices[trip_index].date.clone());
+    ui.set_new_sighting_location(choices[trip_index].location.clone());
+    ui.set_new_sighting_media(SharedString::new());
+    ui.set_trip_labels(ModelRc::new(VecModel::from(labels)));
+    ui.set_trip_choices(ModelRc::new(VecModel::from(choices)));
+    ui.set_taxon_suggestions(ModelRc::new(VecModel::from(vec![])));
+}
+
+/// Offer the taxa best matching what's been typed into the new sighting form
+fn suggest_taxa(ui: &AppWindow, text: &str) {
+    use crate::core::search::run_search_taxa;
+
+    // Half-typed search syntax just offers nothing until it parses
+    let suggestions: Vec<RelatedTaxonItem> = match text.trim().chars().count() {
+        0..=1 => Vec::new(),
+        _ => connect()
+            .and_then(|conn| run_search_taxa(&conn, text))
+            .map(|hits| hits.iter().take(TAXON_SUGGESTIONS).map(|hit| related_taxon_item(&hit.item)).collect())
+            .unwrap_or_default(),
+    };
+    ui.set_taxon_suggestions(ModelRc::new(VecModel::from(suggestions)));
+}
+
+/// Switch the new sighting to another trip. Its date and location fill any field that's
+/// blank or still shows the previous trip's.
+fn pick_form_trip(ui: &AppWindow, index: i32) {
+    let choices = ui.get_trip_choices();
+    let old = choices.row_data(ui.get_new_sighting_trip_index() as usize);
+    let (Some(old), Some(new)) = (old, choices.row_data(index as usize)) else {
+        return;
+    };
+
+    let from_trip = |field: SharedString, old: &SharedString, new: &SharedString| {
+        if field.trim().is_empty() || field == *old { new.clone() } else { field }
+    };
+    ui.set_new_sighting_date(from_trip(ui.get_new_sighting_date(), &old.date, &new.date));
+    ui.set_new_sighting_location(from_trip(ui.get_new_sighting_location(), &old.location, &new.location));
+    ui.set_new_sighting_trip_index(index);
+}
+
+/// Open a "See all" list at its first page
+fn fetch_related_list(ui: &AppWindow, page: Page) {
+    let kind = if matches!(page, Page::AllTrips(_)) { "trips" } else { "sightings" };
+    let (title, entries) = fetch_list_page(page, 0).unwrap_or_else(|e| {
+        eprintln!("Failed to fetch list: {:#}", e);
+        (String::new(), Paged { items: Vec::new(), total: 0 })
+    });
+
+    ui.set_list_title(SharedString::from(title));
+    ui.set_list_kind(SharedString::from(kind));
+    ui.set_list_entries(ModelRc::new(VecModel::from(entries.items)));
+    ui.set_list_total(entries.total as i32);
+}
+
+/// Append the next page to the open "See all" list
+fn load_more_list(ui: &AppWindow, page: Page) {
+    let model = ui.get_list_entries();
+    let Some(entries) = model.as_any().downcast_ref::<VecModel<ListEntry>>() else {
+        return;
+    };
+
+    match fetch_list_page(page, entries.row_count()) {
+        Ok((_, more)) => {
+            entries.extend(more.items);
+            ui.set_list_total(more.total as i32);
         }
+        Err(e) => eprintln!("Failed to load more: {:#}", e),
     }
+}
 
-    let mut related_taxa: Vec<RelatedTaxonItem> = taxa_map.into_values().collect();
-    // Sort by common name for consistent display
-    related_taxa.sort_by(|a, b| a.common_name.to_string().cmp(&b.common_name.to_string()));
+/// Load a taxon's children into the browser, or the top of the tree for taxon 0
+fn fetch_taxon_browser(ui: &AppWindow, id: i32) {
+    use crate::core::taxon::{get_ancestors, get_children, get_root_taxa, get_taxon_by_id};
 
-    ui.set_related_taxa(ModelRc::new(VecModel::from(related_taxa)));
+    let conn = match connect() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return;
+        }
+    };
 
-    // Clear related trips (trips don't have related trips)
-    ui.set_related_trips(ModelRc::new(VecModel::from(vec![])));
+    let mut path = vec![TaxonomySegment {
+        taxon_id: 0,
+        rank: SharedString::new(),
+        name: SharedString::from("All taxa"),
+    }];
+    let (title, children) = if id == 0 {
+        ("All Taxa".to_string(), get_root_taxa(&conn))
+    } else {
+        let taxon = match get_taxon_by_id(&conn, id as i64) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Failed to fetch taxon: {}", e);
+                return;
+            }
+        };
+        let mut lineage = get_ancestors(&conn, taxon.id).unwrap_or_default();
+        lineage.push(taxon.clone());
+        path.extend(lineage.iter().map(|t| TaxonomySegment {
+            taxon_id: t.id as i32,
+            rank: SharedString::from(t.rank.to_string()),
+            name: SharedString::from(t.scientific_name()),
+        }));
+        (taxon.common_name, get_children(&conn, id as i64))
+    };
+    let children = match children {
+        Ok(children) => children,
+        Err(e) => {
+            eprintln!("Failed to fetch taxa: {}", e);
+            return;
+        }
+    };
+
+    let items: Vec<BrowserItem> = children
+        .iter()
+        .map(|t| BrowserItem {
+            id: t.id as i32,
+            common_name: SharedString::from(t.common_name.clone()),
+            scientific_name: SharedString::from(t.scientific_name()),
+            rank: SharedString::from(t.rank.to_string()),
+            children: get_children(&conn, t.id).map(|c| c.len()).unwrap_or(0) as i32,
+        })
+        .collect();
+
+    ui.set_browser_taxon_id(id);
+    ui.set_browser_title(SharedString::from(title));
+    ui.set_browser_path(ModelRc::new(VecModel::from(path)));
+    ui.set_browser_children(ModelRc::new(VecModel::from(items)));
+}
+
+/// Load the life list, or this year's list when `this_year` is set
+fn fetch_species_list(ui: &AppWindow, this_year: bool) {
+    use crate::core::reports::{current_year, life_list, year_list};
+
+    let conn = match connect() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return;
+        }
+    };
+
+    let (title, list) = if this_year {
+        match current_year(&conn) {
+            Ok(year) => (format!("{} Year List", year), year_list(&conn, &year)),
+            Err(e) => (String::new(), Err(e)),
+        }
+    } else {
+        ("Life List".to_string(), life_list(&conn))
+    };
+    let list = match list {
+        Ok(list) => list,
+        Err(e) => {
+            eprintln!("Failed to build species list: {}", e);
+            return;
+        }
+    };
+
+    let items: Vec<SpeciesListItem> = list
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let dates = match (&entry.first_date, &entry.last_date) {
+                (Some(first), Some(last)) if first != last => format!("{} – {}", first, last),
+                (Some(first), _) => first.to_string(),
+                _ => "Undated".to_string(),
+            };
+            SpeciesListItem {
+                taxon_id: entry.taxon_id as i32,
+                position: index as i32 + 1,
+                common_name: SharedString::from(entry.common_name.clone()),
+                scientific_name: SharedString::from(entry.scientific_name.clone()),
+                dates: SharedString::from(dates),
+                sightings: entry.sightings as i32,
+                individuals: entry.individuals as i32,
+                breeding: SharedString::from(
+                    entry.breeding_code.map(|code| format!("{} ({})", code, code.category())).unwrap_or_default(),
+                ),
+                is_new: entry.new_species,
+            }
+        })
+        .collect();
+
+    ui.set_species_list_title(SharedString::from(title));
+    ui.set_species_list(ModelRc::new(VecModel::from(items)));
 }
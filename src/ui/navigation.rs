@@ -0,0 +1,192 @@
+/// A page the GUI can show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Page {
+    Search,
+    Sighting(i32),
+    Taxon(i32),
+    Trip(i32),
+    Location(i32),
+    SpeciesList { this_year: bool },
+    /// Taxon 0 is the top of the tree
+    TaxonBrowser(i32),
+    /// "See all" lists for a detail page
+    AllSightings(ListOwner),
+    AllTrips(ListOwner),
+    NewTrip,
+    /// Trip and taxon 0 leave the form's trip and taxon blank
+    NewSighting { trip_id: i32, taxon_id: i32 },
+}
+
+/// The detail page a "See all" list belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListOwner {
+    Taxon(i32),
+    Trip(i32),
+    Location(i32),
+}
+
+impl Page {
+    /// The owner of this page's related lists, if it's a detail page that has them
+    pub fn list_owner(self) -> Option<ListOwner> {
+        match self {
+            Page::Taxon(id) => Some(ListOwner::Taxon(id)),
+            Page::Trip(id) => Some(ListOwner::Trip(id)),
+            Page::Location(id) => Some(ListOwner::Location(id)),
+            _ => None,
+        }
+    }
+}
+
+/// A visited page: its title for the trail, and where it was scrolled to when left
+#[derive(Debug, Clone)]
+pub struct Visit {
+    pub page: Page,
+    pub title: String,
+    pub scroll_y: f32,
+}
+
+/// Back/forward history, like a web browser's. Visiting a page drops anything ahead
+/// of the current one.
+#[derive(Debug)]
+pub struct History {
+    visits: Vec<Visit>,
+    current: usize,
+}
+
+impl History {
+    pub fn new(page: Page, title: &str) -> Self {
+        History {
+            visits: vec![Visit { page, title: title.to_string(), scroll_y: 0.0 }],
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> &Visit {
+        &self.visits[self.current]
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.current + 1 < self.visits.len()
+    }
+
+    /// Leave the current page, scrolled to `scroll_y`, for `page`. Returns false (and
+    /// stays put) when `page` is already the current one.
+    pub fn visit(&mut self, page: Page, scroll_y: f32) -> bool {
+        if self.current().page == page {
+            return false;
+        }
+        self.visits.truncate(self.current + 1);
+        self.visits[self.current].scroll_y = scroll_y;
+        self.visits.push(Visit { page, title: String::new(), scroll_y: 0.0 });
+        self.current += 1;
+        true
+    }
+
+    /// Put `page` in the current page's place, dropping anything ahead of it
+    pub fn replace(&mut self, page: Page) {
+        self.visits.truncate(self.current + 1);
+        self.visits[self.current] = Visit { page, title: String::new(), scroll_y: 0.0 };
+    }
+
+    /// Leave the current page for the visit at `index` in the history, keeping the
+    /// pages on either side
+    pub fn go_to(&mut self, index: usize, scroll_y: f32) -> Option<&Visit> {
+        if index >= self.visits.len() || index == self.current {
+            return None;
+        }
+        self.visits[self.current].scroll_y = scroll_y;
+        self.current = index;
+        Some(self.current())
+    }
+
+    pub fn back(&mut self, scroll_y: f32) -> Option<&Visit> {
+        self.current.checked_sub(1).and_then(|index| self.go_to(index, scroll_y))
+    }
+
+    pub fn forward(&mut self, scroll_y: f32) -> Option<&Visit> {
+        self.go_to(self.current + 1, scroll_y)
+    }
+
+    /// Name the current page once it's loaded (names can change between visits)
+    pub fn set_title(&mut self, title: &str) {
+        self.visits[self.current].title = title.to_string();
+    }
+
+    /// The last `limit` pages up to the current one, with their history indexes
+    pub fn trail(&self, limit: usize) -> Vec<(usize, &Visit)> {
+        let start = (self.current + 1).saturating_sub(limit);
+        (start..=self.current).map(|index| (index, &self.visits[index])).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(history: &History) -> Vec<Page> {
+        history.trail(usize::MAX).iter().map(|(_, visit)| visit.page).collect()
+    }
+
+    #[test]
+    fn test_back_and_forward_keep_the_path() {
+        let mut history = History::new(Page::Search, "Search");
+        history.visit(Page::Taxon(1), 0.0);
+        history.visit(Page::Sighting(2), 120.0);
+        history.visit(Page::Trip(3), 40.0);
+        assert!(!history.visit(Page::Trip(3), 0.0));
+
+        let back = history.back(10.0).unwrap();
+        assert_eq!((back.page, back.scroll_y), (Page::Sighting(2), 40.0));
+        assert_eq!(history.back(25.0).unwrap().page, Page::Taxon(1));
+        assert!(history.can_go_forward());
+
+        // Forward returns to where each page was last left
+        let forward = history.forward(0.0).unwrap();
+        assert_eq!((forward.page, forward.scroll_y), (Page::Sighting(2), 25.0));
+        assert_eq!(history.forward(0.0).unwrap().scroll_y, 10.0);
+        assert!(history.forward(0.0).is_none());
+        assert_eq!(pages(&history), vec![Page::Search, Page::Taxon(1), Page::Sighting(2), Page::Trip(3)]);
+    }
+
+    #[test]
+    fn test_visiting_drops_forward_history() {
+        let mut history = History::new(Page::Search, "Search");
+        history.visit(Page::Taxon(1), 0.0);
+        history.visit(Page::Sighting(2), 0.0);
+        history.back(0.0);
+        history.back(0.0);
+        assert!(!history.can_go_back());
+
+        history.visit(Page::SpeciesList { this_year: true }, 0.0);
+        assert!(!history.can_go_forward());
+        assert_eq!(pages(&history), vec![Page::Search, Page::SpeciesList { this_year: true }]);
+    }
+
+    #[test]
+    fn test_replace_swaps_the_current_page() {
+        let mut history = History::new(Page::Search, "Search");
+        history.visit(Page::NewTrip, 0.0);
+        history.replace(Page::Trip(7));
+
+        assert_eq!(pages(&history), vec![Page::Search, Page::Trip(7)]);
+        assert_eq!(history.back(0.0).unwrap().page, Page::Search);
+    }
+
+    #[test]
+    fn test_trail_ends_at_the_current_page() {
+        let mut history = History::new(Page::Search, "Search");
+        for id in 1..=5 {
+            history.visit(Page::Taxon(id), 0.0);
+            history.set_title(&format!("Taxon {}", id));
+        }
+        history.go_to(2, 0.0).unwrap();
+
+        let trail: Vec<(usize, &str)> = history.trail(2).iter().map(|(index, visit)| (*index, visit.title.as_str())).collect();
+        assert_eq!(trail, vec![(1, "Taxon 1"), (2, "Taxon 2")]);
+        assert!(history.go_to(9, 0.0).is_none());
+    }
+}
@@ -90,6 +90,9 @@ assert_success "Project built"
 # Binary path
 BIN="./target/debug/fast-watcher"
 
+# Scratch database so the real one (in the XDG data dir) is never dropped
+export FASTWATCHER_DB="./test_cli.db"
+
 # Clean slate
 print_header "SETUP: Initialize Database"
 print_test "Drop existing database"
@@ -253,10 +256,52 @@ print_test "Search sightings by location"
 OUTPUT=$($BIN search-sightings "pond" 2>&1 | clean_output)
 assert_contains "$OUTPUT" "American Robin" "Search finds sighting by location"
 
+print_test "Search sightings with field filter"
+OUTPUT=$($BIN search-sightings "robin location:pond" 2>&1 | clean_output)
+assert_contains "$OUTPUT" "American Robin" "Location filter keeps matching sighting"
+
+print_test "Search with type prefix"
+OUTPUT=$($BIN search "taxon:robin" 2>&1 | clean_output)
+assert_contains "$OUTPUT" "Taxa (" "Type prefix limits results to taxa"
+
+print_test "Reject invalid date filter"
+$BIN search-sightings "robin date:2025-13" > /dev/null 2>&1
+assert_failure "Invalid date rejected"
+
 print_test "Try to create sighting with non-existent taxon ID"
 $BIN add-sighting 99999 --notes "Test" > /dev/null 2>&1
 assert_failure "Non-existent taxon ID rejected"
 
+# ==========================================
+# EDIT TESTS
+# ==========================================
+print_header "EDIT TESTS"
+
+print_test "Edit trip location"
+OUTPUT=$($BIN edit-trip "$TRIP1_ID" --location "Central Park NYC" 2>&1 | clean_output)
+assert_contains "$OUTPUT" "updated" "Trip updated successfully"
+
+print_test "Search trips by edited location"
+OUTPUT=$($BIN search-trips "NYC" 2>&1 | clean_output)
+assert_contains "$OUTPUT" "Morning Birding" "Search finds trip by new location"
+
+print_test "Edit taxon common name"
+OUTPUT=$($BIN edit-taxon "$WARBLER_FAM_ID" --common-name "Wood-Warblers" 2>&1 | clean_output)
+assert_contains "$OUTPUT" "updated" "Taxon updated successfully"
+
+print_test "Renamed taxon propagates to its sightings"
+OUTPUT=$($BIN search-sightings "Wood-Warblers" 2>&1 | clean_output)
+assert_contains "$OUTPUT" "Wood-Warblers" "Sighting search finds new common name"
+$BIN edit-taxon "$WARBLER_FAM_ID" --common-name "Warbler Family" > /dev/null 2>&1
+
+print_test "Edit sighting notes and detach from trip"
+OUTPUT=$($BIN edit-sighting "$SIGHTING1_ID" --notes "Pulling worms" --no-trip 2>&1 | clean_output)
+assert_contains "$OUTPUT" "updated" "Sighting updated successfully"
+
+print_test "Edit with no fields fails"
+$BIN edit-trip "$TRIP1_ID" > /dev/null 2>&1
+assert_failure "Empty edit rejected"
+
 # ==========================================
 # DELETE TESTS
 # ==========================================
@@ -1,16 +1,18 @@
 use rusqlite::Connection;
-use fast_watcher::core::taxon::{create_taxon, get_taxon_by_id, delete_taxon};
-use fast_watcher::core::trip::{create_trip, get_trip_by_id, delete_trip};
-use fast_watcher::core::sighting::{create_sighting, get_sighting_by_id, delete_sighting};
-use fast_watcher::core::search::{run_search_taxa, run_search_sightings, run_search_trips};
+use fast_watcher::core::migrations::migrate;
+use fast_watcher::core::taxon::{create_taxon, get_taxon_by_id, delete_taxon, update_taxon};
+use fast_watcher::core::trip::{create_trip, get_trip_by_id, delete_trip, update_trip};
+use fast_watcher::core::sighting::{create_sighting, get_sighting_by_id, delete_sighting, update_sighting};
+use fast_watcher::core::search::{run_search, run_search_taxa, run_search_sightings, run_search_trips};
+use fast_watcher::core::location::{create_location, get_location_by_id, merge_locations, update_location};
+use fast_watcher::models::{Effort, LocationUpdate, Rank, SightingUpdate, TaxonUpdate, TripUpdate};
 
 /// Helper function to set up a test database with schema
 fn setup_test_db() -> Connection {
     let conn = Connection::open_in_memory().unwrap();
     conn.pragma_update(None, "foreign_keys", "ON").unwrap();
 
-    let schema = std::fs::read_to_string("init.sql").unwrap();
-    conn.execute_batch(&schema).unwrap();
+    migrate(&conn).unwrap();
 
     // Load test taxa (first 100 species + parent taxa)
     let test_taxa = std::fs::read_to_string("seed_taxa_test.sql").unwrap();
@@ -30,7 +32,7 @@ fn test_taxon_workflow() {
     // Create species-level taxon
     let robin_id = create_taxon(
         &conn,
-        "species",
+        Rank::Species,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -39,6 +41,7 @@ fn test_taxon_workflow() {
         None,
         Some("Turdus"),
         Some("migratorius"),
+        None,
         "American Robin",
     ).unwrap();
     assert!(robin_id > 0);
@@ -46,7 +49,7 @@ fn test_taxon_workflow() {
     // Create family-level taxon
     let warbler_fam_id = create_taxon(
         &conn,
-        "family",
+        Rank::Family,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -55,6 +58,7 @@ fn test_taxon_workflow() {
         None,
         None,
         None,
+        None,
         "Warbler Family",
     ).unwrap();
     assert!(warbler_fam_id > 0);
@@ -62,7 +66,7 @@ fn test_taxon_workflow() {
     // Create genus-level taxon
     let buteo_id = create_taxon(
         &conn,
-        "genus",
+        Rank::Genus,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -71,20 +75,21 @@ fn test_taxon_workflow() {
         None,
         Some("Buteo"),
         None,
+        None,
         "Buteo Hawks",
     ).unwrap();
     assert!(buteo_id > 0);
 
     // Retrieve and verify species-level taxon
     let robin = get_taxon_by_id(&conn, robin_id).unwrap();
-    assert_eq!(robin.rank, "species");
+    assert_eq!(robin.rank, Rank::Species);
     assert_eq!(robin.kingdom, "Animalia");
     assert_eq!(robin.species_epithet, Some("migratorius".to_string()));
     assert_eq!(robin.common_name, "American Robin");
 
     // Retrieve and verify family-level taxon
     let warbler_fam = get_taxon_by_id(&conn, warbler_fam_id).unwrap();
-    assert_eq!(warbler_fam.rank, "family");
+    assert_eq!(warbler_fam.rank, Rank::Family);
     assert_eq!(warbler_fam.family, Some("Parulidae".to_string()));
     assert_eq!(warbler_fam.genus, None);
     assert_eq!(warbler_fam.species_epithet, None);
@@ -92,17 +97,17 @@ fn test_taxon_workflow() {
     // Search taxa by common name
     let results = run_search_taxa(&conn, "Robin").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].common_name, "American Robin");
+    assert_eq!(results[0].item.common_name, "American Robin");
 
     // Search taxa by family
     let results = run_search_taxa(&conn, "Parulidae").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].family, Some("Parulidae".to_string()));
+    assert_eq!(results[0].item.family, Some("Parulidae".to_string()));
 
     // Delete taxon and verify
     let temp_id = create_taxon(
         &conn,
-        "species",
+        Rank::Species,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -111,6 +116,7 @@ fn test_taxon_workflow() {
         None,
         Some("Test"),
         Some("temp"),
+        None,
         "Temp Bird",
     ).unwrap();
     let rows = delete_taxon(&conn, temp_id).unwrap();
@@ -120,23 +126,11 @@ fn test_taxon_workflow() {
 
 #[test]
 fn test_invalid_taxon_rank() {
-    let conn = setup_test_db();
+    let result = "invalid_rank".parse::<Rank>();
 
-    let result = create_taxon(
-        &conn,
-        "invalid_rank",
-        "Animalia",
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        "Test",
-    );
-
-    assert!(result.is_err());
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("unknown rank 'invalid_rank'"));
+    assert!(error.contains("kingdom, phylum, class, order, family, subfamily, genus, species"));
 }
 
 // ==========================================
@@ -154,17 +148,19 @@ fn test_trip_workflow() {
         Some("2025-01-15"),
         Some("Central Park"),
         Some("Cold morning, lots of activity"),
+        None,
+        &Effort::default(),
     ).unwrap();
     assert!(trip1_id > 0);
 
     // Create trip with minimal fields
-    let trip2_id = create_trip(&conn, "Quick Walk", None, None, None).unwrap();
+    let trip2_id = create_trip(&conn, "Quick Walk", None, None, None, None, &Effort::default()).unwrap();
     assert!(trip2_id > 0);
 
     // Retrieve and verify trip with all fields
     let trip1 = get_trip_by_id(&conn, trip1_id).unwrap();
     assert_eq!(trip1.name, "Morning Birding");
-    assert_eq!(trip1.date, Some("2025-01-15".to_string()));
+    assert_eq!(trip1.date, Some("2025-01-15".parse().unwrap()));
     assert_eq!(trip1.location, Some("Central Park".to_string()));
     assert_eq!(trip1.notes, Some("Cold morning, lots of activity".to_string()));
 
@@ -176,7 +172,7 @@ fn test_trip_workflow() {
     // Search trips by location
     let results = run_search_trips(&conn, "Central Park").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].name, "Morning Birding");
+    assert_eq!(results[0].item.name, "Morning Birding");
 
     // Delete trip and verify
     let rows = delete_trip(&conn, trip2_id).unwrap();
@@ -184,6 +180,39 @@ fn test_trip_workflow() {
     assert!(get_trip_by_id(&conn, trip2_id).is_err());
 }
 
+#[test]
+fn test_location_workflow() {
+    let conn = setup_test_db();
+
+    // Trips and sightings named after the same place share one location
+    let trip_id = create_trip(&conn, "Morning Birding", None, Some("Central Park"), None, None, &Effort::default()).unwrap();
+    let sighting_id = create_sighting(&conn, None, 1, None, None, None, Some("central park."), None, &[], None, None).unwrap();
+    let location_id = get_trip_by_id(&conn, trip_id).unwrap().location_id.unwrap();
+    assert_eq!(get_sighting_by_id(&conn, sighting_id).unwrap().location_id, Some(location_id));
+
+    // Filling in the hierarchy renames the place everywhere
+    let changes = LocationUpdate {
+        state: Some("NY".to_string()),
+        country: Some("US".to_string()),
+        ..Default::default()
+    };
+    update_location(&conn, location_id, &changes).unwrap();
+    assert_eq!(get_trip_by_id(&conn, trip_id).unwrap().location, Some("Central Park, NY, US".to_string()));
+    assert_eq!(run_search_trips(&conn, "state:ny").unwrap_err().to_string(), "'state:' filter does not apply to trips");
+
+    // A misspelled duplicate can be folded back in
+    let duplicate = create_location(&conn, "Central Park NYC", None, None, None, None, false).unwrap();
+    let changes = TripUpdate { location_id: Some(Some(duplicate)), ..Default::default() };
+    update_trip(&conn, trip_id, &changes).unwrap();
+    assert_eq!(merge_locations(&conn, location_id, &[duplicate]).unwrap(), 1);
+    assert!(get_location_by_id(&conn, duplicate).is_err());
+    assert_eq!(get_trip_by_id(&conn, trip_id).unwrap().location_id, Some(location_id));
+
+    let results = run_search(&conn, "place:central").unwrap();
+    assert_eq!(results.locations.len(), 1);
+    assert!(results.trips.is_empty());
+}
+
 // ==========================================
 // SIGHTING INTEGRATION TESTS
 // ==========================================
@@ -195,7 +224,7 @@ fn test_sighting_workflow() {
     // Set up prerequisites
     let robin_id = create_taxon(
         &conn,
-        "species",
+        Rank::Species,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -204,12 +233,13 @@ fn test_sighting_workflow() {
         None,
         Some("Turdus"),
         Some("migratorius"),
+        None,
         "American Robin",
     ).unwrap();
 
     let warbler_fam_id = create_taxon(
         &conn,
-        "family",
+        Rank::Family,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -218,12 +248,13 @@ fn test_sighting_workflow() {
         None,
         None,
         None,
+        None,
         "Warbler Family",
     ).unwrap();
 
     let buteo_id = create_taxon(
         &conn,
-        "genus",
+        Rank::Genus,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -232,6 +263,7 @@ fn test_sighting_workflow() {
         None,
         Some("Buteo"),
         None,
+        None,
         "Buteo Hawks",
     ).unwrap();
 
@@ -241,6 +273,8 @@ fn test_sighting_workflow() {
         Some("2025-01-15"),
         Some("Central Park"),
         None,
+        None,
+        &Effort::default(),
     ).unwrap();
 
     // Create species-level sighting with trip
@@ -252,6 +286,10 @@ fn test_sighting_workflow() {
         None,
         Some("2025-01-15"),
         Some("Near the pond"),
+        None,
+        &[],
+        None,
+        None,
     ).unwrap();
     assert!(sighting1_id > 0);
 
@@ -264,6 +302,10 @@ fn test_sighting_workflow() {
         None,
         Some("2025-01-15"),
         None,
+        None,
+        &[],
+        None,
+        None,
     ).unwrap();
     assert!(sighting2_id > 0);
 
@@ -276,6 +318,10 @@ fn test_sighting_workflow() {
         None,
         None,
         None,
+        None,
+        &[],
+        None,
+        None,
     ).unwrap();
     assert!(sighting3_id > 0);
 
@@ -304,22 +350,22 @@ fn test_sighting_workflow() {
     // Search sightings by common name
     let results = run_search_sightings(&conn, "Robin").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].common_name, "American Robin");
+    assert_eq!(results[0].item.common_name, "American Robin");
 
     // Search sightings by family
     let results = run_search_sightings(&conn, "Parulidae").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].common_name, "Warbler Family");
+    assert_eq!(results[0].item.common_name, "Warbler Family");
 
     // Search sightings by genus
     let results = run_search_sightings(&conn, "Buteo").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].common_name, "Buteo Hawks");
+    assert_eq!(results[0].item.common_name, "Buteo Hawks");
 
     // Search sightings by location
     let results = run_search_sightings(&conn, "pond").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].common_name, "American Robin");
+    assert_eq!(results[0].item.common_name, "American Robin");
 
     // Delete sighting and verify
     let rows = delete_sighting(&conn, sighting3_id).unwrap();
@@ -339,6 +385,10 @@ fn test_sighting_with_invalid_taxon() {
         None,
         None,
         None,
+        None,
+        &[],
+        None,
+        None,
     );
 
     assert!(result.is_err());
@@ -353,12 +403,12 @@ fn test_search_trips_by_sighting_taxonomy() {
     let conn = setup_test_db();
 
     // Create a trip
-    let trip_id = create_trip(&conn, "Birdwatching", None, None, None).unwrap();
+    let trip_id = create_trip(&conn, "Birdwatching", None, None, None, None, &Effort::default()).unwrap();
 
     // Create a taxon
     let taxon_id = create_taxon(
         &conn,
-        "family",
+        Rank::Family,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -367,16 +417,17 @@ fn test_search_trips_by_sighting_taxonomy() {
         None,
         None,
         None,
+        None,
         "Crow Family",
     ).unwrap();
 
     // Create a sighting linking them
-    create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None).unwrap();
+    create_sighting(&conn, Some(trip_id), taxon_id, None, None, None, None, None, &[], None, None).unwrap();
 
     // Search trips by sighting's taxonomic field
     let results = run_search_trips(&conn, "Corvidae").unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].name, "Birdwatching");
+    assert_eq!(results[0].item.name, "Birdwatching");
 }
 
 #[test]
@@ -386,7 +437,7 @@ fn test_search_across_multiple_ranks() {
     // Create taxa at different ranks
     let robin_id = create_taxon(
         &conn,
-        "species",
+        Rank::Species,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -395,12 +446,13 @@ fn test_search_across_multiple_ranks() {
         None,
         Some("Turdus"),
         Some("migratorius"),
+        None,
         "American Robin",
     ).unwrap();
 
     let warbler_fam_id = create_taxon(
         &conn,
-        "family",
+        Rank::Family,
         "Animalia",
         Some("Chordata"),
         Some("Aves"),
@@ -409,19 +461,20 @@ fn test_search_across_multiple_ranks() {
         None,
         None,
         None,
+        None,
         "Warbler Family",
     ).unwrap();
 
     // Create sightings for both
-    create_sighting(&conn, None, robin_id, None, None, None, None).unwrap();
-    create_sighting(&conn, None, warbler_fam_id, None, None, None, None).unwrap();
+    create_sighting(&conn, None, robin_id, None, None, None, None, None, &[], None, None).unwrap();
+    create_sighting(&conn, None, warbler_fam_id, None, None, None, None, None, &[], None, None).unwrap();
 
     // Search by class - should find both
     let results = run_search_sightings(&conn, "Aves").unwrap();
     assert!(results.len() >= 2);
 
     let common_names: Vec<String> = results.iter()
-        .map(|s| s.common_name.clone())
+        .map(|hit| hit.item.common_name.clone())
         .collect();
     assert!(common_names.contains(&"American Robin".to_string()));
     assert!(common_names.contains(&"Warbler Family".to_string()));
@@ -437,6 +490,52 @@ fn test_empty_search_queries() {
     assert!(run_search_trips(&conn, "").is_err());
 }
 
+#[test]
+fn test_structured_search_query() {
+    let conn = setup_test_db();
+
+    let spring = create_trip(&conn, "Spring Count", Some("2025-04-12"), Some("Tower Grove Park"), None, None, &Effort::default()).unwrap();
+    let fall = create_trip(&conn, "Fall Count", Some("2025-10-03"), Some("Tower Grove Park"), None, None, &Effort::default()).unwrap();
+    let hawk_id = create_taxon(
+        &conn,
+        Rank::Species,
+        "Animalia",
+        Some("Chordata"),
+        Some("Aves"),
+        Some("Accipitriformes"),
+        Some("Accipitridae"),
+        None,
+        Some("Buteo"),
+        Some("jamaicensis"),
+        None,
+        "Red-tailed Hawk",
+    ).unwrap();
+    create_sighting(&conn, Some(spring), hawk_id, Some("Pair on the water tower"), None, Some("2025-04-12"), Some("Tower Grove Park"), None, &[], None, None).unwrap();
+    create_sighting(&conn, Some(fall), hawk_id, Some("Juvenile hunting"), None, Some("2025-10-03"), Some("Tower Grove Park"), None, &[], None, None).unwrap();
+
+    // The README example: type prefix, date range and location filter together
+    let results = run_search_sightings(&conn, "sighting:hawk date:2025-01-01..2025-06-30 location:park").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item.trip_id, Some(spring));
+
+    // Negated phrase
+    let results = run_search_sightings(&conn, "hawk -\"water tower\"").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item.trip_id, Some(fall));
+
+    // Combined search honours the type prefix
+    let results = run_search(&conn, "trip:count date:2025-10").unwrap();
+    assert!(results.sightings.is_empty() && results.taxa.is_empty());
+    assert_eq!(results.trips.len(), 1);
+    assert_eq!(results.trips[0].item.id, fall);
+
+    // Clear errors for bad syntax
+    let err = run_search(&conn, "hawk date:2025-13").unwrap_err();
+    assert!(format!("{:#}", err).contains("invalid date"));
+    let err = run_search(&conn, "hawk colour:red").unwrap_err();
+    assert!(format!("{:#}", err).contains("unknown filter"));
+}
+
 #[test]
 fn test_nonexistent_entity_retrieval() {
     let conn = setup_test_db();
@@ -447,6 +546,61 @@ fn test_nonexistent_entity_retrieval() {
     assert!(get_sighting_by_id(&conn, 99999).is_err());
 }
 
+#[test]
+fn test_edit_workflow() {
+    let conn = setup_test_db();
+
+    let taxon_id = create_taxon(
+        &conn,
+        Rank::Species,
+        "Animalia",
+        Some("Chordata"),
+        Some("Aves"),
+        Some("Passeriformes"),
+        Some("Turdidae"),
+        None,
+        Some("Turdus"),
+        Some("migratorius"),
+        None,
+        "Amercan Robin",
+    ).unwrap();
+    let trip_id = create_trip(&conn, "Morning Birding", Some("2025-01-15"), Some("Centrl Park"), None, None, &Effort::default()).unwrap();
+    let sighting_id = create_sighting(&conn, Some(trip_id), taxon_id, None, None, Some("2025-01-15"), None, None, &[], None, None).unwrap();
+
+    // Fix the typos
+    let trip_changes = TripUpdate {
+        location: Some("Central Park".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(update_trip(&conn, trip_id, &trip_changes).unwrap(), 1);
+
+    let taxon_changes = TaxonUpdate {
+        common_name: Some("American Robin".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(update_taxon(&conn, taxon_id, &taxon_changes).unwrap(), 1);
+
+    let sighting_changes = SightingUpdate {
+        notes: Some("Pulling worms".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(update_sighting(&conn, sighting_id, &sighting_changes).unwrap(), 1);
+
+    // Edits are visible through reads and search
+    assert_eq!(get_trip_by_id(&conn, trip_id).unwrap().location, Some("Central Park".to_string()));
+    assert_eq!(get_taxon_by_id(&conn, taxon_id).unwrap().common_name, "American Robin");
+    assert_eq!(get_sighting_by_id(&conn, sighting_id).unwrap().notes, Some("Pulling worms".to_string()));
+
+    // The taxon rename reaches the sighting's denormalized copy
+    assert_eq!(get_sighting_by_id(&conn, sighting_id).unwrap().common_name, "American Robin");
+    let results = run_search_sightings(&conn, "American Robin").unwrap();
+    assert_eq!(results.len(), 1);
+
+    let results = run_search_trips(&conn, "Central Park").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item.id, trip_id);
+}
+
 // ==========================================
 // SEEDED DATA INTEGRATION TEST
 // ==========================================
@@ -460,16 +614,16 @@ fn test_seeded_data() {
 
     // Search for Highland Tinamou (first bird in test dataset)
     let results = run_search_taxa(&conn, "Highland Tinamou").unwrap();
-    assert!(results.len() > 0);
-    assert!(results.iter().any(|t| t.common_name == "Highland Tinamou"));
+    assert!(!results.is_empty());
+    assert!(results.iter().any(|hit| hit.item.common_name == "Highland Tinamou"));
 
     // Search for Tinamidae family
     let results = run_search_taxa(&conn, "Tinamidae").unwrap();
-    assert!(results.len() > 0);
-    assert!(results.iter().any(|t| t.family == Some("Tinamidae".to_string())));
+    assert!(!results.is_empty());
+    assert!(results.iter().any(|hit| hit.item.family == Some("Tinamidae".to_string())));
 
     // Verify both species and family level taxa are seeded
-    let tinamou = results.iter().find(|t| t.common_name == "Tinamidae");
+    let tinamou = results.iter().find(|hit| hit.item.common_name == "Tinamidae");
     assert!(tinamou.is_some());
-    assert_eq!(tinamou.unwrap().rank, "family");
+    assert_eq!(tinamou.unwrap().item.rank, Rank::Family);
 }